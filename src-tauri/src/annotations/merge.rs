@@ -0,0 +1,318 @@
+//! Merging annotation sets from different annotators.
+//!
+//! Two sets for the same text are combined word by word. Lemma annotations are
+//! one-per-word, so differing lemma or msa assignments on the same word are
+//! reported as [`AnnotationConflict`]s instead of being silently overwritten. The
+//! merged set keeps "our" lemma for every conflict until the conflict is resolved
+//! with [`AnnotationSet::resolve_conflicts`].
+
+use super::{Annotation, AnnotationSet, AnnotationTarget, AnnotationType, AnnotationValue, ReviewStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Result of merging two annotation sets
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    /// Combined set, holding our side of every unresolved conflict
+    pub merged: AnnotationSet,
+    /// Lemma conflicts that need a decision
+    pub conflicts: Vec<AnnotationConflict>,
+}
+
+/// Two different lemma annotations for the same word
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationConflict {
+    pub word_index: u32,
+    /// Names of the differing fields ("lemma", "msa")
+    pub fields: Vec<String>,
+    pub ours: Annotation,
+    pub theirs: Annotation,
+}
+
+/// Which side of a conflict to keep
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictChoice {
+    Ours,
+    Theirs,
+}
+
+/// A decision for the conflict on one word
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    pub word_index: u32,
+    pub choice: ConflictChoice,
+}
+
+/// Lemma and msa of a word-level lemma annotation
+fn lemma_key(ann: &Annotation) -> Option<(u32, &str, &str)> {
+    match (&ann.annotation_type, &ann.target, &ann.value) {
+        (
+            AnnotationType::Lemma,
+            AnnotationTarget::Word { word_index },
+            AnnotationValue::Lemma { lemma, msa, .. },
+        ) => Some((*word_index, lemma.as_str(), msa.as_str())),
+        _ => None,
+    }
+}
+
+/// How far review has got: rejected, proposed, accepted
+fn review_rank(ann: &Annotation) -> u8 {
+    match ann.review_status() {
+        ReviewStatus::Rejected => 0,
+        ReviewStatus::Proposed => 1,
+        ReviewStatus::Accepted => 2,
+    }
+}
+
+impl AnnotationSet {
+    /// Merge another annotator's set into a copy of this one.
+    ///
+    /// - Lemma annotations: a word annotated on only one side takes that
+    ///   annotation. When both sides agree, the side further along in review is
+    ///   kept (accepted over proposed over rejected). When they differ in lemma or
+    ///   msa, a rejected side gives way to the other; otherwise the word is
+    ///   reported as a conflict.
+    /// - Other annotations: added unless an annotation with the same type, target
+    ///   and value already exists. Clashing IDs are suffixed to stay unique.
+    pub fn merge(&self, theirs: &AnnotationSet) -> MergeResult {
+        let mut merged = self.clone();
+        let mut conflicts = Vec::new();
+
+        let our_lemmas: HashMap<u32, usize> = merged
+            .annotations
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, ann)| lemma_key(ann).map(|(idx, _, _)| (idx, pos)))
+            .collect();
+
+        for their_ann in &theirs.annotations {
+            if let Some((word_index, their_lemma, their_msa)) = lemma_key(their_ann) {
+                let Some(&pos) = our_lemmas.get(&word_index) else {
+                    merged.annotations.push(their_ann.clone());
+                    continue;
+                };
+                let our_ann = &merged.annotations[pos];
+                let (_, our_lemma, our_msa) = lemma_key(our_ann).expect("indexed as lemma");
+
+                let mut fields = Vec::new();
+                if our_lemma != their_lemma {
+                    fields.push("lemma".to_string());
+                }
+                if our_msa != their_msa {
+                    fields.push("msa".to_string());
+                }
+                if fields.is_empty() {
+                    if review_rank(their_ann) > review_rank(our_ann) {
+                        merged.annotations[pos] = their_ann.clone();
+                    }
+                    continue;
+                }
+
+                match (our_ann.is_rejected(), their_ann.is_rejected()) {
+                    (true, false) => merged.annotations[pos] = their_ann.clone(),
+                    (_, true) => {}
+                    (false, false) => conflicts.push(AnnotationConflict {
+                        word_index,
+                        fields,
+                        ours: our_ann.clone(),
+                        theirs: their_ann.clone(),
+                    }),
+                }
+                continue;
+            }
+
+            let duplicate = merged.annotations.iter().any(|ann| {
+                ann.annotation_type == their_ann.annotation_type
+                    && ann.target == their_ann.target
+                    && ann.value == their_ann.value
+            });
+            if duplicate {
+                continue;
+            }
+
+            let mut ann = their_ann.clone();
+            let mut suffix = 1;
+            while merged.get(&ann.id).is_some() {
+                ann.id = format!("{}-{}", their_ann.id, suffix);
+                suffix += 1;
+            }
+            merged.annotations.push(ann);
+        }
+
+        MergeResult { merged, conflicts }
+    }
+
+    /// Apply decisions for conflicts reported by [`AnnotationSet::merge`].
+    ///
+    /// The chosen annotation is marked accepted. Choosing "theirs" replaces the
+    /// lemma annotation currently held for the word.
+    pub fn resolve_conflicts(
+        &mut self,
+        conflicts: &[AnnotationConflict],
+        resolutions: &[ConflictResolution],
+    ) -> Result<(), String> {
+        for resolution in resolutions {
+            let conflict = conflicts
+                .iter()
+                .find(|c| c.word_index == resolution.word_index)
+                .ok_or_else(|| format!("No conflict for word {}", resolution.word_index))?;
+
+            let pos = self
+                .annotations
+                .iter()
+                .position(|ann| matches!(lemma_key(ann), Some((idx, _, _)) if idx == conflict.word_index))
+                .ok_or_else(|| format!("No lemma annotation for word {}", conflict.word_index))?;
+
+            let mut chosen = match resolution.choice {
+                ConflictChoice::Ours => conflict.ours.clone(),
+                ConflictChoice::Theirs => conflict.theirs.clone(),
+            };
+            chosen.metadata.get_or_insert_with(Default::default).status =
+                Some(ReviewStatus::Accepted);
+            self.annotations[pos] = chosen;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::AnnotationMetadata;
+
+    fn lemma(idx: u32, lemma: &str, msa: &str, author: &str) -> Annotation {
+        let mut ann = Annotation::lemma(idx, lemma.to_string(), msa.to_string(), None);
+        ann.metadata = Some(AnnotationMetadata {
+            author: Some(author.to_string()),
+            ..Default::default()
+        });
+        ann
+    }
+
+    fn set_of(annotations: Vec<Annotation>) -> AnnotationSet {
+        let mut set = AnnotationSet::new();
+        for ann in annotations {
+            set.add(ann);
+        }
+        set
+    }
+
+    #[test]
+    fn test_merge_disjoint_and_agreeing_lemmas() {
+        let ours = set_of(vec![
+            lemma(0, "maðr", "xNC cN nS gM", "anna"),
+            lemma(1, "vera", "xVB", "anna"),
+        ]);
+        let theirs = set_of(vec![
+            lemma(1, "vera", "xVB", "bjorn"),
+            lemma(2, "kona", "xNC cN nS gF", "bjorn"),
+        ]);
+
+        let result = ours.merge(&theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged.lemma_map().len(), 3);
+        assert_eq!(result.merged.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_reports_lemma_and_msa_conflicts() {
+        let ours = set_of(vec![lemma(0, "maðr", "xNC cN nS gM", "anna")]);
+        let theirs = set_of(vec![lemma(0, "mær", "xNC cA nS gF", "bjorn")]);
+
+        let result = ours.merge(&theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.word_index, 0);
+        assert_eq!(conflict.fields, vec!["lemma", "msa"]);
+        // Our side is held until resolved
+        assert_eq!(result.merged.lemma_map()[&0].lemma, "maðr");
+    }
+
+    #[test]
+    fn test_merge_rejected_side_gives_way() {
+        let mut ours = set_of(vec![lemma(0, "maðr", "xNC", "anna")]);
+        ours.set_status("lemma-0", ReviewStatus::Rejected);
+        let theirs = set_of(vec![lemma(0, "mær", "xNC", "bjorn")]);
+
+        let result = ours.merge(&theirs);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged.lemma_map()[&0].lemma, "mær");
+    }
+
+    #[test]
+    fn test_merge_agreeing_lemmas_keep_the_reviewed_side() {
+        let mut ours = set_of(vec![lemma(0, "maðr", "xNC", "anna")]);
+        ours.set_status("lemma-0", ReviewStatus::Rejected);
+        let theirs = set_of(vec![lemma(0, "maðr", "xNC", "bjorn")]);
+
+        let result = ours.merge(&theirs);
+        assert!(result.conflicts.is_empty());
+        let kept = result.merged.get("lemma-0").unwrap();
+        assert_eq!(kept.review_status(), ReviewStatus::Accepted);
+        assert_eq!(result.merged.lemma_map()[&0].lemma, "maðr");
+
+        // And the other way round, ours is not downgraded
+        let result = theirs.merge(&ours);
+        assert!(!result.merged.get("lemma-0").unwrap().is_rejected());
+    }
+
+    #[test]
+    fn test_merge_dedupes_and_renames_other_annotations() {
+        let mut note = Annotation::note(
+            AnnotationTarget::Word { word_index: 3 },
+            "damaged".to_string(),
+            None,
+        );
+        note.id = "note-1".to_string();
+        let mut other_note = note.clone();
+        other_note.value = AnnotationValue::Note {
+            text: "rubric".to_string(),
+            category: None,
+        };
+
+        let ours = set_of(vec![note.clone()]);
+        let theirs = set_of(vec![note, other_note]);
+
+        let result = ours.merge(&theirs);
+        assert_eq!(result.merged.len(), 2);
+        assert!(result.merged.get("note-1-1").is_some());
+    }
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let ours = set_of(vec![
+            lemma(0, "maðr", "xNC", "anna"),
+            lemma(1, "vera", "xVB", "anna"),
+        ]);
+        let theirs = set_of(vec![
+            lemma(0, "mær", "xNC", "bjorn"),
+            lemma(1, "verða", "xVB", "bjorn"),
+        ]);
+
+        let MergeResult { mut merged, conflicts } = ours.merge(&theirs);
+        merged
+            .resolve_conflicts(
+                &conflicts,
+                &[
+                    ConflictResolution { word_index: 0, choice: ConflictChoice::Theirs },
+                    ConflictResolution { word_index: 1, choice: ConflictChoice::Ours },
+                ],
+            )
+            .unwrap();
+
+        let lemmas = merged.lemma_map();
+        assert_eq!(lemmas[&0].lemma, "mær");
+        assert_eq!(lemmas[&1].lemma, "vera");
+        assert_eq!(merged.get("lemma-0").unwrap().review_status(), ReviewStatus::Accepted);
+
+        let err = merged.resolve_conflicts(
+            &conflicts,
+            &[ConflictResolution { word_index: 9, choice: ConflictChoice::Ours }],
+        );
+        assert!(err.is_err());
+    }
+}
@@ -7,6 +7,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod merge;
+pub mod table;
+
+pub use merge::{AnnotationConflict, ConflictResolution, MergeResult};
+
 /// A single annotation attached to a target in the text.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -81,6 +86,23 @@ pub enum AnnotationTarget {
         #[serde(rename = "endWord")]
         end_word: u32,
     },
+    /// Character range that may cross word (and line) boundaries, e.g. damage
+    /// starting mid-word and ending in the next word
+    #[serde(rename = "charRange")]
+    CharacterRange {
+        /// Word containing the first character
+        #[serde(rename = "startWord")]
+        start_word: u32,
+        /// Offset of the first character in the start word (0-based, inclusive)
+        #[serde(rename = "startChar")]
+        start_char: u32,
+        /// Word containing the last character
+        #[serde(rename = "endWord")]
+        end_word: u32,
+        /// Offset of the last character in the end word (0-based, inclusive)
+        #[serde(rename = "endChar")]
+        end_char: u32,
+    },
 }
 
 /// Annotation value - the actual content of the annotation
@@ -371,6 +393,23 @@ pub struct AnnotationMetadata {
     /// Additional notes about the annotation itself
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+
+    /// Review state; annotations without one are treated as accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ReviewStatus>,
+}
+
+/// Review state of an annotation in a multi-annotator workflow
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStatus {
+    /// Suggested by an annotator, awaiting review
+    Proposed,
+    /// Reviewed and accepted
+    Accepted,
+    /// Reviewed and rejected; kept for provenance but left out of the compiled
+    /// output (lemma attributes, annotation markup and notes)
+    Rejected,
 }
 
 /// Collection of all annotations for a document
@@ -426,6 +465,37 @@ impl AnnotationSet {
             .collect()
     }
 
+    /// Get all non-rejected annotations for a specific word index
+    pub fn active_for_word(&self, word_index: u32) -> Vec<&Annotation> {
+        self.annotations
+            .iter()
+            .filter(|a| a.target.includes_word(word_index) && !a.is_rejected())
+            .collect()
+    }
+
+    /// Set the review status of an annotation. Returns false if the ID is unknown.
+    pub fn set_status(&mut self, id: &str, status: ReviewStatus) -> bool {
+        let Some(ann) = self.get_mut(id) else {
+            return false;
+        };
+        ann.metadata.get_or_insert_with(Default::default).status = Some(status);
+        true
+    }
+
+    /// Distinct authors of non-rejected annotations, sorted
+    pub fn authors(&self) -> Vec<String> {
+        let mut authors: Vec<String> = self
+            .annotations
+            .iter()
+            .filter(|a| !a.is_rejected())
+            .filter_map(|a| a.metadata.as_ref()?.author.clone())
+            .filter(|author| !author.trim().is_empty())
+            .collect();
+        authors.sort();
+        authors.dedup();
+        authors
+    }
+
     /// Get all annotations of a specific type
     pub fn by_type(&self, annotation_type: AnnotationType) -> Vec<&Annotation> {
         self.annotations
@@ -434,10 +504,10 @@ impl AnnotationSet {
             .collect()
     }
 
-    /// Get all lemma annotations as a map (for backward compatibility)
+    /// Get all non-rejected lemma annotations as a map (for backward compatibility)
     pub fn lemma_map(&self) -> HashMap<u32, LemmaInfo> {
         let mut map = HashMap::new();
-        for ann in &self.annotations {
+        for ann in self.annotations.iter().filter(|a| !a.is_rejected()) {
             if let (
                 AnnotationType::Lemma,
                 AnnotationTarget::Word { word_index },
@@ -493,6 +563,36 @@ impl AnnotationTarget {
                 start_word,
                 end_word,
             } => word_index >= *start_word && word_index <= *end_word,
+            AnnotationTarget::CharacterRange {
+                start_word,
+                end_word,
+                ..
+            } => word_index >= *start_word && word_index <= *end_word,
+        }
+    }
+
+    /// Character range covered within a single word, as inclusive offsets.
+    ///
+    /// `None` for word and span targets. For a cross-word range the pieces in the
+    /// first and middle words run to the end of the word, signalled by `u32::MAX`.
+    pub fn char_range_in_word(&self, word_index: u32) -> Option<(u32, u32)> {
+        match self {
+            AnnotationTarget::Character {
+                word_index: idx,
+                char_start,
+                char_end,
+            } if *idx == word_index => Some((*char_start, *char_end)),
+            AnnotationTarget::CharacterRange {
+                start_word,
+                start_char,
+                end_word,
+                end_char,
+            } if self.includes_word(word_index) => {
+                let start = if word_index == *start_word { *start_char } else { 0 };
+                let end = if word_index == *end_word { *end_char } else { u32::MAX };
+                Some((start, end))
+            }
+            _ => None,
         }
     }
 
@@ -502,12 +602,26 @@ impl AnnotationTarget {
             AnnotationTarget::Word { word_index } => *word_index,
             AnnotationTarget::Character { word_index, .. } => *word_index,
             AnnotationTarget::Span { start_word, .. } => *start_word,
+            AnnotationTarget::CharacterRange { start_word, .. } => *start_word,
         }
     }
 }
 
 #[allow(dead_code)]
 impl Annotation {
+    /// Review status of this annotation (accepted when not recorded)
+    pub fn review_status(&self) -> ReviewStatus {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.status)
+            .unwrap_or(ReviewStatus::Accepted)
+    }
+
+    /// Whether a reviewer rejected this annotation
+    pub fn is_rejected(&self) -> bool {
+        self.review_status() == ReviewStatus::Rejected
+    }
+
     /// Create a new lemma annotation (convenience constructor)
     pub fn lemma(word_index: u32, lemma: String, msa: String, normalized: Option<String>) -> Self {
         Self {
@@ -559,6 +673,25 @@ impl Annotation {
     }
 }
 
+/// The default `xml:id` of the `<respStmt>` generated for an annotation author.
+///
+/// Statements that already have an id keep it, and colliding slugs are
+/// numbered; see [`Metadata::add_resp_stmts`], whose returned ids are what
+/// `@resp="#..."` pointers should use.
+///
+/// [`Metadata::add_resp_stmts`]: crate::metadata::Metadata::add_resp_stmts
+pub fn resp_id(author: &str) -> String {
+    let mut slug = String::new();
+    for c in author.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    format!("resp-{}", slug.trim_end_matches('-'))
+}
+
 /// Generate a simple unique ID (timestamp + random suffix)
 #[allow(dead_code)]
 fn uuid_simple() -> String {
@@ -646,6 +779,29 @@ mod tests {
         assert!(!char_target.includes_word(8));
     }
 
+    #[test]
+    fn test_character_range_target() {
+        let target = AnnotationTarget::CharacterRange {
+            start_word: 3,
+            start_char: 2,
+            end_word: 5,
+            end_char: 1,
+        };
+        assert!(!target.includes_word(2));
+        assert!(target.includes_word(4));
+        assert_eq!(target.primary_word_index(), 3);
+        assert_eq!(target.char_range_in_word(3), Some((2, u32::MAX)));
+        assert_eq!(target.char_range_in_word(4), Some((0, u32::MAX)));
+        assert_eq!(target.char_range_in_word(5), Some((0, 1)));
+        assert_eq!(target.char_range_in_word(6), None);
+
+        let json = serde_json::to_string(&target).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"charRange","startWord":3,"startChar":2,"endWord":5,"endChar":1}"#
+        );
+    }
+
     #[test]
     fn test_serialization_roundtrip() {
         let mut set = AnnotationSet::new();
@@ -735,6 +891,24 @@ mod tests {
         assert!(matches!(target, AnnotationTarget::Span { start_word: 10, end_word: 15 }));
     }
 
+    #[test]
+    fn test_authors_and_resp_id() {
+        let mut set = AnnotationSet::new();
+        for (idx, author) in [(0, "Bjørn Ólafsson"), (1, "Anna"), (2, "Anna")] {
+            let mut ann = Annotation::lemma(idx, "ok".to_string(), "xCC".to_string(), None);
+            ann.metadata = Some(AnnotationMetadata {
+                author: Some(author.to_string()),
+                ..Default::default()
+            });
+            set.add(ann);
+        }
+        set.set_status("lemma-1", ReviewStatus::Rejected);
+
+        assert_eq!(set.authors(), vec!["Anna", "Bjørn Ólafsson"]);
+        assert_eq!(resp_id("Bjørn Ólafsson"), "resp-bjørn-ólafsson");
+        assert_eq!(resp_id(" J. R. R. "), "resp-j-r-r");
+    }
+
     #[test]
     fn test_annotation_value_camelcase() {
         // Verify AnnotationValue fields also serialize to camelCase
@@ -0,0 +1,517 @@
+//! Tabular (CSV/TSV) export and import of word annotations.
+//!
+//! Each row is one indexed word of the compiled document, numbered as the compiler
+//! numbers them (words in `.head{}`/`.supplied{}` fragments have no row). The
+//! export lists the word's position, its facs/dipl/norm forms and its
+//! annotations, so lemmatisation can be done in a spreadsheet. The import
+//! checks every row against the word at its index before applying the lemma and
+//! `ana` columns, and reports rows that no longer match.
+
+use super::{
+    Annotation, AnnotationMetadata, AnnotationSet, AnnotationTarget, AnnotationType,
+    AnnotationValue, PaleographicType,
+};
+use crate::importer::tei::find_body;
+use crate::importer::tei::word_annotations::{
+    annotation_from_ana, collect_word_attributes, WordAttributes,
+};
+use libxml::parser::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Column headers, in export order
+const COLUMNS: [&str; 10] = [
+    "index", "page", "line", "facs", "dipl", "norm", "lemma", "msa", "ana", "notes",
+];
+
+/// Separator between multiple notes in the `notes` column
+const NOTE_SEPARATOR: &str = " | ";
+
+/// Delimited text flavour
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    Csv,
+    Tsv,
+}
+
+impl TableFormat {
+    fn delimiter(self) -> char {
+        match self {
+            TableFormat::Csv => ',',
+            TableFormat::Tsv => '\t',
+        }
+    }
+}
+
+/// A table row that could not be applied
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableMismatch {
+    /// 1-based row number in the table (the header is row 1)
+    pub row: usize,
+    pub word_index: Option<u32>,
+    pub message: String,
+}
+
+/// Result of applying a table to an annotation set
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableImportResult {
+    pub annotations: AnnotationSet,
+    /// Number of rows that changed the set
+    pub applied: usize,
+    pub mismatches: Vec<TableMismatch>,
+}
+
+/// Collect the indexed words of a compiled document (full TEI or a bare body
+/// fragment), so that the word with index `i` is at position `i`.
+pub fn words_from_xml(xml: &str) -> Result<Vec<WordAttributes>, String> {
+    let wrapped;
+    let source = if xml.contains("<body") {
+        xml
+    } else {
+        wrapped = format!("<body>{}</body>", xml);
+        &wrapped
+    };
+
+    let doc = Parser::default()
+        .parse_string(source)
+        .map_err(|e| format!("Failed to parse XML: {}", e))?;
+    let root = doc.get_root_element().ok_or("No root element found")?;
+    let body = find_body(&root).ok_or("No <body> element found in XML")?;
+    Ok(collect_word_attributes(&body)
+        .into_iter()
+        .filter(|word| word.word_index.is_some())
+        .collect())
+}
+
+/// Export every word of `xml` with its annotations as a CSV or TSV table.
+pub fn export_table(
+    xml: &str,
+    annotations: &AnnotationSet,
+    format: TableFormat,
+) -> Result<String, String> {
+    let words = words_from_xml(xml)?;
+    let delimiter = format.delimiter();
+    let mut output = String::new();
+
+    write_row(
+        &mut output,
+        COLUMNS.iter().map(|c| c.to_string()),
+        delimiter,
+    );
+
+    for (word_index, word) in (0u32..).zip(&words) {
+        let mut lemma = String::new();
+        let mut msa = String::new();
+        let mut ana = Vec::new();
+        let mut notes = Vec::new();
+
+        for ann in annotations.active_for_word(word_index) {
+            match &ann.value {
+                AnnotationValue::Lemma {
+                    lemma: l, msa: m, ..
+                } => {
+                    lemma = l.clone();
+                    msa = m.clone();
+                }
+                AnnotationValue::Semantic {
+                    category,
+                    subcategory,
+                    ..
+                } => {
+                    ana.push(match subcategory {
+                        Some(sub) => format!("#{}:{}", category, sub),
+                        None => format!("#{}", category),
+                    });
+                }
+                AnnotationValue::Paleographic {
+                    observation_type, ..
+                } => {
+                    ana.push(format!("#paleo:{}", paleo_code(*observation_type)));
+                }
+                AnnotationValue::Note { text, .. } => notes.push(text.clone()),
+                _ => {}
+            }
+        }
+
+        write_row(
+            &mut output,
+            [
+                word_index.to_string(),
+                word.page.clone().unwrap_or_default(),
+                word.line.clone().unwrap_or_default(),
+                word.facsimile.clone().unwrap_or_default(),
+                word.diplomatic.clone().unwrap_or_default(),
+                word.normalized.clone().unwrap_or_default(),
+                lemma,
+                msa,
+                ana.join(" "),
+                notes.join(NOTE_SEPARATOR),
+            ]
+            .into_iter(),
+            delimiter,
+        );
+    }
+
+    Ok(output)
+}
+
+/// Apply a CSV or TSV table to a copy of `annotations`.
+///
+/// Columns are matched by header name, so they may be reordered or omitted; only
+/// `index` is required. A row is applied only if every non-empty `facs`, `dipl`
+/// and `norm` cell equals the form of the word at its index. A non-empty `lemma`
+/// replaces the word's lemma annotation; `ana` codes are added as semantic or
+/// paleographic annotations. Empty cells never remove existing annotations.
+pub fn import_table(
+    xml: &str,
+    table: &str,
+    annotations: &AnnotationSet,
+    format: TableFormat,
+) -> Result<TableImportResult, String> {
+    let words = words_from_xml(xml)?;
+    let rows = parse_rows(table, format.delimiter());
+    let (header, data) = rows.split_first().ok_or("Table is empty")?;
+
+    let columns: HashMap<String, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim().to_lowercase(), i))
+        .collect();
+    let index_col = *columns.get("index").ok_or("Table has no 'index' column")?;
+    let cell = |row: &[String], name: &str| -> String {
+        columns
+            .get(name)
+            .and_then(|&i| row.get(i))
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut result = TableImportResult {
+        annotations: annotations.clone(),
+        applied: 0,
+        mismatches: Vec::new(),
+    };
+
+    for (offset, row) in data.iter().enumerate() {
+        let row_number = offset + 2;
+        if row.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+
+        let raw_index = row.get(index_col).map(|v| v.trim()).unwrap_or("");
+        let Ok(word_index) = raw_index.parse::<u32>() else {
+            result.mismatches.push(TableMismatch {
+                row: row_number,
+                word_index: None,
+                message: format!("Invalid word index '{}'", raw_index),
+            });
+            continue;
+        };
+        let Some(word) = words.get(word_index as usize) else {
+            result.mismatches.push(TableMismatch {
+                row: row_number,
+                word_index: Some(word_index),
+                message: format!(
+                    "Word index {} is out of range ({} words)",
+                    word_index,
+                    words.len()
+                ),
+            });
+            continue;
+        };
+
+        let levels = [
+            ("facs", &word.facsimile),
+            ("dipl", &word.diplomatic),
+            ("norm", &word.normalized),
+        ];
+        let form_mismatch = levels.iter().find_map(|(name, actual)| {
+            let expected = cell(row, name);
+            let actual = actual.as_deref().unwrap_or("");
+            (!expected.is_empty() && expected != actual).then(|| {
+                format!(
+                    "{} form '{}' does not match '{}' in the text",
+                    name, expected, actual
+                )
+            })
+        });
+        if let Some(message) = form_mismatch {
+            result.mismatches.push(TableMismatch {
+                row: row_number,
+                word_index: Some(word_index),
+                message,
+            });
+            continue;
+        }
+
+        let mut changed = false;
+
+        let lemma = cell(row, "lemma");
+        if !lemma.is_empty() {
+            let set = &mut result.annotations;
+            set.annotations.retain(|ann| {
+                !(ann.annotation_type == AnnotationType::Lemma
+                    && ann.target == AnnotationTarget::Word { word_index })
+            });
+            let mut ann =
+                Annotation::lemma(word_index, lemma, cell(row, "msa"), word.normalized.clone());
+            ann.metadata = Some(table_metadata());
+            set.add(ann);
+            changed = true;
+        }
+
+        for (n, code) in cell(row, "ana").split_whitespace().enumerate() {
+            let code = code.trim_start_matches('#');
+            let Some(mut ann) = annotation_from_ana(word_index, code) else {
+                continue;
+            };
+            let set = &mut result.annotations;
+            let exists = set.annotations.iter().any(|a| {
+                a.annotation_type == ann.annotation_type
+                    && a.target == ann.target
+                    && a.value == ann.value
+            });
+            if exists {
+                continue;
+            }
+            let prefix = if ann.annotation_type == AnnotationType::Paleographic {
+                "paleo"
+            } else {
+                "sem"
+            };
+            let base = format!("{}-{}-{}", prefix, word_index, n);
+            ann.id = base.clone();
+            let mut suffix = 1;
+            while set.get(&ann.id).is_some() {
+                ann.id = format!("{}-{}", base, suffix);
+                suffix += 1;
+            }
+            ann.metadata = Some(table_metadata());
+            set.add(ann);
+            changed = true;
+        }
+
+        if changed {
+            result.applied += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+fn table_metadata() -> AnnotationMetadata {
+    AnnotationMetadata {
+        source: Some("table".to_string()),
+        ..Default::default()
+    }
+}
+
+/// `@ana` code for a paleographic observation (mirrors the compiler)
+fn paleo_code(observation_type: PaleographicType) -> &'static str {
+    match observation_type {
+        PaleographicType::Unclear => "unclear",
+        PaleographicType::Damage => "damage",
+        PaleographicType::Erasure => "erasure",
+        PaleographicType::Letterform => "letterform",
+        PaleographicType::Abbreviation => "abbrev-mark",
+        PaleographicType::Correction => "correction",
+        PaleographicType::Addition => "addition",
+        PaleographicType::Decoration => "decoration",
+        PaleographicType::Other => "paleo",
+    }
+}
+
+fn write_row(output: &mut String, fields: impl Iterator<Item = String>, delimiter: char) {
+    let mut first = true;
+    for field in fields {
+        if !first {
+            output.push(delimiter);
+        }
+        first = false;
+        if field.contains(delimiter)
+            || field.contains('"')
+            || field.contains('\n')
+            || field.contains('\r')
+        {
+            output.push('"');
+            output.push_str(&field.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(&field);
+        }
+    }
+    output.push('\n');
+}
+
+/// Split delimited text into rows, honouring double-quoted fields.
+fn parse_rows(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else {
+            field.push(c);
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str =
+        r#"<body><pb n="1r"/><lb n="1"/><w>konungr</w> <w>ok</w><lb n="2"/><w>saga</w></body>"#;
+
+    fn lemma_set() -> AnnotationSet {
+        let mut set = AnnotationSet::new();
+        set.add(Annotation::lemma(
+            0,
+            "konungr".to_string(),
+            "xNC cN nS gM".to_string(),
+            None,
+        ));
+        set.add(Annotation::semantic(
+            AnnotationTarget::Word { word_index: 2 },
+            "work".to_string(),
+            Some("title".to_string()),
+            None,
+        ));
+        set
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let csv = export_table(BODY, &lemma_set(), TableFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "index,page,line,facs,dipl,norm,lemma,msa,ana,notes"
+        );
+        assert_eq!(lines[1], "0,1r,1,konungr,,,konungr,xNC cN nS gM,,");
+        assert_eq!(lines[3], "2,1r,2,saga,,,,,#work:title,");
+    }
+
+    #[test]
+    fn test_heading_words_have_no_row() {
+        use crate::parser::{Compiler, CompilerConfig};
+
+        let mut compiler = Compiler::new().with_config(CompilerConfig {
+            word_wrap: true,
+            multi_level: true,
+            ..Default::default()
+        });
+        let xml = compiler.compile("konungr .head{Saga} ok").unwrap();
+        assert!(xml.contains("<head>"));
+
+        let mut set = AnnotationSet::new();
+        set.add(Annotation::lemma(
+            1,
+            "ok".to_string(),
+            "xCC".to_string(),
+            None,
+        ));
+        let csv = export_table(&xml, &set, TableFormat::Csv).unwrap();
+        let rows = parse_rows(&csv, ',');
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2][..4], ["1", "", "", "ok"]);
+        assert_eq!(rows[2][6], "ok");
+
+        let result = import_table(&xml, &csv, &AnnotationSet::new(), TableFormat::Csv).unwrap();
+        assert!(result.mismatches.is_empty());
+        assert_eq!(result.annotations.lemma_map()[&1].lemma, "ok");
+    }
+
+    #[test]
+    fn test_export_quotes_fields() {
+        let mut set = AnnotationSet::new();
+        set.add(Annotation::note(
+            AnnotationTarget::Word { word_index: 1 },
+            "say \"ok\", twice".to_string(),
+            None,
+        ));
+        let csv = export_table(BODY, &set, TableFormat::Csv).unwrap();
+        assert!(csv.contains("\"say \"\"ok\"\", twice\""));
+        assert_eq!(parse_rows(&csv, ',')[2][9], "say \"ok\", twice");
+    }
+
+    #[test]
+    fn test_tsv_roundtrip() {
+        let set = lemma_set();
+        let tsv = export_table(BODY, &set, TableFormat::Tsv).unwrap();
+        let result = import_table(BODY, &tsv, &AnnotationSet::new(), TableFormat::Tsv).unwrap();
+        assert!(result.mismatches.is_empty());
+        assert_eq!(result.applied, 2);
+        assert_eq!(result.annotations.lemma_map()[&0].msa, "xNC cN nS gM");
+        assert_eq!(
+            result.annotations.by_type(AnnotationType::Semantic).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_import_reports_mismatches() {
+        let table = "lemma,index,facs\n\
+                     ok,1,ok\n\
+                     saga,2,sögu\n\
+                     x,7,\n\
+                     y,abc,\n";
+        let result = import_table(BODY, table, &lemma_set(), TableFormat::Csv).unwrap();
+
+        assert_eq!(result.applied, 1);
+        assert_eq!(result.annotations.lemma_map()[&1].lemma, "ok");
+        let rows: Vec<usize> = result.mismatches.iter().map(|m| m.row).collect();
+        assert_eq!(rows, vec![3, 4, 5]);
+        assert!(result.mismatches[0].message.contains("sögu"));
+    }
+
+    #[test]
+    fn test_import_replaces_lemma_and_requires_index() {
+        let result = import_table(
+            BODY,
+            "index,lemma,msa\n0,konungr,xNC cG\n",
+            &lemma_set(),
+            TableFormat::Csv,
+        )
+        .unwrap();
+        let lemmas = result.annotations.by_type(AnnotationType::Lemma);
+        assert_eq!(lemmas.len(), 1);
+        assert_eq!(result.annotations.lemma_map()[&0].msa, "xNC cG");
+
+        assert!(import_table(BODY, "lemma\nok\n", &lemma_set(), TableFormat::Csv).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! Tauri commands for multi-annotator workflows and tabular annotation exchange.
+
+use crate::annotations::table::{self, TableFormat, TableImportResult};
+use crate::annotations::{
+    AnnotationConflict, AnnotationSet, ConflictResolution, MergeResult, ReviewStatus,
+};
+
+fn parse_set(json: &str, label: &str) -> Result<AnnotationSet, String> {
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse {} annotations: {}", label, e))
+}
+
+/// Merge another annotator's set into ours, reporting lemma/msa conflicts
+#[tauri::command(rename_all = "camelCase")]
+pub fn merge_annotation_sets(ours_json: String, theirs_json: String) -> Result<MergeResult, String> {
+    let ours = parse_set(&ours_json, "our")?;
+    let theirs = parse_set(&theirs_json, "their")?;
+    Ok(ours.merge(&theirs))
+}
+
+/// Apply conflict decisions to a merged set
+#[tauri::command(rename_all = "camelCase")]
+pub fn resolve_annotation_conflicts(
+    merged_json: String,
+    conflicts: Vec<AnnotationConflict>,
+    resolutions: Vec<ConflictResolution>,
+) -> Result<AnnotationSet, String> {
+    let mut merged = parse_set(&merged_json, "merged")?;
+    merged.resolve_conflicts(&conflicts, &resolutions)?;
+    Ok(merged)
+}
+
+/// Set the review status of one annotation
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_annotation_status(
+    annotations_json: String,
+    id: String,
+    status: ReviewStatus,
+) -> Result<AnnotationSet, String> {
+    let mut annotations = parse_set(&annotations_json, "document")?;
+    if !annotations.set_status(&id, status) {
+        return Err(format!("Annotation '{}' not found", id));
+    }
+    Ok(annotations)
+}
+
+/// Export every word of the compiled XML with its annotations as CSV or TSV
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_annotation_table(
+    xml_content: String,
+    annotations_json: String,
+    format: TableFormat,
+) -> Result<String, String> {
+    let annotations = parse_set(&annotations_json, "document")?;
+    table::export_table(&xml_content, &annotations, format)
+}
+
+/// Validate a CSV or TSV table against the compiled XML and apply its rows
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_annotation_table(
+    xml_content: String,
+    table_content: String,
+    annotations_json: String,
+    format: TableFormat,
+) -> Result<TableImportResult, String> {
+    let annotations = parse_set(&annotations_json, "document")?;
+    table::import_table(&xml_content, &table_content, &annotations, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::merge::ConflictChoice;
+    use crate::annotations::Annotation;
+
+    fn set_json(lemma: &str) -> String {
+        let mut set = AnnotationSet::new();
+        set.add(Annotation::lemma(0, lemma.to_string(), "xNC".to_string(), None));
+        serde_json::to_string(&set).unwrap()
+    }
+
+    #[test]
+    fn test_merge_and_resolve() {
+        let result = merge_annotation_sets(set_json("maðr"), set_json("mær")).unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+
+        let merged_json = serde_json::to_string(&result.merged).unwrap();
+        let resolved = resolve_annotation_conflicts(
+            merged_json,
+            result.conflicts,
+            vec![ConflictResolution { word_index: 0, choice: ConflictChoice::Theirs }],
+        )
+        .unwrap();
+        assert_eq!(resolved.lemma_map()[&0].lemma, "mær");
+    }
+
+    #[test]
+    fn test_rejected_lemma_leaves_map() {
+        let id = parse_set(&set_json("maðr"), "test").unwrap().annotations[0].id.clone();
+        let rejected = set_annotation_status(set_json("maðr"), id, ReviewStatus::Rejected).unwrap();
+        assert!(rejected.lemma_map().is_empty());
+
+        let missing = set_annotation_status(set_json("maðr"), "x".to_string(), ReviewStatus::Accepted);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_merge_invalid_json() {
+        assert!(merge_annotation_sets("{".to_string(), set_json("maðr")).is_err());
+    }
+}
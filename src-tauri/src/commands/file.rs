@@ -17,6 +17,12 @@
 //! ├── original_body.xml    # Original body for round-trip (v1.3+)
 //! ├── original_preamble.xml # XML before <body> (v1.4+)
 //! ├── original_postamble.xml # XML after </body> (v1.4+)
+//! ├── resources/           # Embedded compiler resources (v1.7+, optional)
+//! │   ├── compiler.json        # Compiler flags
+//! │   ├── entities.json        # Custom entity definitions
+//! │   ├── entity-mappings.json # Custom diplomatic mappings
+//! │   └── normalizer.json      # Normalizer level dictionary
+//! ├── facsimile.json       # Page layout from an HTR import (v1.8+)
 //! └── manifest.json        # Project metadata
 //! ```
 //!
@@ -29,10 +35,20 @@
 //! | 1.2 | Added annotations.json (full annotation set) |
 //! | 1.3 | Added segments.json and original_body.xml for round-trip |
 //! | 1.4 | Added original_preamble.xml and original_postamble.xml |
+//! | 1.5 | segments.json holds the full manifest, with front/back matter and grouped texts |
+//! | 1.6 | metadata.json keeps a revision history; `created` survives re-saving |
+//! | 1.7 | Optional resources/ folder so the project compiles the same on any machine |
+//! | 1.8 | Added facsimile.json (page images and line zones from PAGE XML / ALTO) |
 
 use crate::annotations::AnnotationSet;
+use crate::entities::Entity;
+use crate::importer::htr::Facsimile;
+use crate::importer::menotec;
 use crate::importer::tei::segments::ImportedDocument;
+use crate::metadata::header;
+use crate::metadata::revisions::{self, SaveStats};
 use crate::metadata::Metadata;
+use crate::parser::CompilerConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
@@ -72,6 +88,15 @@ pub fn export_tei(path: String, tei_content: String) -> Result<(), String> {
     fs::write(&path, &tei_content).map_err(|e| e.to_string())
 }
 
+/// Export DSL as Menotec plain text, returning warnings for constructs that
+/// Menotec cannot represent.
+#[tauri::command]
+pub fn export_menotec(path: String, dsl: String) -> Result<Vec<String>, String> {
+    let export = menotec::export(&dsl)?;
+    fs::write(PathBuf::from(path), &export.text).map_err(|e| e.to_string())?;
+    Ok(export.warnings)
+}
+
 #[tauri::command]
 pub fn export_html(path: String, html_content: String) -> Result<(), String> {
     let path = PathBuf::from(path);
@@ -88,7 +113,7 @@ pub fn export_html(path: String, html_content: String) -> Result<(), String> {
 /// Project metadata stored in manifest.json within the archive.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectManifest {
-    /// Archive format version (e.g., "1.4")
+    /// Archive format version (e.g., "1.6")
     pub version: String,
     /// ID of the TEI template used
     pub template_id: String,
@@ -106,6 +131,56 @@ pub struct LemmaConfirmation {
     pub normalized: Option<String>,
 }
 
+/// A change entry to record in the revision history when saving.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionRequest {
+    /// Name of the person saving
+    pub who: Option<String>,
+    /// Description of the change, put before the generated statistics
+    pub summary: Option<String>,
+}
+
+/// Compiler configuration and resources embedded in a project (v1.7+).
+///
+/// When present they take the place of the active template's flags and the
+/// user's own entities and dictionaries, so that the project compiles the
+/// same wherever it is opened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectResources {
+    /// Compiler flags in effect when the project was saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler_config: Option<CompilerConfig>,
+    /// Custom entity definitions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entities: Option<HashMap<String, Entity>>,
+    /// Custom diplomatic (entity base letter) mappings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_mappings: Option<HashMap<String, String>>,
+    /// Normalizer level dictionary, as the raw JSON file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalizer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntitiesFile {
+    version: String,
+    entities: HashMap<String, Entity>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MappingsFile {
+    version: String,
+    mappings: HashMap<String, String>,
+}
+
+impl ProjectResources {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
 /// Complete project data loaded from a .teis archive.
 ///
 /// This struct is returned by [`open_project`] and contains all data
@@ -138,12 +213,26 @@ pub struct ProjectData {
     /// XML content after `</body>` element (v1.4+)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub original_postamble: Option<String>,
+    /// Embedded compiler configuration and resources (v1.7+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ProjectResources>,
+    /// Page layout from an HTR import (v1.8+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facsimile: Option<Facsimile>,
 }
 
 /// Saves a project to a .teis archive file.
 ///
 /// Creates a ZIP archive containing all project data. The archive format
 /// is versioned to support backward compatibility with older app versions.
+///
+/// With a `revision_json` request, a `<change>` describing what differs from
+/// the archive being overwritten is added to the metadata's revision history
+/// and to the header of `output`. The updated metadata is returned so the
+/// editor can pick up the new entry.
+///
+/// With `resources_json`, the given [`ProjectResources`] are embedded under
+/// `resources/`; `facsimile_json` is stored as `facsimile.json`.
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub fn save_project(
@@ -158,8 +247,66 @@ pub fn save_project(
     original_body_xml: Option<String>,
     original_preamble: Option<String>,
     original_postamble: Option<String>,
-) -> Result<(), String> {
+    revision_json: Option<String>,
+    resources_json: Option<String>,
+    facsimile_json: Option<String>,
+) -> Result<Option<Metadata>, String> {
+    let resources: Option<ProjectResources> = match resources_json {
+        Some(json) => Some(
+            serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse project resources: {}", e))?,
+        ),
+        None => None,
+    };
     let path = PathBuf::from(&path);
+    let previous = open_project(path.to_string_lossy().into_owned()).ok();
+    let now = chrono_lite_now();
+
+    let mut metadata_json = metadata_json;
+    let mut output = output;
+    let mut revised = None;
+    if let Some(json) = revision_json {
+        let request: RevisionRequest =
+            serde_json::from_str(&json).map_err(|e| format!("Failed to parse revision: {}", e))?;
+        let mut metadata: Metadata = match metadata_json {
+            Some(ref json) => serde_json::from_str(json)
+                .map_err(|e| format!("Failed to parse metadata: {}", e))?,
+            None => Metadata::new(),
+        };
+
+        let stats = match previous {
+            Some(ref previous) => {
+                let annotations: Option<AnnotationSet> = annotations_json
+                    .as_deref()
+                    .and_then(|json| serde_json::from_str(json).ok());
+                SaveStats::compare(
+                    &previous.source,
+                    &source,
+                    previous.annotations.as_ref(),
+                    annotations.as_ref(),
+                )
+                .summary()
+            }
+            None => "Project created".to_string(),
+        };
+        let summary = [request.summary.unwrap_or_default(), stats]
+            .into_iter()
+            .map(|part| part.trim().to_string())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if !summary.is_empty() {
+            revisions::record_change(&mut metadata, &now[..10], request.who.as_deref(), summary);
+            output = header::update_revisions(&output, &metadata)?;
+            metadata_json = Some(
+                serde_json::to_string(&metadata)
+                    .map_err(|e| format!("Failed to serialize metadata: {}", e))?,
+            );
+            revised = Some(metadata);
+        }
+    }
+
     let file = File::create(&path).map_err(|e| format!("Failed to create file: {}", e))?;
     let mut zip = ZipWriter::new(file);
     let options = SimpleFileOptions::default()
@@ -231,12 +378,53 @@ pub fn save_project(
             .map_err(|e| format!("Failed to write original_postamble.xml: {}", e))?;
     }
 
+    // Write embedded compiler resources (new in v1.7)
+    if let Some(resources) = resources {
+        let mut files = Vec::new();
+        if let Some(config) = resources.compiler_config {
+            files.push(("resources/compiler.json", serde_json::to_string_pretty(&config)));
+        }
+        if let Some(entities) = resources.entities {
+            let file = EntitiesFile {
+                version: "1.0".to_string(),
+                entities,
+            };
+            files.push(("resources/entities.json", serde_json::to_string_pretty(&file)));
+        }
+        if let Some(mappings) = resources.entity_mappings {
+            let file = MappingsFile {
+                version: "1.0".to_string(),
+                mappings,
+            };
+            files.push(("resources/entity-mappings.json", serde_json::to_string_pretty(&file)));
+        }
+        if let Some(normalizer) = resources.normalizer {
+            files.push(("resources/normalizer.json", Ok(normalizer)));
+        }
+        for (name, content) in files {
+            let content = content.map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+            zip.start_file(name, options)
+                .map_err(|e| format!("Failed to start {}: {}", name, e))?;
+            zip.write_all(content.as_bytes())
+                .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        }
+    }
+
+    // Write facsimile.json if provided (new in v1.8)
+    if let Some(ref facs_json) = facsimile_json {
+        zip.start_file("facsimile.json", options)
+            .map_err(|e| format!("Failed to start facsimile.json: {}", e))?;
+        zip.write_all(facs_json.as_bytes())
+            .map_err(|e| format!("Failed to write facsimile.json: {}", e))?;
+    }
+
     // Create and write manifest.json
-    let now = chrono_lite_now();
     let manifest = ProjectManifest {
-        version: "1.4".to_string(),
+        version: "1.8".to_string(),
         template_id,
-        created: now.clone(),
+        created: previous
+            .map(|previous| previous.manifest.created)
+            .unwrap_or_else(|| now.clone()),
         modified: now,
     };
     let manifest_json = serde_json::to_string_pretty(&manifest)
@@ -249,7 +437,7 @@ pub fn save_project(
     zip.finish()
         .map_err(|e| format!("Failed to finalize archive: {}", e))?;
 
-    Ok(())
+    Ok(revised)
 }
 
 /// Opens a .teis project archive and loads all contained data.
@@ -299,6 +487,14 @@ pub fn open_project(path: String) -> Result<ProjectData, String> {
     let original_preamble = read_zip_file(&mut archive, "original_preamble.xml").ok();
     let original_postamble = read_zip_file(&mut archive, "original_postamble.xml").ok();
 
+    // Read embedded compiler resources (optional, new in v1.7)
+    let resources = read_resources(&mut archive)?;
+
+    // Read facsimile.json (optional, new in v1.8)
+    let facsimile: Option<Facsimile> = read_zip_file(&mut archive, "facsimile.json")
+        .ok()
+        .and_then(|facs_str| serde_json::from_str(&facs_str).ok());
+
     Ok(ProjectData {
         source,
         output,
@@ -310,9 +506,49 @@ pub fn open_project(path: String) -> Result<ProjectData, String> {
         original_body_xml,
         original_preamble,
         original_postamble,
+        resources,
+        facsimile,
     })
 }
 
+/// Read the `resources/` folder, if the archive has one.
+fn read_resources<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<ProjectResources>, String> {
+    let compiler_config = match read_zip_file(archive, "resources/compiler.json") {
+        Ok(json) => Some(
+            serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse resources/compiler.json: {}", e))?,
+        ),
+        Err(_) => None,
+    };
+    let entities = match read_zip_file(archive, "resources/entities.json") {
+        Ok(json) => Some(
+            serde_json::from_str::<EntitiesFile>(&json)
+                .map_err(|e| format!("Failed to parse resources/entities.json: {}", e))?
+                .entities,
+        ),
+        Err(_) => None,
+    };
+    let entity_mappings = match read_zip_file(archive, "resources/entity-mappings.json") {
+        Ok(json) => Some(
+            serde_json::from_str::<MappingsFile>(&json)
+                .map_err(|e| format!("Failed to parse resources/entity-mappings.json: {}", e))?
+                .mappings,
+        ),
+        Err(_) => None,
+    };
+    let normalizer = read_zip_file(archive, "resources/normalizer.json").ok();
+
+    let resources = ProjectResources {
+        compiler_config,
+        entities,
+        entity_mappings,
+        normalizer,
+    };
+    Ok((!resources.is_empty()).then_some(resources))
+}
+
 fn read_zip_file<R: Read + std::io::Seek>(
     archive: &mut ZipArchive<R>,
     name: &str,
@@ -327,7 +563,7 @@ fn read_zip_file<R: Read + std::io::Seek>(
 }
 
 // Simple ISO 8601 timestamp without external crate
-fn chrono_lite_now() -> String {
+pub(crate) fn chrono_lite_now() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let duration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -378,3 +614,88 @@ fn chrono_lite_now() -> String {
 fn is_leap_year(year: i64) -> bool {
     (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save(
+        path: &std::path::Path,
+        resources_json: Option<String>,
+        facsimile_json: Option<String>,
+    ) {
+        save_project(
+            path.to_string_lossy().into_owned(),
+            "a b c".to_string(),
+            "<TEI/>".to_string(),
+            "{}".to_string(),
+            "tei-p5".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            resources_json,
+            facsimile_json,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_project_resources_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("saga-scribe-resources-{}.teis", std::process::id()));
+
+        save(&path, None, None);
+        let project = open_project(path.to_string_lossy().into_owned()).unwrap();
+        assert_eq!(project.manifest.version, "1.8");
+        assert!(project.resources.is_none());
+
+        let resources = ProjectResources {
+            compiler_config: Some(CompilerConfig {
+                word_wrap: true,
+                multi_level: true,
+                ..Default::default()
+            }),
+            entities: Some(HashMap::from([(
+                "aacute".to_string(),
+                Entity {
+                    unicode: "U+00E1".to_string(),
+                    char: "á".to_string(),
+                    description: "a with acute".to_string(),
+                    category: "custom".to_string(),
+                },
+            )])),
+            entity_mappings: Some(HashMap::from([("aacute".to_string(), "a".to_string())])),
+            normalizer: Some(r#"{"version":"1.0"}"#.to_string()),
+        };
+        save(&path, Some(serde_json::to_string(&resources).unwrap()), None);
+        let project = open_project(path.to_string_lossy().into_owned()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(project.resources, Some(resources));
+    }
+
+    #[test]
+    fn test_project_facsimile_round_trip() {
+        use crate::importer::htr::Surface;
+
+        let path =
+            std::env::temp_dir().join(format!("saga-scribe-facsimile-{}.teis", std::process::id()));
+        let facsimile = Facsimile {
+            surfaces: vec![Surface {
+                id: "p1".to_string(),
+                n: "1".to_string(),
+                image: Some("1r.jpg".to_string()),
+                ..Default::default()
+            }],
+        };
+        save(&path, None, Some(serde_json::to_string(&facsimile).unwrap()));
+        let project = open_project(path.to_string_lossy().into_owned()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(project.facsimile, Some(facsimile));
+    }
+}
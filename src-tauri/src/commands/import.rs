@@ -1,14 +1,38 @@
+use crate::dictionary::InflectionStore;
+use crate::importer::plaintext::{self, PlainTextPreview, PlainTextProfile};
+use crate::importer::{docx, htr, menotec};
+use crate::importer::tei::fidelity::{self, FidelityReport};
 use crate::importer::tei::{self, ImportResult};
 use std::fs;
 use std::path::Path;
+use tauri::AppHandle;
 
 /// Import a file and convert it to DSL format, also extracting metadata if available.
 ///
 /// This command is async, which means Tauri executes it on a separate async task
 /// (not the main thread), preventing UI blocking. The actual file I/O and parsing
 /// runs on a blocking thread pool via spawn_blocking.
-#[tauri::command(async)]
-pub async fn import_file(path: String) -> Result<ImportResult, String> {
+///
+/// XML files are sniffed by their root element: PAGE XML and ALTO exports from HTR
+/// tools are imported as plain DSL with their page layout; anything else is TEI.
+///
+/// `.menotec` files are converted from the Menotec plain-text format, and `.docx`
+/// files from word-processor formatting, with unmapped formatting reported.
+///
+/// When `harvest_inflections` is set, lemmatised words found in a TEI import are
+/// also merged into the persistent inflection store. When `level_overrides` is set,
+/// MENOTA words keep their diplomatic and normalized text as `.dipl{}.norm{}`.
+///
+/// Other files are plain text: converted with `plain_text_profile` if given,
+/// otherwise taken as DSL unchanged.
+#[tauri::command(async, rename_all = "camelCase")]
+pub async fn import_file(
+    app: AppHandle,
+    path: String,
+    harvest_inflections: Option<bool>,
+    level_overrides: Option<bool>,
+    plain_text_profile: Option<PlainTextProfile>,
+) -> Result<ImportResult, String> {
     // spawn_blocking moves the CPU-bound work to a thread pool,
     // while the async command itself runs off the main thread
     tauri::async_runtime::spawn_blocking(move || {
@@ -19,22 +43,81 @@ pub async fn import_file(path: String) -> Result<ImportResult, String> {
             .unwrap_or("")
             .to_lowercase();
 
+        if extension == "docx" {
+            let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+            return docx::parse(&bytes);
+        }
+
         let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
 
         match extension.as_str() {
-            "xml" | "tei" => tei::parse(&content),
-            // Plain text files - return as DSL with no metadata or segments
-            _ => Ok(ImportResult {
-                dsl: content,
-                metadata: None,
-                imported_document: None,
-                original_body_xml: None,
-                original_preamble: None,
-                original_postamble: None,
-                is_imported_mode: false,
-            }),
+            "xml" | "tei" => {
+                if let Some(format) = htr::detect_format(&content) {
+                    return htr::parse(&content, format);
+                }
+
+                let options = tei::ImportOptions {
+                    level_overrides: level_overrides.unwrap_or(false),
+                };
+                let result = tei::parse_with_options(&content, options)?;
+                if harvest_inflections.unwrap_or(false) && !result.inflections.is_empty() {
+                    let mut store = InflectionStore::load(&app)?;
+                    for harvested in &result.inflections {
+                        store.add(&harvested.wordform, harvested.inflection.clone());
+                    }
+                    store.save(&app)?;
+                }
+                Ok(result)
+            }
+            "menotec" => menotec::parse(&content),
+            _ => match &plain_text_profile {
+                Some(profile) => plaintext::parse(&content, profile),
+                // Plain text files - return as DSL with no metadata or segments
+                None => Ok(ImportResult {
+                    dsl: content,
+                    metadata: None,
+                    imported_document: None,
+                    original_body_xml: None,
+                    original_preamble: None,
+                    original_postamble: None,
+                    is_imported_mode: false,
+                    annotations: None,
+                    inflections: Vec::new(),
+                    facsimile: None,
+                    report: None,
+                }),
+            },
         }
     })
     .await
     .map_err(|e| format!("Import task failed: {}", e))?
 }
+
+/// Preview how a plain-text file converts with a profile, without importing it.
+#[tauri::command(async)]
+pub async fn preview_plain_text(
+    path: String,
+    profile: PlainTextProfile,
+) -> Result<PlainTextPreview, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        plaintext::convert(&content, &profile)
+    })
+    .await
+    .map_err(|e| format!("Preview task failed: {}", e))?
+}
+
+/// Check that a TEI file survives import and export without edits.
+///
+/// The file is imported, turned into DSL and exported again through the imported-mode
+/// patching path; the result is compared with the original in canonical XML and
+/// every difference is reported with its location.
+#[tauri::command(async)]
+pub async fn check_round_trip(path: String) -> Result<FidelityReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        fidelity::check_round_trip(&content)
+    })
+    .await
+    .map_err(|e| format!("Round-trip check failed: {}", e))?
+}
@@ -1,28 +1,96 @@
 //! Tauri commands for metadata operations.
 
+use std::fs;
+
+use crate::annotations::AnnotationSet;
+use crate::metadata::catalogue::{self, CatalogueImport};
+use crate::metadata::crosswalk::{self, MetadataFormat};
+use crate::metadata::header;
+use crate::metadata::validate::{self, MetadataDiagnostic};
 use crate::metadata::Metadata;
 
-/// Generate a TEI header from structured metadata
+/// Generate a TEI header from structured metadata.
+///
+/// When annotations are given, each annotation author gets a `<respStmt>` that
+/// the compiled `@resp` pointers refer to.
 #[tauri::command]
-pub fn generate_tei_header(metadata_json: String, include_menota_ns: bool) -> Result<String, String> {
-    let metadata: Metadata = serde_json::from_str(&metadata_json)
+pub fn generate_tei_header(
+    metadata_json: String,
+    include_menota_ns: bool,
+    annotations_json: Option<String>,
+) -> Result<String, String> {
+    let mut metadata: Metadata = serde_json::from_str(&metadata_json)
         .map_err(|e| format!("Failed to parse metadata: {}", e))?;
 
+    if let Some(json) = annotations_json {
+        let annotations: AnnotationSet = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse annotations: {}", e))?;
+        metadata.add_annotator_resp_stmts(&annotations.authors());
+    }
+
     Ok(metadata.to_tei_header(include_menota_ns))
 }
 
+/// Copy the revision history of the metadata into the `<teiHeader>` of `xml`,
+/// for headers that come from a template rather than the metadata.
+#[tauri::command]
+pub fn update_header_revisions(xml: String, metadata_json: String) -> Result<String, String> {
+    let metadata: Metadata = serde_json::from_str(&metadata_json)
+        .map_err(|e| format!("Failed to parse metadata: {}", e))?;
+    header::update_revisions(&xml, &metadata)
+}
+
 /// Generate a TEI footer (closing tags)
 #[tauri::command]
 pub fn generate_tei_footer() -> String {
     Metadata::tei_footer()
 }
 
-/// Validate metadata JSON structure
+/// Validate metadata, returning a diagnostic per problematic field.
+///
+/// `schema_id` is the active template's validation schema and decides which
+/// fields are required. Malformed JSON is an error rather than a diagnostic.
 #[tauri::command]
-pub fn validate_metadata(metadata_json: String) -> Result<bool, String> {
-    let _: Metadata = serde_json::from_str(&metadata_json)
-        .map_err(|e| format!("Invalid metadata: {}", e))?;
-    Ok(true)
+pub fn validate_metadata(
+    metadata_json: String,
+    schema_id: Option<String>,
+) -> Result<Vec<MetadataDiagnostic>, String> {
+    let metadata: Metadata =
+        serde_json::from_str(&metadata_json).map_err(|e| format!("Invalid metadata: {}", e))?;
+    Ok(validate::validate(&metadata, schema_id.as_deref()))
+}
+
+/// Merge a manuscript description from a local catalogue file (handrit.is,
+/// e-manuscripta) into the given metadata.
+///
+/// The `<msDesc>` is chosen by `shelfmark`, or by the shelfmark already in the
+/// metadata. The result lists the fields taken from the catalogue and those
+/// that conflicted with existing values.
+#[tauri::command]
+pub fn import_catalogue_record(
+    path: String,
+    metadata_json: String,
+    shelfmark: Option<String>,
+    prefer_catalogue: bool,
+) -> Result<CatalogueImport, String> {
+    let existing: Metadata = serde_json::from_str(&metadata_json)
+        .map_err(|e| format!("Failed to parse metadata: {}", e))?;
+    let xml = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    catalogue::import_catalogue(&xml, shelfmark.as_deref(), &existing, prefer_catalogue)
+}
+
+/// Write metadata as a Dublin Core record, schema.org JSON-LD or the
+/// descriptive properties of a IIIF manifest.
+#[tauri::command]
+pub fn export_metadata(
+    path: String,
+    metadata_json: String,
+    format: MetadataFormat,
+) -> Result<(), String> {
+    let metadata: Metadata = serde_json::from_str(&metadata_json)
+        .map_err(|e| format!("Failed to parse metadata: {}", e))?;
+    fs::write(&path, crosswalk::export(&metadata, format))
+        .map_err(|e| format!("Failed to write file: {}", e))
 }
 
 /// Create empty metadata with default values
@@ -39,25 +107,40 @@ mod tests {
     #[test]
     fn test_generate_tei_header() {
         let json = r#"{"titleStmt":{"title":"Test"},"publicationStmt":{},"msIdentifier":{},"msContents":{},"physDesc":{},"history":{},"languages":[]}"#;
-        let result = generate_tei_header(json.to_string(), false);
+        let result = generate_tei_header(json.to_string(), false, None);
         assert!(result.is_ok());
         let header = result.unwrap();
         assert!(header.contains("<title>Test</title>"));
     }
 
+    #[test]
+    fn test_generate_tei_header_with_annotators() {
+        let json = r#"{"titleStmt":{"title":"Test"},"publicationStmt":{},"msIdentifier":{},"msContents":{},"physDesc":{},"history":{},"languages":[]}"#;
+        let annotations = r#"{"version":"1.0","annotations":[{"id":"lemma-0","type":"lemma","target":{"type":"word","wordIndex":0},"value":{"kind":"lemma","lemma":"ok","msa":"xCC"},"metadata":{"author":"Anna"}}]}"#;
+        let header =
+            generate_tei_header(json.to_string(), false, Some(annotations.to_string())).unwrap();
+        assert!(header.contains(r#"<respStmt xml:id="resp-anna">"#));
+        assert!(header.contains("<name>Anna</name>"));
+    }
+
     #[test]
     fn test_validate_metadata() {
-        let valid = r#"{"titleStmt":{},"publicationStmt":{},"msIdentifier":{},"msContents":{},"physDesc":{},"history":{},"languages":[]}"#;
-        assert!(validate_metadata(valid.to_string()).is_ok());
+        let valid = r#"{"titleStmt":{"title":"Test"},"publicationStmt":{},"msIdentifier":{},"msContents":{},"physDesc":{},"history":{},"languages":[]}"#;
+        assert_eq!(validate_metadata(valid.to_string(), None), Ok(vec![]));
+
+        let diagnostics = validate_metadata(valid.to_string(), Some("menota".into())).unwrap();
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.field == "msIdentifier.idno" && !d.is_warning));
 
         let invalid = r#"{"bad": "json"#;
-        assert!(validate_metadata(invalid.to_string()).is_err());
+        assert!(validate_metadata(invalid.to_string(), None).is_err());
     }
 
     #[test]
     fn test_create_empty_metadata() {
         let json = create_empty_metadata();
         assert!(json.contains("titleStmt"));
-        assert!(validate_metadata(json).is_ok());
+        assert!(validate_metadata(json, None).is_ok());
     }
 }
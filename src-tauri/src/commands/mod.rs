@@ -7,14 +7,15 @@
 //!
 //! - **[`file`]**: Project file operations (open, save, export)
 //! - **[`parse`]**: DSL compilation (`compile_dsl`, `compile_imported`)
-//! - **[`import`]**: TEI-XML import (`import_file`)
+//! - **[`import`]**: TEI-XML, HTR, Menotec and plain-text import (`import_file`), round-trip check
 //! - **[`validate`]**: XML validation against RelaxNG/XSD schemas
-//! - **[`template`]**: Template management for TEI headers
+//! - **[`template`]**: Template management for TEI headers, package import/export
 //! - **[`entities`]**: Entity registry loading
 //! - **[`dictionary`]**: ONP dictionary lookup
 //! - **[`settings`]**: User settings persistence
 //! - **[`stylesheet`]**: XSLT stylesheet management
-//! - **[`metadata`]**: TEI header metadata generation
+//! - **[`metadata`]**: TEI header metadata generation, validation, catalogue import and export
+//! - **[`annotations`]**: Annotation merging, conflict resolution, review status and CSV/TSV exchange
 //!
 //! ## Async Patterns
 //!
@@ -36,6 +37,7 @@
 //! 1. `#[tauri::command(async)]` - Command runs off main thread
 //! 2. `spawn_blocking` - CPU-bound work runs on a thread pool
 
+pub mod annotations;
 pub mod dictionary;
 pub mod entities;
 pub mod file;
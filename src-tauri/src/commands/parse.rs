@@ -1,13 +1,34 @@
 use crate::annotations::AnnotationSet;
+use crate::commands::file::chrono_lite_now;
 use crate::entities::EntityRegistry;
-use crate::importer::tei::patching::{apply_patches_and_reconstruct, compute_patches};
+use crate::importer::htr::Facsimile;
+use crate::importer::tei::attribute_policy::AttributePolicy;
+use crate::importer::tei::patching::{reconstruct_document, Reconstruction};
 use crate::importer::tei::segments::{ImportedDocument, Segment};
+use crate::metadata::{header, Metadata};
 use crate::normalizer::LevelDictionary;
 use crate::parser::{Compiler, CompilerConfig, LemmaMapping};
+use crate::template::{render_template, template_context};
 use std::collections::HashMap;
 
 /// Compile DSL input to TEI-XML.
 ///
+/// Placeholders in the template header and footer are filled from
+/// `metadata_json` (empty metadata if omitted) and `file_name`; see
+/// [`crate::template::template_context`]. Annotation `@resp` pointers use the
+/// ids of the metadata's `<respStmt>`s, as [`generate_tei_header`] writes them.
+///
+/// [`generate_tei_header`]: crate::commands::metadata::generate_tei_header
+///
+/// Statements those pointers need are added to the header if it lacks them, so
+/// a plain template header gets them too. Annotation confidence is written as
+/// `<certainty>` only when `schema_id` (the template's validation schema,
+/// TEI P5 if omitted) allows it, which MENOTA and multi-level output do not.
+///
+/// With `facsimile_json` (the page layout of an HTR import), page and line
+/// breaks link to their surfaces and zones, and the `<facsimile>` element is
+/// written after the header.
+///
 /// This command is async to avoid blocking the UI during compilation.
 /// The actual compilation runs on a blocking thread pool.
 #[allow(clippy::too_many_arguments)]
@@ -26,6 +47,10 @@ pub async fn compile_dsl(
     custom_mappings: Option<HashMap<String, String>>,
     lemma_mappings_json: Option<String>,
     annotations_json: Option<String>,
+    metadata_json: Option<String>,
+    file_name: Option<String>,
+    facsimile_json: Option<String>,
+    schema_id: Option<String>,
 ) -> Result<String, String> {
     // Move all the work to a blocking thread pool to avoid blocking the UI
     tauri::async_runtime::spawn_blocking(move || {
@@ -68,6 +93,15 @@ pub async fn compile_dsl(
             None => None,
         };
 
+        // Parse facsimile layout if provided
+        let facsimile: Option<Facsimile> = match facsimile_json {
+            Some(json) => Some(
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("Failed to parse facsimile: {}", e))?,
+            ),
+            None => None,
+        };
+
         // Configure compiler
         let config = CompilerConfig {
             word_wrap: word_wrap.unwrap_or(false),
@@ -76,10 +110,25 @@ pub async fn compile_dsl(
             wrap_pages: wrap_pages.unwrap_or(false),
         };
 
+        // Annotation authors need a <respStmt> in the header for @resp to point at
+        let mut metadata = match metadata_json {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse metadata: {}", e))?,
+            None => Metadata::new(),
+        };
+        let resp_ids = match annotations {
+            Some(ref ann) => metadata.add_annotator_resp_stmts(&ann.authors()),
+            None => HashMap::new(),
+        };
+
+        let certainty = !config.multi_level
+            && !matches!(schema_id.as_deref(), Some("menota" | "menota-p5"));
         let mut compiler = Compiler::new()
             .with_entities(&registry)
             .with_config(config)
-            .with_lemma_mappings(lemma_mappings);
+            .with_lemma_mappings(lemma_mappings)
+            .with_resp_ids(resp_ids.clone())
+            .with_certainty(certainty);
 
         // Add annotations if available
         if let Some(ref ann) = annotations {
@@ -91,8 +140,36 @@ pub async fn compile_dsl(
             compiler = compiler.with_dictionary(dict);
         }
 
+        if let Some(ref facsimile) = facsimile {
+            compiler = compiler.with_facsimile(facsimile);
+        }
+
         let body = compiler.compile(&input)?;
-        Ok(format!("{}\n{}\n{}", template_header, body, template_footer))
+
+        let compile_date = &chrono_lite_now()[..10];
+        let context = template_context(&metadata, file_name.as_deref(), compile_date);
+        let header = render_template(&template_header, &context)
+            .map_err(|e| format!("Template header: {}", e))?;
+        let footer = render_template(&template_footer, &context)
+            .map_err(|e| format!("Template footer: {}", e))?;
+        let pointed_at: Vec<_> = metadata
+            .title_stmt
+            .resp_stmts
+            .iter()
+            .filter(|r| r.id.as_ref().is_some_and(|id| resp_ids.values().any(|v| v == id)))
+            .cloned()
+            .collect();
+        let header = if pointed_at.is_empty() {
+            header
+        } else {
+            header::add_resp_stmts(&header, &pointed_at)?
+        };
+        let header = match facsimile {
+            Some(ref facsimile) => facsimile.insert_into(&header),
+            None => header,
+        };
+
+        Ok(format!("{}\n{}\n{}", header, body, footer))
     })
     .await
     .map_err(|e| format!("Compilation task failed: {}", e))?
@@ -102,6 +179,13 @@ pub async fn compile_dsl(
 ///
 /// This command takes edited DSL, the original segment manifest, and original body XML,
 /// computes the differences, and reconstructs the XML with minimal changes.
+///
+/// Attributes of modified words are merged according to `attribute_policy` (the
+/// default policy if omitted); words whose linguistic attributes went stale are
+/// returned alongside the XML.
+///
+/// When `metadata_json` is given, its edited fields are patched into the
+/// original `<teiHeader>`; the rest of the header is left as it was.
 #[allow(clippy::too_many_arguments)]
 #[tauri::command(async, rename_all = "camelCase")]
 pub async fn compile_imported(
@@ -113,7 +197,9 @@ pub async fn compile_imported(
     normalizer_json: Option<String>,
     entity_mappings_json: Option<String>,
     custom_mappings: Option<HashMap<String, String>>,
-) -> Result<String, String> {
+    attribute_policy: Option<AttributePolicy>,
+    metadata_json: Option<String>,
+) -> Result<Reconstruction, String> {
     tauri::async_runtime::spawn_blocking(move || {
         // Deserialize the imported document (accept either full manifest or raw segments list)
         let imported_doc: ImportedDocument = match serde_json::from_str(&segments_json) {
@@ -124,9 +210,22 @@ pub async fn compile_imported(
                 ImportedDocument {
                     segments,
                     is_menota: false,
+                    label: String::new(),
+                    units: Vec::new(),
                 }
             }
         };
+
+        // Apply metadata edits to the header in the preamble
+        let preamble = match metadata_json {
+            Some(json) => {
+                let metadata: Metadata = serde_json::from_str(&json)
+                    .map_err(|e| format!("Failed to parse metadata: {}", e))?;
+                header::replace_header(&preamble, &metadata)?
+            }
+            None => preamble,
+        };
+
         // Load entities if provided
         let mut registry = EntityRegistry::new();
         if let Some(json) = entities_json {
@@ -165,14 +264,17 @@ pub async fn compile_imported(
             compiler = compiler.with_dictionary(dict);
         }
 
-        // Compute patches between original segments and edited DSL
-        let patches = compute_patches(&imported_doc.segments, &edited_dsl);
-
-        // Apply patches and reconstruct the body XML
-        let body = apply_patches_and_reconstruct(&imported_doc.segments, &patches, &mut compiler);
-
-        // Combine with preamble (everything before body) and postamble (everything after)
-        Ok(format!("{}{}{}", preamble, body, postamble))
+        // Patch the main body and any further text units, and combine them with
+        // the preamble (everything before body) and postamble (everything after)
+        let policy = attribute_policy.unwrap_or_default();
+        reconstruct_document(
+            &imported_doc,
+            &edited_dsl,
+            &preamble,
+            &postamble,
+            &mut compiler,
+            &policy,
+        )
     })
     .await
     .map_err(|e| format!("Compilation task failed: {}", e))?
@@ -1,4 +1,13 @@
+use crate::commands::validate::{built_in_schema_file, get_user_schemas_dir, resolve_schema_path};
+use crate::entities::{CustomEntitiesManager, CustomMappingsManager};
+use crate::stylesheet::StylesheetManager;
+use crate::template::package::{
+    ConflictAction, PackageFile, PackageImport, PackageManifest, PackageStore, TemplatePackage,
+};
 use crate::template::{Template, TemplateManager};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use tauri::AppHandle;
 
 #[tauri::command]
@@ -22,9 +31,104 @@ pub fn save_template(app: AppHandle, template: Template) -> Result<(), String> {
 #[tauri::command]
 pub fn delete_template(app: AppHandle, id: String) -> Result<(), String> {
     // Prevent deletion of built-in templates
-    if id == "tei-p5" || id == "menota" {
+    if TemplateManager::is_built_in(&id) {
         return Err("Cannot delete built-in templates".to_string());
     }
     let manager = TemplateManager::new(&app)?;
     manager.delete_template(&id)
 }
+
+/// Export a template as a package with its custom schema, optionally a
+/// stylesheet, and optionally the custom entities and entity mappings.
+#[tauri::command(rename_all = "camelCase")]
+pub fn export_template_package(
+    app: AppHandle,
+    path: String,
+    template_id: String,
+    stylesheet_id: Option<String>,
+    include_entities: bool,
+) -> Result<PackageManifest, String> {
+    let template = TemplateManager::new(&app)?.get_template(&template_id)?;
+
+    // Built-in schemas ship with every installation
+    let schema = match template.validation_schema_id {
+        Some(ref id) if built_in_schema_file(id).is_none() => {
+            let schema_path = resolve_schema_path(&app, id)?;
+            Some(package_file(&schema_path, None)?)
+        }
+        _ => None,
+    };
+
+    let stylesheet = match stylesheet_id {
+        Some(id) => {
+            let info = StylesheetManager::new(&app)?
+                .list_stylesheets()?
+                .into_iter()
+                .find(|s| s.id == id)
+                .ok_or_else(|| format!("Stylesheet '{}' not found", id))?;
+            if info.built_in {
+                None
+            } else {
+                Some(package_file(Path::new(&info.path), Some(info.name))?)
+            }
+        }
+        None => None,
+    };
+
+    let (entities, entity_mappings) = if include_entities {
+        (
+            CustomEntitiesManager::new(&app)?.load(),
+            CustomMappingsManager::new(&app)?.load(),
+        )
+    } else {
+        (HashMap::new(), HashMap::new())
+    };
+
+    let package = TemplatePackage {
+        template,
+        schema,
+        stylesheet,
+        entities,
+        entity_mappings,
+    };
+    package.write(Path::new(&path))
+}
+
+/// Import a template package.
+///
+/// When the template id is already taken and `on_conflict` is not given,
+/// nothing is installed and the returned import describes the conflict.
+#[tauri::command(rename_all = "camelCase")]
+pub fn import_template_package(
+    app: AppHandle,
+    path: String,
+    on_conflict: Option<ConflictAction>,
+) -> Result<PackageImport, String> {
+    let (_, package) = TemplatePackage::read(Path::new(&path))?;
+    let store = PackageStore {
+        templates: TemplateManager::new(&app)?,
+        stylesheets: StylesheetManager::new(&app)?,
+        entities: CustomEntitiesManager::new(&app)?,
+        entity_mappings: CustomMappingsManager::new(&app)?,
+        schemas_dir: get_user_schemas_dir(&app)?,
+    };
+    package.install(&store, on_conflict)
+}
+
+fn package_file(path: &Path, name: Option<String>) -> Result<PackageFile, String> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name.unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let content = fs::read(path).map_err(|e| format!("Failed to read {}: {}", file_name, e))?;
+    Ok(PackageFile {
+        name,
+        file_name,
+        content,
+    })
+}
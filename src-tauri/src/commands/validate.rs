@@ -32,6 +32,47 @@ fn get_schemas_dir(app: &AppHandle) -> Result<PathBuf, String> {
     Err("Schemas directory not found".to_string())
 }
 
+/// Get the directory for schemas installed from template packages
+pub(crate) fn get_user_schemas_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let dir = app_data.join("schemas");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// File name of a built-in schema
+pub(crate) fn built_in_schema_file(schema_id: &str) -> Option<&'static str> {
+    match schema_id {
+        "tei-p5" => Some("tei_all.xsd"),
+        "menota" => Some("menota.xsd"),
+        "menota-p5" => Some("menotaP5.rng"),
+        _ => None,
+    }
+}
+
+/// Find the file for a schema ID. Custom IDs are a file name or its stem,
+/// looked up among installed schemas before bundled ones.
+pub(crate) fn resolve_schema_path(app: &AppHandle, schema_id: &str) -> Result<PathBuf, String> {
+    let not_found = || format!("Schema not found: {}", schema_id);
+    if let Some(file_name) = built_in_schema_file(schema_id) {
+        let path = get_schemas_dir(app)?.join(file_name);
+        return if path.exists() { Ok(path) } else { Err(not_found()) };
+    }
+
+    let mut dirs = vec![get_user_schemas_dir(app)?];
+    dirs.extend(get_schemas_dir(app).ok());
+    dirs.iter()
+        .flat_map(|dir| {
+            [
+                dir.join(schema_id),
+                dir.join(format!("{}.rng", schema_id)),
+                dir.join(format!("{}.xsd", schema_id)),
+            ]
+        })
+        .find(|path| path.is_file())
+        .ok_or_else(not_found)
+}
+
 /// List available schemas
 #[tauri::command]
 pub fn list_schemas(app: AppHandle) -> Result<Vec<SchemaInfo>, String> {
@@ -104,6 +145,29 @@ pub fn list_schemas(app: AppHandle) -> Result<Vec<SchemaInfo>, String> {
         }
     }
 
+    // Schemas installed from template packages
+    if let Ok(entries) = fs::read_dir(get_user_schemas_dir(&app)?) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let ext = path.extension().map(|e| e.to_string_lossy().to_string());
+            if matches!(ext.as_deref(), Some("xsd" | "rng")) {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let id = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if !schemas.iter().any(|s| s.id == id) {
+                    schemas.push(SchemaInfo {
+                        id: id.clone(),
+                        name: id,
+                        description: format!("Installed schema: {}", file_name),
+                        file_name,
+                    });
+                }
+            }
+        }
+    }
+
     Ok(schemas)
 }
 
@@ -115,21 +179,7 @@ pub async fn validate_xml(
     xml_content: String,
     schema_id: String,
 ) -> Result<ValidationResult, String> {
-    let schemas_dir = get_schemas_dir(&app)?;
-
-    // Map schema ID to filename
-    let schema_file = match schema_id.as_str() {
-        "tei-p5" => "tei_all.xsd",
-        "menota" => "menota.xsd",
-        "menota-p5" => "menotaP5.rng",
-        _ => &schema_id, // Assume it's a filename for custom schemas
-    };
-
-    let schema_path = schemas_dir.join(schema_file);
-
-    if !schema_path.exists() {
-        return Err(format!("Schema not found: {}", schema_id));
-    }
+    let schema_path = resolve_schema_path(&app, &schema_id)?;
 
     let is_rng = schema_path.extension().and_then(|s| s.to_str()) == Some("rng");
 
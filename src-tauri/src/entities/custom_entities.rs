@@ -28,8 +28,12 @@ impl CustomEntitiesManager {
     pub fn new(app: &AppHandle) -> Result<Self, String> {
         let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
         fs::create_dir_all(&app_data).map_err(|e| e.to_string())?;
-        let path = app_data.join("custom-entities.json");
-        Ok(Self { path })
+        Ok(Self::with_path(app_data.join("custom-entities.json")))
+    }
+
+    /// A manager for the file at `path` rather than in the app data directory
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
     }
 
     /// Load all custom entities
@@ -27,8 +27,12 @@ impl CustomMappingsManager {
     pub fn new(app: &AppHandle) -> Result<Self, String> {
         let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
         fs::create_dir_all(&app_data).map_err(|e| e.to_string())?;
-        let path = app_data.join("custom-entity-mappings.json");
-        Ok(Self { path })
+        Ok(Self::with_path(app_data.join("custom-entity-mappings.json")))
+    }
+
+    /// A manager for the file at `path` rather than in the app data directory
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
     }
 
     pub fn load(&self) -> HashMap<String, String> {
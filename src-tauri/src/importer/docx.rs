@@ -0,0 +1,417 @@
+//! # DOCX Importer
+//!
+//! Imports transcriptions typed in a word processor. The main document part
+//! (`word/document.xml`) is read from the archive and its paragraphs are turned
+//! into DSL lines:
+//!
+//! | Word formatting | DSL |
+//! |-----------------|-----|
+//! | Paragraph, manual line break | `//` |
+//! | Leading `12⇥` or `12 ` in a line | `//12` |
+//! | Italic letters inside a word | `.abbr[abbr]{expansion}` |
+//! | Superscript | `+{text}+` |
+//! | Strikethrough | `-{text}-` |
+//! | Page break | `///n`, numbered from 2 (the text before the first break is page 1) |
+//!
+//! Italics mark expansions, so the letters of a word in roman type form the
+//! abbreviation: `k`*`onung`*`r` becomes `.abbr[kr]{konungr}`. Formatting with no
+//! DSL equivalent (bold, underline, styles, footnotes, tracked deletions, ...) is
+//! imported as plain text or skipped, and listed as [`UnmappedFormatting`] in the
+//! import's [`ImportReport`].
+
+use crate::importer::tei::helpers::{child_named, elements, local_name};
+use crate::importer::tei::report::ImportReport;
+use crate::importer::tei::ImportResult;
+use libxml::parser::Parser;
+use libxml::tree::Node;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Characters that end a word being assembled for abbreviation detection
+const WORD_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// Formatting that has no DSL equivalent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmappedFormatting {
+    /// 1-based paragraph number
+    pub paragraph: usize,
+    /// What was found, e.g. "bold", "footnote", "style Heading1"
+    pub formatting: String,
+    /// Text the formatting applied to
+    pub text: String,
+}
+
+/// How a piece of text maps to the DSL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Plain,
+    Expansion,
+    Addition,
+    Deletion,
+}
+
+#[derive(Debug)]
+struct Piece {
+    mark: Mark,
+    text: String,
+}
+
+/// A line of text: a paragraph, or part of one after a manual line break
+#[derive(Debug)]
+struct Line {
+    paragraph: usize,
+    /// Page number when the line is a page break
+    page_break: Option<usize>,
+    pieces: Vec<Piece>,
+}
+
+/// Import a `.docx` file.
+pub fn parse(bytes: &[u8]) -> Result<ImportResult, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("Failed to read DOCX archive: {}", e))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("Failed to find word/document.xml: {}", e))?
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Failed to read word/document.xml: {}", e))?;
+
+    parse_document_xml(&xml)
+}
+
+/// Import the main document part of a DOCX file.
+pub fn parse_document_xml(xml: &str) -> Result<ImportResult, String> {
+    let doc = Parser::default()
+        .parse_string(xml)
+        .map_err(|e| format!("Failed to parse document.xml: {}", e))?;
+    let root = doc.get_root_element().ok_or("No root element found")?;
+    let body = elements(&root)
+        .into_iter()
+        .find(|child| local_name(child) == "body")
+        .ok_or("No <w:body> element found in document.xml")?;
+
+    let mut reader = DocxReader::default();
+    reader.block(&body);
+    let dsl = reader.render();
+
+    Ok(ImportResult {
+        dsl,
+        metadata: None,
+        imported_document: None,
+        original_body_xml: None,
+        original_preamble: None,
+        original_postamble: None,
+        is_imported_mode: false,
+        annotations: None,
+        inflections: Vec::new(),
+        facsimile: None,
+        report: Some(ImportReport {
+            formatting: reader.report,
+            ..Default::default()
+        }),
+    })
+}
+
+/// Whether an on/off run property such as `<w:i/>` is switched on
+fn toggle(properties: &Node, name: &str) -> bool {
+    child_named(properties, name).is_some_and(|prop| {
+        !matches!(
+            prop.get_property("val").as_deref(),
+            Some("0" | "false" | "off" | "none")
+        )
+    })
+}
+
+/// Text of all `<w:t>` descendants
+fn run_text(node: &Node) -> String {
+    let mut text = String::new();
+    for child in elements(node) {
+        match local_name(&child).as_str() {
+            "t" | "delText" => text.push_str(&child.get_content()),
+            _ => text.push_str(&run_text(&child)),
+        }
+    }
+    text
+}
+
+#[derive(Default)]
+struct DocxReader {
+    lines: Vec<Line>,
+    paragraph: usize,
+    /// Number of page breaks seen so far
+    page_breaks: usize,
+    report: Vec<UnmappedFormatting>,
+}
+
+impl DocxReader {
+    /// Block-level content: paragraphs, tables and content controls
+    fn block(&mut self, node: &Node) {
+        for child in elements(node) {
+            match local_name(&child).as_str() {
+                "p" => self.paragraph(&child),
+                "tbl" => {
+                    self.unmapped("table", run_text(&child));
+                    self.block(&child);
+                }
+                "sectPr" => {}
+                _ => self.block(&child),
+            }
+        }
+    }
+
+    fn paragraph(&mut self, node: &Node) {
+        self.paragraph += 1;
+        self.new_line();
+
+        if let Some(style) = child_named(node, "pPr")
+            .and_then(|ppr| child_named(&ppr, "pStyle"))
+            .and_then(|style| style.get_property("val"))
+        {
+            if style != "Normal" {
+                self.unmapped(&format!("style {}", style), run_text(node));
+            }
+        }
+
+        self.inline(node);
+    }
+
+    /// Paragraph content: runs, possibly wrapped in links, fields or revisions
+    fn inline(&mut self, node: &Node) {
+        for child in elements(node) {
+            match local_name(&child).as_str() {
+                "r" => self.run(&child),
+                "del" => self.unmapped("tracked deletion", run_text(&child)),
+                "hyperlink" | "smartTag" | "ins" | "sdt" | "sdtContent" | "fldSimple"
+                | "customXml" => self.inline(&child),
+                _ => {}
+            }
+        }
+    }
+
+    fn run(&mut self, node: &Node) {
+        let text = run_text(node);
+        let mark = match child_named(node, "rPr") {
+            Some(properties) => self.run_mark(&properties, &text),
+            None => Mark::Plain,
+        };
+
+        for child in elements(node) {
+            match local_name(&child).as_str() {
+                "t" => self.push(mark, &child.get_content()),
+                "tab" => self.push(mark, "\t"),
+                "noBreakHyphen" => self.push(mark, "-"),
+                "br" | "cr" => {
+                    if child.get_property("type").as_deref() == Some("page") {
+                        self.page_break();
+                    }
+                    self.new_line();
+                }
+                "footnoteReference" | "endnoteReference" => {
+                    self.unmapped("footnote", String::new())
+                }
+                "sym" => self.unmapped("symbol", child.get_property("char").unwrap_or_default()),
+                "drawing" | "pict" | "object" => self.unmapped("image", String::new()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Map run properties to a mark and report the ones without a mapping
+    fn run_mark(&mut self, properties: &Node, text: &str) -> Mark {
+        let vertical = child_named(properties, "vertAlign").and_then(|v| v.get_property("val"));
+        let mark = if toggle(properties, "strike") || toggle(properties, "dstrike") {
+            Mark::Deletion
+        } else if vertical.as_deref() == Some("superscript") {
+            Mark::Addition
+        } else if toggle(properties, "i") {
+            Mark::Expansion
+        } else {
+            Mark::Plain
+        };
+
+        if text.trim().is_empty() {
+            return mark;
+        }
+        for (name, label) in [
+            ("b", "bold"),
+            ("u", "underline"),
+            ("smallCaps", "small caps"),
+            ("caps", "all caps"),
+            ("highlight", "highlight"),
+        ] {
+            if toggle(properties, name) {
+                self.unmapped(label, text.to_string());
+            }
+        }
+        if vertical.as_deref() == Some("subscript") {
+            self.unmapped("subscript", text.to_string());
+        }
+        if let Some(style) = child_named(properties, "rStyle").and_then(|s| s.get_property("val")) {
+            self.unmapped(&format!("character style {}", style), text.to_string());
+        }
+        mark
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push(Line {
+            paragraph: self.paragraph,
+            page_break: None,
+            pieces: Vec::new(),
+        });
+    }
+
+    fn page_break(&mut self) {
+        self.page_breaks += 1;
+        self.lines.push(Line {
+            paragraph: self.paragraph,
+            page_break: Some(self.page_breaks + 1),
+            pieces: Vec::new(),
+        });
+    }
+
+    fn push(&mut self, mark: Mark, text: &str) {
+        let Some(line) = self.lines.last_mut() else {
+            return;
+        };
+        match line.pieces.last_mut() {
+            Some(piece) if piece.mark == mark => piece.text.push_str(text),
+            _ => line.pieces.push(Piece {
+                mark,
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    /// Record unmapped formatting, extending the previous entry for the same
+    /// formatting in the same paragraph
+    fn unmapped(&mut self, formatting: &str, text: String) {
+        if let Some(last) = self.report.last_mut() {
+            if last.paragraph == self.paragraph && last.formatting == formatting {
+                last.text.push_str(&text);
+                return;
+            }
+        }
+        self.report.push(UnmappedFormatting {
+            paragraph: self.paragraph,
+            formatting: formatting.to_string(),
+            text,
+        });
+    }
+
+    /// Render the collected lines as DSL, one `//` per non-empty line and one
+    /// `///n` per page break
+    fn render(&mut self) -> String {
+        let lines = std::mem::take(&mut self.lines);
+        let mut output = Vec::new();
+
+        for mut line in lines {
+            if let Some(page) = line.page_break {
+                output.push(format!("///{}", page));
+                continue;
+            }
+            if line.pieces.iter().all(|piece| piece.text.trim().is_empty()) {
+                continue;
+            }
+            let number = take_line_number(&mut line.pieces);
+            let text = self.render_pieces(line.paragraph, &line.pieces);
+            output.push(match number {
+                Some(n) => format!("//{} {}", n, text.trim()),
+                None => format!("// {}", text.trim()),
+            });
+        }
+
+        output.join("\n")
+    }
+
+    fn render_pieces(&mut self, paragraph: usize, pieces: &[Piece]) -> String {
+        let mut output = String::new();
+        let mut word = Word::default();
+
+        for piece in pieces {
+            match piece.mark {
+                Mark::Plain | Mark::Expansion => {
+                    let italic = piece.mark == Mark::Expansion;
+                    for c in piece.text.chars() {
+                        if c.is_whitespace() || (!italic && WORD_PUNCTUATION.contains(&c)) {
+                            self.flush_word(paragraph, &mut word, &mut output);
+                            output.push(c);
+                        } else {
+                            word.push(c, italic);
+                        }
+                    }
+                }
+                Mark::Addition | Mark::Deletion => {
+                    self.flush_word(paragraph, &mut word, &mut output);
+                    if piece.text.trim().is_empty() {
+                        output.push_str(&piece.text);
+                        continue;
+                    }
+                    let (open, close) = match piece.mark {
+                        Mark::Addition => ("+{", "}+"),
+                        _ => ("-{", "}-"),
+                    };
+                    output.push_str(open);
+                    output.push_str(&piece.text);
+                    output.push_str(close);
+                }
+            }
+        }
+        self.flush_word(paragraph, &mut word, &mut output);
+        output
+    }
+
+    fn flush_word(&mut self, paragraph: usize, word: &mut Word, output: &mut String) {
+        let word = std::mem::take(word);
+        if !word.italic {
+            output.push_str(&word.expansion);
+        } else if word.abbr.is_empty() {
+            // A fully italic word has no abbreviated form to show
+            self.report.push(UnmappedFormatting {
+                paragraph,
+                formatting: "italic word".to_string(),
+                text: word.expansion.clone(),
+            });
+            output.push_str(&word.expansion);
+        } else {
+            output.push_str(&format!(".abbr[{}]{{{}}}", word.abbr, word.expansion));
+        }
+    }
+}
+
+/// A word being assembled from roman and italic letters
+#[derive(Default)]
+struct Word {
+    abbr: String,
+    expansion: String,
+    italic: bool,
+}
+
+impl Word {
+    fn push(&mut self, c: char, italic: bool) {
+        self.expansion.push(c);
+        if italic {
+            self.italic = true;
+        } else {
+            self.abbr.push(c);
+        }
+    }
+}
+
+/// Strip a manual line number (`12⇥` or `12 `) from the start of a line
+fn take_line_number(pieces: &mut [Piece]) -> Option<String> {
+    let first = pieces
+        .first_mut()
+        .filter(|piece| piece.mark == Mark::Plain)?;
+    let text = first.text.trim_start();
+    let digits = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    if digits == 0 || !text[digits..].starts_with(char::is_whitespace) {
+        return None;
+    }
+    let number = text[..digits].to_string();
+    first.text = text[digits..].trim_start().to_string();
+    Some(number)
+}
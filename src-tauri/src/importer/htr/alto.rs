@@ -0,0 +1,129 @@
+//! ALTO XML parsing.
+//!
+//! Reads every `Layout/Page`, its `TextBlock`s (also inside `ComposedBlock`s) and
+//! their `TextLine`s. Line text is the `String/@CONTENT` values joined by spaces,
+//! with `HYP` hyphens attached to the preceding word. Rectangles come from
+//! `HPOS`/`VPOS`/`WIDTH`/`HEIGHT`; `Shape/Polygon/@POINTS` is kept when present.
+//! The image reference is `Description/sourceImageInformation/fileName`.
+
+use super::{HtrDocument, HtrLine, HtrPage, HtrRegion};
+use crate::importer::tei::helpers::{child_named, children, elements, local_name};
+use libxml::parser::Parser;
+use libxml::tree::Node;
+
+pub(crate) fn parse(xml: &str) -> Result<HtrDocument, String> {
+    let doc = Parser::default()
+        .parse_string(xml)
+        .map_err(|e| format!("Failed to parse ALTO XML: {}", e))?;
+    let root = doc.get_root_element().ok_or("No root element found")?;
+
+    let image = child_named(&root, "Description")
+        .and_then(|d| child_named(&d, "sourceImageInformation"))
+        .and_then(|s| child_named(&s, "fileName"))
+        .map(|f| f.get_content().trim().to_string())
+        .filter(|f| !f.is_empty());
+
+    let layout = child_named(&root, "Layout").ok_or("No <Layout> element found in ALTO")?;
+    let pages: Vec<HtrPage> = children(&layout, "Page")
+        .iter()
+        .map(|page| {
+            let mut blocks = Vec::new();
+            collect_blocks(page, &mut blocks);
+            HtrPage {
+                id: page.get_property("ID"),
+                image: image.clone(),
+                width: number(page, "WIDTH").map(|v| v as u32),
+                height: number(page, "HEIGHT").map(|v| v as u32),
+                regions: blocks.iter().map(parse_block).collect(),
+            }
+        })
+        .collect();
+
+    if pages.is_empty() {
+        return Err("No <Page> element found in ALTO".to_string());
+    }
+    Ok(HtrDocument { pages })
+}
+
+/// Text blocks in document order, at any depth below the page
+fn collect_blocks(node: &Node, blocks: &mut Vec<Node>) {
+    for c in elements(node) {
+        if local_name(&c) == "TextBlock" {
+            blocks.push(c.clone());
+        } else {
+            collect_blocks(&c, blocks);
+        }
+    }
+}
+
+fn parse_block(block: &Node) -> HtrRegion {
+    HtrRegion {
+        id: block.get_property("ID"),
+        points: polygon(block),
+        bounds: rectangle(block),
+        lines: children(block, "TextLine")
+            .iter()
+            .map(|line| HtrLine {
+                id: line.get_property("ID"),
+                points: polygon(line),
+                bounds: rectangle(line),
+                text: line_text(line),
+            })
+            .collect(),
+    }
+}
+
+fn line_text(line: &Node) -> String {
+    let mut text = String::new();
+    for c in elements(line) {
+        match local_name(&c).as_str() {
+            "String" => {
+                if let Some(content) = c.get_property("CONTENT") {
+                    if !text.is_empty() && !text.ends_with(' ') {
+                        text.push(' ');
+                    }
+                    text.push_str(&content);
+                }
+            }
+            "HYP" => text.push_str(&c.get_property("CONTENT").unwrap_or_else(|| "-".to_string())),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn rectangle(node: &Node) -> Option<[i64; 4]> {
+    let x = number(node, "HPOS")?;
+    let y = number(node, "VPOS")?;
+    let w = number(node, "WIDTH")?;
+    let h = number(node, "HEIGHT")?;
+    Some([x, y, x + w, y + h])
+}
+
+/// `Shape/Polygon/@POINTS`, normalised to "x,y x,y ..."
+fn polygon(node: &Node) -> Option<String> {
+    let points = child_named(node, "Shape")
+        .and_then(|s| child_named(&s, "Polygon"))
+        .and_then(|p| p.get_property("POINTS"))?;
+    if points.contains(',') {
+        return Some(points);
+    }
+    // ALTO also allows a flat "x y x y" list
+    let values: Vec<&str> = points.split_whitespace().collect();
+    Some(
+        values
+            .chunks(2)
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| format!("{},{}", pair[0], pair[1]))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn number(node: &Node, attr: &str) -> Option<i64> {
+    node.get_property(attr)?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|v| v.round() as i64)
+}
@@ -0,0 +1,344 @@
+//! # HTR Importers (PAGE XML and ALTO)
+//!
+//! Handwritten text recognition tools such as Transkribus export their results as
+//! PAGE XML or ALTO. These importers turn pages into `///n` page breaks and text
+//! lines into `//n` line breaks, producing plain DSL for a first-pass transcription.
+//!
+//! The layout is kept alongside the text as a [`Facsimile`]: one [`Surface`] per
+//! page with its image reference, and one [`Zone`] per text region and line with
+//! its coordinates. Line zones carry the same `n` as the `//n` break emitted for
+//! them. The editor keeps the facsimile with the project; on compile it is written
+//! out as TEI `<facsimile>/<surface>/<zone>` after the header, and the compiler
+//! links `<pb facs="#...">` and `<lb facs="#...">` to it.
+//!
+//! ```text
+//! PAGE/ALTO → [page::parse | alto::parse] → HtrDocument → DSL + Facsimile
+//! ```
+
+pub mod alto;
+pub mod page;
+
+use crate::importer::tei::helpers::escape_xml_attr;
+use crate::importer::tei::ImportResult;
+use serde::{Deserialize, Serialize};
+
+/// Supported HTR export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HtrFormat {
+    /// PRImA PAGE XML (`<PcGts>` root), as exported by Transkribus
+    Page,
+    /// ALTO XML (`<alto>` root)
+    Alto,
+}
+
+/// Page layout of an HTR import, convertible to a TEI `<facsimile>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Facsimile {
+    pub surfaces: Vec<Surface>,
+}
+
+/// A page image
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Surface {
+    pub id: String,
+    /// Page number, matching the `///n` break in the DSL
+    pub n: String,
+    /// Image file name or URL
+    pub image: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub zones: Vec<Zone>,
+}
+
+/// Kind of layout zone
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ZoneKind {
+    Region,
+    Line,
+}
+
+/// A region or line on a page
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Zone {
+    pub id: String,
+    pub kind: ZoneKind,
+    /// Line number, matching the `//n` break in the DSL (lines only)
+    pub n: Option<String>,
+    /// Polygon as TEI `@points` ("x,y x,y ...")
+    pub points: Option<String>,
+    /// Bounding box (`ulx`, `uly`, `lrx`, `lry`)
+    pub bounds: Option<[i64; 4]>,
+}
+
+/// Intermediate result shared by the PAGE and ALTO parsers
+#[derive(Debug, Default)]
+pub(crate) struct HtrDocument {
+    pub pages: Vec<HtrPage>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct HtrPage {
+    pub id: Option<String>,
+    pub image: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub regions: Vec<HtrRegion>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct HtrRegion {
+    pub id: Option<String>,
+    pub points: Option<String>,
+    pub bounds: Option<[i64; 4]>,
+    pub lines: Vec<HtrLine>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct HtrLine {
+    pub id: Option<String>,
+    pub points: Option<String>,
+    pub bounds: Option<[i64; 4]>,
+    pub text: String,
+}
+
+/// Detect an HTR format from the root element of an XML document.
+///
+/// Returns `None` for anything else (TEI included).
+pub fn detect_format(xml: &str) -> Option<HtrFormat> {
+    let name = root_element_name(xml)?;
+    let local = name.rsplit(':').next().unwrap_or(name);
+    match local {
+        "PcGts" => Some(HtrFormat::Page),
+        "alto" => Some(HtrFormat::Alto),
+        _ => None,
+    }
+}
+
+/// Name of the first element, skipping the XML declaration, comments,
+/// processing instructions and DOCTYPE.
+fn root_element_name(xml: &str) -> Option<&str> {
+    let mut rest = xml;
+    loop {
+        let start = rest.find('<')?;
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            rest = &rest[rest.find("-->")? + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = &rest[rest.find('>')? + 1..];
+        } else {
+            let name = &rest[1..];
+            let end = name
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .unwrap_or(name.len());
+            return Some(&name[..end]);
+        }
+    }
+}
+
+/// Parse a PAGE XML or ALTO document into DSL plus its facsimile layout.
+pub fn parse(xml: &str, format: HtrFormat) -> Result<ImportResult, String> {
+    let document = match format {
+        HtrFormat::Page => page::parse(xml)?,
+        HtrFormat::Alto => alto::parse(xml)?,
+    };
+    let (dsl, facsimile) = to_dsl_and_facsimile(&document);
+
+    Ok(ImportResult {
+        dsl,
+        metadata: None,
+        imported_document: None,
+        original_body_xml: None,
+        original_preamble: None,
+        original_postamble: None,
+        is_imported_mode: false,
+        annotations: None,
+        inflections: Vec::new(),
+        facsimile: Some(facsimile),
+        report: None,
+    })
+}
+
+/// Emit `///n` per page and `//n` per line (numbered within the page), and
+/// build the matching surfaces and zones.
+fn to_dsl_and_facsimile(document: &HtrDocument) -> (String, Facsimile) {
+    let mut dsl = String::new();
+    let mut facsimile = Facsimile::default();
+
+    for (page_idx, page) in document.pages.iter().enumerate() {
+        let page_n = (page_idx + 1).to_string();
+        let surface_id = valid_id(page.id.as_deref(), || format!("surface-{}", page_n));
+
+        if !dsl.is_empty() {
+            dsl.push('\n');
+        }
+        dsl.push_str("///");
+        dsl.push_str(&page_n);
+
+        let mut surface = Surface {
+            id: surface_id.clone(),
+            n: page_n,
+            image: page.image.clone(),
+            width: page.width,
+            height: page.height,
+            zones: Vec::new(),
+        };
+
+        let mut line_n = 0;
+        for (region_idx, region) in page.regions.iter().enumerate() {
+            surface.zones.push(Zone {
+                id: valid_id(region.id.as_deref(), || {
+                    format!("{}-r{}", surface_id, region_idx + 1)
+                }),
+                kind: ZoneKind::Region,
+                n: None,
+                points: region.points.clone(),
+                bounds: region.bounds,
+            });
+
+            for line in &region.lines {
+                line_n += 1;
+                dsl.push_str(&format!("\n//{}", line_n));
+                let text = line.text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !text.is_empty() {
+                    dsl.push(' ');
+                    dsl.push_str(&text);
+                }
+
+                surface.zones.push(Zone {
+                    id: valid_id(line.id.as_deref(), || format!("{}-l{}", surface_id, line_n)),
+                    kind: ZoneKind::Line,
+                    n: Some(line_n.to_string()),
+                    points: line.points.clone(),
+                    bounds: line.bounds,
+                });
+            }
+        }
+
+        facsimile.surfaces.push(surface);
+    }
+
+    (dsl, facsimile)
+}
+
+/// Use a source ID if it is a usable `xml:id`, otherwise a generated one.
+fn valid_id(id: Option<&str>, fallback: impl FnOnce() -> String) -> String {
+    match id {
+        Some(id)
+            if id.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && id
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) =>
+        {
+            id.to_string()
+        }
+        _ => fallback(),
+    }
+}
+
+impl Facsimile {
+    /// Find the surface for page `page_n` (for `<pb facs>`).
+    pub fn surface(&self, page_n: &str) -> Option<&Surface> {
+        self.surfaces.iter().find(|s| s.n == page_n)
+    }
+
+    /// Find the zone for line `line_n` on page `page_n` (for `<lb facs>`).
+    pub fn line_zone(&self, page_n: &str, line_n: &str) -> Option<&Zone> {
+        self.surface(page_n)?
+            .zones
+            .iter()
+            .find(|z| z.kind == ZoneKind::Line && z.n.as_deref() == Some(line_n))
+    }
+
+    /// Insert the `<facsimile>` element into a TEI document, right after
+    /// `</teiHeader>` (or before `<text` when there is no header).
+    pub fn insert_into(&self, document: &str) -> String {
+        let at = match document.find("</teiHeader>") {
+            Some(pos) => pos + "</teiHeader>".len(),
+            None => match document.find("<text") {
+                Some(pos) => pos,
+                None => return document.to_string(),
+            },
+        };
+        let (before, after) = document.split_at(at);
+        let separator = if before.ends_with('\n') { "" } else { "\n" };
+        format!(
+            "{}{}{}{}",
+            before,
+            separator,
+            self.to_tei(),
+            after.trim_start_matches('\n')
+        )
+    }
+
+    /// Serialize as a TEI `<facsimile>` element.
+    pub fn to_tei(&self) -> String {
+        let mut xml = String::from("<facsimile>\n");
+        for surface in &self.surfaces {
+            xml.push_str(&format!(
+                "  <surface xml:id=\"{}\" n=\"{}\"",
+                escape_xml_attr(&surface.id),
+                escape_xml_attr(&surface.n)
+            ));
+            if let (Some(w), Some(h)) = (surface.width, surface.height) {
+                xml.push_str(&format!(" ulx=\"0\" uly=\"0\" lrx=\"{}\" lry=\"{}\"", w, h));
+            }
+            xml.push_str(">\n");
+
+            if let Some(image) = &surface.image {
+                xml.push_str(&format!("    <graphic url=\"{}\"", escape_xml_attr(image)));
+                if let (Some(w), Some(h)) = (surface.width, surface.height) {
+                    xml.push_str(&format!(" width=\"{}px\" height=\"{}px\"", w, h));
+                }
+                xml.push_str("/>\n");
+            }
+
+            for zone in &surface.zones {
+                let kind = match zone.kind {
+                    ZoneKind::Region => "region",
+                    ZoneKind::Line => "line",
+                };
+                xml.push_str(&format!(
+                    "    <zone xml:id=\"{}\" type=\"{}\"",
+                    escape_xml_attr(&zone.id),
+                    kind
+                ));
+                if let Some(n) = &zone.n {
+                    xml.push_str(&format!(" n=\"{}\"", escape_xml_attr(n)));
+                }
+                if let Some([ulx, uly, lrx, lry]) = zone.bounds {
+                    xml.push_str(&format!(
+                        " ulx=\"{}\" uly=\"{}\" lrx=\"{}\" lry=\"{}\"",
+                        ulx, uly, lrx, lry
+                    ));
+                }
+                if let Some(points) = &zone.points {
+                    xml.push_str(&format!(" points=\"{}\"", escape_xml_attr(points)));
+                }
+                xml.push_str("/>\n");
+            }
+
+            xml.push_str("  </surface>\n");
+        }
+        xml.push_str("</facsimile>\n");
+        xml
+    }
+}
+
+/// Bounding box of a TEI-style point list
+pub(crate) fn bounds_of_points(points: &str) -> Option<[i64; 4]> {
+    let mut bounds: Option<[i64; 4]> = None;
+    for pair in points.split_whitespace() {
+        let (x, y) = pair.split_once(',')?;
+        let x = x.trim().parse::<f64>().ok()?.round() as i64;
+        let y = y.trim().parse::<f64>().ok()?.round() as i64;
+        bounds = Some(match bounds {
+            None => [x, y, x, y],
+            Some([ulx, uly, lrx, lry]) => [ulx.min(x), uly.min(y), lrx.max(x), lry.max(y)],
+        });
+    }
+    bounds
+}
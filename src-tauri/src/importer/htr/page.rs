@@ -0,0 +1,140 @@
+//! PAGE XML (PRImA page content) parsing.
+//!
+//! Reads `Page/@imageFilename`, `TextRegion`s (in reading order when a
+//! `ReadingOrder` is given, document order otherwise) and their `TextLine`s. Line
+//! text comes from the line's own `TextEquiv/Unicode`; word-level `TextEquiv`s are
+//! ignored. Both `Coords/@points` (2013+) and `Coords/Point` (2010) are supported.
+
+use super::{bounds_of_points, HtrDocument, HtrLine, HtrPage, HtrRegion};
+use crate::importer::tei::helpers::{children, elements, local_name};
+use libxml::parser::Parser;
+use libxml::tree::Node;
+use std::collections::HashMap;
+
+pub(crate) fn parse(xml: &str) -> Result<HtrDocument, String> {
+    let doc = Parser::default()
+        .parse_string(xml)
+        .map_err(|e| format!("Failed to parse PAGE XML: {}", e))?;
+    let root = doc.get_root_element().ok_or("No root element found")?;
+
+    let pages = children(&root, "Page");
+    if pages.is_empty() {
+        return Err("No <Page> element found in PAGE XML".to_string());
+    }
+
+    Ok(HtrDocument {
+        pages: pages.iter().map(parse_page).collect(),
+    })
+}
+
+fn parse_page(page: &Node) -> HtrPage {
+    let mut regions = Vec::new();
+    collect_regions(page, &mut regions);
+
+    // Apply the explicit reading order if there is one
+    let order = reading_order(page);
+    if !order.is_empty() {
+        regions.sort_by_key(|node| {
+            node.get_property("id")
+                .and_then(|id| order.get(&id).copied())
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    HtrPage {
+        id: page.get_property("id"),
+        image: page.get_property("imageFilename"),
+        width: page.get_property("imageWidth").and_then(|v| v.parse().ok()),
+        height: page
+            .get_property("imageHeight")
+            .and_then(|v| v.parse().ok()),
+        regions: regions.iter().map(parse_region).collect(),
+    }
+}
+
+/// Text regions in document order, including those nested in table cells
+fn collect_regions(node: &Node, regions: &mut Vec<Node>) {
+    for child in elements(node) {
+        if local_name(&child) == "TextRegion" {
+            regions.push(child.clone());
+        }
+        collect_regions(&child, regions);
+    }
+}
+
+/// Region id → position from `ReadingOrder//RegionRefIndexed`
+fn reading_order(page: &Node) -> HashMap<String, usize> {
+    let mut refs = Vec::new();
+    if let Some(order) = children(page, "ReadingOrder").first() {
+        collect_region_refs(order, &mut refs);
+    }
+    refs.sort_by_key(|(index, _)| *index);
+    refs.into_iter()
+        .enumerate()
+        .map(|(position, (_, id))| (id, position))
+        .collect()
+}
+
+fn collect_region_refs(node: &Node, refs: &mut Vec<(usize, String)>) {
+    for child in elements(node) {
+        if local_name(&child) == "RegionRefIndexed" {
+            if let Some(id) = child.get_property("regionRef") {
+                let index = child
+                    .get_property("index")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(refs.len());
+                refs.push((index, id));
+            }
+        }
+        collect_region_refs(&child, refs);
+    }
+}
+
+fn parse_region(region: &Node) -> HtrRegion {
+    let points = coords(region);
+    HtrRegion {
+        id: region.get_property("id"),
+        bounds: points.as_deref().and_then(bounds_of_points),
+        points,
+        lines: children(region, "TextLine")
+            .iter()
+            .map(|line| {
+                let points = coords(line);
+                HtrLine {
+                    id: line.get_property("id"),
+                    bounds: points.as_deref().and_then(bounds_of_points),
+                    points,
+                    text: line_text(line),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Polygon of the element's `Coords` child as "x,y x,y ..."
+fn coords(node: &Node) -> Option<String> {
+    let coords = children(node, "Coords").into_iter().next()?;
+    if let Some(points) = coords.get_property("points") {
+        return Some(points);
+    }
+    let points: Vec<String> = children(&coords, "Point")
+        .iter()
+        .filter_map(|p| Some(format!("{},{}", p.get_property("x")?, p.get_property("y")?)))
+        .collect();
+    (!points.is_empty()).then(|| points.join(" "))
+}
+
+/// Text of the line's own `TextEquiv` (lowest `@index` wins)
+fn line_text(line: &Node) -> String {
+    children(line, "TextEquiv")
+        .iter()
+        .min_by_key(|equiv| {
+            equiv
+                .get_property("index")
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(0)
+        })
+        .and_then(|equiv| children(equiv, "Unicode").into_iter().next())
+        .map(|unicode| unicode.get_content())
+        .unwrap_or_default()
+}
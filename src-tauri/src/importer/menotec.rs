@@ -0,0 +1,497 @@
+//! # Menotec Plain-Text Format
+//!
+//! Menotec is a line-oriented plain-text transcription format in which editorial
+//! features are marked with short codes. This module converts it to DSL on import
+//! and writes the compiled word stream back out on export.
+//!
+//! ## Supported Codes
+//!
+//! There is no published grammar for Menotec, so the table is not traced to a
+//! specification. Instead, each code is listed with the MENOTA (TEI) encoding
+//! it stands for, which is what a transcriber's key should be checked against.
+//!
+//! | Menotec | DSL | MENOTA encoding |
+//! |---------|-----|-----------------|
+//! | `#1r` (own line) | `///1r` | `<pb n="1r"/>` |
+//! | text line | `//` | `<lb/>` |
+//! | `5⇥text` | `//5 text` | `<lb n="5"/>` (digits followed by a tab) |
+//! | `text=` (line end) | `text//` | `<lb/>` inside the word |
+//! | `&name;` | `:name:` | Character entity |
+//! | `k(onung)r` | `.abbr[kr]{konungr}` | `<choice><abbr>`/`<expan>`, `<am>`/`<ex>` |
+//! | `form\|dipl\|norm` | `form.dipl{dipl}.norm{norm}` | `<me:facs>`, `<me:dipl>`, `<me:norm>` |
+//! | `[...]`, `[...3]` | same | `<gap/>`, `<gap quantity="3"/>` |
+//! | `<text>` | same | `<supplied>` |
+//! | `{text}` | `-{text}-` | `<del>` |
+//! | `\text/` | `+{text}+` | `<add>` |
+//! | `upp~haf` | same | Compound join |
+//!
+//! Level codes give the diplomatic and, optionally, the normalised form of a
+//! word after its facsimile form, separated by `|`; the facsimile form may
+//! itself be abbreviated. Blank lines are ignored. A continued word is joined by the word tokenizer's
+//! line-break heuristic, so the `=` must follow a letter; continuation across a
+//! page marker is not kept.
+//!
+//! ## Export
+//!
+//! Export runs the DSL through the lexer and [`WordTokenizer`], the same word
+//! stream the compiler uses, so words and punctuation come out with normalised
+//! spacing. Constructs without a Menotec code (unclear readings, notes, headings,
+//! normalised-only text) are written as plain text or dropped, with a warning.
+
+use crate::importer::tei::ImportResult;
+use crate::parser::{Lexer, Node, WordTokenizer};
+use serde::{Deserialize, Serialize};
+
+/// Characters that delimit other codes and never belong to an abbreviated word
+/// or its level forms
+const CODE_DELIMITERS: &[char] = &['{', '}', '\\', '/', '<', '>', '[', ']'];
+
+/// Punctuation stripped from the edges of a word before looking for parentheses
+const EDGE_PUNCTUATION: &[char] = &['.', ',', ';', '!', '?'];
+
+/// Result of exporting DSL to Menotec
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MenotecExport {
+    pub text: String,
+    /// Constructs that could not be represented exactly
+    pub warnings: Vec<String>,
+}
+
+/// Convert a Menotec transcription to DSL.
+pub fn parse(content: &str) -> Result<ImportResult, String> {
+    let mut dsl = String::new();
+    let mut continues = false;
+
+    for raw_line in content.trim_start_matches('\u{feff}').lines() {
+        let line = raw_line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(page) = line.strip_prefix('#') {
+            let page = page.trim();
+            if page.contains(char::is_whitespace) {
+                return Err(format!("Page number may not contain spaces: {}", line));
+            }
+            if !dsl.is_empty() {
+                dsl.push('\n');
+            }
+            dsl.push_str("///");
+            dsl.push_str(page);
+            continues = false;
+            continue;
+        }
+
+        let (number, text) = split_line_number(line);
+        let (text, next_continues) = match text.strip_suffix('=') {
+            Some(text) => (text, true),
+            None => (text, false),
+        };
+
+        if !continues && !dsl.is_empty() {
+            dsl.push('\n');
+        }
+        dsl.push_str("//");
+        match number {
+            Some(n) => dsl.push_str(n),
+            None if continues => {}
+            None => dsl.push(' '),
+        }
+        if number.is_some() && !continues {
+            dsl.push(' ');
+        }
+        dsl.push_str(&convert_codes(text.trim()));
+        continues = next_continues;
+    }
+
+    Ok(ImportResult {
+        dsl,
+        metadata: None,
+        imported_document: None,
+        original_body_xml: None,
+        original_preamble: None,
+        original_postamble: None,
+        is_imported_mode: false,
+        annotations: None,
+        inflections: Vec::new(),
+        facsimile: None,
+        report: None,
+    })
+}
+
+/// Split a leading `digits<TAB>` line number from a text line
+fn split_line_number(line: &str) -> (Option<&str>, &str) {
+    if let Some((number, text)) = line.split_once('\t') {
+        if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+            return (Some(number), text);
+        }
+    }
+    (None, line)
+}
+
+/// Rewrite the codes of one line as DSL.
+///
+/// Order matters: entities first so they can appear inside other codes, then
+/// deletions and additions, and abbreviations and levels last so that the
+/// braces they introduce are not mistaken for deletions.
+fn convert_codes(text: &str) -> String {
+    let text = convert_entities(text);
+    let text = wrap_delimited(&text, '{', '}', "-{", "}-");
+    let text = wrap_delimited(&text, '\\', '/', "+{", "}+");
+    convert_words(&text)
+}
+
+/// `&name;` → `:name:`
+fn convert_entities(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find(';') {
+            Some(end) if end > 0 && after[..end].chars().all(|c| c.is_ascii_alphanumeric()) => {
+                output.push(':');
+                output.push_str(&after[..end]);
+                output.push(':');
+                rest = &after[end + 1..];
+            }
+            _ => {
+                output.push('&');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Replace `open text close` with `prefix text suffix`; unclosed codes are kept as-is
+fn wrap_delimited(text: &str, open: char, close: char, prefix: &str, suffix: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len_utf8()..];
+        let Some(end) = after.find(close) else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        output.push_str(prefix);
+        output.push_str(&after[..end]);
+        output.push_str(suffix);
+        rest = &after[end + close.len_utf8()..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Convert the abbreviations and level codes in every run of word characters
+fn convert_words(text: &str) -> String {
+    let mut output = String::new();
+    let mut run = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || CODE_DELIMITERS.contains(&c) {
+            output.push_str(&convert_run(&std::mem::take(&mut run)));
+            output.push(c);
+        } else {
+            run.push(c);
+        }
+    }
+    output.push_str(&convert_run(&run));
+    output
+}
+
+/// `k(onung)r|konungr|konungr,` → `.abbr[kr]{konungr}.dipl{konungr}.norm{konungr},`
+fn convert_run(run: &str) -> String {
+    let core = run.trim_matches(EDGE_PUNCTUATION);
+    if core.is_empty() {
+        return run.to_string();
+    }
+    let start = run.find(core).unwrap_or(0);
+
+    let mut levels = core.split('|');
+    let mut word = abbreviate(levels.next().unwrap_or_default());
+    if let Some(dipl) = levels.next() {
+        word.push_str(&format!(".dipl{{{}}}", dipl));
+    }
+    if let Some(norm) = levels.next() {
+        word.push_str(&format!(".norm{{{}}}", norm));
+    }
+    if levels.next().is_some() {
+        // More forms than there are levels: leave the run alone
+        return run.to_string();
+    }
+
+    format!("{}{}{}", &run[..start], word, &run[start + core.len()..])
+}
+
+/// `k(onung)r` → `.abbr[kr]{konungr}`; anything else is returned unchanged
+fn abbreviate(core: &str) -> String {
+    if !core.contains('(') {
+        return core.to_string();
+    }
+
+    let mut abbr = String::new();
+    let mut expansion = String::new();
+    let mut depth = 0;
+    for c in core.chars() {
+        match c {
+            '(' if depth == 0 => depth = 1,
+            ')' if depth == 1 => depth = 0,
+            '(' | ')' => return core.to_string(),
+            _ => {
+                if depth == 0 {
+                    abbr.push(c);
+                }
+                expansion.push(c);
+            }
+        }
+    }
+    if depth != 0 || abbr.is_empty() {
+        return core.to_string();
+    }
+    format!(".abbr[{}]{{{}}}", abbr, expansion)
+}
+
+/// Export DSL as Menotec text.
+pub fn export(dsl: &str) -> Result<MenotecExport, String> {
+    let document = Lexer::new(dsl).parse()?;
+    let nodes = WordTokenizer::new().tokenize(document.nodes);
+
+    let mut writer = MenotecWriter::default();
+    for node in &nodes {
+        writer.top_level(node);
+    }
+    Ok(writer.finish())
+}
+
+#[derive(Default)]
+struct MenotecWriter {
+    lines: Vec<String>,
+    line: String,
+    /// Whether `line` has text after its line-number prefix
+    has_text: bool,
+    /// Whether a line has been opened at all (by a break or by text)
+    started: bool,
+    warnings: Vec<String>,
+}
+
+impl MenotecWriter {
+    fn top_level(&mut self, node: &Node) {
+        match node {
+            Node::Word(children) => {
+                self.separate();
+                for child in children {
+                    self.in_word(child);
+                }
+            }
+            Node::Punctuation(children) => {
+                for child in children {
+                    if let Node::Text(text) = child {
+                        if text.starts_with(['(', '[']) {
+                            self.separate();
+                        }
+                        self.push_text(text);
+                    }
+                }
+            }
+            Node::LineBreak(n) => self.new_line(n.as_deref()),
+            Node::PageBreak(n) => {
+                self.close_line();
+                self.lines.push(format!("#{}", n));
+            }
+            Node::Head(text) => {
+                self.warn(format!("Heading written as plain text: {}", text));
+                self.separate();
+                self.push_text(&entities_to_menotec(text));
+            }
+            Node::SuppliedBlock(text) => {
+                self.warn(format!("Supplied block written as supplied text: {}", text));
+                self.separate();
+                self.push_text(&format!("<{}>", entities_to_menotec(text)));
+            }
+            Node::Norm(text) => {
+                self.warn(format!("Normalised-only text dropped: {}", text));
+            }
+            _ => {}
+        }
+    }
+
+    fn in_word(&mut self, node: &Node) {
+        match node {
+            Node::Text(text) => self.push_text(text),
+            Node::Entity(name) => self.push_text(&format!("&{};", name)),
+            Node::Abbreviation { abbr, expansion } => {
+                let abbr = entities_to_menotec(abbr);
+                let expansion = entities_to_menotec(expansion);
+                match align_abbreviation(&abbr, &expansion) {
+                    Some(aligned) => self.push_text(&aligned),
+                    None => {
+                        self.warn(format!(
+                            "Abbreviation {} is not contained in its expansion {}; wrote the expansion",
+                            abbr, expansion
+                        ));
+                        self.push_text(&expansion);
+                    }
+                }
+            }
+            Node::Gap { quantity, supplied } => {
+                let mut gap = String::from("[...");
+                if let Some(q) = quantity {
+                    gap.push_str(&q.to_string());
+                }
+                if let Some(s) = supplied {
+                    gap.push_str(&format!("<{}>", entities_to_menotec(s)));
+                }
+                gap.push(']');
+                self.push_text(&gap);
+            }
+            Node::Supplied(text) => self.push_text(&format!("<{}>", entities_to_menotec(text))),
+            Node::Deletion(text) => self.push_text(&format!("{{{}}}", entities_to_menotec(text))),
+            Node::Addition(text) => self.push_text(&format!("\\{}/", entities_to_menotec(text))),
+            Node::Unclear(text) => {
+                self.warn(format!("Unclear reading written as plain text: {}", text));
+                self.push_text(&entities_to_menotec(text));
+            }
+            Node::Note(text) => self.warn(format!("Note dropped: {}", text)),
+            Node::DiplOverride(text) | Node::NormOverride(text) => {
+                self.push_text(&format!("|{}", entities_to_menotec(text)))
+            }
+            Node::CompoundJoin => self.push_text("~"),
+            Node::LineBreak(n) => {
+                self.line.push('=');
+                self.new_line(n.as_deref());
+            }
+            Node::PageBreak(n) => {
+                self.warn(format!("Word continuation across page {} not kept", n));
+                self.close_line();
+                self.lines.push(format!("#{}", n));
+            }
+            _ => {}
+        }
+    }
+
+    /// Space before a word, unless at line start or after opening punctuation
+    fn separate(&mut self) {
+        if self.has_text && !self.line.ends_with(['(', '[']) {
+            self.line.push(' ');
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.line.push_str(text);
+        self.has_text = true;
+        self.started = true;
+    }
+
+    fn new_line(&mut self, n: Option<&str>) {
+        self.close_line();
+        self.started = true;
+        if let Some(n) = n {
+            self.line.push_str(n);
+            self.line.push('\t');
+        }
+    }
+
+    fn close_line(&mut self) {
+        if self.started {
+            self.lines.push(std::mem::take(&mut self.line));
+        }
+        self.has_text = false;
+        self.started = false;
+    }
+
+    fn warn(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+
+    fn finish(mut self) -> MenotecExport {
+        self.close_line();
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        MenotecExport {
+            text,
+            warnings: self.warnings,
+        }
+    }
+}
+
+/// `:name:` → `&name;` inside the raw text of inline constructs
+fn entities_to_menotec(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find(':') {
+            Some(end)
+                if end > 0
+                    && after[..end]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                output.push('&');
+                output.push_str(&after[..end]);
+                output.push(';');
+                rest = &after[end + 1..];
+            }
+            _ => {
+                output.push(':');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Write an expansion with the letters missing from the abbreviation in
+/// parentheses, e.g. `kr` + `konungr` → `k(onung)r`.
+///
+/// Letters are matched greedily, so the abbreviation must be a subsequence of
+/// the expansion; returns `None` otherwise. Entity codes are matched as units.
+fn align_abbreviation(abbr: &str, expansion: &str) -> Option<String> {
+    let abbr_units = units(abbr);
+    let mut next = abbr_units.iter().peekable();
+    let mut output = String::new();
+    let mut open = false;
+
+    for unit in units(expansion) {
+        if next.peek() == Some(&&unit) {
+            next.next();
+            if open {
+                output.push(')');
+                open = false;
+            }
+        } else if !open {
+            output.push('(');
+            open = true;
+        }
+        output.push_str(unit);
+    }
+    if open {
+        output.push(')');
+    }
+
+    if next.next().is_some() {
+        None
+    } else {
+        Some(output)
+    }
+}
+
+/// Split text into characters, keeping `&name;` codes whole
+fn units(text: &str) -> Vec<&str> {
+    let mut units = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '&' {
+            rest.find(';').map(|end| end + 1).unwrap_or(1)
+        } else {
+            c.len_utf8()
+        };
+        units.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    units
+}
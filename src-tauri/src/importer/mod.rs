@@ -1,3 +1,7 @@
+pub mod docx;
+pub mod htr;
+pub mod menotec;
+pub mod plaintext;
 pub mod tei;
 
 #[cfg(test)]
@@ -0,0 +1,520 @@
+//! # Plain-Text Importer
+//!
+//! Converts typed transcriptions to DSL with a configurable [`PlainTextProfile`].
+//! Each rule maps a marker or a pair of delimiters to a DSL construct. The
+//! default profile follows common typing conventions:
+//!
+//! | Typed | Meaning | DSL |
+//! |-------|---------|-----|
+//! | `\|\|` | Page end | `///` |
+//! | `\|` | Line end | `//` |
+//! | `k(onun)gr` | Expansion | `.abbr[kgr]{konungr}` |
+//! | `[text]` | Supplied text | `<text>` |
+//! | `[...]` | Lacuna | `[...]` |
+//!
+//! A break inside a word (`hal|fr`) becomes a word continuation (`hal~// fr`).
+//! Markers are matched longest first, so `||` is never read as two `|`.
+//!
+//! [`convert`] also lists every conversion with its source line, so a profile
+//! can be previewed on a file before it is imported.
+
+use crate::importer::tei::ImportResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Characters that end a word being assembled for expansion detection
+const WORD_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// DSL construct a rule produces
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Construct {
+    LineBreak,
+    PageBreak,
+    /// Expanded letters of an abbreviated word
+    Expansion,
+    Supplied,
+    Gap,
+    Unclear,
+    Deletion,
+    Addition,
+    Note,
+    /// Keep the enclosed text, dropping the delimiters
+    Text,
+}
+
+impl Construct {
+    fn is_break(self) -> bool {
+        matches!(self, Construct::LineBreak | Construct::PageBreak)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Construct::LineBreak => "line break",
+            Construct::PageBreak => "page break",
+            Construct::Expansion => "expansion",
+            Construct::Supplied => "supplied",
+            Construct::Gap => "gap",
+            Construct::Unclear => "unclear",
+            Construct::Deletion => "deletion",
+            Construct::Addition => "addition",
+            Construct::Note => "note",
+            Construct::Text => "text",
+        }
+    }
+}
+
+/// Maps a typing convention to a DSL construct
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlainTextRule {
+    /// Break marker, or opening delimiter of a span
+    pub open: String,
+    /// Closing delimiter of a span; empty for breaks
+    #[serde(default)]
+    pub close: String,
+    pub construct: Construct,
+}
+
+impl PlainTextRule {
+    fn new(open: &str, close: &str, construct: Construct) -> Self {
+        Self {
+            open: open.to_string(),
+            close: close.to_string(),
+            construct,
+        }
+    }
+
+    /// Short description used in previews, e.g. "( ) → expansion"
+    pub fn describe(&self) -> String {
+        if self.construct.is_break() {
+            format!("{} → {}", self.open, self.construct.name())
+        } else {
+            format!("{} {} → {}", self.open, self.close, self.construct.name())
+        }
+    }
+}
+
+/// A named set of rules for importing plain text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlainTextProfile {
+    pub name: String,
+    pub rules: Vec<PlainTextRule>,
+}
+
+impl Default for PlainTextProfile {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            rules: vec![
+                PlainTextRule::new("||", "", Construct::PageBreak),
+                PlainTextRule::new("|", "", Construct::LineBreak),
+                PlainTextRule::new("(", ")", Construct::Expansion),
+                PlainTextRule::new("[", "]", Construct::Supplied),
+            ],
+        }
+    }
+}
+
+impl PlainTextProfile {
+    /// Checks that every rule can be applied.
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in &self.rules {
+            if rule.open.is_empty() {
+                return Err(format!("Rule '{}' has no marker", rule.describe()));
+            }
+            if !rule.construct.is_break() && rule.close.is_empty() {
+                return Err(format!(
+                    "Rule '{}' needs a closing delimiter",
+                    rule.describe()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One place where a rule changed the text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlainTextConversion {
+    /// 1-based source line
+    pub line: usize,
+    /// The rule that applied, as described by [`PlainTextRule::describe`]
+    pub rule: String,
+    pub original: String,
+    pub dsl: String,
+}
+
+/// Something a rule could not convert
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlainTextWarning {
+    /// 1-based source line
+    pub line: usize,
+    pub message: String,
+}
+
+/// Outcome of converting plain text with a profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlainTextPreview {
+    pub dsl: String,
+    /// Number of conversions per rule
+    pub counts: BTreeMap<String, usize>,
+    pub conversions: Vec<PlainTextConversion>,
+    pub warnings: Vec<PlainTextWarning>,
+}
+
+/// Import plain text with a profile.
+pub fn parse(content: &str, profile: &PlainTextProfile) -> Result<ImportResult, String> {
+    let preview = convert(content, profile)?;
+
+    Ok(ImportResult {
+        dsl: preview.dsl,
+        metadata: None,
+        imported_document: None,
+        original_body_xml: None,
+        original_preamble: None,
+        original_postamble: None,
+        is_imported_mode: false,
+        annotations: None,
+        inflections: Vec::new(),
+        facsimile: None,
+        report: None,
+    })
+}
+
+/// Convert plain text to DSL, listing every conversion made.
+pub fn convert(content: &str, profile: &PlainTextProfile) -> Result<PlainTextPreview, String> {
+    profile.validate()?;
+
+    // Longest markers first; the sort is stable, so ties keep profile order
+    let mut rules: Vec<&PlainTextRule> = profile.rules.iter().collect();
+    rules.sort_by(|a, b| b.open.len().cmp(&a.open.len()));
+
+    let mut converter = Converter {
+        preview: PlainTextPreview::default(),
+        pieces: Vec::new(),
+    };
+    converter.scan(content, &rules);
+    converter.render();
+    Ok(converter.preview)
+}
+
+/// Text split into plain text and the output of rules
+#[derive(Debug)]
+enum Piece {
+    Text(String),
+    Expansion {
+        source: String,
+        text: String,
+        rule: String,
+    },
+    Break(&'static str),
+    Markup(String),
+}
+
+struct Converter {
+    preview: PlainTextPreview,
+    /// Pieces with the source line they start on
+    pieces: Vec<(usize, Piece)>,
+}
+
+impl Converter {
+    fn scan(&mut self, content: &str, rules: &[&PlainTextRule]) {
+        let mut line = 1;
+        let mut pos = 0;
+        let mut text = String::new();
+        let mut text_line = 1;
+
+        while pos < content.len() {
+            let rest = &content[pos..];
+            let matched = rules.iter().find(|rule| rest.starts_with(rule.open.as_str()));
+
+            let Some(rule) = matched else {
+                let c = rest.chars().next().unwrap_or_default();
+                if text.is_empty() {
+                    text_line = line;
+                }
+                text.push(c);
+                if c == '\n' {
+                    line += 1;
+                }
+                pos += c.len_utf8();
+                continue;
+            };
+
+            if rule.construct.is_break() {
+                self.flush_text(&mut text, text_line);
+                let marker = if rule.construct == Construct::PageBreak {
+                    "///"
+                } else {
+                    "//"
+                };
+                self.record(line, rule, &rule.open, marker);
+                self.pieces.push((line, Piece::Break(marker)));
+                pos += rule.open.len();
+                continue;
+            }
+
+            let inner_start = pos + rule.open.len();
+            let Some(inner_len) = content[inner_start..].find(rule.close.as_str()) else {
+                self.preview.warnings.push(PlainTextWarning {
+                    line,
+                    message: format!("'{}' is never closed with '{}'", rule.open, rule.close),
+                });
+                if text.is_empty() {
+                    text_line = line;
+                }
+                text.push_str(&rule.open);
+                pos = inner_start;
+                continue;
+            };
+            let inner = &content[inner_start..inner_start + inner_len];
+            let end = inner_start + inner_len + rule.close.len();
+            let source = &content[pos..end];
+
+            self.flush_text(&mut text, text_line);
+            if rule.construct == Construct::Expansion {
+                self.pieces.push((
+                    line,
+                    Piece::Expansion {
+                        source: source.to_string(),
+                        text: inner.to_string(),
+                        rule: rule.describe(),
+                    },
+                ));
+            } else {
+                let dsl = span_dsl(rule.construct, inner);
+                self.record(line, rule, source, &dsl);
+                self.pieces.push((line, Piece::Markup(dsl)));
+            }
+            line += source.matches('\n').count();
+            pos = end;
+        }
+        self.flush_text(&mut text, text_line);
+    }
+
+    fn flush_text(&mut self, text: &mut String, line: usize) {
+        if !text.is_empty() {
+            self.pieces.push((line, Piece::Text(std::mem::take(text))));
+        }
+    }
+
+    fn record(&mut self, line: usize, rule: &PlainTextRule, original: &str, dsl: &str) {
+        self.record_described(line, rule.describe(), original, dsl);
+    }
+
+    fn record_described(&mut self, line: usize, rule: String, original: &str, dsl: &str) {
+        *self.preview.counts.entry(rule.clone()).or_default() += 1;
+        self.preview.conversions.push(PlainTextConversion {
+            line,
+            rule,
+            original: original.to_string(),
+            dsl: dsl.to_string(),
+        });
+    }
+
+    /// Assemble the pieces into DSL, building abbreviations from words with
+    /// expansions and placing breaks at the start of a line
+    fn render(&mut self) {
+        let pieces = std::mem::take(&mut self.pieces);
+        let mut output = String::new();
+        let mut word = Word::default();
+        let mut after_break = false;
+
+        for (idx, (line, piece)) in pieces.iter().enumerate() {
+            match piece {
+                Piece::Text(text) => {
+                    let mut text = text.as_str();
+                    if after_break {
+                        text = text.trim_start();
+                        if !text.is_empty() {
+                            output.push(' ');
+                        }
+                        after_break = false;
+                    }
+                    for c in text.chars() {
+                        if c.is_whitespace() || WORD_PUNCTUATION.contains(&c) {
+                            self.flush_word(&mut word, &mut output);
+                            output.push(c);
+                        } else {
+                            word.push(c, false, *line);
+                            word.source.push(c);
+                        }
+                    }
+                }
+                Piece::Expansion { source, text, rule } => {
+                    if after_break {
+                        output.push(' ');
+                        after_break = false;
+                    }
+                    for c in text.chars() {
+                        word.push(c, true, *line);
+                    }
+                    word.source.push_str(source);
+                    word.rule = Some(rule.clone());
+                }
+                Piece::Break(marker) => {
+                    // A break between two letters splits a word
+                    let continues = !word.is_empty()
+                        && match pieces.get(idx + 1) {
+                            Some((_, Piece::Text(next))) => {
+                                next.starts_with(|c: char| !c.is_whitespace())
+                            }
+                            Some((_, Piece::Expansion { .. })) => true,
+                            _ => false,
+                        };
+                    self.flush_word(&mut word, &mut output);
+                    if continues {
+                        output.push('~');
+                        output.push_str(marker);
+                    } else {
+                        let trimmed = output.trim_end_matches([' ', '\t']).len();
+                        output.truncate(trimmed);
+                        if !output.is_empty() && !output.ends_with('\n') {
+                            output.push('\n');
+                        }
+                        output.push_str(marker);
+                    }
+                    after_break = true;
+                }
+                Piece::Markup(dsl) => {
+                    if after_break {
+                        output.push(' ');
+                        after_break = false;
+                    }
+                    self.flush_word(&mut word, &mut output);
+                    output.push_str(dsl);
+                }
+            }
+        }
+        self.flush_word(&mut word, &mut output);
+
+        self.preview.dsl = output;
+    }
+
+    fn flush_word(&mut self, word: &mut Word, output: &mut String) {
+        let word = std::mem::take(word);
+        let Some(rule) = word.rule else {
+            output.push_str(&word.expansion);
+            return;
+        };
+        if word.abbr.is_empty() {
+            // Nothing left of the word once the expansion is removed
+            self.preview.warnings.push(PlainTextWarning {
+                line: word.line,
+                message: format!("'{}' has no abbreviated letters", word.source),
+            });
+            output.push_str(&word.expansion);
+            return;
+        }
+        let dsl = format!(".abbr[{}]{{{}}}", word.abbr, word.expansion);
+        self.record_described(word.line, rule, &word.source, &dsl);
+        output.push_str(&dsl);
+    }
+}
+
+/// A word being assembled from typed letters and expansions
+#[derive(Default)]
+struct Word {
+    abbr: String,
+    expansion: String,
+    /// Typed form, with the expansion delimiters
+    source: String,
+    /// Expansion rule that applied, if any
+    rule: Option<String>,
+    line: usize,
+}
+
+impl Word {
+    fn push(&mut self, c: char, expanded: bool, line: usize) {
+        if self.expansion.is_empty() {
+            self.line = line;
+        }
+        self.expansion.push(c);
+        if !expanded {
+            self.abbr.push(c);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.expansion.is_empty()
+    }
+}
+
+/// DSL for the content of a span rule
+fn span_dsl(construct: Construct, inner: &str) -> String {
+    let is_lacuna = !inner.is_empty() && inner.chars().all(|c| c == '.' || c == '…');
+    match construct {
+        Construct::Supplied if is_lacuna => "[...]".to_string(),
+        Construct::Supplied => format!("<{}>", inner),
+        Construct::Gap => {
+            let digits = inner.trim_matches(|c| c == '.' || c == '…');
+            if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+                format!("[...{}]", digits)
+            } else {
+                "[...]".to_string()
+            }
+        }
+        Construct::Unclear => format!("?{{{}}}?", inner),
+        Construct::Deletion => format!("-{{{}}}-", inner),
+        Construct::Addition => format!("+{{{}}}+", inner),
+        Construct::Note => format!("^{{{}}}", inner),
+        Construct::Text | Construct::Expansion | Construct::LineBreak | Construct::PageBreak => {
+            inner.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_profile_conventions() {
+        let text = "ok k(onun)gr sagði | at [hann] fór ||\nheim [...] hal|fr";
+        let preview = convert(text, &PlainTextProfile::default()).unwrap();
+        assert_eq!(
+            preview.dsl,
+            "ok .abbr[kgr]{konungr} sagði\n// at <hann> fór\n/// heim [...] hal~// fr"
+        );
+        assert_eq!(preview.counts["| → line break"], 2);
+        assert_eq!(preview.counts["|| → page break"], 1);
+        assert_eq!(preview.counts["( ) → expansion"], 1);
+
+        let expansion = preview
+            .conversions
+            .iter()
+            .find(|c| c.rule == "( ) → expansion")
+            .unwrap();
+        assert_eq!(expansion.original, "k(onun)gr");
+        assert_eq!(expansion.dsl, ".abbr[kgr]{konungr}");
+        let lacuna = preview.conversions.iter().find(|c| c.original == "[...]").unwrap();
+        assert_eq!(lacuna.line, 2);
+    }
+
+    #[test]
+    fn test_custom_rules_and_warnings() {
+        let profile = PlainTextProfile {
+            name: "Brackets as deletions".to_string(),
+            rules: vec![
+                PlainTextRule::new("/", "", Construct::LineBreak),
+                PlainTextRule::new("[", "]", Construct::Deletion),
+                PlainTextRule::new("{", "}", Construct::Gap),
+            ],
+        };
+        let preview = convert("a [b] {..3} / c [d", &profile).unwrap();
+        assert_eq!(preview.dsl, "a -{b}- [...3]\n// c [d");
+        assert_eq!(preview.warnings.len(), 1);
+        assert_eq!(preview.warnings[0].line, 1);
+
+        let invalid = PlainTextProfile {
+            name: String::new(),
+            rules: vec![PlainTextRule::new("(", "", Construct::Expansion)],
+        };
+        assert!(convert("x", &invalid).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//! # Attribute Policy for Modified Words
+//!
+//! When the patching system recompiles a `<w>` whose DSL was edited, the
+//! attributes of the original element are merged onto the new one according to
+//! an [`AttributePolicy`]:
+//!
+//! - **Identity attributes** (`xml:id`, `n`, `facs`, `corresp`, ... — anything
+//!   not listed as linguistic) always carry over: they name or link the word,
+//!   whatever its spelling.
+//! - **Linguistic attributes** (`lemma`, `me:msa`, `ana`, ...) describe the old
+//!   form. They carry over for small edits such as a typo fix. When the form
+//!   changes substantially they are reported as [`StaleAttributes`], and kept or
+//!   dropped depending on [`AttributePolicy::drop_stale`].
+//!
+//! The size of an edit is the character edit distance between the old and new
+//! word forms relative to the longer of the two.
+
+use crate::parser::{Lexer, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Rules for carrying word attributes over a modification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AttributePolicy {
+    /// Attributes that only hold for the original form
+    pub linguistic: Vec<String>,
+    /// Largest share of changed characters that still counts as a small edit
+    pub max_change_ratio: f64,
+    /// Leave stale attributes off the recompiled word instead of keeping them
+    pub drop_stale: bool,
+}
+
+impl Default for AttributePolicy {
+    fn default() -> Self {
+        Self {
+            linguistic: ["lemma", "lemmaRef", "me:msa", "msa", "ana", "pos"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            max_change_ratio: 0.4,
+            drop_stale: false,
+        }
+    }
+}
+
+/// Linguistic attributes of a word whose form changed substantially.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleAttributes {
+    /// Segment ID of the modified word
+    pub segment_id: usize,
+    /// Word form before the edit
+    pub original_form: String,
+    /// Word form after the edit
+    pub new_form: String,
+    /// The stale attributes with their original values
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl AttributePolicy {
+    /// Merges the original attributes of a word onto its edited DSL.
+    ///
+    /// Returns the attributes to write, and the linguistic attributes that went
+    /// stale (empty for a small edit).
+    pub fn merge(
+        &self,
+        attributes: &HashMap<String, String>,
+        original_dsl: &str,
+        new_dsl: &str,
+    ) -> (HashMap<String, String>, BTreeMap<String, String>) {
+        let mut merged = attributes.clone();
+        let mut stale = BTreeMap::new();
+
+        if self.is_substantial(&word_form(original_dsl), &word_form(new_dsl)) {
+            for name in &self.linguistic {
+                let Some(value) = attributes.get(name) else {
+                    continue;
+                };
+                stale.insert(name.clone(), value.clone());
+                if self.drop_stale {
+                    merged.remove(name);
+                }
+            }
+        }
+
+        (merged, stale)
+    }
+
+    fn is_substantial(&self, original: &str, new: &str) -> bool {
+        let longest = original.chars().count().max(new.chars().count());
+        if longest == 0 {
+            return false;
+        }
+        edit_distance(original, new) as f64 / longest as f64 > self.max_change_ratio
+    }
+}
+
+/// The reading of a word's DSL: expansions, supplied and added text and
+/// entities, without notes, deletions, breaks or level overrides.
+pub fn word_form(dsl: &str) -> String {
+    let Ok(doc) = Lexer::new(dsl).parse() else {
+        return dsl.to_string();
+    };
+
+    let mut form = String::new();
+    for node in &doc.nodes {
+        match node {
+            Node::Text(text)
+            | Node::Supplied(text)
+            | Node::Addition(text)
+            | Node::Unclear(text) => form.push_str(text),
+            Node::Abbreviation { expansion, .. } => form.push_str(expansion),
+            Node::Gap {
+                supplied: Some(text),
+                ..
+            } => form.push_str(text),
+            Node::Entity(name) => form.push_str(&format!(":{}:", name)),
+            _ => {}
+        }
+    }
+    form.trim().to_string()
+}
+
+/// Levenshtein distance in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes() -> HashMap<String, String> {
+        [
+            ("xml:id", "w12"),
+            ("lemma", "konungr"),
+            ("me:msa", "xNC cN nS gM"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_small_edit_keeps_everything() {
+        let policy = AttributePolicy::default();
+        let (merged, stale) = policy.merge(&attributes(), "konongr", "konungr");
+        assert_eq!(merged, attributes());
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn test_substantial_edit_flags_linguistic_attributes() {
+        let policy = AttributePolicy::default();
+        let (merged, stale) = policy.merge(&attributes(), "konungr", "drottning");
+        assert_eq!(merged, attributes());
+        assert_eq!(stale.len(), 2);
+        assert_eq!(stale["lemma"], "konungr");
+
+        let dropping = AttributePolicy {
+            drop_stale: true,
+            ..Default::default()
+        };
+        let (merged, _) = dropping.merge(&attributes(), "konungr", "drottning");
+        assert_eq!(merged.get("xml:id").map(String::as_str), Some("w12"));
+        assert!(!merged.contains_key("lemma"));
+        assert!(!merged.contains_key("me:msa"));
+    }
+
+    #[test]
+    fn test_word_form_reads_through_markup() {
+        assert_eq!(word_form(".abbr[kr]{konungr}"), "konungr");
+        assert_eq!(word_form("ko<n>ungr^{note}"), "konungr");
+        assert_eq!(edit_distance("konungr", "konongr"), 1);
+    }
+}
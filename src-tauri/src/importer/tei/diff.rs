@@ -0,0 +1,345 @@
+//! # Token Sequence Diff
+//!
+//! Linear-space diff used by the [`patching`](super::patching) module to line up
+//! the original segments with the edited DSL.
+//!
+//! ## Algorithm
+//!
+//! 1. **Interning**: Tokens are compared by content, so each distinct content is
+//!    mapped to an integer once and the diff runs on integer slices.
+//! 2. **Anchoring**: Page and line breaks that occur exactly once on both sides
+//!    are matched up (longest increasing run of their positions), and the
+//!    stretches between anchors are diffed independently. An edit on one page
+//!    can then never pull a match from another page.
+//! 3. **Myers**: Each stretch is diffed with Myers' O((N+M)·D) algorithm in its
+//!    linear-space, divide-and-conquer form (middle snake bisection).
+//!
+//! Cost grows with the number of edits rather than with the document size, so a
+//! single insertion near the start of a 50,000-word saga is found in one pass
+//! and everything after it stays a Keep.
+//!
+//! ## Cost Limit
+//!
+//! A stretch that was rewritten wholesale (or pasted over from another text)
+//! would make D, and so the running time, grow with the stretch. The search for
+//! each middle snake gives up after [`MAX_EDIT_COST`] edits, and that stretch
+//! is aligned position by position instead: tokens that happen to match at the
+//! same offset are kept, the rest are replaced.
+
+use std::collections::HashMap;
+
+/// Largest edit distance searched for per middle snake before a stretch falls
+/// back to a positional alignment
+pub const MAX_EDIT_COST: usize = 2000;
+
+/// One step of an edit script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// Next token of both sides matches
+    Keep,
+    /// Next original token is removed
+    Delete,
+    /// Next edited token is added
+    Insert,
+}
+
+/// Computes an edit script turning `original` into `edited`.
+///
+/// Within a changed stretch all deletions come before the insertions.
+pub fn diff<T: AsRef<str>>(original: &[T], edited: &[T]) -> Vec<Edit> {
+    diff_with_limit(original, edited, MAX_EDIT_COST)
+}
+
+/// [`diff`] with the given cost limit instead of [`MAX_EDIT_COST`].
+fn diff_with_limit<T: AsRef<str>>(original: &[T], edited: &[T], max_cost: usize) -> Vec<Edit> {
+    let mut ids: HashMap<&str, u32> = HashMap::new();
+    let a: Vec<u32> = original
+        .iter()
+        .map(|token| {
+            let next = ids.len() as u32;
+            *ids.entry(token.as_ref()).or_insert(next)
+        })
+        .collect();
+    let b: Vec<u32> = edited
+        .iter()
+        .map(|token| {
+            let next = ids.len() as u32;
+            *ids.entry(token.as_ref()).or_insert(next)
+        })
+        .collect();
+    let break_a: Vec<bool> = original.iter().map(|t| t.as_ref().starts_with("//")).collect();
+    let break_b: Vec<bool> = edited.iter().map(|t| t.as_ref().starts_with("//")).collect();
+
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    for (anchor_i, anchor_j) in anchors(&a, &b, &break_a, &break_b) {
+        myers(&a[i..anchor_i], &b[j..anchor_j], max_cost, &mut edits);
+        edits.push(Edit::Keep);
+        i = anchor_i + 1;
+        j = anchor_j + 1;
+    }
+    myers(&a[i..], &b[j..], max_cost, &mut edits);
+    edits
+}
+
+/// Breaks that appear exactly once on each side, as `(original, edited)`
+/// positions increasing on both sides.
+fn anchors(a: &[u32], b: &[u32], break_a: &[bool], break_b: &[bool]) -> Vec<(usize, usize)> {
+    // token → (count in a, position in a, count in b, position in b)
+    let mut occurrences: HashMap<u32, (usize, usize, usize, usize)> = HashMap::new();
+    for (pos, &token) in a.iter().enumerate().filter(|&(pos, _)| break_a[pos]) {
+        let entry = occurrences.entry(token).or_default();
+        entry.0 += 1;
+        entry.1 = pos;
+    }
+    for (pos, &token) in b.iter().enumerate().filter(|&(pos, _)| break_b[pos]) {
+        let entry = occurrences.entry(token).or_default();
+        entry.2 += 1;
+        entry.3 = pos;
+    }
+
+    let mut pairs: Vec<(usize, usize)> = occurrences
+        .into_values()
+        .filter(|&(count_a, _, count_b, _)| count_a == 1 && count_b == 1)
+        .map(|(_, pos_a, _, pos_b)| (pos_a, pos_b))
+        .collect();
+    pairs.sort_unstable();
+    longest_increasing(&pairs)
+}
+
+/// Longest subsequence of `pairs` (sorted by the first position) whose second
+/// positions also increase, by patience sorting.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // tails[k] = index of the smallest tail of an increasing run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+
+    for (idx, &(_, pos_b)) in pairs.iter().enumerate() {
+        let len = tails.partition_point(|&tail| pairs[tail].1 < pos_b);
+        previous[idx] = len.checked_sub(1).map(|k| tails[k]);
+        if len == tails.len() {
+            tails.push(idx);
+        } else {
+            tails[len] = idx;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(idx) = current {
+        result.push(pairs[idx]);
+        current = previous[idx];
+    }
+    result.reverse();
+    result
+}
+
+/// Appends the edit script for `a` → `b` to `edits`.
+fn myers(a: &[u32], b: &[u32], max_cost: usize, edits: &mut Vec<Edit>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    edits.resize(edits.len() + prefix, Edit::Keep);
+    if a.is_empty() || b.is_empty() {
+        replace(a, b, edits);
+    } else {
+        match bisect(a, b, max_cost) {
+            Some((x, y)) if (x, y) != (0, 0) && (x, y) != (a.len(), b.len()) => {
+                myers(&a[..x], &b[..y], max_cost, edits);
+                myers(&a[x..], &b[y..], max_cost, edits);
+            }
+            Some(_) => replace(a, b, edits),
+            None => positional(a, b, edits),
+        }
+    }
+    edits.resize(edits.len() + suffix, Edit::Keep);
+}
+
+/// Deletes all of `a` and inserts all of `b`
+fn replace(a: &[u32], b: &[u32], edits: &mut Vec<Edit>) {
+    edits.resize(edits.len() + a.len(), Edit::Delete);
+    edits.resize(edits.len() + b.len(), Edit::Insert);
+}
+
+/// Aligns `a` and `b` token by token: equal tokens at the same offset are
+/// kept, others replaced, and the longer side's tail deleted or inserted
+fn positional(a: &[u32], b: &[u32], edits: &mut Vec<Edit>) {
+    for (x, y) in a.iter().zip(b) {
+        if x == y {
+            edits.push(Edit::Keep);
+        } else {
+            edits.push(Edit::Delete);
+            edits.push(Edit::Insert);
+        }
+    }
+    let shared = a.len().min(b.len());
+    replace(&a[shared..], &b[shared..], edits);
+}
+
+/// Finds the middle snake of the shortest edit path and returns the point
+/// where the forward and reverse searches meet, splitting the problem in two.
+///
+/// Returns `None` if the paths have not met after `max_cost` steps each way.
+fn bisect(a: &[u32], b: &[u32], max_cost: usize) -> Option<(usize, usize)> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max_d = ((n + m + 1) / 2).min(max_cost as isize);
+    let offset = max_d;
+    let v_len = 2 * max_d + 2;
+    // Furthest x reached on each diagonal k (index offset + k), forward and reverse
+    let mut forward = vec![-1isize; v_len as usize];
+    let mut reverse = vec![-1isize; v_len as usize];
+    forward[(offset + 1) as usize] = 0;
+    reverse[(offset + 1) as usize] = 0;
+
+    let delta = n - m;
+    // With an odd delta the paths meet during a forward step, otherwise reverse
+    let check_forward = delta % 2 != 0;
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let idx = (offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[idx - 1] < forward[idx + 1]) {
+                forward[idx + 1]
+            } else {
+                forward[idx - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[idx] = x1;
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if check_forward {
+                let k2_idx = offset + delta - k1;
+                if (0..v_len).contains(&k2_idx) && reverse[k2_idx as usize] != -1 {
+                    let x2 = n - reverse[k2_idx as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let idx = (offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && reverse[idx - 1] < reverse[idx + 1]) {
+                reverse[idx + 1]
+            } else {
+                reverse[idx - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            reverse[idx] = x2;
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !check_forward {
+                let k1_idx = offset + delta - k2;
+                if (0..v_len).contains(&k1_idx) && forward[k1_idx as usize] != -1 {
+                    let x1 = forward[k1_idx as usize];
+                    let y1 = offset + x1 - k1_idx;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies an edit script and checks it reproduces `edited`
+    fn apply(original: &[&str], edited: &[&str], edits: &[Edit]) -> Vec<String> {
+        let (mut i, mut j) = (0, 0);
+        let mut output = Vec::new();
+        for edit in edits {
+            match edit {
+                Edit::Keep => {
+                    assert_eq!(original[i], edited[j]);
+                    output.push(original[i].to_string());
+                    i += 1;
+                    j += 1;
+                }
+                Edit::Delete => i += 1,
+                Edit::Insert => {
+                    output.push(edited[j].to_string());
+                    j += 1;
+                }
+            }
+        }
+        assert_eq!((i, j), (original.len(), edited.len()));
+        output
+    }
+
+    #[test]
+    fn test_diff_is_minimal() {
+        let original = ["a", "b", "c", "a", "b", "b", "a"];
+        let edited = ["c", "b", "a", "b", "a", "c"];
+        let edits = diff(&original, &edited);
+        assert_eq!(apply(&original, &edited, &edits), edited);
+        // Classic example from Myers' paper: shortest edit script has length 5
+        let changes = edits.iter().filter(|e| **e != Edit::Keep).count();
+        assert_eq!(changes, 5);
+    }
+
+    #[test]
+    fn test_diff_disjoint_and_empty() {
+        assert_eq!(diff(&["a", "b"], &["c"]).len(), 3);
+        assert_eq!(diff::<&str>(&[], &["c"]), vec![Edit::Insert]);
+        assert_eq!(diff::<&str>(&["a"], &[]), vec![Edit::Delete]);
+    }
+
+    #[test]
+    fn test_diff_falls_back_past_cost_limit() {
+        let original = ["a", "b", "c", "d", "e", "f"];
+        let edited = ["f", "e", "c", "d", "b", "a", "g"];
+        let edits = diff_with_limit(&original, &edited, 1);
+        assert_eq!(apply(&original, &edited, &edits), edited);
+        // Tokens at the same offset are still kept
+        assert_eq!(edits.iter().filter(|e| **e == Edit::Keep).count(), 2);
+
+        // Within the limit the result is the minimal script
+        let edits = diff(&original, &edited);
+        assert_eq!(apply(&original, &edited, &edits), edited);
+    }
+
+    #[test]
+    fn test_diff_anchors_on_unique_breaks() {
+        // The repeated "x" could be matched across the page break; the unique
+        // break pins the alignment so each page is diffed on its own
+        let original = ["x", "y", "///1v", "z"];
+        let edited = ["y", "///1v", "x", "z"];
+        let edits = diff(&original, &edited);
+        assert_eq!(apply(&original, &edited, &edits), edited);
+        assert_eq!(
+            edits,
+            vec![Edit::Delete, Edit::Keep, Edit::Keep, Edit::Insert, Edit::Keep]
+        );
+    }
+}
@@ -53,11 +53,24 @@ use std::collections::HashMap;
 pub struct Extractor {
     /// Next segment ID to assign
     next_id: usize,
+    /// Carry `<me:dipl>`/`<me:norm>` of MENOTA words into the DSL as
+    /// `.dipl{}.norm{}` overrides
+    level_overrides: bool,
 }
 
 impl Extractor {
     pub fn new() -> Self {
-        Self { next_id: 0 }
+        Self {
+            next_id: 0,
+            level_overrides: false,
+        }
+    }
+
+    /// Emit the diplomatic and normalized text of each MENOTA word as level
+    /// overrides, so hand-made readings survive when the word is edited.
+    pub fn with_level_overrides(mut self, enabled: bool) -> Self {
+        self.level_overrides = enabled;
+        self
     }
 
     fn next_id(&mut self) -> usize {
@@ -438,6 +451,12 @@ impl Extractor {
             }
         }
 
+        if self.level_overrides && !dsl_content.starts_with(".norm{") {
+            if let Some(overrides) = Self::level_overrides(node) {
+                dsl_content.push_str(&overrides);
+            }
+        }
+
         Segment::Word {
             id: self.next_id(),
             original_xml,
@@ -447,6 +466,46 @@ impl Extractor {
         }
     }
 
+    /// Builds `.dipl{…}.norm{…}` from a MENOTA word's diplomatic and normalized
+    /// levels. Both levels must be present and non-empty.
+    fn level_overrides(node: &Node) -> Option<String> {
+        let dipl = Self::level_override_text(&Self::find_descendant(node, "dipl")?);
+        let norm = Self::level_override_text(&Self::find_descendant(node, "norm")?);
+        if dipl.is_empty() || norm.is_empty() {
+            return None;
+        }
+        Some(format!(".dipl{{{}}}.norm{{{}}}", dipl, norm))
+    }
+
+    /// Plain text of a MENOTA level with entity references as `:name:`. Line
+    /// breaks and notes are left to the facsimile DSL.
+    ///
+    /// Markup inside the level (`<ex>`, `<hi>`, `<c>`) has no place in an
+    /// override, so it is dropped here; patching puts the original level back
+    /// when a word is edited but its override text is unchanged.
+    fn level_override_text(node: &Node) -> String {
+        fn collect(node: &Node, output: &mut String) {
+            for child in node.get_child_nodes() {
+                match child.get_type() {
+                    Some(NodeType::TextNode) => output.push_str(&child.get_content()),
+                    Some(NodeType::EntityRefNode) => {
+                        output.push_str(&format!(":{}:", child.get_name()))
+                    }
+                    Some(NodeType::ElementNode) => {
+                        if !matches!(helpers::local_name(&child).as_str(), "lb" | "pb" | "note") {
+                            collect(&child, output);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut text = String::new();
+        collect(node, &mut text);
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
     fn extract_punctuation(&mut self, node: &Node) -> Segment {
         let original_xml = helpers::serialize_node(node);
 
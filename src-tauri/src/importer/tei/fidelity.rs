@@ -0,0 +1,308 @@
+//! # Round-Trip Fidelity Check
+//!
+//! Verifies that an imported document comes out unchanged when it is not edited.
+//! The check runs the same path as an export in imported mode:
+//!
+//! ```text
+//! TEI-XML → parse → segments_to_dsl → compute_patches → apply_patches → TEI-XML
+//! ```
+//!
+//! and compares the result with the original in canonical XML (C14N 1.0 with
+//! comments), so attribute order, quoting and namespace placement do not count as
+//! changes. When the canonical forms differ, both documents are walked node by
+//! node and every difference is reported with its path and the line numbers in
+//! the original and round-tripped files.
+
+use super::attribute_policy::AttributePolicy;
+use super::extraction::segments_to_dsl;
+use super::helpers;
+use super::patching::reconstruct_document;
+use crate::parser::{Compiler, CompilerConfig};
+use libxml::tree::c14n::{CanonicalizationMode, CanonicalizationOptions};
+use libxml::tree::{Document, Node, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Maximum length of the original and round-tripped excerpts in a difference
+const EXCERPT_CHARS: usize = 60;
+
+/// Outcome of a round-trip check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FidelityReport {
+    /// The round-tripped file is byte for byte the original
+    pub byte_identical: bool,
+    /// The canonical forms of both files are equal
+    pub canonical_identical: bool,
+    /// Every difference found between the canonical forms
+    pub differences: Vec<FidelityDifference>,
+}
+
+/// One difference between the original and the round-tripped document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FidelityDifference {
+    /// Location as a path of element names with 1-based positions, e.g.
+    /// `/TEI[1]/text[1]/body[1]/p[2]/w[5]/@lemma`
+    pub path: String,
+    /// Source line in the original file (0 if unknown)
+    pub original_line: u32,
+    /// Source line in the round-tripped file (0 if unknown)
+    pub round_trip_line: u32,
+    /// What differs, e.g. "text", "attribute lemma", "missing node"
+    pub kind: String,
+    pub original: String,
+    pub round_trip: String,
+}
+
+/// Imports `xml`, exports it again without edits and compares the two.
+pub fn check_round_trip(xml: &str) -> Result<FidelityReport, String> {
+    let round_trip = round_trip(xml)?;
+    compare(xml, &round_trip)
+}
+
+/// Runs an unedited document through import and imported-mode export.
+pub fn round_trip(xml: &str) -> Result<String, String> {
+    let imported = super::parse(xml)?;
+    let document = imported
+        .imported_document
+        .ok_or("Import produced no segment manifest")?;
+
+    let dsl = segments_to_dsl(&document.segments);
+    let mut compiler = Compiler::new().with_config(CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    });
+    let rebuilt = reconstruct_document(
+        &document,
+        &dsl,
+        &imported.original_preamble.unwrap_or_default(),
+        &imported.original_postamble.unwrap_or_default(),
+        &mut compiler,
+        &AttributePolicy::default(),
+    )?;
+    Ok(rebuilt.xml)
+}
+
+/// Compares two serialisations of a document.
+pub fn compare(original: &str, round_trip: &str) -> Result<FidelityReport, String> {
+    // Reported line numbers go past 65535 in long transcriptions
+    let mut original_doc = helpers::parse_with_line_numbers(original)
+        .map_err(|e| format!("Failed to parse original XML: {}", e))?;
+    let mut round_trip_doc = helpers::parse_with_line_numbers(round_trip)
+        .map_err(|e| format!("Failed to parse round-tripped XML: {}", e))?;
+    spell_out_entities(&mut original_doc)?;
+    spell_out_entities(&mut round_trip_doc)?;
+
+    let original_c14n = canonicalize(&original_doc)?;
+    let round_trip_c14n = canonicalize(&round_trip_doc)?;
+
+    let mut report = FidelityReport {
+        byte_identical: original == round_trip,
+        canonical_identical: original_c14n == round_trip_c14n,
+        differences: Vec::new(),
+    };
+    if report.canonical_identical {
+        return Ok(report);
+    }
+
+    if let (Some(a), Some(b)) = (
+        original_doc.get_root_element(),
+        round_trip_doc.get_root_element(),
+    ) {
+        let path = format!("/{}[1]", helpers::qualified_name(&a));
+        compare_nodes(&a, &b, &path, &mut report.differences);
+    }
+
+    // Canonical forms differ in something the node walk does not look at
+    // (namespace declarations, for instance): point at the first differing byte
+    if report.differences.is_empty() {
+        let offset = original_c14n
+            .bytes()
+            .zip(round_trip_c14n.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        report.differences.push(FidelityDifference {
+            path: "/".to_string(),
+            original_line: 0,
+            round_trip_line: 0,
+            kind: format!("canonical form at byte {}", offset),
+            original: excerpt(original_c14n.get(offset..).unwrap_or_default()),
+            round_trip: excerpt(round_trip_c14n.get(offset..).unwrap_or_default()),
+        });
+    }
+
+    Ok(report)
+}
+
+/// Replaces entity references with text nodes holding the literal `&name;`.
+///
+/// MENOTA entities are declared in an external DTD that is not loaded, and C14N
+/// cannot serialise unresolved references; spelled out, they still compare.
+fn spell_out_entities(doc: &mut Document) -> Result<(), String> {
+    let Some(root) = doc.get_root_element() else {
+        return Ok(());
+    };
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        for mut child in node.get_child_nodes() {
+            match child.get_type() {
+                Some(NodeType::EntityRefNode) => {
+                    let literal = format!("&{};", child.get_name());
+                    let mut text = Node::new_text(&literal, doc)
+                        .map_err(|_| "Failed to create text node".to_string())?;
+                    child
+                        .add_prev_sibling(&mut text)
+                        .map_err(|e| format!("Failed to replace entity: {}", e))?;
+                    child.unlink();
+                }
+                Some(NodeType::ElementNode) => stack.push(child),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn canonicalize(doc: &Document) -> Result<String, String> {
+    doc.canonicalize(
+        CanonicalizationOptions {
+            mode: CanonicalizationMode::Canonical1_0,
+            with_comments: true,
+            inclusive_ns_prefixes: Vec::new(),
+        },
+        None,
+    )
+    .map_err(|_| "Failed to canonicalize XML".to_string())
+}
+
+/// Compares two elements and their descendants
+fn compare_nodes(a: &Node, b: &Node, path: &str, differences: &mut Vec<FidelityDifference>) {
+    let mut push = |kind: String, path: String, original: String, round_trip: String| {
+        differences.push(FidelityDifference {
+            path,
+            original_line: helpers::line_number(a),
+            round_trip_line: helpers::line_number(b),
+            kind,
+            original: excerpt(&original),
+            round_trip: excerpt(&round_trip),
+        });
+    };
+
+    let name_a = helpers::qualified_name(a);
+    let name_b = helpers::qualified_name(b);
+    if name_a != name_b {
+        push("element name".to_string(), path.to_string(), name_a, name_b);
+        return;
+    }
+
+    let attrs_a: BTreeMap<String, String> = helpers::attributes_with_ns(a).into_iter().collect();
+    let attrs_b: BTreeMap<String, String> = helpers::attributes_with_ns(b).into_iter().collect();
+    for name in attrs_a.keys().chain(attrs_b.keys().filter(|k| !attrs_a.contains_key(*k))) {
+        let value_a = attrs_a.get(name);
+        let value_b = attrs_b.get(name);
+        if value_a != value_b {
+            push(
+                format!("attribute {}", name),
+                format!("{}/@{}", path, name),
+                value_a.cloned().unwrap_or_default(),
+                value_b.cloned().unwrap_or_default(),
+            );
+        }
+    }
+
+    let children_a = a.get_child_nodes();
+    let children_b = b.get_child_nodes();
+    let mut positions: BTreeMap<String, usize> = BTreeMap::new();
+    for idx in 0..children_a.len().max(children_b.len()) {
+        let reference = children_a.get(idx).or(children_b.get(idx)).unwrap();
+        let step = step_name(reference);
+        let position = positions.entry(step.clone()).or_default();
+        *position += 1;
+        let child_path = format!("{}/{}[{}]", path, step, position);
+
+        match (children_a.get(idx), children_b.get(idx)) {
+            (Some(child_a), Some(child_b)) => {
+                compare_child(child_a, child_b, &child_path, differences)
+            }
+            (Some(child_a), None) => differences.push(FidelityDifference {
+                path: child_path,
+                original_line: helpers::line_number(child_a),
+                round_trip_line: helpers::line_number(b),
+                kind: "missing node".to_string(),
+                original: excerpt(&node_text(child_a)),
+                round_trip: String::new(),
+            }),
+            (None, Some(child_b)) => differences.push(FidelityDifference {
+                path: child_path,
+                original_line: helpers::line_number(a),
+                round_trip_line: helpers::line_number(child_b),
+                kind: "extra node".to_string(),
+                original: String::new(),
+                round_trip: excerpt(&node_text(child_b)),
+            }),
+            (None, None) => {}
+        }
+    }
+}
+
+fn compare_child(a: &Node, b: &Node, path: &str, differences: &mut Vec<FidelityDifference>) {
+    if a.get_type() != b.get_type() {
+        differences.push(FidelityDifference {
+            path: path.to_string(),
+            original_line: helpers::line_number(a),
+            round_trip_line: helpers::line_number(b),
+            kind: "node type".to_string(),
+            original: excerpt(&node_text(a)),
+            round_trip: excerpt(&node_text(b)),
+        });
+        return;
+    }
+
+    if a.get_type() == Some(NodeType::ElementNode) {
+        compare_nodes(a, b, path, differences);
+        return;
+    }
+
+    let (text_a, text_b) = (node_text(a), node_text(b));
+    if text_a != text_b {
+        differences.push(FidelityDifference {
+            path: path.to_string(),
+            original_line: helpers::line_number(a),
+            round_trip_line: helpers::line_number(b),
+            kind: step_name(a).trim_end_matches("()").to_string(),
+            original: excerpt(&text_a),
+            round_trip: excerpt(&text_b),
+        });
+    }
+}
+
+/// Path step for a node: the element name, or an XPath node test
+fn step_name(node: &Node) -> String {
+    match node.get_type() {
+        Some(NodeType::ElementNode) => helpers::qualified_name(node),
+        Some(NodeType::CommentNode) => "comment()".to_string(),
+        Some(NodeType::PiNode) => "processing-instruction()".to_string(),
+        Some(NodeType::EntityRefNode) => "entity()".to_string(),
+        _ => "text()".to_string(),
+    }
+}
+
+/// Comparable content of a node
+fn node_text(node: &Node) -> String {
+    match node.get_type() {
+        Some(NodeType::ElementNode) => helpers::serialize_node(node),
+        Some(NodeType::EntityRefNode) => format!("&{};", node.get_name()),
+        Some(NodeType::PiNode) => format!("<?{} {}?>", node.get_name(), node.get_content()),
+        _ => node.get_content(),
+    }
+}
+
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
@@ -1,6 +1,10 @@
 //! Helper functions for TEI XML extraction and serialization.
 
-use libxml::tree::{Namespace, Node, NodeType};
+use libxml::bindings::{
+    xmlParserOption_XML_PARSE_BIG_LINES, xmlParserOption_XML_PARSE_NOERROR,
+    xmlParserOption_XML_PARSE_NOWARNING, xmlParserOption_XML_PARSE_RECOVER, xmlReadMemory,
+};
+use libxml::tree::{Document, Namespace, Node, NodeType};
 
 /// Extract the local name of an element, stripping any namespace prefix.
 /// For example, "me:facs" becomes "facs".
@@ -13,6 +17,61 @@ pub fn local_name(node: &Node) -> String {
     }
 }
 
+/// Element children of a node
+pub fn elements(node: &Node) -> Vec<Node> {
+    node.get_child_nodes()
+        .into_iter()
+        .filter(|c| c.get_type() == Some(NodeType::ElementNode))
+        .collect()
+}
+
+/// Element children with the given local name
+pub fn children(node: &Node, name: &str) -> Vec<Node> {
+    elements(node)
+        .into_iter()
+        .filter(|c| local_name(c) == name)
+        .collect()
+}
+
+/// First element child with the given local name
+pub fn child_named(node: &Node, name: &str) -> Option<Node> {
+    elements(node).into_iter().find(|c| local_name(c) == name)
+}
+
+/// Parse XML with the options of `Parser::default()`, plus `XML_PARSE_BIG_LINES`
+/// so that [`line_number`] works past line 65535.
+pub fn parse_with_line_numbers(xml: &str) -> Result<Document, String> {
+    let len = i32::try_from(xml.len()).map_err(|_| "Document is too large".to_string())?;
+    let options = xmlParserOption_XML_PARSE_RECOVER
+        | xmlParserOption_XML_PARSE_NOERROR
+        | xmlParserOption_XML_PARSE_NOWARNING
+        | xmlParserOption_XML_PARSE_BIG_LINES;
+    // The libxml crate's ParserOptions has no flag for big lines
+    let doc = unsafe {
+        xmlReadMemory(
+            xml.as_ptr().cast(),
+            len,
+            std::ptr::null(),
+            std::ptr::null(),
+            options as i32,
+        )
+    };
+    if doc.is_null() {
+        return Err("No document could be parsed".to_string());
+    }
+    Ok(Document::new_ptr(doc))
+}
+
+/// Line number of a node in the parsed source (1-based, 0 if unknown).
+///
+/// Lines past 65535 are only known if the document was parsed with
+/// [`parse_with_line_numbers`].
+pub fn line_number(node: &Node) -> u32 {
+    // The libxml crate has no safe wrapper for xmlGetLineNo
+    let line = unsafe { libxml::bindings::xmlGetLineNo(node.node_ptr()) };
+    u32::try_from(line).unwrap_or(0)
+}
+
 /// Escape special characters in XML text content.
 pub fn escape_xml_text(text: &str) -> String {
     let mut result = String::with_capacity(text.len());
@@ -181,6 +240,15 @@ mod tests {
         assert_eq!(result, "w");
     }
 
+    #[test]
+    fn test_line_numbers_past_65535() {
+        let xml = format!("<root>{}<last/></root>", "\n".repeat(70_000));
+        let doc = parse_with_line_numbers(&xml).unwrap();
+        let root = doc.get_root_element().unwrap();
+        let last = root.get_first_element_child().unwrap();
+        assert_eq!(line_number(&last), 70_001);
+    }
+
     #[test]
     fn test_escape_xml_text() {
         assert_eq!(escape_xml_text("hello"), "hello");
@@ -13,18 +13,20 @@
 //! All TEI imports operate in "imported mode" which preserves:
 //! - Original XML preamble (DOCTYPE, comments before body)
 //! - Original XML postamble (content after body)
+//! - Front matter, back matter and grouped texts as separately editable units
 //! - Segment manifest for structure preservation
 //! - MENOTA detection for multi-level handling
 
-use crate::metadata::{
-    Availability, DateRange, History, Language, Metadata, MsContents, MsIdentifier, Person,
-    PhysDesc, PublicationStmt, RespStmt, TitleStmt,
-};
+use crate::annotations::AnnotationSet;
+use crate::metadata::{header, Metadata};
+use crate::importer::htr::Facsimile;
 use crate::importer::tei::extraction::Extractor;
 use crate::importer::tei::helpers;
-use crate::importer::tei::segments::ImportedDocument;
+use crate::importer::tei::report::{self, ImportReport};
+use crate::importer::tei::segments::{ImportedDocument, TextUnit};
+use crate::importer::tei::word_annotations::{self, HarvestedInflection};
 use libxml::parser::Parser;
-use libxml::tree::{Document, Node, NodeType};
+use libxml::tree::{Node, NodeType};
 
 /// Result of parsing a TEI-XML file.
 ///
@@ -50,6 +52,32 @@ pub struct ImportResult {
     pub original_postamble: Option<String>,
     /// Whether this file was imported in "imported mode" (preserves structure)
     pub is_imported_mode: bool,
+    /// Annotations harvested from `<w>` attributes (lemma, me:msa, @ana)
+    #[serde(default)]
+    pub annotations: Option<AnnotationSet>,
+    /// Inflections harvested from lemmatised words, keyed by wordform
+    #[serde(default)]
+    pub inflections: Vec<HarvestedInflection>,
+    /// Page layout (images, regions, line coordinates) from HTR imports
+    #[serde(default)]
+    pub facsimile: Option<Facsimile>,
+    /// Unsupported constructs and non-editable attributes (TEI imports), or
+    /// formatting with no DSL equivalent (DOCX imports)
+    #[serde(default)]
+    pub report: Option<ImportReport>,
+}
+
+/// Options controlling how a TEI-XML file is turned into DSL.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    /// Carry each MENOTA word's `<me:dipl>` and `<me:norm>` into the DSL as
+    /// `.dipl{}.norm{}` overrides instead of regenerating them on edit
+    pub level_overrides: bool,
+}
+
+/// Parses TEI-XML content into Saga-Scribe format with default options.
+pub fn parse(xml_content: &str) -> Result<ImportResult, String> {
+    parse_with_options(xml_content, ImportOptions::default())
 }
 
 /// Parses TEI-XML content into Saga-Scribe format.
@@ -58,11 +86,14 @@ pub struct ImportResult {
 ///
 /// 1. **XML Parsing**: Parse input via libxml2
 /// 2. **Metadata Extraction**: Extract teiHeader into [`Metadata`] struct
-/// 3. **Body Location**: Find the `<body>` element
+/// 3. **Text Units**: Find every `<front>`, `<body>` and `<back>`; the first
+///    `<body>` is the main unit, the others become [`TextUnit`]s
 /// 4. **MENOTA Detection**: Check for multi-level structure
 /// 5. **Section Splitting**: Preserve preamble/body/postamble for round-trip
 /// 6. **Segment Extraction**: Convert body to segment manifest
 /// 7. **DSL Generation**: Convert segments to editable DSL text
+/// 8. **Annotation Harvesting**: Collect lemma/msa/@ana from `<w>` elements
+/// 9. **Import Report**: List constructs and attributes that will not be editable
 ///
 /// # Errors
 ///
@@ -70,7 +101,10 @@ pub struct ImportResult {
 /// - XML is malformed
 /// - No root element found
 /// - No `<body>` element found
-pub fn parse(xml_content: &str) -> Result<ImportResult, String> {
+pub fn parse_with_options(
+    xml_content: &str,
+    options: ImportOptions,
+) -> Result<ImportResult, String> {
     let parser = Parser::default();
 
     // Attempt to parse
@@ -83,16 +117,33 @@ pub fn parse(xml_content: &str) -> Result<ImportResult, String> {
         .ok_or("No root element found")?;
 
     // Extract metadata from teiHeader
-    let metadata = extract_metadata(&doc, &root);
+    let metadata = extract_metadata(xml_content, &root);
 
-    // Find the <body> element specifically to avoid importing header metadata
-    let body = find_body(&root).ok_or("No <body> element found in XML")?;
+    // Locate the editable text units: front, body and back of every <text>
+    let units = find_text_units(&root);
+    let main = units
+        .iter()
+        .position(|unit| unit.kind == "body")
+        .ok_or("No <body> element found in XML")?;
+    let body = units[main].node.clone();
 
     // Check if this is a MENOTA file (has me:facs/me:dipl/me:norm structure)
-    let is_menota = has_menota_structure(&body);
-
-    // Split original XML into preamble/body/postamble for exact preservation
-    let (original_preamble, original_body_xml, original_postamble) =
+    let is_menota = units.iter().any(|unit| has_menota_structure(&unit.node));
+
+    // Split original XML into preamble/body/postamble for exact preservation.
+    // When the source spans line up with the parsed units, the other units are
+    // cut from the same spans; otherwise only the main body is editable.
+    let spans = find_unit_spans(xml_content);
+    let spans_match = spans.len() == units.len()
+        && spans.iter().zip(&units).all(|(span, unit)| span.kind == unit.kind);
+    let (original_preamble, original_body_xml, original_postamble) = if spans_match {
+        let (start, end) = (spans[main].start, spans[main].end);
+        (
+            xml_content[..start].to_string(),
+            xml_content[start..end].to_string(),
+            xml_content[end..].to_string(),
+        )
+    } else {
         match split_xml_sections(xml_content) {
             Some((preamble, body_xml, postamble)) => (preamble, body_xml, postamble),
             None => (
@@ -100,21 +151,51 @@ pub fn parse(xml_content: &str) -> Result<ImportResult, String> {
                 helpers::serialize_node(&body),
                 String::new(),
             ),
-        };
+        }
+    };
 
     // Extract segments using the new segment-based extractor
-    let mut extractor = Extractor::new();
+    let mut extractor = Extractor::new().with_level_overrides(options.level_overrides);
     let segments = extractor.extract_segments(&body);
 
     // Generate DSL from segments (this is the new, segment-aware path)
     let dsl = crate::importer::tei::extraction::segments_to_dsl(&segments);
 
+    let mut text_units = Vec::new();
+    if spans_match {
+        for (idx, (unit, span)) in units.iter().zip(&spans).enumerate() {
+            if idx == main {
+                continue;
+            }
+            let mut extractor = Extractor::new().with_level_overrides(options.level_overrides);
+            let unit_segments = extractor.extract_segments(&unit.node);
+            text_units.push(TextUnit {
+                label: unit.label.clone(),
+                kind: unit.kind.clone(),
+                original_xml: xml_content[span.start..span.end].to_string(),
+                dsl: crate::importer::tei::extraction::segments_to_dsl(&unit_segments)
+                    .trim()
+                    .to_string(),
+                segments: unit_segments,
+            });
+        }
+    }
+
     // Create the imported document manifest
     let imported_document = ImportedDocument {
         segments,
         is_menota,
+        label: units[main].label.clone(),
+        units: text_units,
     };
 
+    // Harvest linguistic annotations already present on <w> elements
+    let words = word_annotations::collect_word_attributes(&body);
+    let annotation_set = word_annotations::build_annotation_set(&words);
+    let inflections = word_annotations::build_inflections(&words);
+
+    let import_report = report::build_report(&body);
+
     // Trim output to avoid massive trailing/leading whitespace
     let trimmed = dsl.trim();
 
@@ -126,9 +207,188 @@ pub fn parse(xml_content: &str) -> Result<ImportResult, String> {
         original_preamble: Some(original_preamble),
         original_postamble: Some(original_postamble),
         is_imported_mode: true,
+        annotations: (!annotation_set.is_empty()).then_some(annotation_set),
+        inflections,
+        facsimile: None,
+        report: Some(import_report),
     })
 }
 
+/// A `<front>`, `<body>` or `<back>` element in the parsed document
+struct ParsedUnit {
+    node: Node,
+    kind: String,
+    label: String,
+}
+
+/// Collects the text units of a document in document order. Units are not
+/// looked for inside other units, so a `<body>` within a `<floatingText>` stays
+/// part of the body that contains it.
+fn find_text_units(root: &Node) -> Vec<ParsedUnit> {
+    /// Unit element, its name, and the number and `@n` of its `<text>`
+    type Found = (Node, String, usize, Option<String>);
+
+    fn walk(node: &Node, text: (usize, Option<String>), texts: &mut usize, out: &mut Vec<Found>) {
+        for child in element_children(node) {
+            let name = helpers::local_name(&child);
+            match name.as_str() {
+                "front" | "body" | "back" => out.push((child, name, text.0, text.1.clone())),
+                "text" => {
+                    *texts += 1;
+                    let n = child.get_property("n");
+                    walk(&child, (*texts, n), texts, out);
+                }
+                _ => walk(&child, text.clone(), texts, out),
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    let root_name = helpers::local_name(root);
+    if matches!(root_name.as_str(), "front" | "body" | "back") {
+        found.push((root.clone(), root_name, 0, None));
+    }
+    let mut texts = 0;
+    let start = if helpers::local_name(root) == "text" {
+        texts = 1;
+        (1, root.get_property("n"))
+    } else {
+        (0, None)
+    };
+    if found.is_empty() {
+        walk(root, start, &mut texts, &mut found);
+    }
+
+    // Name the <text> only when units come from more than one
+    let mut numbers: Vec<usize> = found.iter().map(|(_, _, number, _)| *number).collect();
+    numbers.dedup();
+    let several_texts = numbers.len() > 1;
+
+    found
+        .into_iter()
+        .map(|(node, kind, number, n)| {
+            let mut label = match kind.as_str() {
+                "front" => "Front".to_string(),
+                "back" => "Back".to_string(),
+                _ => "Body".to_string(),
+            };
+            if several_texts {
+                label = format!("Text {} · {}", n.unwrap_or_else(|| number.to_string()), label);
+            }
+            ParsedUnit { node, kind, label }
+        })
+        .collect()
+}
+
+/// Byte span of a text unit in the source XML
+#[derive(Debug, PartialEq)]
+struct UnitSpan {
+    kind: String,
+    start: usize,
+    end: usize,
+}
+
+/// Finds the source spans of the text units by scanning the markup, with the
+/// same rule as [`find_text_units`]: every `<front>`, `<body>` or `<back>` not
+/// inside another unit. Comments, CDATA, processing instructions and the
+/// DOCTYPE are skipped.
+fn find_unit_spans(xml: &str) -> Vec<UnitSpan> {
+    let mut spans = Vec::new();
+    // Open unit: kind, qualified tag name, start offset, nesting depth
+    let mut open: Option<(String, String, usize, usize)> = None;
+    let mut pos = 0;
+
+    while let Some(offset) = xml[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &xml[start..];
+        let skip_to = |terminator: &str| {
+            rest.find(terminator)
+                .map(|end| start + end + terminator.len())
+                .unwrap_or(xml.len())
+        };
+        if rest.starts_with("<!--") {
+            pos = skip_to("-->");
+            continue;
+        }
+        if rest.starts_with("<![CDATA[") {
+            pos = skip_to("]]>");
+            continue;
+        }
+        if rest.starts_with("<?") {
+            pos = skip_to("?>");
+            continue;
+        }
+        if rest.starts_with("<!") {
+            // DOCTYPE, possibly with an internal subset
+            let bracket = rest.find('[');
+            let close = rest.find('>');
+            pos = match (bracket, close) {
+                (Some(b), Some(c)) if b < c => skip_to("]>"),
+                _ => skip_to(">"),
+            };
+            continue;
+        }
+
+        let tag_end = tag_end(xml, start);
+        let closing = rest.starts_with("</");
+        let name_start = start + if closing { 2 } else { 1 };
+        let name: String = xml[name_start..tag_end]
+            .chars()
+            .take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/')
+            .collect();
+        let self_closing = !closing && xml[..tag_end].ends_with("/>");
+        let local = name.rsplit(':').next().unwrap_or_default();
+        pos = tag_end;
+
+        match open.as_mut() {
+            None if !closing && matches!(local, "front" | "body" | "back") => {
+                if self_closing {
+                    spans.push(UnitSpan {
+                        kind: local.to_string(),
+                        start,
+                        end: tag_end,
+                    });
+                } else {
+                    open = Some((local.to_string(), name.clone(), start, 1));
+                }
+            }
+            Some((_, open_name, _, depth)) if *open_name == name && !self_closing => {
+                if closing {
+                    *depth -= 1;
+                } else {
+                    *depth += 1;
+                }
+                if *depth == 0 {
+                    let (kind, _, unit_start, _) = open.take().unwrap();
+                    spans.push(UnitSpan {
+                        kind,
+                        start: unit_start,
+                        end: tag_end,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+/// Offset just past the `>` closing the tag that starts at `start`, skipping
+/// quoted attribute values
+fn tag_end(xml: &str, start: usize) -> usize {
+    let mut quote = None;
+    for (idx, c) in xml[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return start + idx + 1,
+            _ => {}
+        }
+    }
+    xml.len()
+}
+
 /// Split original XML into preamble/body/postamble sections using string search.
 /// Returns None if <body> tags cannot be found.
 fn split_xml_sections(xml: &str) -> Option<(String, String, String)> {
@@ -200,340 +460,13 @@ pub fn parse_dsl_only(xml_content: &str) -> Result<String, String> {
 // Metadata Extraction
 // ============================================================================
 
-fn extract_metadata(_doc: &Document, root: &Node) -> Option<Metadata> {
+fn extract_metadata(xml_content: &str, root: &Node) -> Option<Metadata> {
     let header = find_element(root, "teiHeader")?;
-
-    let mut metadata = Metadata::default();
-
-    // Extract fileDesc
-    if let Some(file_desc) = find_element(&header, "fileDesc") {
-        // titleStmt
-        if let Some(title_stmt) = find_element(&file_desc, "titleStmt") {
-            metadata.title_stmt = extract_title_stmt(&title_stmt);
-        }
-
-        // publicationStmt
-        if let Some(pub_stmt) = find_element(&file_desc, "publicationStmt") {
-            metadata.publication_stmt = extract_publication_stmt(&pub_stmt);
-        }
-
-        // sourceDesc -> msDesc
-        if let Some(source_desc) = find_element(&file_desc, "sourceDesc") {
-            if let Some(ms_desc) = find_element(&source_desc, "msDesc") {
-                // msIdentifier
-                if let Some(ms_id) = find_element(&ms_desc, "msIdentifier") {
-                    metadata.ms_identifier = extract_ms_identifier(&ms_id);
-                }
-
-                // msContents
-                if let Some(ms_contents) = find_element(&ms_desc, "msContents") {
-                    metadata.ms_contents = extract_ms_contents(&ms_contents);
-                }
-
-                // physDesc
-                if let Some(phys_desc) = find_element(&ms_desc, "physDesc") {
-                    metadata.phys_desc = extract_phys_desc(&phys_desc);
-                }
-
-                // history
-                if let Some(history) = find_element(&ms_desc, "history") {
-                    metadata.history = extract_history(&history);
-                }
-            }
-        }
-    }
-
-    // Extract profileDesc -> langUsage
-    if let Some(profile_desc) = find_element(&header, "profileDesc") {
-        if let Some(lang_usage) = find_element(&profile_desc, "langUsage") {
-            metadata.languages = extract_languages(&lang_usage);
-        }
-    }
-
-    // Extract encodingDesc -> editorialDecl -> normalization
-    if let Some(encoding_desc) = find_element(&header, "encodingDesc") {
-        if let Some(editorial_decl) = find_element(&encoding_desc, "editorialDecl") {
-            if let Some(normalization) = find_element(&editorial_decl, "normalization") {
-                if let Some(p) = find_element(&normalization, "p") {
-                    metadata.encoding_notes = Some(get_text_content(&p));
-                }
-            }
-        }
-    }
-
-    // Only return metadata if we found something meaningful
-    if metadata.title_stmt.title.is_some()
-        || metadata.ms_identifier.repository.is_some()
-        || metadata.ms_identifier.idno.is_some()
-        || !metadata.languages.is_empty()
-    {
-        Some(metadata)
-    } else {
-        None
-    }
-}
-
-fn extract_title_stmt(node: &Node) -> TitleStmt {
-    let mut stmt = TitleStmt::default();
-
-    // Title (first one without type attribute, or just first)
-    for child in element_children(node) {
-        if child.get_name() == "title" {
-            let type_attr = child.get_property("type");
-            if type_attr.is_none() || type_attr.as_deref() == Some("main") {
-                if stmt.title.is_none() {
-                    stmt.title = Some(get_text_content(&child));
-                }
-            } else if type_attr.as_deref() == Some("sub") {
-                stmt.subtitle = Some(get_text_content(&child));
-            }
-        }
-    }
-
-    // Author
-    if let Some(author) = find_element(node, "author") {
-        stmt.author = Some(get_text_content(&author));
-    }
-
-    // Editors
-    for child in element_children(node) {
-        if child.get_name() == "editor" {
-            let name = get_text_content(&child);
-            if !name.is_empty() {
-                let mut person = Person {
-                    name,
-                    identifier: None,
-                    identifier_type: None,
-                };
-                // Check for ref attribute (e.g., "orcid:0000-0000-0000-0000")
-                if let Some(ref_attr) = child.get_property("ref") {
-                    if let Some((id_type, id)) = ref_attr.split_once(':') {
-                        person.identifier_type = Some(id_type.to_string());
-                        person.identifier = Some(id.to_string());
-                    }
-                }
-                stmt.editors.push(person);
-            }
-        }
-    }
-
-    // respStmt
-    for child in element_children(node) {
-        if child.get_name() == "respStmt" {
-            let resp = find_element(&child, "resp").map(|n| get_text_content(&n));
-            let name = find_element(&child, "name").map(|n| get_text_content(&n));
-            if let (Some(resp), Some(name)) = (resp, name) {
-                if !resp.is_empty() && !name.is_empty() {
-                    stmt.resp_stmts.push(RespStmt { resp, name });
-                }
-            }
-        }
-    }
-
-    stmt
-}
-
-fn extract_publication_stmt(node: &Node) -> PublicationStmt {
-    let mut stmt = PublicationStmt::default();
-
-    if let Some(publisher) = find_element(node, "publisher") {
-        stmt.publisher = Some(get_text_content(&publisher));
-    }
-
-    if let Some(pub_place) = find_element(node, "pubPlace") {
-        stmt.pub_place = Some(get_text_content(&pub_place));
-    }
-
-    if let Some(date) = find_element(node, "date") {
-        // Prefer @when attribute, fall back to text content
-        stmt.date = date
-            .get_property("when")
-            .or_else(|| Some(get_text_content(&date)));
-    }
-
-    if let Some(distributor) = find_element(node, "distributor") {
-        stmt.distributor = Some(get_text_content(&distributor));
-    }
-
-    // idno
-    if let Some(idno) = find_element(node, "idno") {
-        stmt.idno = Some(get_text_content(&idno));
-        stmt.idno_type = idno.get_property("type");
-    }
-
-    // availability
-    if let Some(avail) = find_element(node, "availability") {
-        let availability = Availability {
-            status: avail.get_property("status"),
-            license: find_element(&avail, "licence").map(|l| get_text_content(&l)),
-            license_url: find_element(&avail, "licence").and_then(|l| l.get_property("target")),
-            note: find_element(&avail, "p").map(|p| get_text_content(&p)),
-        };
-
-        stmt.availability = Some(availability);
-    }
-
-    stmt
-}
-
-fn extract_ms_identifier(node: &Node) -> MsIdentifier {
-    let mut id = MsIdentifier::default();
-
-    if let Some(country) = find_element(node, "country") {
-        id.country = Some(get_text_content(&country));
-    }
-
-    if let Some(settlement) = find_element(node, "settlement") {
-        id.settlement = Some(get_text_content(&settlement));
-    }
-
-    if let Some(repository) = find_element(node, "repository") {
-        id.repository = Some(get_text_content(&repository));
-    }
-
-    if let Some(collection) = find_element(node, "collection") {
-        id.collection = Some(get_text_content(&collection));
-    }
-
-    if let Some(idno) = find_element(node, "idno") {
-        id.idno = Some(get_text_content(&idno));
-    }
-
-    if let Some(ms_name) = find_element(node, "msName") {
-        id.ms_name = Some(get_text_content(&ms_name));
-    }
-
-    // altIdentifier elements
-    for child in element_children(node) {
-        if child.get_name() == "altIdentifier" {
-            if let Some(idno) = find_element(&child, "idno") {
-                let id_type = child.get_property("type").unwrap_or_default();
-                let idno_text = get_text_content(&idno);
-                if !idno_text.is_empty() {
-                    id.alt_identifiers.push(crate::metadata::AltIdentifier {
-                        id_type,
-                        idno: idno_text,
-                    });
-                }
-            }
-        }
-    }
-
-    id
-}
-
-fn extract_ms_contents(node: &Node) -> MsContents {
-    let mut contents = MsContents::default();
-
-    if let Some(summary) = find_element(node, "summary") {
-        contents.summary = Some(get_text_content(&summary));
-    }
-
-    if let Some(text_lang) = find_element(node, "textLang") {
-        contents.text_lang = text_lang.get_property("mainLang");
-        let note = get_text_content(&text_lang);
-        if !note.is_empty() && contents.text_lang.as_deref() != Some(&note) {
-            contents.text_lang_note = Some(note);
-        }
-    }
-
-    contents
-}
-
-fn extract_phys_desc(node: &Node) -> PhysDesc {
-    let mut desc = PhysDesc::default();
-
-    // objectDesc -> supportDesc
-    if let Some(object_desc) = find_element(node, "objectDesc") {
-        if let Some(support_desc) = find_element(&object_desc, "supportDesc") {
-            if let Some(support) = find_element(&support_desc, "support") {
-                desc.material = Some(get_text_content(&support));
-            }
-            if let Some(extent) = find_element(&support_desc, "extent") {
-                desc.extent = Some(get_text_content(&extent));
-            }
-            if let Some(foliation) = find_element(&support_desc, "foliation") {
-                desc.foliation = Some(get_text_content(&foliation));
-            }
-            if let Some(condition) = find_element(&support_desc, "condition") {
-                desc.condition = Some(get_text_content(&condition));
-            }
-        }
-        if let Some(layout_desc) = find_element(&object_desc, "layoutDesc") {
-            if let Some(layout) = find_element(&layout_desc, "layout") {
-                desc.layout = Some(get_text_content(&layout));
-            }
-        }
-    }
-
-    // handDesc
-    if let Some(hand_desc) = find_element(node, "handDesc") {
-        for child in element_children(&hand_desc) {
-            if child.get_name() == "handNote" {
-                let hand = crate::metadata::HandNote {
-                    id: child.get_property("xml:id").or_else(|| child.get_property("id")),
-                    script: child.get_property("script"),
-                    scope: child.get_property("scope"),
-                    description: Some(get_text_content(&child)),
-                };
-                desc.hands.push(hand);
-            }
-        }
-    }
-
-    desc
-}
-
-fn extract_history(node: &Node) -> History {
-    let mut history = History::default();
-
-    if let Some(origin) = find_element(node, "origin") {
-        if let Some(orig_date) = find_element(&origin, "origDate") {
-            let date_range = DateRange {
-                display: Some(get_text_content(&orig_date)),
-                when: orig_date.get_property("when"),
-                not_before: orig_date.get_property("notBefore"),
-                not_after: orig_date.get_property("notAfter"),
-            };
-            history.orig_date = Some(date_range);
-        }
-
-        if let Some(orig_place) = find_element(&origin, "origPlace") {
-            history.orig_place = Some(get_text_content(&orig_place));
-        }
-    }
-
-    if let Some(provenance) = find_element(node, "provenance") {
-        history.provenance = Some(get_text_content(&provenance));
-    }
-
-    if let Some(acquisition) = find_element(node, "acquisition") {
-        history.acquisition = Some(get_text_content(&acquisition));
-    }
-
-    history
-}
-
-fn extract_languages(node: &Node) -> Vec<Language> {
-    let mut languages = Vec::new();
-
-    for child in element_children(node) {
-        if child.get_name() == "language" {
-            if let Some(ident) = child.get_property("ident") {
-                let usage = child
-                    .get_property("usage")
-                    .and_then(|s| s.parse::<u8>().ok());
-                let name = get_text_content(&child);
-                languages.push(Language {
-                    ident,
-                    usage,
-                    name: if name.is_empty() { None } else { Some(name) },
-                });
-            }
-        }
-    }
-
-    languages
+    let mut metadata = header::read_header(&header);
+    // Keep the header's source text so unmodelled content survives edits
+    metadata.original_header =
+        header::header_span(xml_content).map(|span| xml_content[span].to_string());
+    Some(metadata)
 }
 
 // ============================================================================
@@ -563,17 +496,11 @@ fn element_children(node: &Node) -> Vec<Node> {
     children
 }
 
-fn get_text_content(node: &Node) -> String {
-    let content = node.get_content();
-    // Normalize whitespace
-    content.split_whitespace().collect::<Vec<_>>().join(" ")
-}
-
 // ============================================================================
 // DSL Extraction (existing code)
 // ============================================================================
 
-fn find_body(node: &Node) -> Option<Node> {
+pub(crate) fn find_body(node: &Node) -> Option<Node> {
     if node.get_name() == "body" {
         return Some(node.clone());
     }
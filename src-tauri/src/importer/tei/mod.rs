@@ -21,7 +21,12 @@
 //! - **[`extraction`]**: Extracts segments from TEI-XML, converting content to DSL
 //! - **[`segments`]**: Defines the [`Segment`](segments::Segment) manifest structure
 //! - **[`patching`]**: Computes and applies diffs for round-trip export
+//! - **[`diff`]**: Linear-space token diff (Myers, anchored on breaks) used by patching
+//! - **[`attribute_policy`]**: Which attributes of an edited word carry over or go stale
+//! - **[`fidelity`]**: Checks that an unedited import exports to the same canonical XML
 //! - **[`helpers`]**: XML serialization and attribute handling utilities
+//! - **[`word_annotations`]**: Harvests lemma/msa/@ana from `<w>` into annotations
+//! - **[`report`]**: Lists constructs and attributes kept without being editable
 //! - **[`importer`]**: Main entry point ([`parse`](importer::parse)) and metadata extraction
 //!
 //! ## Round-Trip Fidelity
@@ -39,11 +44,20 @@
 //! facsimile level for editing and preserves the full multi-level structure for export.
 //! The [`menota_abbr_expansion`](extraction::Extractor::menota_abbr_expansion) function
 //! detects abbreviation patterns (am/ex markers) and converts them to `.abbr[]{}` syntax.
+//!
+//! With [`ImportOptions::level_overrides`](importer::ImportOptions) set, each word also
+//! carries its diplomatic and normalized text as `.dipl{}.norm{}`, so readings corrected
+//! by hand in the source are kept when the word is edited instead of being regenerated.
 
 pub mod helpers;
 pub mod segments;
 pub mod extraction;
+pub mod attribute_policy;
+pub mod diff;
+pub mod fidelity;
 pub mod patching;
+pub mod report;
+pub mod word_annotations;
 mod importer;
 
 // Re-export everything from the main importer for backward compatibility
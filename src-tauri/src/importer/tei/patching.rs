@@ -8,39 +8,35 @@
 //!
 //! 1. **Fast path**: If edited DSL matches original, return empty patch list
 //! 2. **Tokenize**: Convert both original segments and edited DSL to token lists
-//! 3. **Diff**: Use LCS (Longest Common Subsequence) to find changes:
-//!    - Common prefix: Keep operations
-//!    - Common suffix: Keep operations
-//!    - Middle: LCS-based diff → Keep/Insert/Delete operations
-//! 4. **Combine**: Adjacent Delete+Insert pairs become Modify operations
+//! 3. **Diff**: Align the two token lists with [`diff::diff`] (Myers, anchored on
+//!    page and line breaks) into Keep/Insert/Delete operations
+//! 4. **Combine**: Runs of Deletes followed by Inserts become Modify operations
 //!
-//! ## LCS Diff Algorithm
-//!
-//! The [`diff_tokens_lcs`] function implements the classic LCS algorithm:
+//! ## Example
 //!
 //! ```text
 //! Original: [A, B, C, D, E]
 //! Edited:   [A, B, X, Y, E]
 //!
-//! LCS:      [A, B, E]
-//!
-//! Patches:
+//! Diff:
 //!   Keep(A), Keep(B), Delete(C), Delete(D), Insert(X), Insert(Y), Keep(E)
 //!
 //! After combine_to_modify:
 //!   Keep(A), Keep(B), Modify(C→X), Modify(D→Y), Keep(E)
 //! ```
 //!
-//! ## Safety Limits
-//!
-//! For very large documents (>1000 tokens on both sides), the LCS algorithm
-//! falls back to a linear comparison to avoid O(n²) memory usage.
+//! The diff runs in linear space and in time proportional to the number of edits,
+//! so full sagas of tens of thousands of words are aligned exactly: only the
+//! tokens that were actually edited lose their original XML.
 
+use crate::importer::tei::attribute_policy::{word_form, AttributePolicy, StaleAttributes};
+use crate::importer::tei::diff::{self, Edit};
 use crate::importer::tei::extraction::segments_to_dsl;
 use crate::importer::tei::helpers;
-use crate::importer::tei::segments::Segment;
+use crate::importer::tei::segments::{ImportedDocument, Segment};
 use crate::parser::{Compiler, Lexer, Node, WordTokenizer};
 use std::collections::HashMap;
+use std::ops::Range;
 
 /// A patch operation describing how to transform a segment.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -60,7 +56,7 @@ pub enum PatchOperation {
 /// This is the main entry point for the patching system. It handles:
 /// - Fast-path detection (no changes → empty patch list)
 /// - Token extraction from both sources
-/// - LCS-based diffing
+/// - Token diffing
 /// - Patch combination (Delete+Insert → Modify)
 pub fn compute_patches(segments: &[Segment], edited_dsl: &str) -> Vec<PatchOperation> {
     // Fast-path: if the edited DSL matches the original extraction, keep everything.
@@ -81,6 +77,12 @@ struct TokenInfo {
     segment_id: Option<usize>,
 }
 
+impl AsRef<str> for TokenInfo {
+    fn as_ref(&self) -> &str {
+        &self.content
+    }
+}
+
 fn extract_tokens_from_segments(segments: &[Segment]) -> Vec<TokenInfo> {
     let mut tokens = Vec::new();
     for seg in segments {
@@ -186,7 +188,8 @@ fn node_to_dsl(node: &Node) -> String {
         }
         Node::Supplied(t) => format!("<{}>", t),
         Node::SuppliedBlock(t) => format!(".supplied{{{}}}", t),
-        Node::Norm(t) => format!(".norm{{{}}}", t),
+        Node::Norm(t) | Node::NormOverride(t) => format!(".norm{{{}}}", t),
+        Node::DiplOverride(t) => format!(".dipl{{{}}}", t),
         Node::Deletion(t) => format!("-{{{}}}-", t),
         Node::Addition(t) => format!("+{{{}}}+", t),
         Node::Note(t) => format!("^{{{}}}", t),
@@ -214,203 +217,162 @@ fn node_to_dsl(node: &Node) -> String {
 }
 
 fn diff_tokens(original: &[TokenInfo], edited: &[TokenInfo]) -> Vec<PatchOperation> {
-    let mut start = 0;
-    let m = original.len();
-    let n = edited.len();
-
-    // 1. Skip common prefix
-    while start < m && start < n && original[start].content == edited[start].content {
-        start += 1;
-    }
-
-    // 2. Skip common suffix
-    let mut original_end = m;
-    let mut edited_end = n;
-    while original_end > start
-        && edited_end > start
-        && original[original_end - 1].content == edited[edited_end - 1].content
-    {
-        original_end -= 1;
-        edited_end -= 1;
-    }
-
-    let mut patches = Vec::new();
-
-    // Add Keep for common prefix
-    for token in original.iter().take(start) {
-        patches.push(PatchOperation::Keep {
-            segment_id: token.segment_id.unwrap(),
-        });
-    }
-
-    // Handle middle part with LCS
-    if start < original_end || start < edited_end {
-        let middle_patches =
-            diff_tokens_lcs(&original[start..original_end], &edited[start..edited_end]);
-        patches.extend(middle_patches);
-    }
-
-    // Add Keep for common suffix
-    for token in original.iter().skip(original_end) {
-        patches.push(PatchOperation::Keep {
-            segment_id: token.segment_id.unwrap(),
-        });
-    }
-
-    combine_to_modify(patches)
-}
-
-/// Computes patches using the Longest Common Subsequence algorithm.
-///
-/// # Algorithm
-///
-/// 1. Build LCS length table via dynamic programming: O(m×n) time and space
-/// 2. Backtrack through table to generate patch operations
-/// 3. Matching tokens → Keep
-/// 4. Tokens in edited but not LCS → Insert
-/// 5. Tokens in original but not LCS → Delete
-///
-/// # Safety Limit
-///
-/// If both sequences exceed 1000 tokens, falls back to linear comparison
-/// to avoid O(m×n) memory allocation on very large documents.
-fn diff_tokens_lcs(original: &[TokenInfo], edited: &[TokenInfo]) -> Vec<PatchOperation> {
-    let m = original.len();
-    let n = edited.len();
-
-    // Safety limit to avoid O(MN) explosion on very large unsynced docs
-    // If it's too big, fallback to a linear diff that preserves structure
-    if m > 1000 && n > 1000 {
-        let mut fallback = Vec::new();
-        let min_len = m.min(n);
-
-        for (original_token, edited_token) in original
-            .iter()
-            .take(min_len)
-            .zip(edited.iter().take(min_len))
-        {
-            let segment_id = original_token.segment_id.unwrap();
-
-            if original_token.content == edited_token.content {
-                fallback.push(PatchOperation::Keep { segment_id });
-            } else {
-                fallback.push(PatchOperation::Modify {
-                    segment_id,
-                    new_dsl: edited_token.content.clone(),
+    let mut patches = Vec::with_capacity(original.len().max(edited.len()));
+    let (mut i, mut j) = (0, 0);
+
+    for edit in diff::diff(original, edited) {
+        match edit {
+            Edit::Keep => {
+                patches.push(PatchOperation::Keep {
+                    segment_id: original[i].segment_id.unwrap(),
                 });
+                i += 1;
+                j += 1;
             }
-        }
-
-        if m > n {
-            for t in &original[min_len..] {
-                fallback.push(PatchOperation::Delete {
-                    segment_id: t.segment_id.unwrap(),
+            Edit::Delete => {
+                patches.push(PatchOperation::Delete {
+                    segment_id: original[i].segment_id.unwrap(),
                 });
+                i += 1;
             }
-        } else if n > m {
-            for t in &edited[min_len..] {
-                fallback.push(PatchOperation::Insert {
-                    dsl: t.content.clone(),
+            Edit::Insert => {
+                patches.push(PatchOperation::Insert {
+                    dsl: edited[j].content.clone(),
                 });
+                j += 1;
             }
         }
-
-        return fallback;
     }
 
-    let mut dp = vec![vec![0; n + 1]; m + 1];
+    combine_to_modify(patches)
+}
 
-    for i in 1..=m {
-        for j in 1..=n {
-            if original[i - 1].content == edited[j - 1].content {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
-            } else {
-                dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
-            }
+/// Turns each run of deletions directly followed by insertions into
+/// modifications, pairing them in order, so replaced words keep their original
+/// attributes. Unpaired deletions or insertions are left as they are.
+fn combine_to_modify(ops: Vec<PatchOperation>) -> Vec<PatchOperation> {
+    let mut result = Vec::with_capacity(ops.len());
+    let mut ops = ops.into_iter().peekable();
+
+    while let Some(op) = ops.next() {
+        let PatchOperation::Delete { segment_id } = op else {
+            result.push(op);
+            continue;
+        };
+
+        let mut deleted = vec![segment_id];
+        while let Some(PatchOperation::Delete { segment_id }) = ops.peek() {
+            deleted.push(*segment_id);
+            ops.next();
+        }
+        let mut inserted = Vec::new();
+        while let Some(PatchOperation::Insert { dsl }) = ops.peek() {
+            inserted.push(dsl.clone());
+            ops.next();
         }
-    }
-
-    let mut patches = Vec::new();
-    let mut i = m;
-    let mut j = n;
 
-    while i > 0 || j > 0 {
-        if i > 0 && j > 0 && original[i - 1].content == edited[j - 1].content {
-            patches.push(PatchOperation::Keep {
-                segment_id: original[i - 1].segment_id.unwrap(),
-            });
-            i -= 1;
-            j -= 1;
-        } else if j > 0 && (i == 0 || dp[i][j - 1] >= dp[i - 1][j]) {
-            patches.push(PatchOperation::Insert {
-                dsl: edited[j - 1].content.clone(),
+        let paired = deleted.len().min(inserted.len());
+        for (segment_id, new_dsl) in deleted.iter().zip(inserted.iter()) {
+            result.push(PatchOperation::Modify {
+                segment_id: *segment_id,
+                new_dsl: new_dsl.clone(),
             });
-            j -= 1;
-        } else {
-            patches.push(PatchOperation::Delete {
-                segment_id: original[i - 1].segment_id.unwrap(),
+        }
+        for segment_id in &deleted[paired..] {
+            result.push(PatchOperation::Delete {
+                segment_id: *segment_id,
             });
-            i -= 1;
+        }
+        for dsl in &inserted[paired..] {
+            result.push(PatchOperation::Insert { dsl: dsl.clone() });
         }
     }
+    result
+}
 
-    patches.reverse();
-    patches
+/// Reconstructed body XML, with the words whose linguistic attributes went stale.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reconstruction {
+    pub xml: String,
+    pub stale_attributes: Vec<StaleAttributes>,
 }
 
-fn combine_to_modify(ops: Vec<PatchOperation>) -> Vec<PatchOperation> {
-    let mut result = Vec::new();
-    let mut i = 0;
-    while i < ops.len() {
-        if i + 1 < ops.len() {
-            if let (PatchOperation::Delete { segment_id }, PatchOperation::Insert { dsl }) =
-                (&ops[i], &ops[i + 1])
-            {
-                result.push(PatchOperation::Modify {
-                    segment_id: *segment_id,
-                    new_dsl: dsl.clone(),
-                });
-                i += 2;
-                continue;
-            }
+/// Rebuilds a whole imported document: the main body from `edited_dsl`, and each
+/// further text unit from its own DSL, spliced over its original XML in the
+/// preamble or postamble. Units whose DSL is unchanged keep their source text.
+pub fn reconstruct_document(
+    document: &ImportedDocument,
+    edited_dsl: &str,
+    preamble: &str,
+    postamble: &str,
+    compiler: &mut Compiler,
+    policy: &AttributePolicy,
+) -> Result<Reconstruction, String> {
+    let patches = compute_patches(&document.segments, edited_dsl);
+    let body = apply_patches_with_policy(&document.segments, &patches, compiler, policy);
+    let mut stale_attributes = body.stale_attributes;
+
+    // Units are in document order, so each is searched for after the previous one
+    let mut sections = [preamble.to_string(), postamble.to_string()];
+    let mut cursors = [0, 0];
+    let mut current = 0;
+    for unit in &document.units {
+        let (section, start) = sections
+            .iter()
+            .zip(cursors)
+            .enumerate()
+            .skip(current)
+            .find_map(|(idx, (text, cursor))| {
+                text[cursor..]
+                    .find(&unit.original_xml)
+                    .map(|offset| (idx, cursor + offset))
+            })
+            .ok_or_else(|| format!("Original XML of text unit '{}' not found", unit.label))?;
+        let end = start + unit.original_xml.len();
+        current = section;
+
+        if segments_to_dsl(&unit.segments).trim() == unit.dsl.trim() {
+            cursors[section] = end;
+            continue;
         }
-        result.push(ops[i].clone());
-        i += 1;
+        let patches = compute_patches(&unit.segments, &unit.dsl);
+        let rebuilt = apply_patches_with_policy(&unit.segments, &patches, compiler, policy);
+        sections[section].replace_range(start..end, &rebuilt.xml);
+        cursors[section] = start + rebuilt.xml.len();
+        stale_attributes.extend(rebuilt.stale_attributes);
     }
-    result
+
+    let [preamble, postamble] = sections;
+    Ok(Reconstruction {
+        xml: format!("{}{}{}", preamble, body.xml, postamble),
+        stale_attributes,
+    })
 }
 
 /// Applies patch operations to reconstruct TEI-XML from segments.
 ///
 /// This function walks through the original segments and patches in parallel,
 /// producing XML that combines preserved original structure with edited content.
+/// The compiler generates multi-level XML for modified and inserted content.
 ///
 /// # Reconstruction Strategy
 ///
 /// - **Keep**: Emit original XML verbatim (preserves attributes, structure)
-/// - **Modify**: Compile new DSL to XML, preserving original word attributes
+/// - **Modify**: Compile new DSL to XML, merging original word attributes
+///   according to `policy`
 /// - **Delete**: Skip the segment
 /// - **Insert**: Compile new DSL to XML (no original attributes available)
 /// - **Structural**: Always emit verbatim (divs, paragraphs, etc.)
 /// - **Whitespace**: Always emit verbatim (formatting preservation)
-pub fn apply_patches_and_reconstruct(
+pub fn apply_patches_with_policy(
     segments: &[Segment],
     patches: &[PatchOperation],
     compiler: &mut Compiler,
-) -> String {
-    reconstruct_from_segments_and_patches(segments, patches, compiler)
-}
-
-/// Internal implementation of patch application.
-///
-/// Processes segments in order, interleaving patch operations. Uses the compiler
-/// to generate multi-level XML for modified and inserted content.
-fn reconstruct_from_segments_and_patches(
-    segments: &[Segment],
-    patches: &[PatchOperation],
-    compiler: &mut Compiler,
-) -> String {
+    policy: &AttributePolicy,
+) -> Reconstruction {
     let mut xml = String::new();
+    let mut stale_attributes = Vec::new();
     let mut current_patch_idx = 0;
 
     // 1. Process segments in order
@@ -442,16 +404,35 @@ fn reconstruct_from_segments_and_patches(
                                     xml.push_str(&serialize_original_segment(seg));
                                 }
                                 PatchOperation::Modify { new_dsl, .. } => match seg {
-                                    Segment::Word { attributes, .. } => {
+                                    Segment::Word {
+                                        attributes,
+                                        dsl_content,
+                                        original_xml,
+                                        ..
+                                    } => {
                                         if new_dsl.trim_start().starts_with(".head{") {
                                             xml.push_str(
                                                 &compiler.compile_fragment_from_dsl(new_dsl),
                                             );
                                         } else {
-                                            xml.push_str(
-                                                &compiler
-                                                    .compile_word_from_dsl(new_dsl, attributes),
-                                            );
+                                            let (merged, stale) =
+                                                policy.merge(attributes, dsl_content, new_dsl);
+                                            if !stale.is_empty() {
+                                                stale_attributes.push(StaleAttributes {
+                                                    segment_id: seg_id,
+                                                    original_form: word_form(dsl_content),
+                                                    new_form: word_form(new_dsl),
+                                                    attributes: stale,
+                                                });
+                                            }
+                                            let compiled =
+                                                compiler.compile_word_from_dsl(new_dsl, &merged);
+                                            xml.push_str(&keep_unchanged_levels(
+                                                compiled,
+                                                original_xml,
+                                                dsl_content,
+                                                new_dsl,
+                                            ));
                                         }
                                     }
                                     Segment::Punctuation { .. } => {
@@ -494,9 +475,80 @@ fn reconstruct_from_segments_and_patches(
         current_patch_idx += 1;
     }
 
+    Reconstruction {
+        xml,
+        stale_attributes,
+    }
+}
+
+/// Put the original `<me:dipl>` / `<me:norm>` content back into a recompiled
+/// word whose `.dipl{}` / `.norm{}` override text did not change, so markup the
+/// override text does not show (`<ex>`, `<hi>`, `<c>`) survives an edit of the
+/// facsimile.
+fn keep_unchanged_levels(
+    compiled: String,
+    original_xml: &str,
+    old_dsl: &str,
+    new_dsl: &str,
+) -> String {
+    let (old_dipl, old_norm) = override_texts(old_dsl);
+    let (new_dipl, new_norm) = override_texts(new_dsl);
+
+    let mut xml = compiled;
+    for (level, old, new) in [("dipl", old_dipl, new_dipl), ("norm", old_norm, new_norm)] {
+        if old.is_none() || old != new {
+            continue;
+        }
+        let original = level_content(original_xml, level).map(|range| &original_xml[range]);
+        if let (Some(original), Some(range)) = (original, level_content(&xml, level)) {
+            xml.replace_range(range, original);
+        }
+    }
     xml
 }
 
+/// The `.dipl{}` and `.norm{}` override texts of a word's DSL
+fn override_texts(dsl: &str) -> (Option<String>, Option<String>) {
+    let nodes = Lexer::new(dsl).parse().unwrap_or_default().nodes;
+    let mut dipl = None;
+    let mut norm = None;
+    for node in nodes {
+        match node {
+            Node::DiplOverride(text) => dipl = Some(text),
+            Node::NormOverride(text) => norm = Some(text),
+            _ => {}
+        }
+    }
+    (dipl, norm)
+}
+
+/// Byte range of the content of the first non-empty `<me:{level}>` element
+fn level_content(xml: &str, level: &str) -> Option<Range<usize>> {
+    let open = format!("<me:{}", level);
+    let close = format!("</me:{}>", level);
+    let mut from = 0;
+    loop {
+        let tag = from + xml[from..].find(&open)?;
+        let name_end = tag + open.len();
+        from = name_end;
+        // Skip longer names that share the prefix
+        if !xml[name_end..].starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            continue;
+        }
+        let tag_end = name_end + xml[name_end..].find('>')?;
+        from = tag_end + 1;
+        // Self-closing and empty elements have no reading to keep
+        if xml[..tag_end].ends_with('/') {
+            continue;
+        }
+        let end = tag_end + 1 + xml[tag_end + 1..].find(&close)?;
+        if end > tag_end + 1 {
+            return Some(tag_end + 1..end);
+        }
+        from = end + close.len();
+    }
+}
+
 fn serialize_original_segment(seg: &Segment) -> String {
     match seg {
         Segment::Structural { xml, .. } => xml.clone(),
@@ -531,3 +583,25 @@ fn format_tag(name: &str, attrs: &HashMap<String, String>) -> String {
     s.push_str("/>");
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_content_skips_empty_levels() {
+        let xml = "<w><choice><me:facs>ok</me:facs><me:dipl/><me:norm>ok</me:norm></choice>\
+                   <choice><me:dipl></me:dipl><me:dipl>ok</me:dipl></choice></w>";
+
+        let norm = level_content(xml, "norm").unwrap();
+        assert_eq!(&xml[norm], "ok");
+        let dipl = level_content(xml, "dipl").unwrap();
+        assert_eq!(&xml[dipl.clone()], "ok");
+        assert!(xml[..dipl.start].ends_with("</me:dipl><me:dipl>"));
+
+        assert_eq!(
+            level_content("<w><me:dipl/><me:norm>ok</me:norm></w>", "dipl"),
+            None
+        );
+    }
+}
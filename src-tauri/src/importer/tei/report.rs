@@ -0,0 +1,184 @@
+//! # Import Report
+//!
+//! Describes what an import did with the TEI it could not model. The extractor
+//! keeps every element it does not know as [`Segment::Structural`](super::segments::Segment),
+//! and flattens unknown markup inside words into plain DSL text, so without a
+//! report such markup is silently frozen or hidden from the user.
+//!
+//! The report walks the same `<body>` the extractor sees and lists:
+//! - how many elements of each kind were found,
+//! - elements whose markup became read-only structure or was flattened inside a
+//!   word (kept only until that word is edited),
+//! - attributes that are preserved but cannot be edited through the DSL.
+//!
+//! Every entry carries the line number of the element in the source file.
+//!
+//! DOCX imports use the same report for word-processor formatting that has no
+//! DSL equivalent (see [`crate::importer::docx`]).
+
+use super::helpers;
+use crate::importer::docx::UnmappedFormatting;
+use libxml::tree::{Node, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Elements the extractor turns into segments of their own
+const SEGMENT_ELEMENTS: &[&str] = &[
+    "w", "pc", "lb", "pb", "handShift", "choice", "head", "gap", "supplied", "del", "add",
+    "unclear", "note", "am",
+];
+
+/// Block containers that are expected to stay structural
+const CONTAINER_ELEMENTS: &[&str] = &["body", "div", "p", "ab", "lg", "l", "s"];
+
+/// Elements the DSL represents inside a word
+const WORD_ELEMENTS: &[&str] = &[
+    "w", "pc", "c", "choice", "abbr", "expan", "am", "ex", "facs", "dipl", "norm", "add",
+    "del", "unclear", "supplied", "gap", "note", "lb",
+];
+
+/// Attributes the DSL or the annotation layer can edit, per element
+const EDITABLE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("lb", "n"),
+    ("pb", "n"),
+    ("gap", "quantity"),
+    ("w", "lemma"),
+    ("w", "me:msa"),
+    ("w", "msa"),
+    ("w", "ana"),
+];
+
+/// Maximum length of the text excerpt stored for a construct
+const EXCERPT_CHARS: usize = 40;
+
+/// Structured summary of what an import preserved without making editable
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Number of elements by qualified name
+    pub element_counts: BTreeMap<String, usize>,
+    /// Elements the DSL does not model
+    pub constructs: Vec<UnsupportedConstruct>,
+    /// Attributes kept from the original that the DSL cannot edit
+    pub attributes: Vec<PreservedAttribute>,
+    /// Word-processor formatting with no DSL equivalent (DOCX imports)
+    #[serde(default)]
+    pub formatting: Vec<UnmappedFormatting>,
+}
+
+/// How an unmodelled element was handled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConstructHandling {
+    /// Markup kept as read-only structure; its text stays editable
+    ReadOnly,
+    /// Markup inside a word reduced to plain text in the DSL; the original is
+    /// kept until the word is edited
+    Flattened,
+}
+
+/// An element the DSL does not model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedConstruct {
+    pub element: String,
+    pub handling: ConstructHandling,
+    /// Source line number (1-based)
+    pub line: u32,
+    /// Start of the element's text content
+    pub text: String,
+}
+
+/// One attribute name on one element kind, with every line it occurs on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreservedAttribute {
+    pub element: String,
+    pub attribute: String,
+    /// Source line numbers (1-based) of each occurrence
+    pub lines: Vec<u32>,
+}
+
+/// Build the report for an imported `<body>`.
+pub fn build_report(body: &Node) -> ImportReport {
+    let mut builder = ReportBuilder::default();
+    builder.visit(body, false);
+    builder.finish()
+}
+
+#[derive(Default)]
+struct ReportBuilder {
+    report: ImportReport,
+    /// (element, attribute) → index into `report.attributes`
+    attribute_index: BTreeMap<(String, String), usize>,
+}
+
+impl ReportBuilder {
+    /// Visit an element; `in_word` is set below elements the extractor converts
+    /// to DSL as a whole
+    fn visit(&mut self, node: &Node, in_word: bool) {
+        let name = helpers::qualified_name(node);
+        let local = helpers::local_name(node);
+        let line = helpers::line_number(node);
+        *self.report.element_counts.entry(name.clone()).or_default() += 1;
+
+        for (attribute, _) in helpers::attributes_with_ns(node) {
+            if !EDITABLE_ATTRIBUTES.contains(&(local.as_str(), attribute.as_str())) {
+                self.attribute(&name, &attribute, line);
+            }
+        }
+
+        let handling = if in_word {
+            (!WORD_ELEMENTS.contains(&local.as_str())).then_some(ConstructHandling::Flattened)
+        } else if SEGMENT_ELEMENTS.contains(&local.as_str())
+            || CONTAINER_ELEMENTS.contains(&local.as_str())
+        {
+            None
+        } else {
+            Some(ConstructHandling::ReadOnly)
+        };
+        if let Some(handling) = handling {
+            self.report.constructs.push(UnsupportedConstruct {
+                element: name,
+                handling,
+                line,
+                text: excerpt(&node.get_content()),
+            });
+        }
+
+        let child_in_word = in_word || SEGMENT_ELEMENTS.contains(&local.as_str());
+        for child in node.get_child_nodes() {
+            if child.get_type() == Some(NodeType::ElementNode) {
+                self.visit(&child, child_in_word);
+            }
+        }
+    }
+
+    fn attribute(&mut self, element: &str, attribute: &str, line: u32) {
+        let key = (element.to_string(), attribute.to_string());
+        match self.attribute_index.get(&key) {
+            Some(&idx) => self.report.attributes[idx].lines.push(line),
+            None => {
+                self.attribute_index
+                    .insert(key, self.report.attributes.len());
+                self.report.attributes.push(PreservedAttribute {
+                    element: element.to_string(),
+                    attribute: attribute.to_string(),
+                    lines: vec![line],
+                });
+            }
+        }
+    }
+
+    fn finish(self) -> ImportReport {
+        self.report
+    }
+}
+
+fn excerpt(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &collapsed[..end]),
+        None => collapsed,
+    }
+}
@@ -94,6 +94,10 @@ impl Segment {
 /// This structure preserves all information needed for round-trip fidelity:
 /// the segment list tracks structural and content elements, while `is_menota`
 /// indicates whether the document uses MENOTA multi-level transcription.
+///
+/// `segments` belong to the main text unit (the first `<body>`). Front and back
+/// matter and further texts in a `<group>` are kept in `units`; their original
+/// XML sits in the preamble or postamble and is replaced on export.
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportedDocument {
@@ -101,4 +105,26 @@ pub struct ImportedDocument {
     pub segments: Vec<Segment>,
     /// Whether the document has MENOTA three-level structure (me:facs/dipl/norm)
     pub is_menota: bool,
+    /// Display label of the main text unit
+    #[serde(default)]
+    pub label: String,
+    /// Further editable text units, in document order
+    #[serde(default)]
+    pub units: Vec<TextUnit>,
+}
+
+/// An editable text unit besides the main body: `<front>`, `<back>`, or the
+/// `<body>` of another `<text>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextUnit {
+    /// Display label, e.g. "Front" or "Text 2 · Body"
+    pub label: String,
+    /// Element name of the unit: front, body or back
+    pub kind: String,
+    /// The unit's XML exactly as it appears in the source file
+    pub original_xml: String,
+    /// Segments of the unit
+    pub segments: Vec<Segment>,
+    /// Current DSL of the unit; the imported DSL until edited
+    pub dsl: String,
 }
@@ -0,0 +1,329 @@
+//! # Word Annotation Harvesting
+//!
+//! Lemmatised TEI/MENOTA files carry their linguistic analysis as attributes on
+//! `<w>` elements (`@lemma`, `@me:msa`, `@ana`). This module turns those attributes
+//! into an [`AnnotationSet`] so imported files arrive already annotated, and into
+//! wordform → [`InflectedForm`] pairs that can be merged into the inflection store.
+//!
+//! Words are numbered the way the compiler numbers them: in document order, but
+//! leaving out words the compiler renders without an index. Those are words inside
+//! the elements in [`UNINDEXED_ELEMENTS`] (compiled from `.head{}`/`.supplied{}`)
+//! and words with only a normalized form (compiled from `.norm{}`). They are still
+//! harvested for inflections, but carry no annotations.
+
+use crate::annotations::{
+    Annotation, AnnotationMetadata, AnnotationSet, AnnotationTarget, AnnotationType,
+    AnnotationValue, PaleographicType,
+};
+use crate::dictionary::InflectedForm;
+use crate::importer::tei::helpers;
+use crate::parser::UNINDEXED_ELEMENTS;
+use libxml::tree::{Node, NodeType};
+use serde::{Deserialize, Serialize};
+
+/// Prefix for ONP ids of inflections harvested from imported files.
+const IMPORTED_ONP_PREFIX: &str = "imported:";
+
+/// Linguistic data found on a single `<w>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordAttributes {
+    /// Index the compiler gives the word, if any
+    pub word_index: Option<u32>,
+    /// `@n` of the last `<pb>` before the word
+    pub page: Option<String>,
+    /// `@n` of the last `<lb>` before the word
+    pub line: Option<String>,
+    pub lemma: Option<String>,
+    pub msa: Option<String>,
+    /// Individual `@ana` pointers, without the leading `#`
+    pub ana: Vec<String>,
+    pub facsimile: Option<String>,
+    pub diplomatic: Option<String>,
+    pub normalized: Option<String>,
+}
+
+/// A wordform and the inflection harvested for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestedInflection {
+    /// Lookup key (diplomatic form, falling back to facsimile)
+    pub wordform: String,
+    pub inflection: InflectedForm,
+}
+
+/// Collect the attributes of every `<w>` element below `body`, in document order.
+pub fn collect_word_attributes(body: &Node) -> Vec<WordAttributes> {
+    let mut words = Vec::new();
+    let mut position = Position::default();
+    collect_into(body, &mut words, &mut position, true);
+    words
+}
+
+/// Current page, line and next word index while walking the body
+#[derive(Default)]
+struct Position {
+    page: Option<String>,
+    line: Option<String>,
+    next_index: u32,
+}
+
+impl Position {
+    /// Update from a `<pb>`/`<lb>` element; returns false for other elements.
+    fn update(&mut self, node: &Node) -> bool {
+        match helpers::local_name(node).as_str() {
+            "pb" => {
+                self.page = node.get_property("n");
+                self.line = None;
+                true
+            }
+            "lb" => {
+                self.line = node.get_property("n");
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Apply any breaks inside a word (e.g. an `<lb/>` splitting it)
+    fn update_within(&mut self, node: &Node) {
+        for child in node.get_child_nodes() {
+            if child.get_type() == Some(NodeType::ElementNode) && !self.update(&child) {
+                self.update_within(&child);
+            }
+        }
+    }
+}
+
+fn collect_into(
+    node: &Node,
+    words: &mut Vec<WordAttributes>,
+    position: &mut Position,
+    indexed: bool,
+) {
+    for child in node.get_child_nodes() {
+        if child.get_type() != Some(NodeType::ElementNode) || position.update(&child) {
+            continue;
+        }
+        let name = helpers::local_name(&child);
+        if name == "w" {
+            let mut word = read_word(&child);
+            if indexed && (word.facsimile.is_some() || word.diplomatic.is_some()) {
+                word.word_index = Some(position.next_index);
+                position.next_index += 1;
+            }
+            word.page = position.page.clone();
+            word.line = position.line.clone();
+            words.push(word);
+            position.update_within(&child);
+        } else {
+            let indexed = indexed && !UNINDEXED_ELEMENTS.contains(&name.as_str());
+            collect_into(&child, words, position, indexed);
+        }
+    }
+}
+
+fn read_word(node: &Node) -> WordAttributes {
+    let mut word = WordAttributes::default();
+
+    for (key, value) in helpers::attributes_with_ns(node) {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            "lemma" => word.lemma = Some(value.to_string()),
+            "me:msa" | "msa" => word.msa = Some(value.to_string()),
+            "ana" => {
+                word.ana = value
+                    .split_whitespace()
+                    .map(|code| code.trim_start_matches('#').to_string())
+                    .filter(|code| !code.is_empty())
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    let has_levels = read_levels(node, &mut word);
+
+    // Plain TEI words have no levels; their text content is the facsimile form
+    if !has_levels {
+        word.facsimile = level_text(node);
+    }
+
+    word
+}
+
+/// Fill the MENOTA level forms from `me:facs`/`me:dipl`/`me:norm` descendants
+/// (normally wrapped in `<choice>`). Returns whether any level was found.
+fn read_levels(node: &Node, word: &mut WordAttributes) -> bool {
+    let mut found = false;
+    for child in node.get_child_nodes() {
+        if child.get_type() != Some(NodeType::ElementNode) {
+            continue;
+        }
+        let slot = match helpers::local_name(&child).as_str() {
+            "facs" => &mut word.facsimile,
+            "dipl" => &mut word.diplomatic,
+            "norm" => &mut word.normalized,
+            _ => {
+                found |= read_levels(&child, word);
+                continue;
+            }
+        };
+        if slot.is_none() {
+            *slot = level_text(&child);
+        }
+        found = true;
+    }
+    found
+}
+
+fn level_text(node: &Node) -> Option<String> {
+    let text = node.get_content();
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join("");
+    if collapsed.is_empty() {
+        None
+    } else {
+        Some(collapsed)
+    }
+}
+
+/// Build an annotation set from harvested word attributes.
+///
+/// Words with a `@lemma` get a lemma annotation (with an empty msa if none was
+/// given). `@ana` pointers become semantic annotations, except `#paleo:*` codes,
+/// which become paleographic observations. The bare MENOTA codes (`#unclear`,
+/// `#addition`, `#deletion`, `#supplied`) are skipped: their details live in other
+/// word attributes, which are preserved in the segment manifest.
+pub fn build_annotation_set(words: &[WordAttributes]) -> AnnotationSet {
+    let mut set = AnnotationSet::new();
+
+    for word in words {
+        let Some(word_index) = word.word_index else {
+            continue;
+        };
+        if let Some(lemma) = &word.lemma {
+            let mut annotation = Annotation::lemma(
+                word_index,
+                lemma.clone(),
+                word.msa.clone().unwrap_or_default(),
+                word.normalized.clone(),
+            );
+            annotation.metadata = Some(imported_metadata());
+            set.add(annotation);
+        }
+
+        for (n, code) in word.ana.iter().enumerate() {
+            if let Some(mut annotation) = annotation_from_ana(word_index, code) {
+                // Deterministic ids so re-importing the same file yields the same set
+                annotation.id = format!(
+                    "{}-{}-{}",
+                    id_prefix(&annotation.annotation_type),
+                    word_index,
+                    n
+                );
+                annotation.metadata = Some(imported_metadata());
+                set.add(annotation);
+            }
+        }
+    }
+
+    set
+}
+
+/// Convert one `@ana` code (without `#`) into an annotation; the caller assigns the ID.
+/// Returns `None` for the bare MENOTA paleographic codes.
+pub(crate) fn annotation_from_ana(word_index: u32, code: &str) -> Option<Annotation> {
+    let target = AnnotationTarget::Word { word_index };
+
+    if matches!(code, "unclear" | "addition" | "deletion" | "supplied") {
+        return None;
+    }
+
+    let (category, subcategory) = match code.split_once(':') {
+        Some((category, sub)) if !sub.is_empty() => (category, Some(sub)),
+        Some((category, _)) => (category, None),
+        None => (code, None),
+    };
+
+    if category == "paleo" {
+        let observation_type = match subcategory.unwrap_or("") {
+            "unclear" => PaleographicType::Unclear,
+            "damage" => PaleographicType::Damage,
+            "erasure" => PaleographicType::Erasure,
+            "letterform" => PaleographicType::Letterform,
+            "abbrev-mark" => PaleographicType::Abbreviation,
+            "correction" => PaleographicType::Correction,
+            "addition" => PaleographicType::Addition,
+            "decoration" => PaleographicType::Decoration,
+            _ => PaleographicType::Other,
+        };
+        return Some(Annotation {
+            id: String::new(),
+            annotation_type: AnnotationType::Paleographic,
+            target,
+            value: AnnotationValue::Paleographic {
+                observation_type,
+                description: None,
+                certainty: None,
+            },
+            metadata: None,
+        });
+    }
+
+    Some(Annotation::semantic(
+        target,
+        category.to_string(),
+        subcategory.map(str::to_string),
+        None,
+    ))
+}
+
+fn id_prefix(annotation_type: &AnnotationType) -> &'static str {
+    match annotation_type {
+        AnnotationType::Paleographic => "paleo",
+        _ => "sem",
+    }
+}
+
+fn imported_metadata() -> AnnotationMetadata {
+    AnnotationMetadata {
+        source: Some("imported".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Build inflection store entries from lemmatised words.
+///
+/// Only words with both a lemma and an msa are harvested. Each entry is keyed by
+/// the lowercased diplomatic form (or facsimile form when there is no diplomatic
+/// level) and gets a synthetic `imported:<lemma>` ONP id.
+pub fn build_inflections(words: &[WordAttributes]) -> Vec<HarvestedInflection> {
+    words
+        .iter()
+        .filter_map(|word| {
+            let lemma = word.lemma.as_ref()?;
+            let msa = word.msa.as_ref()?;
+            let wordform = word
+                .diplomatic
+                .as_ref()
+                .or(word.facsimile.as_ref())?
+                .to_lowercase();
+            let part_of_speech = msa.split_whitespace().next().unwrap_or("").to_string();
+
+            Some(HarvestedInflection {
+                wordform,
+                inflection: InflectedForm {
+                    onp_id: format!("{}{}", IMPORTED_ONP_PREFIX, lemma),
+                    lemma: lemma.clone(),
+                    analysis: msa.clone(),
+                    part_of_speech,
+                    facsimile: word.facsimile.clone(),
+                    diplomatic: word.diplomatic.clone(),
+                    normalized: word.normalized.clone(),
+                },
+            })
+        })
+        .collect()
+}
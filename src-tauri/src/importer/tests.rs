@@ -1,7 +1,12 @@
-use super::tei::parse;
+use super::tei::{parse, parse_with_options, ImportOptions};
 use crate::importer::tei::helpers;
-use crate::importer::tei::patching::{apply_patches_and_reconstruct, compute_patches};
+use crate::importer::tei::attribute_policy::AttributePolicy;
+use crate::importer::tei::fidelity;
+use crate::importer::tei::patching::{
+    apply_patches_with_policy, compute_patches, reconstruct_document, PatchOperation,
+};
 use crate::importer::tei::segments::Segment;
+use crate::metadata::header;
 use crate::parser::{Compiler, CompilerConfig, Lexer};
 use libxml::parser::Parser;
 use libxml::tree::NodeType;
@@ -699,8 +704,13 @@ fn test_menota_file_roundtrip_stability() {
         multi_level: true,
         wrap_pages: false,
     });
-    let reconstructed_body =
-        apply_patches_and_reconstruct(&imported_doc.segments, &patches, &mut compiler);
+    let reconstructed_body = apply_patches_with_policy(
+        &imported_doc.segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .xml;
     let reconstructed_xml = format!(
         "{}{}{}",
         import_result.original_preamble.unwrap_or_default(),
@@ -794,8 +804,13 @@ fn test_menota_file_imported_roundtrip_xml() {
         wrap_pages: false,
     });
 
-    let reconstructed_body =
-        apply_patches_and_reconstruct(&imported_doc.segments, &patches, &mut compiler);
+    let reconstructed_body = apply_patches_with_policy(
+        &imported_doc.segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .xml;
     let reconstructed_xml = format!("{}{}{}", preamble, reconstructed_body, postamble);
 
     let normalized_original = normalize_xml(&xml_content);
@@ -972,3 +987,1143 @@ fn test_roundtrip_space_before_tag() {
     assert_eq!(dsl2, "word +{added}+", "Cycle 1 should preserve space before tag");
 }
 
+
+// ============================================================================
+// WORD ANNOTATION HARVESTING TESTS
+// ============================================================================
+
+const LEMMATISED_MENOTA: &str = r##"<TEI xmlns:me="http://www.menota.org/ns/1.0"><text><body><p>
+<w lemma="konungr" me:msa="xNC cN nS gM" ana="#person:title"><choice><me:facs>konungr</me:facs><me:dipl>konungr</me:dipl><me:norm>konungr</me:norm></choice></w>
+<head><w lemma="saga" me:msa="xNC cN nS gF"><choice><me:facs>ſaga</me:facs><me:dipl>saga</me:dipl><me:norm>saga</me:norm></choice></w></head>
+<w ana="#paleo:damage #unclear"><choice><me:facs>ok</me:facs><me:dipl>ok</me:dipl><me:norm>ok</me:norm></choice></w>
+</p></body></text></TEI>"##;
+
+#[test]
+fn test_import_harvests_lemma_annotations() {
+    let result = parse(LEMMATISED_MENOTA).unwrap();
+    let annotations = result.annotations.expect("Should harvest annotations");
+    let lemmas = annotations.lemma_map();
+
+    // The word inside <head> is compiled from a .head{} fragment without an
+    // index, so it gets no annotation and the next word is index 1
+    assert_eq!(lemmas.len(), 1);
+    assert_eq!(lemmas[&0].lemma, "konungr");
+    assert_eq!(lemmas[&0].msa, "xNC cN nS gM");
+    assert!(annotations.for_word(1).iter().all(|a| a.annotation_type != crate::annotations::AnnotationType::Lemma));
+}
+
+#[test]
+fn test_harvested_indices_match_compiler() {
+    use crate::parser::{Compiler, CompilerConfig, LemmaMapping};
+
+    let xml = r##"<TEI xmlns:me="http://www.menota.org/ns/1.0"><text><body><p>
+<w lemma="konungr"><choice><me:facs>konungr</me:facs><me:dipl>konungr</me:dipl><me:norm>konungr</me:norm></choice></w>
+<head><w lemma="saga"><choice><me:facs>ſaga</me:facs><me:dipl>saga</me:dipl><me:norm>saga</me:norm></choice></w></head>
+<w lemma="ok"><choice><me:facs>ok</me:facs><me:dipl>ok</me:dipl><me:norm>ok</me:norm></choice></w>
+<w lemma="sva"><choice><me:facs>ſva</me:facs><me:dipl>sva</me:dipl><me:norm>svá</me:norm></choice></w>
+</p></body></text></TEI>"##;
+    let result = parse(xml).unwrap();
+    let annotations = result.annotations.unwrap();
+
+    let mut compiler = Compiler::new()
+        .with_config(CompilerConfig {
+            word_wrap: true,
+            multi_level: true,
+            ..Default::default()
+        })
+        .with_lemma_mappings(
+            annotations
+                .lemma_map()
+                .into_iter()
+                .map(|(index, info)| {
+                    let mapping = LemmaMapping {
+                        lemma: info.lemma,
+                        msa: info.msa,
+                        normalized: None,
+                    };
+                    (index, mapping)
+                })
+                .collect(),
+        );
+    let output = compiler.compile(&result.dsl).unwrap();
+
+    // Every lemma lands on the word it came from
+    for (lemma, facs) in [("konungr", "konungr"), ("ok", "ok"), ("sva", "ſva")] {
+        let start = output
+            .find(&format!("lemma=\"{}\"", lemma))
+            .unwrap_or_else(|| panic!("lemma {} missing in {}", lemma, output));
+        let word = &output[start..];
+        let word = &word[..word.find("</w>").unwrap()];
+        assert!(
+            word.contains(&format!("<me:facs>{}</me:facs>", facs)),
+            "lemma {} on wrong word: {}",
+            lemma,
+            word
+        );
+    }
+    assert!(!output.contains("lemma=\"saga\""));
+}
+
+#[test]
+fn test_import_harvests_ana_annotations() {
+    use crate::annotations::{AnnotationType, AnnotationValue, PaleographicType};
+
+    let result = parse(LEMMATISED_MENOTA).unwrap();
+    let annotations = result.annotations.unwrap();
+
+    let semantic = annotations.by_type(AnnotationType::Semantic);
+    assert_eq!(semantic.len(), 1);
+    assert_eq!(semantic[0].id, "sem-0-0");
+    match &semantic[0].value {
+        AnnotationValue::Semantic { category, subcategory, .. } => {
+            assert_eq!(category, "person");
+            assert_eq!(subcategory.as_deref(), Some("title"));
+        }
+        other => panic!("Expected semantic value, got {:?}", other),
+    }
+
+    // #paleo:damage becomes an observation; the bare #unclear code is skipped
+    let paleo = annotations.for_word(1);
+    assert_eq!(paleo.len(), 1);
+    assert!(matches!(
+        paleo[0].value,
+        AnnotationValue::Paleographic { observation_type: PaleographicType::Damage, .. }
+    ));
+    assert_eq!(
+        paleo[0].metadata.as_ref().and_then(|m| m.source.as_deref()),
+        Some("imported")
+    );
+}
+
+#[test]
+fn test_import_harvests_inflections() {
+    let result = parse(LEMMATISED_MENOTA).unwrap();
+    assert_eq!(result.inflections.len(), 2);
+
+    let saga = &result.inflections[1];
+    assert_eq!(saga.wordform, "saga");
+    assert_eq!(saga.inflection.onp_id, "imported:saga");
+    assert_eq!(saga.inflection.part_of_speech, "xNC");
+    assert_eq!(saga.inflection.facsimile.as_deref(), Some("ſaga"));
+}
+
+#[test]
+fn test_import_without_word_attributes_has_no_annotations() {
+    let result = parse("<body><w>ok</w> <w>svá</w></body>").unwrap();
+    assert!(result.annotations.is_none());
+    assert!(result.inflections.is_empty());
+}
+
+// ============================================================================
+// HTR IMPORT TESTS (PAGE XML / ALTO → DSL)
+// ============================================================================
+
+const PAGE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<PcGts xmlns="http://schema.primaresearch.org/PAGE/gts/pagecontent/2013-07-15">
+  <Page imageFilename="AM-132-fol-1r.jpg" imageWidth="2000" imageHeight="3000">
+    <ReadingOrder>
+      <OrderedGroup id="ro_1">
+        <RegionRefIndexed index="0" regionRef="r2"/>
+        <RegionRefIndexed index="1" regionRef="r1"/>
+      </OrderedGroup>
+    </ReadingOrder>
+    <TextRegion id="r1">
+      <Coords points="100,1000 1900,1000 1900,1500 100,1500"/>
+      <TextLine id="r1l1">
+        <Coords points="100,1000 1900,1000 1900,1100 100,1100"/>
+        <Word id="w1"><TextEquiv><Unicode>ignored</Unicode></TextEquiv></Word>
+        <TextEquiv><Unicode>ok   svá</Unicode></TextEquiv>
+      </TextLine>
+    </TextRegion>
+    <TextRegion id="r2">
+      <Coords points="100,100 1900,100 1900,900 100,900"/>
+      <TextLine id="r2l1">
+        <Coords points="100,100 1900,100 1900,200 100,200"/>
+        <TextEquiv><Unicode>Her hefr upp</Unicode></TextEquiv>
+      </TextLine>
+      <TextLine id="r2l2">
+        <Coords points="100,210 1900,210 1900,300 100,300"/>
+        <TextEquiv><Unicode>sogu</Unicode></TextEquiv>
+      </TextLine>
+    </TextRegion>
+  </Page>
+</PcGts>"#;
+
+const ALTO_XML: &str = r#"<alto xmlns="http://www.loc.gov/standards/alto/ns-v4#">
+  <Description><sourceImageInformation><fileName>page1.tif</fileName></sourceImageInformation></Description>
+  <Layout>
+    <Page ID="p1" WIDTH="1500" HEIGHT="2200" PHYSICAL_IMG_NR="1">
+      <PrintSpace>
+        <TextBlock ID="tb1" HPOS="10" VPOS="20" WIDTH="500" HEIGHT="300">
+          <TextLine ID="tl1" HPOS="10" VPOS="20" WIDTH="500" HEIGHT="40.4">
+            <String CONTENT="Þat" HPOS="10" VPOS="20" WIDTH="60" HEIGHT="40"/><SP/>
+            <String CONTENT="var" HPOS="80" VPOS="20" WIDTH="60" HEIGHT="40"/><HYP CONTENT="-"/>
+          </TextLine>
+        </TextBlock>
+      </PrintSpace>
+    </Page>
+    <Page ID="p2" WIDTH="1500" HEIGHT="2200">
+      <PrintSpace>
+        <ComposedBlock ID="cb1">
+          <TextBlock ID="tb2" HPOS="10" VPOS="20" WIDTH="500" HEIGHT="300">
+            <TextLine ID="tl2" HPOS="10" VPOS="20" WIDTH="500" HEIGHT="40">
+              <String CONTENT="eitt"/>
+            </TextLine>
+          </TextBlock>
+        </ComposedBlock>
+      </PrintSpace>
+    </Page>
+  </Layout>
+</alto>"#;
+
+#[test]
+fn test_htr_detect_format() {
+    use super::htr::{detect_format, HtrFormat};
+
+    assert_eq!(detect_format(PAGE_XML), Some(HtrFormat::Page));
+    assert_eq!(detect_format(ALTO_XML), Some(HtrFormat::Alto));
+    assert_eq!(
+        detect_format("<?xml version=\"1.0\"?><!-- c --><TEI><text/></TEI>"),
+        None
+    );
+}
+
+#[test]
+fn test_htr_page_xml_to_dsl() {
+    use super::htr::{self, HtrFormat, ZoneKind};
+
+    let result = htr::parse(PAGE_XML, HtrFormat::Page).unwrap();
+    // Reading order puts r2 first; word-level TextEquiv is ignored
+    assert_eq!(result.dsl, "///1\n//1 Her hefr upp\n//2 sogu\n//3 ok svá");
+    assert!(!result.is_imported_mode);
+
+    let facsimile = result.facsimile.unwrap();
+    let surface = &facsimile.surfaces[0];
+    assert_eq!(surface.image.as_deref(), Some("AM-132-fol-1r.jpg"));
+    assert_eq!(surface.width, Some(2000));
+
+    let line = facsimile.line_zone("1", "3").unwrap();
+    assert_eq!(line.id, "r1l1");
+    assert_eq!(line.kind, ZoneKind::Line);
+    assert_eq!(line.bounds, Some([100, 1000, 1900, 1100]));
+
+    let tei = facsimile.to_tei();
+    assert!(tei.contains(r#"<graphic url="AM-132-fol-1r.jpg" width="2000px" height="3000px"/>"#));
+    assert!(tei.contains(r#"<zone xml:id="r2l2" type="line" n="2" ulx="100" uly="210" lrx="1900" lry="300" points="100,210 1900,210 1900,300 100,300"/>"#));
+}
+
+#[test]
+fn test_htr_alto_to_dsl() {
+    use super::htr::{self, HtrFormat};
+
+    let result = htr::parse(ALTO_XML, HtrFormat::Alto).unwrap();
+    assert_eq!(result.dsl, "///1\n//1 Þat var-\n///2\n//1 eitt");
+
+    let facsimile = result.facsimile.unwrap();
+    assert_eq!(facsimile.surfaces.len(), 2);
+    assert_eq!(facsimile.surfaces[1].image.as_deref(), Some("page1.tif"));
+    let line = facsimile.line_zone("1", "1").unwrap();
+    assert_eq!(line.id, "tl1");
+    assert_eq!(line.bounds, Some([10, 20, 510, 60]));
+}
+
+#[test]
+fn test_htr_dsl_compiles_with_breaks() {
+    use super::htr::{self, HtrFormat};
+
+    let result = htr::parse(PAGE_XML, HtrFormat::Page).unwrap();
+    let xml = compile_dsl(&result.dsl);
+    assert!(xml.contains("<pb n=\"1\"/>"), "Got: {}", xml);
+    assert!(xml.contains("<lb n=\"3\"/>"), "Got: {}", xml);
+}
+
+#[test]
+fn test_htr_facsimile_links_on_compile() {
+    use super::htr::{self, HtrFormat};
+
+    let result = htr::parse(PAGE_XML, HtrFormat::Page).unwrap();
+    let facsimile = result.facsimile.unwrap();
+    let mut compiler = Compiler::new().with_facsimile(&facsimile);
+    let body = compiler.compile(&result.dsl).unwrap();
+    let surface_id = &facsimile.surfaces[0].id;
+    assert!(body.contains(&format!("<pb n=\"1\" facs=\"#{}\"/>", surface_id)), "Got: {}", body);
+    assert!(body.contains("<lb n=\"1\" facs=\"#r2l1\"/>"), "Got: {}", body);
+    assert!(body.contains("<lb n=\"3\" facs=\"#r1l1\"/>"), "Got: {}", body);
+
+    let document = facsimile.insert_into("<TEI><teiHeader/></teiHeader>\n<text><body/></text></TEI>");
+    let facsimile_at = document.find("<facsimile>").unwrap();
+    assert!(document.find("</teiHeader>").unwrap() < facsimile_at);
+    assert!(facsimile_at < document.find("<text>").unwrap());
+}
+
+// ============================================================================
+// MENOTEC IMPORT/EXPORT
+// ============================================================================
+
+/// Menotec texts that survive import → DSL → export unchanged. These are
+/// written for the tests; real transcriptions are checked by
+/// `test_menotec_round_trip_fixtures` and `test_menotec_round_trip_files`.
+const MENOTEC_CORPUS: &[&str] = &[
+    // Numbered lines with entities, abbreviations and a continued word
+    "#1r\n1\tHer hefr upp s&oslash;gu k(onung)s Ólafs.\n2\t&thorn;at var ein=\n3\tn vetr.\n",
+    // Gaps, supplied text, deletions and additions
+    "#12v\n1\tok [...] <hann> {var} \\ok/ kom [...3] heim.\n",
+    // Unnumbered lines, compound joins and abbreviations with entities
+    "#2\nupp~haf &thorn;(at) sv(á), er m(a&eth;)r.\nok (s&aacute;) fór\n",
+    // Level codes, with and without an abbreviated facsimile form
+    "#3r\n1\tk(onung)r|konungr|konungr, &thorn;at|þat sagði|sagði|sagði.\n",
+];
+
+#[test]
+fn test_menotec_import_codes() {
+    use super::menotec;
+
+    let result = menotec::parse(MENOTEC_CORPUS[0]).unwrap();
+    assert_eq!(
+        result.dsl,
+        "///1r\n//1 Her hefr upp s:oslash:gu .abbr[ks]{konungs} Ólafs.\n//2 :thorn:at var ein//3n vetr."
+    );
+
+    let result = menotec::parse(MENOTEC_CORPUS[1]).unwrap();
+    assert_eq!(
+        result.dsl,
+        "///12v\n//1 ok [...] <hann> -{var}- +{ok}+ kom [...3] heim."
+    );
+
+    let result = menotec::parse(MENOTEC_CORPUS[3]).unwrap();
+    assert_eq!(
+        result.dsl,
+        "///3r\n//1 .abbr[kr]{konungr}.dipl{konungr}.norm{konungr}, :thorn:at.dipl{þat} \
+         sagði.dipl{sagði}.norm{sagði}."
+    );
+}
+
+#[test]
+fn test_menotec_import_compiles() {
+    use super::menotec;
+
+    let result = menotec::parse(MENOTEC_CORPUS[0]).unwrap();
+    let xml = compile_dsl(&result.dsl);
+    assert!(xml.contains("<pb n=\"1r\"/>"), "Got: {}", xml);
+    assert!(xml.contains("<abbr>ks</abbr>"), "Got: {}", xml);
+    // The continued word has no space before its line break
+    assert!(xml.contains("ein<lb n=\"3\"/>"), "Got: {}", xml);
+
+    let result = menotec::parse(MENOTEC_CORPUS[3]).unwrap();
+    let config = CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    };
+    let xml = Compiler::new().with_config(config).compile(&result.dsl).unwrap();
+    assert!(xml.contains("<me:dipl>þat</me:dipl>"), "Got: {}", xml);
+}
+
+#[test]
+fn test_menotec_round_trip_corpus() {
+    use super::menotec;
+
+    for text in MENOTEC_CORPUS {
+        let dsl = menotec::parse(text).unwrap().dsl;
+        let export = menotec::export(&dsl).unwrap();
+        assert_eq!(&export.text, text, "DSL was: {}", dsl);
+        assert!(export.warnings.is_empty(), "{:?}", export.warnings);
+    }
+}
+
+/// Files with the given extension in a corpus directory, sorted by name
+fn corpus_files(dir: &std::path::Path, extension: &str) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(extension))
+        .collect();
+    files.sort();
+    files
+}
+
+/// The real transcriptions kept in `static/tests` (see the README there)
+fn fixture_files(extension: &str) -> Vec<std::path::PathBuf> {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("static/tests");
+    corpus_files(&dir, extension)
+}
+
+fn assert_menotec_round_trips(path: &std::path::Path) {
+    use super::menotec;
+
+    let text = std::fs::read_to_string(path).expect("Should read corpus file");
+    let dsl = menotec::parse(&text).unwrap().dsl;
+    let export = menotec::export(&dsl).unwrap();
+    assert_eq!(export.text, text, "{:?} changed on round trip", path);
+}
+
+#[test]
+fn test_menotec_round_trip_fixtures() {
+    let files = fixture_files("menotec");
+    if files.is_empty() {
+        println!("No Menotec fixtures in static/tests, skipping");
+    }
+    for path in files {
+        assert_menotec_round_trips(&path);
+    }
+}
+
+/// Runs the round trip over every `.menotec` file in a further directory of
+/// real transcriptions. Run it with
+///
+/// ```text
+/// MENOTEC_CORPUS=/path/to/menotec cargo test test_menotec_round_trip_files -- --ignored
+/// ```
+#[test]
+#[ignore = "needs Menotec transcriptions in $MENOTEC_CORPUS"]
+fn test_menotec_round_trip_files() {
+    let corpus = std::env::var("MENOTEC_CORPUS").expect("MENOTEC_CORPUS should be set");
+    let files = corpus_files(std::path::Path::new(&corpus), "menotec");
+    assert!(!files.is_empty(), "MENOTEC_CORPUS has no .menotec files");
+    for path in files {
+        assert_menotec_round_trips(&path);
+    }
+}
+
+#[test]
+fn test_menotec_export_warns_on_lossy_constructs() {
+    use super::menotec;
+
+    let export = menotec::export("//1 ?{ok}? kom^{rubric} .abbr[xy]{ab}").unwrap();
+    assert_eq!(export.text, "1\tok kom ab\n");
+    assert_eq!(export.warnings.len(), 3);
+}
+
+// ============================================================================
+// DOCX IMPORT
+// ============================================================================
+
+/// Wrap body content in a minimal DOCX archive
+fn docx_bytes(body: &str) -> Vec<u8> {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}<w:sectPr/></w:body></w:document>"#,
+        body
+    );
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("word/document.xml", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_docx_paragraphs_and_line_numbers() {
+    use super::docx;
+
+    let body = r#"<w:p><w:r><w:t xml:space="preserve">1	Her hefr upp</w:t></w:r></w:p>
+        <w:p/>
+        <w:p><w:r><w:t xml:space="preserve">2 sogu</w:t><w:br/><w:t>ok svá</w:t></w:r></w:p>"#;
+    let result = docx::parse(&docx_bytes(body)).unwrap();
+    assert_eq!(result.dsl, "//1 Her hefr upp\n//2 sogu\n// ok svá");
+    assert!(result.report.unwrap().formatting.is_empty());
+}
+
+#[test]
+fn test_docx_page_breaks() {
+    use super::docx;
+
+    let body = r#"<w:p><w:r><w:t>Her hefr upp</w:t><w:br w:type="page"/><w:t>sogu</w:t></w:r></w:p>
+        <w:p><w:r><w:br w:type="page"/></w:r></w:p>
+        <w:p><w:r><w:t>ok svá</w:t></w:r></w:p>"#;
+    let result = docx::parse(&docx_bytes(body)).unwrap();
+    assert_eq!(result.dsl, "// Her hefr upp\n///2\n// sogu\n///3\n// ok svá");
+    assert!(result.report.unwrap().formatting.is_empty());
+
+    let xml = compile_dsl(&result.dsl);
+    assert!(xml.contains("<pb n=\"2\"/>"), "Got: {}", xml);
+}
+
+#[test]
+fn test_docx_formatting_to_dsl() {
+    use super::docx;
+
+    let body = r#"<w:p>
+        <w:r><w:t xml:space="preserve">var k</w:t></w:r>
+        <w:r><w:rPr><w:i/></w:rPr><w:t>onung</w:t></w:r>
+        <w:r><w:t xml:space="preserve">r, </w:t></w:r>
+        <w:r><w:rPr><w:strike/></w:rPr><w:t>hann</w:t></w:r>
+        <w:r><w:t xml:space="preserve"> </w:t></w:r>
+        <w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:t>ok</w:t></w:r>
+        <w:r><w:rPr><w:i w:val="0"/></w:rPr><w:t xml:space="preserve"> kom</w:t></w:r>
+    </w:p>"#;
+    let result = docx::parse(&docx_bytes(body)).unwrap();
+    assert_eq!(
+        result.dsl,
+        "// var .abbr[kr]{konungr}, -{hann}- +{ok}+ kom"
+    );
+
+    let xml = compile_dsl(&result.dsl);
+    assert!(xml.contains("<abbr>kr</abbr>"), "Got: {}", xml);
+    assert!(xml.contains("<del>hann</del>"), "Got: {}", xml);
+    assert!(xml.contains("<add>ok</add>"), "Got: {}", xml);
+}
+
+#[test]
+fn test_docx_reports_unmapped_formatting() {
+    use super::docx;
+
+    let body = r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Kapituli</w:t></w:r></w:p>
+        <w:p>
+            <w:r><w:rPr><w:b/></w:rPr><w:t>Her</w:t></w:r>
+            <w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve"> hefr</w:t></w:r>
+            <w:r><w:t xml:space="preserve"> </w:t></w:r>
+            <w:r><w:rPr><w:i/></w:rPr><w:t>sic</w:t></w:r>
+            <w:del><w:r><w:delText>gamalt</w:delText></w:r></w:del>
+        </w:p>"#;
+    let result = docx::parse(&docx_bytes(body)).unwrap();
+    assert_eq!(result.dsl, "// Kapituli\n// Her hefr sic");
+
+    let report: Vec<_> = result
+        .report
+        .as_ref()
+        .unwrap()
+        .formatting
+        .iter()
+        .map(|u| (u.paragraph, u.formatting.as_str(), u.text.as_str()))
+        .collect();
+    assert_eq!(
+        report,
+        vec![
+            (1, "style Heading1", "Kapituli"),
+            (2, "bold", "Her hefr"),
+            (2, "tracked deletion", "gamalt"),
+            (2, "italic word", "sic"),
+        ]
+    );
+}
+
+#[test]
+fn test_docx_rejects_non_archive() {
+    assert!(super::docx::parse(b"not a zip").is_err());
+}
+
+// ============================================================================
+// IMPORT REPORT
+// ============================================================================
+
+#[test]
+fn test_import_report_lists_unsupported_constructs() {
+    use super::tei::report::ConstructHandling;
+
+    let xml = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0">
+<text>
+<body>
+<p>
+<lb n="1" ed="ms"/>
+<damage agent="water">Her hefr</damage>
+<w lemma="upp" xml:id="w1">u<hi rend="red">pp</hi></w>
+</p>
+</body>
+</text>
+</TEI>"#;
+    let result = parse(xml).unwrap();
+    let report = result.report.expect("TEI imports carry a report");
+
+    assert_eq!(report.element_counts["damage"], 1);
+    assert_eq!(report.element_counts["lb"], 1);
+
+    let constructs: Vec<_> = report
+        .constructs
+        .iter()
+        .map(|c| (c.element.as_str(), c.handling, c.line, c.text.as_str()))
+        .collect();
+    assert_eq!(
+        constructs,
+        vec![
+            ("damage", ConstructHandling::ReadOnly, 6, "Her hefr"),
+            ("hi", ConstructHandling::Flattened, 7, "pp"),
+        ]
+    );
+
+    let attributes: Vec<_> = report
+        .attributes
+        .iter()
+        .map(|a| (a.element.as_str(), a.attribute.as_str(), a.lines.clone()))
+        .collect();
+    assert_eq!(
+        attributes,
+        vec![
+            ("lb", "ed", vec![5]),
+            ("damage", "agent", vec![6]),
+            ("w", "xml:id", vec![7]),
+            ("hi", "rend", vec![7]),
+        ]
+    );
+}
+
+#[test]
+fn test_level_overrides_survive_edit() {
+    let xml = "<TEI><text><body xmlns:me=\"http://www.menota.org/ns/1.0\"><p>\
+<w><choice><me:facs>kononge</me:facs><me:dipl>konongi</me:dipl><me:norm>konungi</me:norm></choice></w> \
+<w><choice><me:facs>ok</me:facs><me:dipl>ok</me:dipl><me:norm>ok</me:norm></choice></w>\
+</p></body></text></TEI>";
+
+    let plain = parse(xml).unwrap();
+    assert_eq!(plain.dsl, "kononge ok");
+
+    let options = ImportOptions {
+        level_overrides: true,
+    };
+    let result = parse_with_options(xml, options).unwrap();
+    assert_eq!(
+        result.dsl,
+        "kononge.dipl{konongi}.norm{konungi} ok.dipl{ok}.norm{ok}"
+    );
+
+    // Edit the facsimile of the first word; its hand-made levels must be kept
+    let edited = result.dsl.replacen("kononge", "konnonge", 1);
+    let segments = result.imported_document.unwrap().segments;
+    let patches = compute_patches(&segments, &edited);
+    let mut compiler = Compiler::new().with_config(CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    });
+    let body = apply_patches_with_policy(
+        &segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .xml;
+
+    assert!(body.contains("<me:facs>konnonge</me:facs>"));
+    assert!(body.contains("<me:dipl>konongi</me:dipl>"));
+    assert!(body.contains("<me:norm>konungi</me:norm>"));
+}
+
+#[test]
+fn test_level_override_markup_survives_edit() {
+    let xml = "<TEI><text><body xmlns:me=\"http://www.menota.org/ns/1.0\"><p>\
+<w><choice><me:facs>kgi</me:facs><me:dipl>k<ex>onun</ex>gi</me:dipl><me:norm><hi rend=\"sup\">k</hi>onungi</me:norm></choice></w> \
+<w><choice><me:facs>ok</me:facs><me:dipl>ok</me:dipl><me:norm>ok</me:norm></choice></w>\
+</p></body></text></TEI>";
+
+    let result = parse_with_options(xml, ImportOptions { level_overrides: true }).unwrap();
+    assert_eq!(
+        result.dsl,
+        ".abbr[kgi]{konungi}.dipl{konungi}.norm{konungi} ok.dipl{ok}.norm{ok}"
+    );
+    let segments = result.imported_document.unwrap().segments;
+    let mut compiler = Compiler::new().with_config(CompilerConfig {
+        word_wrap: true,
+        multi_level: true,
+        ..Default::default()
+    });
+
+    // Facsimile edited, levels untouched: their markup is kept
+    let edited = result.dsl.replacen("[kgi]", "[kngi]", 1);
+    let patches = compute_patches(&segments, &edited);
+    let body = apply_patches_with_policy(
+        &segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .xml;
+    assert!(body.contains("<me:facs><abbr>kngi</abbr></me:facs>"), "Got: {}", body);
+    assert!(body.contains("<me:dipl>k<ex>onun</ex>gi</me:dipl>"), "Got: {}", body);
+    assert!(body.contains("<me:norm><hi rend=\"sup\">k</hi>onungi</me:norm>"), "Got: {}", body);
+
+    // A changed level is compiled from its new text
+    let edited = result.dsl.replacen(".norm{konungi}", ".norm{konungr}", 1);
+    let patches = compute_patches(&segments, &edited);
+    let body = apply_patches_with_policy(
+        &segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .xml;
+    assert!(body.contains("<me:dipl>k<ex>onun</ex>gi</me:dipl>"), "Got: {}", body);
+    assert!(body.contains("<me:norm>konungr</me:norm>"), "Got: {}", body);
+}
+
+/// Builds a saga-sized document of `words` lemmatised words, 10 words to a line
+/// and 30 lines to a page, with a small vocabulary so that words repeat.
+fn large_document(words: usize) -> Vec<Segment> {
+    let vocabulary = ["ok", "konungr", "sagði", "hann", "þat", "var", "maðr", "einn"];
+    let mut segments = Vec::new();
+    let mut next_id = 0;
+    let mut id = || {
+        next_id += 1;
+        next_id - 1
+    };
+
+    for i in 0..words {
+        if i % 300 == 0 {
+            segments.push(Segment::PageBreak {
+                id: id(),
+                attributes: [("n".to_string(), format!("{}", i / 300 + 1))].into(),
+            });
+        }
+        if i % 10 == 0 {
+            segments.push(Segment::LineBreak {
+                id: id(),
+                attributes: [("n".to_string(), format!("{}", (i % 300) / 10 + 1))].into(),
+            });
+        } else {
+            segments.push(Segment::Whitespace {
+                id: id(),
+                content: " ".to_string(),
+            });
+        }
+        let word = vocabulary[i % vocabulary.len()];
+        segments.push(Segment::Word {
+            id: id(),
+            original_xml: format!("<w lemma=\"l{}\">{}</w>", i, word),
+            attributes: [("lemma".to_string(), format!("l{}", i))].into(),
+            dsl_content: word.to_string(),
+            has_inline_lb: false,
+        });
+    }
+    segments
+}
+
+/// Segment ids of the words in a document built by [`large_document`]
+fn word_ids(segments: &[Segment]) -> Vec<usize> {
+    segments
+        .iter()
+        .filter(|seg| matches!(seg, Segment::Word { .. }))
+        .map(Segment::id)
+        .collect()
+}
+
+/// Edits the DSL of the words at the given positions, counted over all words
+fn edit_words(dsl: &str, edit: impl Fn(usize, &str) -> Option<String>) -> String {
+    let mut index = 0;
+    let mut lines = Vec::new();
+    for line in dsl.split('\n') {
+        let mut parts = Vec::new();
+        for (pos, part) in line.split(' ').enumerate() {
+            if pos == 0 && part.starts_with("//") {
+                parts.push(part.to_string());
+                continue;
+            }
+            if let Some(replacement) = edit(index, part) {
+                parts.push(replacement);
+            } else {
+                parts.push(part.to_string());
+            }
+            index += 1;
+        }
+        lines.push(parts.into_iter().filter(|p| !p.is_empty()).collect::<Vec<_>>().join(" "));
+    }
+    lines.join("\n")
+}
+
+fn changed(patches: &[PatchOperation]) -> Vec<&PatchOperation> {
+    patches
+        .iter()
+        .filter(|op| !matches!(op, PatchOperation::Keep { .. }))
+        .collect()
+}
+
+#[test]
+fn test_large_document_insertion_near_start() {
+    let segments = large_document(50_000);
+    let ids = word_ids(&segments);
+    let dsl = crate::importer::tei::extraction::segments_to_dsl(&segments);
+
+    let edited = edit_words(&dsl, |i, word| (i == 5).then(|| format!("nýtt {}", word)));
+    let patches = compute_patches(&segments, &edited);
+
+    let changes = changed(&patches);
+    assert_eq!(changes.len(), 1, "only the inserted word may change: {:?}", changes);
+    assert!(matches!(changes[0], PatchOperation::Insert { dsl } if dsl == "nýtt"));
+
+    // Every original word after the insertion is kept with its XML
+    let ids: std::collections::HashSet<usize> = ids.into_iter().collect();
+    let kept = patches
+        .iter()
+        .filter(|op| matches!(op, PatchOperation::Keep { segment_id } if ids.contains(segment_id)))
+        .count();
+    assert_eq!(kept, 50_000);
+}
+
+#[test]
+fn test_large_document_scattered_edits() {
+    let segments = large_document(50_000);
+    let ids = word_ids(&segments);
+    let dsl = crate::importer::tei::extraction::segments_to_dsl(&segments);
+
+    let edited = edit_words(&dsl, |i, word| match i {
+        10_000 => Some("breytt".to_string()),
+        25_000 => Some(String::new()),
+        40_000 => Some(format!("{} nýtt", word)),
+        _ => None,
+    });
+    let patches = compute_patches(&segments, &edited);
+
+    let changes = changed(&patches);
+    assert_eq!(changes.len(), 3, "unexpected changes: {:?}", changes);
+    assert!(matches!(
+        changes[0],
+        PatchOperation::Modify { segment_id, new_dsl }
+            if *segment_id == ids[10_000] && new_dsl == "breytt"
+    ));
+    assert!(matches!(
+        changes[1],
+        PatchOperation::Delete { segment_id } if *segment_id == ids[25_000]
+    ));
+    assert!(matches!(changes[2], PatchOperation::Insert { dsl } if dsl == "nýtt"));
+
+    // The modified word keeps its lemma; untouched words keep their original XML
+    let mut compiler = Compiler::new().with_config(CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    });
+    let body = apply_patches_with_policy(
+        &segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .xml;
+    assert!(body.contains("<w lemma=\"l10000\">"));
+    assert!(body.contains("<w lemma=\"l49999\">"));
+    assert!(!body.contains("lemma=\"l25000\""));
+}
+
+#[test]
+fn test_modified_word_attribute_policy() {
+    let xml = "<TEI><text><body><p>\
+<w xml:id=\"w1\" lemma=\"konungr\" me:msa=\"xNC\" xmlns:me=\"http://www.menota.org/ns/1.0\">konongr</w> \
+<w xml:id=\"w2\" lemma=\"ok\">ok</w>\
+</p></body></text></TEI>";
+    let result = parse(xml).unwrap();
+    assert_eq!(result.dsl, "konongr ok");
+    let segments = result.imported_document.unwrap().segments;
+    let mut compiler = Compiler::new().with_config(CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    });
+
+    // A typo fix keeps identity and linguistic attributes silently
+    let patches = compute_patches(&segments, "konungr ok");
+    let fixed = apply_patches_with_policy(
+        &segments,
+        &patches,
+        &mut compiler,
+        &AttributePolicy::default(),
+    );
+    assert!(fixed.xml.contains("lemma=\"konungr\" me:msa=\"xNC\" xml:id=\"w1\""));
+    assert!(fixed.stale_attributes.is_empty());
+
+    // A different word keeps its xml:id but its lemma is flagged, or dropped
+    let patches = compute_patches(&segments, "konungr en");
+    let policy = AttributePolicy {
+        drop_stale: true,
+        ..Default::default()
+    };
+    let replaced = apply_patches_with_policy(&segments, &patches, &mut compiler, &policy);
+    assert_eq!(replaced.stale_attributes.len(), 1);
+    assert_eq!(replaced.stale_attributes[0].original_form, "ok");
+    assert_eq!(replaced.stale_attributes[0].new_form, "en");
+    assert!(replaced.xml.contains("<w xml:id=\"w2\">"));
+    assert!(!replaced.xml.contains("lemma=\"ok\""));
+}
+
+// ============================================================================
+// MULTIPLE TEXT UNITS
+// ============================================================================
+
+const GROUPED_TEXTS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TEI xmlns="http://www.tei-c.org/ns/1.0">
+  <teiHeader><fileDesc><titleStmt><title>Miscellany</title></titleStmt></fileDesc></teiHeader>
+  <text>
+    <front>
+      <!-- <body> in a comment is not a unit -->
+      <p><w>Formáli</w></p>
+    </front>
+    <group>
+      <text n="A">
+        <body><p><w>fyrsta</w> <w>saga</w></p></body>
+      </text>
+      <text n="B">
+        <body><p><w>önnur</w> <w>saga</w></p></body>
+      </text>
+    </group>
+    <back>
+      <p><w>Eftirmáli</w></p>
+    </back>
+  </text>
+</TEI>
+"#;
+
+#[test]
+fn test_import_front_back_and_grouped_texts() {
+    let result = parse(GROUPED_TEXTS).unwrap();
+    let document = result.imported_document.unwrap();
+
+    // The first <body> is the main unit; everything else is kept as a unit
+    assert_eq!(result.dsl, "fyrsta saga");
+    assert_eq!(document.label, "Text A · Body");
+    let units: Vec<(&str, &str, &str)> = document
+        .units
+        .iter()
+        .map(|unit| (unit.label.as_str(), unit.kind.as_str(), unit.dsl.as_str()))
+        .collect();
+    assert_eq!(
+        units,
+        vec![
+            ("Text 1 · Front", "front", "Formáli"),
+            ("Text B · Body", "body", "önnur saga"),
+            ("Text 1 · Back", "back", "Eftirmáli"),
+        ]
+    );
+
+    assert!(result.original_body_xml.unwrap().starts_with("<body><p><w>fyrsta"));
+    let preamble = result.original_preamble.unwrap();
+    assert!(preamble.contains(&document.units[0].original_xml));
+    assert!(result.original_postamble.unwrap().contains(&document.units[1].original_xml));
+
+    let report = fidelity::check_round_trip(GROUPED_TEXTS).unwrap();
+    assert!(report.byte_identical, "{:#?}", report.differences);
+}
+
+#[test]
+fn test_text_units_round_trip_independently() {
+    let result = parse(GROUPED_TEXTS).unwrap();
+    let mut document = result.imported_document.unwrap();
+    document.units[1].dsl = "önnur löng saga".to_string();
+
+    let mut compiler = Compiler::new().with_config(CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: false,
+        wrap_pages: false,
+    });
+    let rebuilt = reconstruct_document(
+        &document,
+        &result.dsl,
+        &result.original_preamble.unwrap(),
+        &result.original_postamble.unwrap(),
+        &mut compiler,
+        &AttributePolicy::default(),
+    )
+    .unwrap();
+
+    // Only the edited unit changes; the main body and the other units are untouched
+    let (before, after) = GROUPED_TEXTS.split_once("<w>önnur</w> <w>").unwrap();
+    assert!(rebuilt.xml.starts_with(before));
+    assert!(rebuilt.xml.ends_with(after.split_once("</w>").unwrap().1));
+    assert!(rebuilt.xml[before.len()..].contains("löng"));
+}
+
+// ============================================================================
+// ROUND-TRIP FIDELITY
+// ============================================================================
+
+/// A small MENOTA-style file exercising the constructs real transcriptions use:
+/// DOCTYPE, comments, namespaces, entity references, attributes in arbitrary
+/// order and whitespace between words
+const MENOTA_SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE TEI [
+<!ENTITY aenl "&#xEFE0;">
+]>
+<!-- MENOTA sample -->
+<TEI xmlns="http://www.tei-c.org/ns/1.0" xmlns:me="http://www.menota.org/ns/1.0">
+  <teiHeader><fileDesc><titleStmt><title>Sample</title></titleStmt></fileDesc></teiHeader>
+  <text>
+    <body>
+      <div type="chapter" n="1">
+        <p>
+          <pb n="1r"/>
+          <lb n="1"/>
+          <w me:msa="xNC" lemma="konungr" xml:id="w1"><choice><me:facs>kon&aenl;gr</me:facs><me:dipl>konungr</me:dipl><me:norm>konungr</me:norm></choice></w>
+          <w lemma="segja"><choice><me:facs>s<am>&#x0304;</am></me:facs><me:dipl>s<ex>agði</ex></me:dipl><me:norm>sagði</me:norm></choice></w>
+          <pc><choice><me:facs>.</me:facs><me:dipl>.</me:dipl><me:norm>.</me:norm></choice></pc>
+          <!-- end of line -->
+          <lb n="2"/>
+          <w><choice><me:facs>ok</me:facs><me:dipl>ok</me:dipl><me:norm>ok</me:norm></choice></w>
+        </p>
+      </div>
+    </body>
+  </text>
+</TEI>
+"#;
+
+#[test]
+fn test_fidelity_unedited_menota_sample() {
+    let report = fidelity::check_round_trip(MENOTA_SAMPLE).unwrap();
+    assert!(
+        report.canonical_identical,
+        "unexpected differences: {:#?}",
+        report.differences
+    );
+    assert!(report.differences.is_empty());
+}
+
+#[test]
+fn test_fidelity_reports_differences_with_location() {
+    let altered = MENOTA_SAMPLE
+        .replace("lemma=\"segja\"", "lemma=\"sjá\"")
+        .replace("<me:norm>ok</me:norm>", "<me:norm>og</me:norm>");
+    let report = fidelity::compare(MENOTA_SAMPLE, &altered).unwrap();
+
+    assert!(!report.byte_identical);
+    assert!(!report.canonical_identical);
+    assert_eq!(report.differences.len(), 2, "{:#?}", report.differences);
+
+    let lemma = &report.differences[0];
+    assert_eq!(lemma.kind, "attribute lemma");
+    assert!(lemma.path.ends_with("/p[1]/w[2]/@lemma"), "{}", lemma.path);
+    assert_eq!(lemma.original_line, 15);
+    assert_eq!(
+        (lemma.original.as_str(), lemma.round_trip.as_str()),
+        ("segja", "sjá")
+    );
+
+    let norm = &report.differences[1];
+    assert_eq!(norm.kind, "text");
+    assert!(
+        norm.path.ends_with("/w[3]/choice[1]/me:norm[1]/text()[1]"),
+        "{}",
+        norm.path
+    );
+    assert_eq!(norm.original_line, 19);
+}
+
+fn assert_fidelity(path: &std::path::Path) {
+    let xml = std::fs::read_to_string(path).expect("Should read corpus file");
+    let report = fidelity::check_round_trip(&xml).expect("Round trip should run");
+    assert!(
+        report.canonical_identical,
+        "{:?} changed on round trip: {:#?}",
+        path,
+        report.differences.iter().take(20).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_fidelity_menota_fixtures() {
+    let files = fixture_files("xml");
+    if files.is_empty() {
+        println!("No MENOTA fixtures in static/tests, skipping");
+    }
+    for path in files {
+        assert_fidelity(&path);
+    }
+}
+
+/// Runs the fidelity check over every `.xml` file in a further directory of
+/// real MENOTA transcriptions. Run it with
+///
+/// ```text
+/// MENOTA_CORPUS=/path/to/menota cargo test test_fidelity_menota_corpus -- --ignored
+/// ```
+#[test]
+#[ignore = "needs a MENOTA corpus in $MENOTA_CORPUS"]
+fn test_fidelity_menota_corpus() {
+    let corpus = std::env::var("MENOTA_CORPUS").expect("MENOTA_CORPUS should be set");
+    let files = corpus_files(std::path::Path::new(&corpus), "xml");
+    assert!(!files.is_empty(), "MENOTA_CORPUS has no .xml files");
+    for path in files {
+        assert_fidelity(&path);
+    }
+}
+
+// ============================================================================
+// HEADER ROUND-TRIP
+// ============================================================================
+
+const CATALOGUED: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE TEI SYSTEM "menota.dtd">
+<TEI xmlns="http://www.tei-c.org/ns/1.0" xmlns:me="http://www.menota.org/ns/1.0">
+  <teiHeader xml:lang="en">
+    <fileDesc>
+      <titleStmt>
+        <title>Heimskringla</title>
+      </titleStmt>
+      <publicationStmt>
+        <distributor>handrit.is</distributor>
+      </publicationStmt>
+      <sourceDesc>
+        <msDesc xml:id="AM02-0035">
+          <msIdentifier>
+            <settlement>Reykjavík</settlement>
+            <repository>Stofnun Árna Magnússonar</repository>
+            <idno>AM 35 fol.</idno>
+          </msIdentifier>
+          <msContents>
+            <msItem n="1">
+              <locus from="1r" to="70v">1r-70v</locus>
+              <title>Heimskringla</title>
+              <note>Copied by &aenl; Ásgeir Jónsson</note>
+            </msItem>
+          </msContents>
+          <physDesc>
+            <bindingDesc>
+              <binding><p>Brown calf</p></binding>
+            </bindingDesc>
+          </physDesc>
+        </msDesc>
+      </sourceDesc>
+    </fileDesc>
+    <revisionDesc>
+      <change when="2009-02-03" who="#EM">Created</change>
+    </revisionDesc>
+  </teiHeader>
+  <text>
+    <body>
+      <p>Kringla heimsins</p>
+    </body>
+  </text>
+</TEI>
+"##;
+
+#[test]
+fn test_header_round_trip_keeps_unmodelled_content() {
+    let result = parse(CATALOGUED).unwrap();
+    let preamble = result.original_preamble.unwrap();
+    let mut metadata = result.metadata.unwrap();
+    assert!(metadata.original_header.as_deref().unwrap().starts_with("<teiHeader xml:lang"));
+
+    // Unedited: the preamble comes back byte for byte
+    assert_eq!(header::replace_header(&preamble, &metadata).unwrap(), preamble);
+
+    metadata.ms_identifier.idno = Some("AM 35 fol. (Kringla)".to_string());
+    metadata.phys_desc.material = Some("Parchment".to_string());
+    let patched = header::replace_header(&preamble, &metadata).unwrap();
+
+    assert!(patched.contains("<idno>AM 35 fol. (Kringla)</idno>"));
+    assert!(patched.contains(
+        "<physDesc>\n            <objectDesc>\n              <supportDesc>\n                <support>Parchment</support>"
+    ));
+    for kept in [
+        "<locus from=\"1r\" to=\"70v\">1r-70v</locus>",
+        "<note>Copied by &aenl; Ásgeir Jónsson</note>",
+        "<binding><p>Brown calf</p></binding>",
+        "<change when=\"2009-02-03\" who=\"#EM\">Created</change>",
+        "<msDesc xml:id=\"AM02-0035\">",
+    ] {
+        assert!(patched.contains(kept), "lost {kept}");
+    }
+
+    // The generated header for a non-imported compile uses the same patch
+    let generated = metadata.to_tei_header(true);
+    assert!(generated.contains("<bindingDesc>"));
+    assert!(generated.contains("<idno>AM 35 fol. (Kringla)</idno>"));
+}
@@ -67,6 +67,7 @@ pub fn run() {
             commands::file::open_file,
             commands::file::save_file,
             commands::file::export_tei,
+            commands::file::export_menotec,
             commands::file::export_html,
             commands::file::load_text_file,
             commands::file::save_project,
@@ -75,6 +76,8 @@ pub fn run() {
             commands::template::get_template,
             commands::template::save_template,
             commands::template::delete_template,
+            commands::template::export_template_package,
+            commands::template::import_template_package,
             commands::stylesheet::list_stylesheets,
             commands::stylesheet::import_stylesheet,
             commands::stylesheet::delete_stylesheet,
@@ -113,10 +116,20 @@ pub fn run() {
             commands::validate::validate_xml,
             commands::validate::validate_xml_with_schema,
             commands::import::import_file,
+            commands::import::check_round_trip,
+            commands::import::preview_plain_text,
             commands::metadata::generate_tei_header,
             commands::metadata::generate_tei_footer,
+            commands::metadata::update_header_revisions,
             commands::metadata::validate_metadata,
+            commands::metadata::import_catalogue_record,
+            commands::metadata::export_metadata,
             commands::metadata::create_empty_metadata,
+            commands::annotations::merge_annotation_sets,
+            commands::annotations::resolve_annotation_conflicts,
+            commands::annotations::set_annotation_status,
+            commands::annotations::export_annotation_table,
+            commands::annotations::import_annotation_table,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
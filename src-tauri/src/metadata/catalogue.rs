@@ -0,0 +1,363 @@
+//! Importing manuscript descriptions from catalogue records.
+//!
+//! handrit.is and e-manuscripta publish one TEI file per manuscript with the
+//! catalogue description in `<msDesc>`. [`import_catalogue`] picks the
+//! description of the manuscript being edited, reads it with the same code as
+//! imported headers, and merges it into the existing metadata field by field:
+//! empty fields are filled in, and fields that already hold a different value
+//! are reported as conflicts instead of being silently overwritten.
+
+use libxml::parser::Parser;
+use libxml::tree::Node;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::header::read_ms_desc;
+use super::Metadata;
+
+/// Metadata sections that come from `<msDesc>`
+const SECTIONS: &[&str] = &[
+    "msIdentifier",
+    "msContents",
+    "physDesc",
+    "history",
+    "msParts",
+];
+
+/// Sections merged field by field; anything else is merged as a whole
+const NESTED: &[&str] = &["msIdentifier", "msContents", "physDesc", "history"];
+
+/// Result of merging a catalogue record into existing metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogueImport {
+    /// The merged metadata
+    pub metadata: Metadata,
+    /// Shelfmark of the `<msDesc>` that was used
+    pub shelfmark: Option<String>,
+    /// Fields filled in from the catalogue, e.g. `physDesc.hands`
+    pub taken: Vec<String>,
+    /// Fields where the catalogue disagreed with a value already present
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// A field whose catalogue value differs from the existing one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldConflict {
+    pub field: String,
+    pub existing: Value,
+    pub catalogue: Value,
+}
+
+/// Merge the description of one manuscript in a catalogue file into `existing`.
+///
+/// The `<msDesc>` is chosen by `shelfmark`, falling back to the shelfmark
+/// already in `existing`; a file with a single description needs neither.
+/// On conflict the existing value is kept unless `prefer_catalogue` is set.
+pub fn import_catalogue(
+    xml: &str,
+    shelfmark: Option<&str>,
+    existing: &Metadata,
+    prefer_catalogue: bool,
+) -> Result<CatalogueImport, String> {
+    let doc = Parser::default()
+        .parse_string(xml)
+        .map_err(|e| format!("Failed to parse catalogue file: {:?}", e))?;
+    let root = doc
+        .get_root_element()
+        .ok_or_else(|| "Catalogue file has no root element".to_string())?;
+
+    let mut descriptions = Vec::new();
+    collect_ms_descs(&root, &mut descriptions);
+    if descriptions.is_empty() {
+        return Err("No <msDesc> found in catalogue file".to_string());
+    }
+
+    let wanted = shelfmark
+        .filter(|s| !s.trim().is_empty())
+        .or(existing.ms_identifier.idno.as_deref());
+    let ms_desc = select_ms_desc(&descriptions, wanted)?;
+
+    let mut catalogue = Metadata::default();
+    read_ms_desc(ms_desc, &mut catalogue);
+
+    let mut merged = to_value(existing)?;
+    let from_catalogue = to_value(&catalogue)?;
+    let mut taken = Vec::new();
+    let mut conflicts = Vec::new();
+    for &section in SECTIONS {
+        merge(
+            &mut merged[section],
+            &from_catalogue[section],
+            section,
+            prefer_catalogue,
+            &mut taken,
+            &mut conflicts,
+        );
+    }
+
+    Ok(CatalogueImport {
+        metadata: serde_json::from_value(merged)
+            .map_err(|e| format!("Failed to merge catalogue record: {}", e))?,
+        shelfmark: catalogue.ms_identifier.idno,
+        taken,
+        conflicts,
+    })
+}
+
+fn collect_ms_descs(node: &Node, found: &mut Vec<Node>) {
+    for child in node.get_child_elements() {
+        if child.get_name() == "msDesc" {
+            found.push(child);
+        } else {
+            collect_ms_descs(&child, found);
+        }
+    }
+}
+
+/// Pick the `<msDesc>` whose shelfmark or `xml:id` matches `wanted`
+fn select_ms_desc<'a>(descriptions: &'a [Node], wanted: Option<&str>) -> Result<&'a Node, String> {
+    let Some(wanted) = wanted else {
+        return match descriptions {
+            [only] => Ok(only),
+            _ => Err(format!(
+                "Catalogue file describes {} manuscripts ({}); choose a shelfmark",
+                descriptions.len(),
+                shelfmarks(descriptions).join(", ")
+            )),
+        };
+    };
+
+    let key = shelfmark_key(wanted);
+    descriptions
+        .iter()
+        .find(|ms_desc| {
+            shelfmark_of(ms_desc).is_some_and(|idno| shelfmark_key(&idno) == key)
+                || ms_desc
+                    .get_property("id")
+                    .is_some_and(|id| shelfmark_key(&id) == key)
+        })
+        .ok_or_else(|| {
+            format!(
+                "No description of {} in catalogue file (found: {})",
+                wanted,
+                shelfmarks(descriptions).join(", ")
+            )
+        })
+}
+
+fn shelfmark_of(ms_desc: &Node) -> Option<String> {
+    let identifier = ms_desc
+        .get_child_elements()
+        .into_iter()
+        .find(|n| n.get_name() == "msIdentifier")?;
+    let idno = identifier
+        .get_child_elements()
+        .into_iter()
+        .find(|n| n.get_name() == "idno")?;
+    Some(
+        idno.get_content()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn shelfmarks(descriptions: &[Node]) -> Vec<String> {
+    descriptions
+        .iter()
+        .map(|d| shelfmark_of(d).unwrap_or_else(|| "no shelfmark".to_string()))
+        .collect()
+}
+
+/// Shelfmarks are compared on letters and digits only, so "AM 132 fol." and
+/// "AM02-0132"-style ids differ but "AM 132 fol" and "am 132 fol." match.
+fn shelfmark_key(shelfmark: &str) -> String {
+    shelfmark
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn to_value(metadata: &Metadata) -> Result<Value, String> {
+    serde_json::to_value(metadata).map_err(|e| format!("Failed to serialize metadata: {}", e))
+}
+
+fn merge(
+    existing: &mut Value,
+    catalogue: &Value,
+    path: &str,
+    prefer_catalogue: bool,
+    taken: &mut Vec<String>,
+    conflicts: &mut Vec<FieldConflict>,
+) {
+    if is_empty(catalogue) || existing == catalogue {
+        return;
+    }
+
+    if NESTED.contains(&path) {
+        if let (Value::Object(fields), Value::Object(from)) = (&mut *existing, catalogue) {
+            for (key, value) in from {
+                merge(
+                    fields.entry(key.clone()).or_insert(Value::Null),
+                    value,
+                    &format!("{}.{}", path, key),
+                    prefer_catalogue,
+                    taken,
+                    conflicts,
+                );
+            }
+            return;
+        }
+    }
+
+    if is_empty(existing) {
+        *existing = catalogue.clone();
+        taken.push(path.to_string());
+    } else {
+        conflicts.push(FieldConflict {
+            field: path.to_string(),
+            existing: existing.clone(),
+            catalogue: catalogue.clone(),
+        });
+        if prefer_catalogue {
+            *existing = catalogue.clone();
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.values().all(is_empty),
+        Value::Bool(_) | Value::Number(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOGUE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TEI xmlns="http://www.tei-c.org/ns/1.0">
+  <teiHeader>
+    <fileDesc>
+      <titleStmt><title>Egils saga</title></titleStmt>
+      <publicationStmt><publisher>handrit.is</publisher></publicationStmt>
+      <sourceDesc>
+        <msDesc xml:id="AM02-0132" xml:lang="en">
+          <msIdentifier>
+            <country>Iceland</country>
+            <settlement>Reykjavík</settlement>
+            <repository>Stofnun Árna Magnússonar</repository>
+            <idno>AM 132 fol.</idno>
+            <msName>Möðruvallabók</msName>
+          </msIdentifier>
+          <msContents>
+            <msItem n="1"><locus from="1r" to="1v">1r-1v</locus><title>Njáls saga</title></msItem>
+          </msContents>
+          <physDesc>
+            <objectDesc form="codex">
+              <supportDesc material="perg"><support>Parchment</support><extent>200 leaves</extent></supportDesc>
+            </objectDesc>
+            <handDesc>
+              <handNote xml:id="H1" scope="major">Main hand</handNote>
+            </handDesc>
+          </physDesc>
+          <history>
+            <origin><origDate>s. xiv med.</origDate><origPlace>Iceland</origPlace></origin>
+          </history>
+        </msDesc>
+      </sourceDesc>
+    </fileDesc>
+  </teiHeader>
+  <facsimile/>
+</TEI>"#;
+
+    fn existing() -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.title_stmt.title = Some("My edition".to_string());
+        metadata.ms_identifier.idno = Some("AM 132 fol".to_string());
+        metadata.ms_identifier.repository = Some("Árni Magnússon Institute".to_string());
+        metadata
+    }
+
+    #[test]
+    fn test_fills_empty_fields_and_reports_conflicts() {
+        let result = import_catalogue(CATALOGUE, None, &existing(), false).unwrap();
+        let metadata = &result.metadata;
+
+        assert_eq!(result.shelfmark.as_deref(), Some("AM 132 fol."));
+        assert_eq!(metadata.title_stmt.title.as_deref(), Some("My edition"));
+        assert_eq!(
+            metadata.ms_identifier.settlement.as_deref(),
+            Some("Reykjavík")
+        );
+        assert_eq!(
+            metadata.ms_contents.items[0].title.as_deref(),
+            Some("Njáls saga")
+        );
+        assert_eq!(metadata.phys_desc.hands[0].id.as_deref(), Some("H1"));
+        let date = metadata.history.orig_date.as_ref().unwrap();
+        assert_eq!(date.not_before.as_deref(), Some("1325"));
+
+        assert!(result
+            .taken
+            .contains(&"msIdentifier.settlement".to_string()));
+        assert!(result.taken.contains(&"physDesc.hands".to_string()));
+        assert!(result.taken.contains(&"history.origDate".to_string()));
+
+        // Existing values win, and are reported
+        assert_eq!(
+            metadata.ms_identifier.repository.as_deref(),
+            Some("Árni Magnússon Institute")
+        );
+        let fields: Vec<&str> = result.conflicts.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["msIdentifier.idno", "msIdentifier.repository"]);
+        assert_eq!(result.conflicts[1].catalogue, "Stofnun Árna Magnússonar");
+    }
+
+    #[test]
+    fn test_prefer_catalogue_overwrites_conflicts() {
+        let result = import_catalogue(CATALOGUE, None, &existing(), true).unwrap();
+        assert_eq!(
+            result.metadata.ms_identifier.repository.as_deref(),
+            Some("Stofnun Árna Magnússonar")
+        );
+        assert_eq!(result.conflicts.len(), 2);
+    }
+
+    #[test]
+    fn test_selects_ms_desc_by_shelfmark() {
+        let second = CATALOGUE
+            .replace("AM02-0132", "GKS02-1005")
+            .replace("AM 132 fol.", "GKS 1005 fol.")
+            .replace("Möðruvallabók", "Flateyjarbók");
+        let start = second.find("<msDesc").unwrap();
+        let end = second.find("</msDesc>").unwrap() + "</msDesc>".len();
+        let both = CATALOGUE.replace(
+            "</sourceDesc>",
+            &format!("{}</sourceDesc>", &second[start..end]),
+        );
+
+        let result =
+            import_catalogue(&both, Some("GKS 1005 fol"), &Metadata::new(), false).unwrap();
+        assert_eq!(
+            result.metadata.ms_identifier.ms_name.as_deref(),
+            Some("Flateyjarbók")
+        );
+        assert!(result.conflicts.is_empty());
+
+        // Falls back to the existing shelfmark
+        let result = import_catalogue(&both, None, &existing(), false).unwrap();
+        assert_eq!(result.shelfmark.as_deref(), Some("AM 132 fol."));
+
+        let err = import_catalogue(&both, None, &Metadata::new(), false).unwrap_err();
+        assert!(err.contains("AM 132 fol., GKS 1005 fol."));
+        assert!(import_catalogue(&both, Some("AM 1 fol."), &Metadata::new(), false).is_err());
+    }
+}
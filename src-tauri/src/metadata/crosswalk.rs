@@ -0,0 +1,578 @@
+//! Metadata crosswalks for repositories and image viewers.
+//!
+//! - [`dublin_core`]: an `oai_dc` record for OAI-PMH ingest
+//! - [`json_ld`]: a schema.org description of the edition and its manuscript
+//! - [`iiif`]: the `label`, `summary`, `metadata` and `rights` properties of a
+//!   IIIF Presentation 3 manifest, to merge into a manifest made elsewhere
+//!
+//! The digital edition is the described resource; the manuscript it is based
+//! on is its source (`dc:source`, `isBasedOn`). Fields without a value are left
+//! out rather than written empty.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{escape_xml, DateRange, Metadata, Person};
+
+/// Target of a metadata export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataFormat {
+    DublinCore,
+    JsonLd,
+    Iiif,
+}
+
+/// Serialize metadata in the given format; JSON formats are pretty-printed.
+pub fn export(metadata: &Metadata, format: MetadataFormat) -> String {
+    let json = match format {
+        MetadataFormat::DublinCore => return dublin_core(metadata),
+        MetadataFormat::JsonLd => json_ld(metadata),
+        MetadataFormat::Iiif => iiif(metadata),
+    };
+    serde_json::to_string_pretty(&json).unwrap_or_default()
+}
+
+/// Serialize metadata as an OAI-PMH `oai_dc` Dublin Core record.
+pub fn dublin_core(metadata: &Metadata) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    let mut push = |name, value: Option<&str>| {
+        if let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) {
+            fields.push((name, value.to_string()));
+        }
+    };
+
+    let title = &metadata.title_stmt;
+    push("title", title.title.as_deref());
+    push("creator", title.author.as_deref());
+    push("description", metadata.ms_contents.summary.as_deref());
+    for item in &metadata.ms_contents.items {
+        push("description", item.title.as_deref());
+    }
+    let publication = &metadata.publication_stmt;
+    push("publisher", publication.publisher.as_deref());
+    for editor in &title.editors {
+        push("contributor", Some(&editor.name));
+    }
+    for stmt in &title.resp_stmts {
+        push(
+            "contributor",
+            Some(&format!("{} ({})", stmt.name, stmt.resp)),
+        );
+    }
+    push("date", publication.date.as_deref());
+    push("type", Some("Text"));
+    push("format", Some("application/tei+xml"));
+    push("identifier", publication.idno.as_deref());
+    push("source", metadata.ms_identifier.shelfmark().as_deref());
+    for language in languages(metadata) {
+        push("language", Some(&language));
+    }
+    if let Some(ref date) = metadata.history.orig_date {
+        push("coverage", date_span(date).as_deref());
+    }
+    push("coverage", metadata.history.orig_place.as_deref());
+    if let Some(ref availability) = publication.availability {
+        push("rights", availability.license_url.as_deref());
+        push("rights", availability.license.as_deref());
+    }
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push('\n');
+    xml.push_str(concat!(
+        r#"<oai_dc:dc xmlns:oai_dc="http://www.openarchives.org/OAI/2.0/oai_dc/""#,
+        r#" xmlns:dc="http://purl.org/dc/elements/1.1/""#,
+        r#" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance""#,
+        r#" xsi:schemaLocation="http://www.openarchives.org/OAI/2.0/oai_dc/ http://www.openarchives.org/OAI/2.0/oai_dc.xsd">"#,
+    ));
+    xml.push('\n');
+    for (name, value) in fields {
+        xml.push_str(&format!(
+            "  <dc:{0}>{1}</dc:{0}>\n",
+            name,
+            escape_xml(&value)
+        ));
+    }
+    xml.push_str("</oai_dc:dc>\n");
+    xml
+}
+
+/// Describe the edition and its manuscript as schema.org JSON-LD.
+pub fn json_ld(metadata: &Metadata) -> Value {
+    let title = &metadata.title_stmt;
+    let publication = &metadata.publication_stmt;
+    let mut edition = Map::new();
+    edition.insert("@context".into(), json!("https://schema.org"));
+    edition.insert("@type".into(), json!("CreativeWork"));
+    put(&mut edition, "name", text(&title.title));
+    put(&mut edition, "alternativeHeadline", text(&title.subtitle));
+    put(&mut edition, "author", text(&title.author).map(person));
+    put(
+        &mut edition,
+        "editor",
+        list(title.editors.iter().map(|editor| {
+            let mut value = person(json!(editor.name));
+            put(value.as_object_mut().unwrap(), "sameAs", orcid_url(editor));
+            value
+        })),
+    );
+    put(
+        &mut edition,
+        "contributor",
+        list(title.resp_stmts.iter().map(|stmt| {
+            json!({
+                "@type": "Role",
+                "roleName": stmt.resp,
+                "contributor": person(json!(stmt.name)),
+            })
+        })),
+    );
+    put(
+        &mut edition,
+        "publisher",
+        text(&publication.publisher).map(|name| json!({ "@type": "Organization", "name": name })),
+    );
+    put(&mut edition, "datePublished", text(&publication.date));
+    put(&mut edition, "identifier", text(&publication.idno));
+    if let Some(ref availability) = publication.availability {
+        let license = text(&availability.license_url).or_else(|| text(&availability.license));
+        put(&mut edition, "license", license);
+    }
+    put(
+        &mut edition,
+        "inLanguage",
+        list(languages(metadata).into_iter().map(Value::from)),
+    );
+    edition.insert("encodingFormat".into(), json!("application/tei+xml"));
+
+    let ms = &metadata.ms_identifier;
+    let mut manuscript = Map::new();
+    manuscript.insert("@type".into(), json!(["Manuscript", "ArchiveComponent"]));
+    put(
+        &mut manuscript,
+        "name",
+        text(&ms.ms_name).or_else(|| ms.shelfmark().map(Value::from)),
+    );
+    put(&mut manuscript, "identifier", text(&ms.idno));
+    if let Some(repository) = text(&ms.repository) {
+        let mut archive = Map::new();
+        archive.insert("@type".into(), json!("ArchiveOrganization"));
+        archive.insert("name".into(), repository);
+        let mut address = Map::new();
+        put(&mut address, "addressLocality", text(&ms.settlement));
+        put(&mut address, "addressCountry", text(&ms.country));
+        if !address.is_empty() {
+            address.insert("@type".into(), json!("PostalAddress"));
+            archive.insert("address".into(), Value::Object(address));
+        }
+        manuscript.insert("holdingArchive".into(), Value::Object(archive));
+    }
+    put(
+        &mut manuscript,
+        "description",
+        text(&metadata.ms_contents.summary),
+    );
+    put(
+        &mut manuscript,
+        "temporalCoverage",
+        metadata
+            .history
+            .orig_date
+            .as_ref()
+            .and_then(date_span)
+            .map(Value::from),
+    );
+    put(
+        &mut manuscript,
+        "locationCreated",
+        text(&metadata.history.orig_place).map(|name| json!({ "@type": "Place", "name": name })),
+    );
+    put(
+        &mut manuscript,
+        "material",
+        text(&metadata.phys_desc.material),
+    );
+    put(
+        &mut manuscript,
+        "hasPart",
+        list(metadata.ms_contents.items.iter().map(|item| {
+            let mut part = Map::new();
+            part.insert("@type".into(), json!("CreativeWork"));
+            put(&mut part, "name", text(&item.title));
+            put(&mut part, "author", text(&item.author).map(person));
+            put(&mut part, "position", text(&item.n));
+            Value::Object(part)
+        })),
+    );
+    if manuscript.len() > 1 {
+        edition.insert("isBasedOn".into(), Value::Object(manuscript));
+    }
+
+    Value::Object(edition)
+}
+
+/// The descriptive properties of a IIIF Presentation 3 manifest.
+///
+/// Titles and values are in the language of the manuscript and so go under
+/// `"none"`; field labels are English.
+pub fn iiif(metadata: &Metadata) -> Value {
+    let title = &metadata.title_stmt;
+    let ms = &metadata.ms_identifier;
+    let mut entries: Vec<(&str, Vec<String>)> = Vec::new();
+    let mut push = |label, values: Vec<String>| {
+        let values: Vec<String> = values
+            .into_iter()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+        if !values.is_empty() {
+            entries.push((label, values));
+        }
+    };
+
+    push("Title", title.title.iter().cloned().collect());
+    push("Author", title.author.iter().cloned().collect());
+    push("Shelfmark", ms.shelfmark().into_iter().collect());
+    push("Name", ms.ms_name.iter().cloned().collect());
+    if let Some(ref date) = metadata.history.orig_date {
+        push("Date", date_label(date).into_iter().collect());
+    }
+    push(
+        "Place of origin",
+        metadata.history.orig_place.iter().cloned().collect(),
+    );
+    push(
+        "Material",
+        metadata.phys_desc.material.iter().cloned().collect(),
+    );
+    push(
+        "Extent",
+        metadata.phys_desc.extent.iter().cloned().collect(),
+    );
+    push(
+        "Contents",
+        metadata
+            .ms_contents
+            .items
+            .iter()
+            .filter_map(|item| item.title.clone())
+            .collect(),
+    );
+    push("Language", languages(metadata));
+    push(
+        "Editor",
+        title.editors.iter().map(|e| e.name.clone()).collect(),
+    );
+    push(
+        "Publisher",
+        metadata
+            .publication_stmt
+            .publisher
+            .iter()
+            .cloned()
+            .collect(),
+    );
+    if let Some(ref availability) = metadata.publication_stmt.availability {
+        push("License", availability.license.iter().cloned().collect());
+    }
+
+    let label = text(&title.title)
+        .or_else(|| text(&ms.ms_name))
+        .or_else(|| ms.shelfmark().map(Value::from))
+        .unwrap_or_else(|| json!("Untitled"));
+    let mut manifest = Map::new();
+    manifest.insert("label".into(), json!({ "none": [label] }));
+    put(
+        &mut manifest,
+        "summary",
+        text(&metadata.ms_contents.summary).map(|s| json!({ "none": [s] })),
+    );
+    manifest.insert(
+        "metadata".into(),
+        Value::Array(
+            entries
+                .into_iter()
+                .map(|(label, values)| {
+                    json!({ "label": { "en": [label] }, "value": { "none": values } })
+                })
+                .collect(),
+        ),
+    );
+    // `rights` must be a Creative Commons or RightsStatements.org URI
+    let rights = metadata
+        .publication_stmt
+        .availability
+        .as_ref()
+        .and_then(|a| a.license_url.as_deref())
+        .filter(|url| {
+            url.starts_with("http://creativecommons.org/")
+                || url.starts_with("https://creativecommons.org/")
+                || url.starts_with("http://rightsstatements.org/")
+                || url.starts_with("https://rightsstatements.org/")
+        });
+    put(&mut manifest, "rights", rights.map(Value::from));
+
+    Value::Object(manifest)
+}
+
+fn put(map: &mut Map<String, Value>, key: &str, value: Option<Value>) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), value);
+    }
+}
+
+fn text(value: &Option<String>) -> Option<Value> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(Value::from)
+}
+
+fn list(values: impl Iterator<Item = Value>) -> Option<Value> {
+    let values: Vec<Value> = values.collect();
+    (!values.is_empty()).then_some(Value::Array(values))
+}
+
+fn person(name: Value) -> Value {
+    json!({ "@type": "Person", "name": name })
+}
+
+fn orcid_url(editor: &Person) -> Option<Value> {
+    let id = editor.identifier.as_deref()?.trim();
+    let is_orcid = editor
+        .identifier_type
+        .as_deref()
+        .is_some_and(|t| t.eq_ignore_ascii_case("orcid"))
+        || id.contains("orcid.org");
+    if !is_orcid || id.is_empty() {
+        return None;
+    }
+    Some(Value::from(if id.starts_with("http") {
+        id.to_string()
+    } else {
+        format!("https://orcid.org/{}", id)
+    }))
+}
+
+/// Language codes of the text, from `langUsage` or else `msContents/textLang`
+fn languages(metadata: &Metadata) -> Vec<String> {
+    let idents: Vec<String> = metadata
+        .languages
+        .iter()
+        .map(|l| l.ident.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+    if !idents.is_empty() {
+        return idents;
+    }
+    metadata.ms_contents.text_lang.iter().cloned().collect()
+}
+
+/// An ISO 8601 date or interval, e.g. "1300/1399"
+fn date_span(date: &DateRange) -> Option<String> {
+    let nonempty = |v: &Option<String>| v.clone().filter(|v| !v.trim().is_empty());
+    if let Some(when) = nonempty(&date.when) {
+        return Some(when);
+    }
+    match (nonempty(&date.not_before), nonempty(&date.not_after)) {
+        (Some(from), Some(to)) => Some(format!("{}/{}", from, to)),
+        (Some(from), None) => Some(format!("{}/..", from)),
+        (None, Some(to)) => Some(format!("../{}", to)),
+        (None, None) => nonempty(&date.display),
+    }
+}
+
+/// The date as written, with the normalised span if it says something more
+fn date_label(date: &DateRange) -> Option<String> {
+    let span = date_span(date);
+    match date
+        .display
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        Some(display) => match span {
+            Some(span) if span != display => Some(format!("{} ({})", display, span)),
+            _ => Some(display.to_string()),
+        },
+        None => span,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{Availability, Language, MsItem, RespStmt};
+
+    fn sample() -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.title_stmt.title = Some("Egils saga".to_string());
+        metadata.title_stmt.author = Some("Snorri Sturluson".to_string());
+        metadata.title_stmt.editors = vec![Person {
+            name: "Josiah Carberry".to_string(),
+            identifier: Some("0000-0002-1825-0097".to_string()),
+            identifier_type: Some("orcid".to_string()),
+        }];
+        metadata.title_stmt.resp_stmts = vec![RespStmt {
+            resp: "Transcription".to_string(),
+            name: "Anna".to_string(),
+            id: None,
+        }];
+        metadata.publication_stmt.publisher = Some("Saga Press".to_string());
+        metadata.publication_stmt.date = Some("2024".to_string());
+        metadata.publication_stmt.idno = Some("10.1234/egils".to_string());
+        metadata.publication_stmt.availability = Some(Availability {
+            license: Some("CC BY 4.0".to_string()),
+            license_url: Some("https://creativecommons.org/licenses/by/4.0/".to_string()),
+            ..Default::default()
+        });
+        metadata.ms_identifier.settlement = Some("Reykjavík".to_string());
+        metadata.ms_identifier.repository = Some("Stofnun Árna Magnússonar".to_string());
+        metadata.ms_identifier.idno = Some("AM 132 fol.".to_string());
+        metadata.ms_identifier.ms_name = Some("Möðruvallabók".to_string());
+        metadata.ms_contents.summary = Some("Sagas of Icelanders & others".to_string());
+        metadata.ms_contents.items = vec![MsItem {
+            n: Some("1".to_string()),
+            title: Some("Njáls saga".to_string()),
+            ..Default::default()
+        }];
+        metadata.phys_desc.material = Some("Parchment".to_string());
+        metadata.history.orig_date = Some(DateRange {
+            display: Some("s. xiv med.".to_string()),
+            not_before: Some("1325".to_string()),
+            not_after: Some("1374".to_string()),
+            when: None,
+        });
+        metadata.history.orig_place = Some("Iceland".to_string());
+        metadata.languages = vec![Language {
+            ident: "non".to_string(),
+            usage: None,
+            name: Some("Old Norse".to_string()),
+        }];
+        metadata
+    }
+
+    #[test]
+    fn test_dublin_core_mapping() {
+        let dc = dublin_core(&sample());
+        for element in [
+            "<dc:title>Egils saga</dc:title>",
+            "<dc:creator>Snorri Sturluson</dc:creator>",
+            "<dc:description>Sagas of Icelanders &amp; others</dc:description>",
+            "<dc:description>Njáls saga</dc:description>",
+            "<dc:publisher>Saga Press</dc:publisher>",
+            "<dc:contributor>Josiah Carberry</dc:contributor>",
+            "<dc:contributor>Anna (Transcription)</dc:contributor>",
+            "<dc:date>2024</dc:date>",
+            "<dc:type>Text</dc:type>",
+            "<dc:identifier>10.1234/egils</dc:identifier>",
+            "<dc:source>Reykjavík, Stofnun Árna Magnússonar, AM 132 fol.</dc:source>",
+            "<dc:language>non</dc:language>",
+            "<dc:coverage>1325/1374</dc:coverage>",
+            "<dc:coverage>Iceland</dc:coverage>",
+            "<dc:rights>https://creativecommons.org/licenses/by/4.0/</dc:rights>",
+            "<dc:rights>CC BY 4.0</dc:rights>",
+        ] {
+            assert!(dc.contains(element), "missing {} in\n{}", element, dc);
+        }
+        assert!(dc.starts_with("<?xml"));
+
+        // Only the fixed type and format for empty metadata
+        let empty = dublin_core(&Metadata::new());
+        assert_eq!(empty.matches("<dc:").count(), 2);
+    }
+
+    #[test]
+    fn test_json_ld_mapping() {
+        let ld = json_ld(&sample());
+        assert_eq!(ld["@type"], "CreativeWork");
+        assert_eq!(ld["name"], "Egils saga");
+        assert_eq!(ld["author"]["name"], "Snorri Sturluson");
+        assert_eq!(
+            ld["editor"][0]["sameAs"],
+            "https://orcid.org/0000-0002-1825-0097"
+        );
+        assert_eq!(ld["contributor"][0]["roleName"], "Transcription");
+        assert_eq!(ld["publisher"]["name"], "Saga Press");
+        assert_eq!(
+            ld["license"],
+            "https://creativecommons.org/licenses/by/4.0/"
+        );
+        assert_eq!(ld["inLanguage"], json!(["non"]));
+
+        let ms = &ld["isBasedOn"];
+        assert_eq!(ms["name"], "Möðruvallabók");
+        assert_eq!(ms["identifier"], "AM 132 fol.");
+        assert_eq!(ms["holdingArchive"]["name"], "Stofnun Árna Magnússonar");
+        assert_eq!(
+            ms["holdingArchive"]["address"]["addressLocality"],
+            "Reykjavík"
+        );
+        assert_eq!(ms["temporalCoverage"], "1325/1374");
+        assert_eq!(ms["locationCreated"]["name"], "Iceland");
+        assert_eq!(ms["hasPart"][0]["name"], "Njáls saga");
+
+        let empty = json_ld(&Metadata::new());
+        assert!(empty.get("isBasedOn").is_none());
+        assert!(empty.get("name").is_none());
+    }
+
+    #[test]
+    fn test_iiif_mapping() {
+        let manifest = iiif(&sample());
+        assert_eq!(manifest["label"], json!({ "none": ["Egils saga"] }));
+        assert_eq!(
+            manifest["summary"],
+            json!({ "none": ["Sagas of Icelanders & others"] })
+        );
+        assert_eq!(
+            manifest["rights"],
+            "https://creativecommons.org/licenses/by/4.0/"
+        );
+
+        let entry = |label: &str| {
+            manifest["metadata"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|e| e["label"]["en"][0] == label)
+                .map(|e| e["value"]["none"].clone())
+        };
+        assert_eq!(
+            entry("Shelfmark"),
+            Some(json!(["Reykjavík, Stofnun Árna Magnússonar, AM 132 fol."]))
+        );
+        assert_eq!(entry("Date"), Some(json!(["s. xiv med. (1325/1374)"])));
+        assert_eq!(entry("Contents"), Some(json!(["Njáls saga"])));
+        assert_eq!(entry("Language"), Some(json!(["non"])));
+        assert_eq!(entry("Extent"), None);
+
+        // Labels fall back to the shelfmark; non-CC licence URLs are not rights
+        let mut metadata = sample();
+        metadata.title_stmt.title = None;
+        metadata.ms_identifier.ms_name = None;
+        metadata
+            .publication_stmt
+            .availability
+            .as_mut()
+            .unwrap()
+            .license_url = Some("https://example.org/terms".to_string());
+        let manifest = iiif(&metadata);
+        assert_eq!(
+            manifest["label"]["none"][0],
+            "Reykjavík, Stofnun Árna Magnússonar, AM 132 fol."
+        );
+        assert!(manifest.get("rights").is_none());
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let format: MetadataFormat = serde_json::from_str(r#""dublinCore""#).unwrap();
+        assert!(export(&sample(), format).contains("<dc:title>Egils saga</dc:title>"));
+
+        let format: MetadataFormat = serde_json::from_str(r#""jsonLd""#).unwrap();
+        let ld: Value = serde_json::from_str(&export(&sample(), format)).unwrap();
+        assert_eq!(ld, json_ld(&sample()));
+    }
+}
@@ -0,0 +1,479 @@
+//! Parsing of free-form manuscript dates.
+//!
+//! Catalogues date manuscripts with conventional expressions rather than ISO
+//! dates: "c. 1300", "s. xiv med.", "1250–1275", "early 14th century",
+//! "s. xiii²". This module turns the Latin and English forms into the
+//! `notBefore`/`notAfter` (or `when`) bounds TEI uses for `<origDate>`.
+//!
+//! Centuries run from the 00 year, so the 14th century is 1300–1399, as in
+//! most Nordic manuscript catalogues. The parts of a century are:
+//!
+//! | Expression                            | Years     |
+//! |---------------------------------------|-----------|
+//! | `in.`, `ineunte`, early, beginning of | 00–24     |
+//! | `med.`, `medio`, mid, middle of       | 25–74     |
+//! | `ex.`, `exeunte`, late, end of        | 75–99     |
+//! | `1/2`, `¹`, first half                | 00–49     |
+//! | `2/2`, `²`, second half               | 50–99     |
+//! | `1/4` … `4/4`, first … last quarter   | 00–24 …   |
+//! | `1/3` … `3/3`, first … last third     | 00–32 …   |
+//!
+//! "c. 1300" widens a single year by ten years either way, and "xiv/xv"
+//! (the turn of the century) is 1390–1410.
+
+use super::validate::parse_iso_date;
+use super::DateRange;
+
+/// Years either side of a date given as "circa"
+const CIRCA_YEARS: i32 = 10;
+
+/// Inclusive bounds of a parsed date; either side may be open
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    from: Option<i32>,
+    to: Option<i32>,
+}
+
+impl Bounds {
+    fn years(from: i32, to: i32) -> Self {
+        Self {
+            from: Some(from),
+            to: Some(to),
+        }
+    }
+}
+
+impl DateRange {
+    /// Fill `when`, `notBefore` and `notAfter` by parsing `display`, unless
+    /// any of them is already set. Returns whether anything was filled.
+    pub fn fill_bounds(&mut self) -> bool {
+        if self.when.is_some() || self.not_before.is_some() || self.not_after.is_some() {
+            return false;
+        }
+        let Some(parsed) = self.display.as_deref().and_then(parse_date) else {
+            return false;
+        };
+        self.when = parsed.when;
+        self.not_before = parsed.not_before;
+        self.not_after = parsed.not_after;
+        true
+    }
+}
+
+/// Parse a free-form date into a `DateRange`, keeping the text as `display`.
+///
+/// Returns `None` if the expression is not understood.
+pub fn parse_date(text: &str) -> Option<DateRange> {
+    let trimmed = text
+        .trim()
+        .trim_matches(|c| matches!(c, '[' | ']' | '(' | ')' | '?'))
+        .trim();
+
+    let mut range = DateRange {
+        display: Some(text.to_string()),
+        ..Default::default()
+    };
+
+    // Exact ISO dates are passed through as they are
+    if trimmed.contains('-') && parse_iso_date(trimmed).is_some() {
+        range.when = Some(trimmed.to_string());
+        return Some(range);
+    }
+
+    let bounds = parse_tokens(&tokenize(trimmed))?;
+    match (bounds.from, bounds.to) {
+        (Some(from), Some(to)) if from == to => range.when = Some(format_year(from)),
+        (Some(from), Some(to)) if from > to => return None,
+        (None, None) => return None,
+        (from, to) => {
+            range.not_before = from.map(format_year);
+            range.not_after = to.map(format_year);
+        }
+    }
+    Some(range)
+}
+
+fn format_year(year: i32) -> String {
+    format!("{:04}", year)
+}
+
+/// Words that carry no information about the date
+const NOISE: &[&str] = &[
+    "the",
+    "of",
+    "century",
+    "centuries",
+    "cent",
+    "saec",
+    "sæc",
+    "saeculo",
+    "saeculi",
+    "s",
+    "ad",
+];
+
+/// Split into lowercase words, with dashes, slashes and fractions as tokens
+/// of their own and superscript halves spelled out as fractions.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut spaced = String::with_capacity(text.len());
+    for c in text.to_lowercase().chars() {
+        match c {
+            '-' | '–' | '—' | '‐' | '‑' | '/' => {
+                spaced.push(' ');
+                spaced.push(if c == '/' { '/' } else { '-' });
+                spaced.push(' ');
+            }
+            '¹' => spaced.push_str(" 1/2 "),
+            '²' => spaced.push_str(" 2/2 "),
+            ',' | ';' => spaced.push(' '),
+            _ => spaced.push(c),
+        }
+    }
+
+    let words: Vec<&str> = spaced
+        .split_whitespace()
+        .map(|w| w.trim_end_matches('.'))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // Rejoin single-digit fractions such as "1 / 4"
+    let mut tokens: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let is_digit = |w: &str| w.len() == 1 && w.as_bytes()[0].is_ascii_digit();
+        if i + 2 < words.len()
+            && is_digit(words[i])
+            && words[i + 1] == "/"
+            && is_digit(words[i + 2])
+        {
+            tokens.push(format!("{}/{}", words[i], words[i + 2]));
+            i += 3;
+        } else {
+            tokens.push(words[i].to_string());
+            i += 1;
+        }
+    }
+
+    // "c" after a century is "century"; anywhere else it is "circa". The
+    // hyphen in "mid-14th" joins a qualifier to its century.
+    let mut result: Vec<String> = Vec::new();
+    for token in tokens {
+        let previous = result.last().map(String::as_str);
+        let is_century_abbreviation =
+            token == "c" && previous.is_some_and(|t| century_number(t).is_some());
+        let is_qualifier_hyphen =
+            token == "-" && matches!(previous, Some("early" | "mid" | "middle" | "late"));
+        if !is_century_abbreviation && !is_qualifier_hyphen && !NOISE.contains(&token.as_str()) {
+            result.push(token);
+        }
+    }
+    result
+}
+
+fn parse_tokens(tokens: &[String]) -> Option<Bounds> {
+    let first = tokens.first()?.as_str();
+    let rest = &tokens[1..];
+
+    match first {
+        "c" | "ca" | "circa" | "about" | "around" | "approximately" | "approx" => {
+            let bounds = parse_tokens(rest)?;
+            return Some(match bounds {
+                Bounds {
+                    from: Some(from),
+                    to: Some(to),
+                } if from == to => Bounds::years(from - CIRCA_YEARS, to + CIRCA_YEARS),
+                _ => bounds,
+            });
+        }
+        "before" | "ante" | "a" => {
+            return Some(Bounds {
+                from: None,
+                to: parse_tokens(rest)?.to,
+            })
+        }
+        "after" | "post" | "p" => {
+            return Some(Bounds {
+                from: parse_tokens(rest)?.from,
+                to: None,
+            })
+        }
+        "not" => {
+            return match rest.first().map(String::as_str) {
+                Some("before") => parse_tokens(&rest[1..]).map(|b| Bounds {
+                    from: b.from,
+                    to: None,
+                }),
+                Some("after") => parse_tokens(&rest[1..]).map(|b| Bounds {
+                    from: None,
+                    to: b.to,
+                }),
+                _ => None,
+            }
+        }
+        "between" => return parse_range(rest, &["and"]),
+        _ => {}
+    }
+
+    parse_range(tokens, &["-", "/", "to"])
+}
+
+/// Parse `term (separator term)?`
+///
+/// "x" ("1250 x 1275") also separates two years, but elsewhere it is the
+/// tenth century.
+fn parse_range(tokens: &[String], separators: &[&str]) -> Option<Bounds> {
+    let is_separator = |i: usize| {
+        separators.contains(&tokens[i].as_str())
+            || (tokens[i] == "x"
+                && i > 0
+                && is_number(&tokens[i - 1])
+                && tokens.get(i + 1).is_some_and(|t| is_number(t)))
+    };
+    let Some(split) = (0..tokens.len()).find(|&i| is_separator(i)) else {
+        return parse_term(tokens);
+    };
+    let (left, right) = (&tokens[..split], &tokens[split + 1..]);
+    let start = parse_term(left)?;
+
+    // "1250-75" abbreviates the second year
+    let right_bounds = match right {
+        [year] if year.len() < 4 && is_number(year) && left.len() == 1 && is_number(&left[0]) => {
+            let from = start.from?;
+            let modulus = 10_i32.pow(year.len() as u32);
+            let year = from - from % modulus + year.parse::<i32>().ok()?;
+            Bounds::years(year, year)
+        }
+        _ => parse_term(right)?,
+    };
+
+    // "xiv/xv" is the turn of the century rather than both centuries
+    if tokens[split] == "/" && left.len() == 1 && right.len() == 1 {
+        if let (Some(a), Some(b)) = (century_number(&left[0]), century_number(&right[0])) {
+            if b == a + 1 {
+                let turn = a * 100;
+                return Some(Bounds::years(turn - 10, turn + 10));
+            }
+        }
+    }
+
+    Some(Bounds {
+        from: start.from,
+        to: right_bounds.to,
+    })
+}
+
+/// A year, a decade ("1320s") or a century with an optional qualifier
+fn parse_term(tokens: &[String]) -> Option<Bounds> {
+    if let [token] = tokens {
+        if is_number(token) && (3..=4).contains(&token.len()) {
+            let year = token.parse().ok()?;
+            return Some(Bounds::years(year, year));
+        }
+        if let Some(decade) = token.strip_suffix('s') {
+            if is_number(decade) && decade.len() == 4 && decade.ends_with('0') {
+                let year: i32 = decade.parse().ok()?;
+                return Some(Bounds::years(year, year + 9));
+            }
+        }
+    }
+
+    let position = tokens.iter().position(|t| century_number(t).is_some())?;
+    let century = century_number(&tokens[position])?;
+    let qualifier: Vec<&str> = tokens
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != position)
+        .map(|(_, t)| t.as_str())
+        .collect();
+    let (from, to) = century_part(&qualifier.join(" "))?;
+
+    let start = (century - 1) * 100;
+    Some(Bounds::years(start + from, start + to))
+}
+
+/// Years within a century covered by a qualifier such as "med" or "first half"
+fn century_part(qualifier: &str) -> Option<(i32, i32)> {
+    let quarter = |n: i32| Some((25 * (n - 1), 25 * n - 1));
+    let third = |n: i32| Some(((n - 1) * 33, if n == 3 { 99 } else { n * 33 - 1 }));
+
+    match qualifier {
+        "" => Some((0, 99)),
+        "in" | "ineunte" | "early" | "beginning" | "start" => Some((0, 24)),
+        "med" | "medio" | "mid" | "middle" => Some((25, 74)),
+        "ex" | "exeunte" | "late" | "end" => Some((75, 99)),
+        "1/2" | "first half" => Some((0, 49)),
+        "2/2" | "second half" | "latter half" | "last half" => Some((50, 99)),
+        "1/4" | "first quarter" => quarter(1),
+        "2/4" | "second quarter" => quarter(2),
+        "3/4" | "third quarter" => quarter(3),
+        "4/4" | "fourth quarter" | "last quarter" => quarter(4),
+        "1/3" | "first third" => third(1),
+        "2/3" | "second third" | "middle third" => third(2),
+        "3/3" | "last third" | "third third" => third(3),
+        _ => None,
+    }
+}
+
+/// Number of the century in "xiv", "14th" or "fourteenth"
+fn century_number(token: &str) -> Option<i32> {
+    if !token.is_empty() && token.chars().all(|c| matches!(c, 'i' | 'v' | 'x')) {
+        return roman(token);
+    }
+    for suffix in ["st", "nd", "rd", "th"] {
+        if let Some(n) = token.strip_suffix(suffix) {
+            if is_number(n) {
+                return n.parse().ok().filter(|n| (1..=21).contains(n));
+            }
+        }
+    }
+    const ORDINALS: &[&str] = &[
+        "ninth",
+        "tenth",
+        "eleventh",
+        "twelfth",
+        "thirteenth",
+        "fourteenth",
+        "fifteenth",
+        "sixteenth",
+        "seventeenth",
+        "eighteenth",
+        "nineteenth",
+        "twentieth",
+    ];
+    ORDINALS
+        .iter()
+        .position(|&o| o == token)
+        .map(|i| i as i32 + 9)
+}
+
+fn roman(numeral: &str) -> Option<i32> {
+    let value = |c: char| match c {
+        'i' => 1,
+        'v' => 5,
+        _ => 10,
+    };
+    let digits: Vec<i32> = numeral.chars().map(value).collect();
+    let total = digits.iter().enumerate().fold(0, |total, (i, &d)| {
+        if digits.get(i + 1).is_some_and(|&next| next > d) {
+            total - d
+        } else {
+            total + d
+        }
+    });
+    (1..=21).contains(&total).then_some(total)
+}
+
+fn is_number(token: &str) -> bool {
+    !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(text: &str) -> (Option<String>, Option<String>, Option<String>) {
+        let range = parse_date(text).unwrap_or_else(|| panic!("could not parse {:?}", text));
+        (range.when, range.not_before, range.not_after)
+    }
+
+    fn range(from: &str, to: &str) -> (Option<String>, Option<String>, Option<String>) {
+        (None, Some(from.to_string()), Some(to.to_string()))
+    }
+
+    fn when(year: &str) -> (Option<String>, Option<String>, Option<String>) {
+        (Some(year.to_string()), None, None)
+    }
+
+    #[test]
+    fn test_years_and_ranges() {
+        assert_eq!(bounds("1250"), when("1250"));
+        assert_eq!(bounds("1250-05-03"), when("1250-05-03"));
+        assert_eq!(bounds("1250–1275"), range("1250", "1275"));
+        assert_eq!(bounds("1250-75"), range("1250", "1275"));
+        assert_eq!(bounds("1250 x 1275"), range("1250", "1275"));
+        assert_eq!(bounds("between 1250 and 1275"), range("1250", "1275"));
+        assert_eq!(bounds("1320s"), range("1320", "1329"));
+        assert_eq!(bounds("[1300?]"), when("1300"));
+    }
+
+    #[test]
+    fn test_approximate_and_open_dates() {
+        assert_eq!(bounds("c. 1300"), range("1290", "1310"));
+        assert_eq!(bounds("ca 1300"), range("1290", "1310"));
+        assert_eq!(bounds("circa 1300"), range("1290", "1310"));
+        assert_eq!(
+            bounds("before 1300"),
+            (None, None, Some("1300".to_string()))
+        );
+        assert_eq!(bounds("post 1300"), (None, Some("1300".to_string()), None));
+        assert_eq!(
+            bounds("not after 1350"),
+            (None, None, Some("1350".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_latin_centuries() {
+        assert_eq!(bounds("s. xiv"), range("1300", "1399"));
+        assert_eq!(bounds("saec. XIV"), range("1300", "1399"));
+        assert_eq!(bounds("s. xiv in."), range("1300", "1324"));
+        assert_eq!(bounds("s. xiv med."), range("1325", "1374"));
+        assert_eq!(bounds("s. xiv ex."), range("1375", "1399"));
+        assert_eq!(bounds("s. xiii²"), range("1250", "1299"));
+        assert_eq!(bounds("s. xiv 1/2"), range("1300", "1349"));
+        assert_eq!(bounds("s. xiv 3/4"), range("1350", "1374"));
+        assert_eq!(bounds("s. xiv-xv"), range("1300", "1499"));
+        assert_eq!(bounds("s. xiv/xv"), range("1390", "1410"));
+        assert_eq!(bounds("s. xiv ex.–xv in."), range("1375", "1424"));
+        assert_eq!(bounds("s. x"), range("0900", "0999"));
+        assert_eq!(bounds("s. x ex."), range("0975", "0999"));
+        assert_eq!(bounds("s. x-xi"), range("0900", "1099"));
+    }
+
+    #[test]
+    fn test_english_centuries() {
+        assert_eq!(bounds("14th century"), range("1300", "1399"));
+        assert_eq!(bounds("14th c."), range("1300", "1399"));
+        assert_eq!(bounds("early 14th century"), range("1300", "1324"));
+        assert_eq!(bounds("mid-14th century"), range("1325", "1374"));
+        assert_eq!(bounds("late fourteenth century"), range("1375", "1399"));
+        assert_eq!(
+            bounds("second half of the 13th century"),
+            range("1250", "1299")
+        );
+        assert_eq!(
+            bounds("third quarter of the 14th century"),
+            range("1350", "1374")
+        );
+        assert_eq!(bounds("end of the 14th century"), range("1375", "1399"));
+        assert_eq!(bounds("14th–15th century"), range("1300", "1499"));
+        assert_eq!(bounds("c. 14th century"), range("1300", "1399"));
+    }
+
+    #[test]
+    fn test_unrecognised_dates() {
+        assert!(parse_date("").is_none());
+        assert!(parse_date("undated").is_none());
+        assert!(parse_date("s. xiv sometime").is_none());
+        assert!(parse_date("1300-1250").is_none());
+    }
+
+    #[test]
+    fn test_fill_bounds_keeps_existing_attributes() {
+        let mut date = DateRange {
+            display: Some("s. xiv".to_string()),
+            ..Default::default()
+        };
+        assert!(date.fill_bounds());
+        assert_eq!(date.not_before.as_deref(), Some("1300"));
+        assert_eq!(date.display.as_deref(), Some("s. xiv"));
+
+        let mut date = DateRange {
+            display: Some("s. xiv".to_string()),
+            when: Some("1320".to_string()),
+            ..Default::default()
+        };
+        assert!(!date.fill_bounds());
+        assert_eq!(date.not_before, None);
+    }
+}
@@ -0,0 +1,1808 @@
+//! Reading a `<teiHeader>` into [`Metadata`] and writing edits back into it.
+//!
+//! [`read_header`] pulls the modelled fields out of an imported header, whose
+//! source text is kept in [`Metadata::original_header`]. [`patch_header`]
+//! re-reads that source, compares it with the edited metadata and rewrites only
+//! the elements whose fields changed, so `msItem`s, `decoDesc`, `bindingDesc`,
+//! `revisionDesc` and anything else the model does not cover survive an edit.
+
+use super::{
+    AltIdentifier, Availability, Binding, Change, DateRange, DecoNote, HandNote, History, Language,
+    Locus, Metadata, MsContents, MsIdentifier, MsItem, MsPart, Person, PhysDesc, PublicationStmt,
+    RespStmt, TitleStmt,
+};
+use libxml::parser::Parser;
+use libxml::tree::{Document, Node, NodeType};
+use std::ops::Range;
+
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+// ============================================================================
+// Reading
+// ============================================================================
+
+/// Extract the modelled fields of a `<teiHeader>` element.
+pub fn read_header(header: &Node) -> Metadata {
+    let mut metadata = Metadata::default();
+
+    // Extract fileDesc
+    if let Some(file_desc) = find_element(header, "fileDesc") {
+        // titleStmt
+        if let Some(title_stmt) = find_element(&file_desc, "titleStmt") {
+            metadata.title_stmt = extract_title_stmt(&title_stmt);
+        }
+
+        // publicationStmt
+        if let Some(pub_stmt) = find_element(&file_desc, "publicationStmt") {
+            metadata.publication_stmt = extract_publication_stmt(&pub_stmt);
+        }
+
+        // sourceDesc -> msDesc
+        if let Some(source_desc) = find_element(&file_desc, "sourceDesc") {
+            if let Some(ms_desc) = find_element(&source_desc, "msDesc") {
+                read_ms_desc(&ms_desc, &mut metadata);
+            }
+        }
+    }
+
+    // Extract profileDesc -> langUsage
+    if let Some(profile_desc) = find_element(header, "profileDesc") {
+        if let Some(lang_usage) = find_element(&profile_desc, "langUsage") {
+            metadata.languages = items(&lang_usage, "language", read_language)
+                .into_iter()
+                .map(|(_, language)| language)
+                .collect();
+        }
+    }
+
+    // Extract encodingDesc -> editorialDecl -> normalization
+    if let Some(encoding_desc) = find_element(header, "encodingDesc") {
+        if let Some(editorial_decl) = find_element(&encoding_desc, "editorialDecl") {
+            if let Some(normalization) = find_element(&editorial_decl, "normalization") {
+                if let Some(p) = find_element(&normalization, "p") {
+                    metadata.encoding_notes = Some(get_text_content(&p));
+                }
+            }
+        }
+    }
+
+    // Extract revisionDesc
+    if let Some(revision_desc) = find_element(header, "revisionDesc") {
+        metadata.revisions = items(&revision_desc, "change", read_change)
+            .into_iter()
+            .map(|(_, change)| change)
+            .collect();
+    }
+
+    metadata
+}
+
+/// Read an `<msDesc>` into the manuscript fields of `metadata`
+pub fn read_ms_desc(ms_desc: &Node, metadata: &mut Metadata) {
+    // msIdentifier
+    if let Some(ms_id) = find_element(ms_desc, "msIdentifier") {
+        metadata.ms_identifier = extract_ms_identifier(&ms_id);
+    }
+
+    // msContents
+    if let Some(ms_contents) = find_element(ms_desc, "msContents") {
+        metadata.ms_contents = extract_ms_contents(&ms_contents);
+    }
+
+    // physDesc
+    if let Some(phys_desc) = find_element(ms_desc, "physDesc") {
+        metadata.phys_desc = extract_phys_desc(&phys_desc);
+    }
+
+    // history
+    if let Some(history) = find_element(ms_desc, "history") {
+        metadata.history = extract_history(&history);
+    }
+
+    // msPart
+    metadata.ms_parts = items(ms_desc, "msPart", read_ms_part)
+        .into_iter()
+        .map(|(_, part)| part)
+        .collect();
+}
+
+/// Byte range of the first `<teiHeader>…</teiHeader>` in `xml`.
+pub fn header_span(xml: &str) -> Option<Range<usize>> {
+    let mut from = 0;
+    let start = loop {
+        let at = from + xml[from..].find("<teiHeader")?;
+        let next = xml[at + "<teiHeader".len()..].chars().next()?;
+        if next == '>' || next == '/' || next.is_whitespace() {
+            break at;
+        }
+        from = at + 1;
+    };
+    let open_end = start + xml[start..].find('>')? + 1;
+    if xml[..open_end].ends_with("/>") {
+        return Some(start..open_end);
+    }
+    let close = open_end + xml[open_end..].find("</teiHeader>")?;
+    Some(start..close + "</teiHeader>".len())
+}
+
+fn extract_title_stmt(node: &Node) -> TitleStmt {
+    let mut stmt = TitleStmt::default();
+
+    // Title (first one without type attribute, or just first)
+    if let Some(title) = main_title(node) {
+        stmt.title = Some(get_text_content(&title));
+    }
+    if let Some(subtitle) = subtitle(node) {
+        stmt.subtitle = Some(get_text_content(&subtitle));
+    }
+
+    // Author
+    if let Some(author) = find_element(node, "author") {
+        stmt.author = Some(get_text_content(&author));
+    }
+
+    // Editors and responsibility statements
+    stmt.editors = items(node, "editor", read_editor)
+        .into_iter()
+        .map(|(_, editor)| editor)
+        .collect();
+    stmt.resp_stmts = items(node, "respStmt", read_resp_stmt)
+        .into_iter()
+        .map(|(_, resp)| resp)
+        .collect();
+
+    stmt
+}
+
+fn main_title(node: &Node) -> Option<Node> {
+    element_children(node).into_iter().find(|child| {
+        child.get_name() == "title"
+            && matches!(child.get_property("type").as_deref(), None | Some("main"))
+    })
+}
+
+fn subtitle(node: &Node) -> Option<Node> {
+    element_children(node).into_iter().rev().find(|child| {
+        child.get_name() == "title" && child.get_property("type").as_deref() == Some("sub")
+    })
+}
+
+fn read_editor(node: &Node) -> Option<Person> {
+    let name = get_text_content(node);
+    if name.is_empty() {
+        return None;
+    }
+    let mut person = Person {
+        name,
+        identifier: None,
+        identifier_type: None,
+    };
+    // Check for ref attribute (e.g., "orcid:0000-0000-0000-0000")
+    if let Some(ref_attr) = node.get_property("ref") {
+        if let Some((id_type, id)) = ref_attr.split_once(':') {
+            person.identifier_type = Some(id_type.to_string());
+            person.identifier = Some(id.to_string());
+        }
+    }
+    Some(person)
+}
+
+fn read_resp_stmt(node: &Node) -> Option<RespStmt> {
+    let resp = find_element(node, "resp").map(|n| get_text_content(&n))?;
+    let name = find_element(node, "name").map(|n| get_text_content(&n))?;
+    if resp.is_empty() || name.is_empty() {
+        return None;
+    }
+    let id = node.get_property_ns("id", XML_NS);
+    Some(RespStmt { resp, name, id })
+}
+
+fn extract_publication_stmt(node: &Node) -> PublicationStmt {
+    let mut stmt = PublicationStmt::default();
+
+    if let Some(publisher) = find_element(node, "publisher") {
+        stmt.publisher = Some(get_text_content(&publisher));
+    }
+
+    if let Some(pub_place) = find_element(node, "pubPlace") {
+        stmt.pub_place = Some(get_text_content(&pub_place));
+    }
+
+    if let Some(date) = find_element(node, "date") {
+        // Prefer @when attribute, fall back to text content
+        stmt.date = date
+            .get_property("when")
+            .or_else(|| Some(get_text_content(&date)));
+    }
+
+    if let Some(distributor) = find_element(node, "distributor") {
+        stmt.distributor = Some(get_text_content(&distributor));
+    }
+
+    // idno
+    if let Some(idno) = find_element(node, "idno") {
+        stmt.idno = Some(get_text_content(&idno));
+        stmt.idno_type = idno.get_property("type");
+    }
+
+    // availability
+    if let Some(avail) = find_element(node, "availability") {
+        let availability = Availability {
+            status: avail.get_property("status"),
+            license: find_element(&avail, "licence").map(|l| get_text_content(&l)),
+            license_url: find_element(&avail, "licence").and_then(|l| l.get_property("target")),
+            note: find_element(&avail, "p").map(|p| get_text_content(&p)),
+        };
+
+        stmt.availability = Some(availability);
+    }
+
+    stmt
+}
+
+fn extract_ms_identifier(node: &Node) -> MsIdentifier {
+    let mut id = MsIdentifier::default();
+
+    if let Some(country) = find_element(node, "country") {
+        id.country = Some(get_text_content(&country));
+    }
+
+    if let Some(settlement) = find_element(node, "settlement") {
+        id.settlement = Some(get_text_content(&settlement));
+    }
+
+    if let Some(repository) = find_element(node, "repository") {
+        id.repository = Some(get_text_content(&repository));
+    }
+
+    if let Some(collection) = find_element(node, "collection") {
+        id.collection = Some(get_text_content(&collection));
+    }
+
+    if let Some(idno) = find_element(node, "idno") {
+        id.idno = Some(get_text_content(&idno));
+    }
+
+    if let Some(ms_name) = find_element(node, "msName") {
+        id.ms_name = Some(get_text_content(&ms_name));
+    }
+
+    // altIdentifier elements
+    id.alt_identifiers = items(node, "altIdentifier", read_alt_identifier)
+        .into_iter()
+        .map(|(_, alt)| alt)
+        .collect();
+
+    id
+}
+
+fn read_alt_identifier(node: &Node) -> Option<AltIdentifier> {
+    let idno = get_text_content(&find_element(node, "idno")?);
+    if idno.is_empty() {
+        return None;
+    }
+    Some(AltIdentifier {
+        id_type: node.get_property("type").unwrap_or_default(),
+        idno,
+    })
+}
+
+fn extract_ms_contents(node: &Node) -> MsContents {
+    let mut contents = MsContents::default();
+
+    if let Some(summary) = find_element(node, "summary") {
+        contents.summary = Some(get_text_content(&summary));
+    }
+
+    if let Some(text_lang) = find_element(node, "textLang") {
+        contents.text_lang = text_lang.get_property("mainLang");
+        let note = get_text_content(&text_lang);
+        if !note.is_empty() && contents.text_lang.as_deref() != Some(&note) {
+            contents.text_lang_note = Some(note);
+        }
+    }
+
+    contents.items = items(node, "msItem", read_ms_item)
+        .into_iter()
+        .map(|(_, item)| item)
+        .collect();
+
+    contents
+}
+
+fn read_ms_item(node: &Node) -> Option<MsItem> {
+    let text = |name: &str| find_element(node, name).map(|n| get_text_content(&n));
+    Some(MsItem {
+        n: node.get_property("n"),
+        locus: find_element(node, "locus").map(|l| read_locus(&l)),
+        author: text("author"),
+        title: text("title"),
+        rubric: text("rubric"),
+        incipit: text("incipit"),
+        explicit: text("explicit"),
+        colophon: text("colophon"),
+        text_lang: find_element(node, "textLang").and_then(|l| l.get_property("mainLang")),
+        note: text("note"),
+        items: items(node, "msItem", read_ms_item)
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect(),
+    })
+}
+
+fn read_locus(node: &Node) -> Locus {
+    let text = get_text_content(node);
+    Locus {
+        from: node.get_property("from"),
+        to: node.get_property("to"),
+        text: if text.is_empty() { None } else { Some(text) },
+    }
+}
+
+fn read_ms_part(node: &Node) -> Option<MsPart> {
+    let mut part = MsPart {
+        idno: find_element(node, "msIdentifier")
+            .and_then(|id| find_element(&id, "idno"))
+            .map(|idno| get_text_content(&idno)),
+        ..MsPart::default()
+    };
+    if let Some(contents) = find_element(node, "msContents") {
+        part.ms_contents = extract_ms_contents(&contents);
+    }
+    if let Some(phys_desc) = find_element(node, "physDesc") {
+        part.phys_desc = extract_phys_desc(&phys_desc);
+    }
+    if let Some(history) = find_element(node, "history") {
+        part.history = extract_history(&history);
+    }
+    Some(part)
+}
+
+fn extract_phys_desc(node: &Node) -> PhysDesc {
+    let mut desc = PhysDesc::default();
+
+    // objectDesc -> supportDesc
+    if let Some(object_desc) = find_element(node, "objectDesc") {
+        desc.form = object_desc.get_property("form");
+        if let Some(support_desc) = find_element(&object_desc, "supportDesc") {
+            if let Some(support) = find_element(&support_desc, "support") {
+                desc.material = Some(get_text_content(&support));
+            }
+            if let Some(extent) = find_element(&support_desc, "extent") {
+                desc.extent = Some(get_text_content(&extent));
+            }
+            if let Some(foliation) = find_element(&support_desc, "foliation") {
+                desc.foliation = Some(get_text_content(&foliation));
+            }
+            if let Some(collation) = find_element(&support_desc, "collation") {
+                desc.collation = Some(collation_text(&collation));
+            }
+            if let Some(condition) = find_element(&support_desc, "condition") {
+                desc.condition = Some(get_text_content(&condition));
+            }
+        }
+        if let Some(layout_desc) = find_element(&object_desc, "layoutDesc") {
+            if let Some(layout) = find_element(&layout_desc, "layout") {
+                desc.layout = Some(get_text_content(&layout));
+            }
+        }
+    }
+
+    // handDesc
+    if let Some(hand_desc) = find_element(node, "handDesc") {
+        desc.hands = items(&hand_desc, "handNote", read_hand_note)
+            .into_iter()
+            .map(|(_, hand)| hand)
+            .collect();
+    }
+
+    // decoDesc
+    if let Some(deco_desc) = find_element(node, "decoDesc") {
+        desc.decorations = items(&deco_desc, "decoNote", read_deco_note)
+            .into_iter()
+            .map(|(_, deco)| deco)
+            .collect();
+    }
+
+    if let Some(additions) = find_element(node, "additions") {
+        desc.additions = Some(get_text_content(&additions));
+    }
+
+    // bindingDesc (the first binding; later rebindings are left as they are)
+    if let Some(binding_desc) = find_element(node, "bindingDesc") {
+        desc.binding = find_element(&binding_desc, "binding").and_then(|b| read_binding(&b));
+    }
+
+    if let Some(acc_mat) = find_element(node, "accMat") {
+        desc.acc_mat = Some(get_text_content(&acc_mat));
+    }
+
+    desc
+}
+
+fn read_deco_note(node: &Node) -> Option<DecoNote> {
+    // The locus is modelled separately, so leave it out of the description
+    let description = node
+        .get_child_nodes()
+        .iter()
+        .filter(|child| {
+            !(child.get_type() == Some(NodeType::ElementNode) && child.get_name() == "locus")
+        })
+        .map(|child| child.get_content())
+        .collect::<String>();
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(DecoNote {
+        deco_type: node.get_property("type"),
+        locus: find_element(node, "locus").map(|l| read_locus(&l)),
+        description: if description.is_empty() {
+            None
+        } else {
+            Some(description)
+        },
+    })
+}
+
+fn read_binding(node: &Node) -> Option<Binding> {
+    let description = get_text_content(node);
+    Some(Binding {
+        contemporary: node
+            .get_property("contemporary")
+            .and_then(|c| c.parse::<bool>().ok()),
+        description: if description.is_empty() {
+            None
+        } else {
+            Some(description)
+        },
+    })
+}
+
+fn read_hand_note(node: &Node) -> Option<HandNote> {
+    Some(HandNote {
+        id: node
+            .get_property("xml:id")
+            .or_else(|| node.get_property("id")),
+        script: node.get_property("script"),
+        scope: node.get_property("scope"),
+        description: Some(get_text_content(node)),
+    })
+}
+
+fn extract_history(node: &Node) -> History {
+    let mut history = History::default();
+
+    if let Some(origin) = find_element(node, "origin") {
+        if let Some(orig_date) = find_element(&origin, "origDate") {
+            let mut date_range = DateRange {
+                display: Some(get_text_content(&orig_date)),
+                when: orig_date.get_property("when"),
+                not_before: orig_date.get_property("notBefore"),
+                not_after: orig_date.get_property("notAfter"),
+            };
+            // Catalogues often give only the date as written
+            date_range.fill_bounds();
+            history.orig_date = Some(date_range);
+        }
+
+        if let Some(orig_place) = find_element(&origin, "origPlace") {
+            history.orig_place = Some(get_text_content(&orig_place));
+        }
+    }
+
+    if let Some(provenance) = find_element(node, "provenance") {
+        history.provenance = Some(get_text_content(&provenance));
+    }
+
+    if let Some(acquisition) = find_element(node, "acquisition") {
+        history.acquisition = Some(get_text_content(&acquisition));
+    }
+
+    history
+}
+
+fn read_language(node: &Node) -> Option<Language> {
+    let ident = node.get_property("ident")?;
+    let usage = node
+        .get_property("usage")
+        .and_then(|s| s.parse::<u8>().ok());
+    let name = get_text_content(node);
+    Some(Language {
+        ident,
+        usage,
+        name: if name.is_empty() { None } else { Some(name) },
+    })
+}
+
+fn read_change(node: &Node) -> Option<Change> {
+    Some(Change {
+        when: node.get_property("when"),
+        who: node.get_property("who"),
+        summary: get_text_content(node),
+    })
+}
+
+/// The `name` children of `parent` that `read` accepts, with their nodes.
+fn items<T>(parent: &Node, name: &str, read: fn(&Node) -> Option<T>) -> Vec<(Node, T)> {
+    element_children(parent)
+        .into_iter()
+        .filter(|child| child.get_name() == name)
+        .filter_map(|child| read(&child).map(|item| (child, item)))
+        .collect()
+}
+
+// ============================================================================
+// Patching
+// ============================================================================
+
+/// Apply the edited fields of `edited` to the header source `original`.
+///
+/// Only elements whose modelled value differs from `original` are touched;
+/// everything else, including unmodelled subtrees, comments and formatting,
+/// is kept. An unedited header comes back byte-identical.
+pub fn patch_header(original: &str, edited: &Metadata) -> Result<String, String> {
+    // Namespace prefixes and entities are declared on the document, not the
+    // header: wrap it so both resolve as they did in the full file. The external
+    // DTD is never loaded, which leaves entity references as they are.
+    let doc = Parser::default()
+        .parse_string(wrap(original))
+        .map_err(|e| format!("Failed to parse teiHeader: {}", e))?;
+    let patch = Patch { doc: &doc };
+
+    let baseline = read_header(&patch.header()?);
+    let mut modelled = edited.clone();
+    modelled.original_header = None;
+    if baseline == modelled {
+        return Ok(original.to_string());
+    }
+
+    patch.title_stmt(&baseline.title_stmt, &edited.title_stmt)?;
+    patch.publication_stmt(&baseline.publication_stmt, &edited.publication_stmt)?;
+    patch.ms_identifier(&baseline.ms_identifier, &edited.ms_identifier)?;
+    patch.ms_contents(&baseline.ms_contents, &edited.ms_contents)?;
+    patch.phys_desc(&baseline.phys_desc, &edited.phys_desc)?;
+    patch.history(&baseline.history, &edited.history)?;
+    patch.list(
+        &MS_DESC,
+        "msPart",
+        read_ms_part,
+        &edited.ms_parts,
+        |_| true,
+        Patch::ms_part,
+    )?;
+    patch.list(
+        &["profileDesc", "langUsage"],
+        "language",
+        read_language,
+        &edited.languages,
+        |l| !l.ident.is_empty(),
+        Patch::language,
+    )?;
+    patch.text(
+        &["encodingDesc", "editorialDecl", "normalization"],
+        "p",
+        &baseline.encoding_notes,
+        &edited.encoding_notes,
+    )?;
+    patch.list(
+        &["revisionDesc"],
+        "change",
+        read_change,
+        &edited.revisions,
+        |c| !c.summary.is_empty(),
+        Patch::change,
+    )?;
+
+    Ok(doc.node_to_string(&patch.header()?))
+}
+
+/// Bring the revision history in the `<teiHeader>` of `xml` up to date with
+/// `metadata`, adding the `<respStmt>`s its changes point to. The rest of the
+/// header is left as it is.
+pub fn update_revisions(xml: &str, metadata: &Metadata) -> Result<String, String> {
+    let Some(span) = header_span(xml) else {
+        return Ok(xml.to_string());
+    };
+    let doc = Parser::default()
+        .parse_string(wrap(&xml[span]))
+        .map_err(|e| format!("Failed to parse teiHeader: {}", e))?;
+    let header = Patch { doc: &doc }.header()?;
+    let mut edited = read_header(&header);
+
+    edited.revisions = metadata.revisions.clone();
+    let pointers: Vec<&str> = metadata
+        .revisions
+        .iter()
+        .filter_map(|c| c.who.as_deref()?.strip_prefix('#'))
+        .collect();
+    for stmt in &metadata.title_stmt.resp_stmts {
+        let is_pointed_to = stmt.id.as_deref().is_some_and(|id| pointers.contains(&id));
+        if is_pointed_to && !edited.title_stmt.resp_stmts.contains(stmt) {
+            edited.title_stmt.resp_stmts.push(stmt.clone());
+        }
+    }
+    replace_header(xml, &edited)
+}
+
+/// Add to the `<titleStmt>` in the `<teiHeader>` of `xml` each of `stmts`
+/// whose `xml:id` the header does not have yet, so that `@resp` pointers at
+/// them resolve whatever the header came from.
+pub fn add_resp_stmts(xml: &str, stmts: &[RespStmt]) -> Result<String, String> {
+    let Some(span) = header_span(xml) else {
+        return Ok(xml.to_string());
+    };
+    let doc = Parser::default()
+        .parse_string(wrap(&xml[span]))
+        .map_err(|e| format!("Failed to parse teiHeader: {}", e))?;
+    let header = Patch { doc: &doc }.header()?;
+    let mut edited = read_header(&header);
+
+    for stmt in stmts {
+        let present = edited.title_stmt.resp_stmts.iter().any(|r| r.id == stmt.id);
+        if !present {
+            edited.title_stmt.resp_stmts.push(stmt.clone());
+        }
+    }
+    replace_header(xml, &edited)
+}
+
+/// Replace the `<teiHeader>` in `xml` with one patched from `edited`.
+///
+/// Leaves `xml` untouched when it has no header or nothing was edited.
+pub fn replace_header(xml: &str, edited: &Metadata) -> Result<String, String> {
+    let Some(span) = header_span(xml) else {
+        return Ok(xml.to_string());
+    };
+    let original = &xml[span.clone()];
+    let patched = patch_header(original, edited)?;
+    if patched == original {
+        return Ok(xml.to_string());
+    }
+    Ok(format!(
+        "{}{}{}",
+        &xml[..span.start],
+        patched,
+        &xml[span.end..]
+    ))
+}
+
+/// Wrap a standalone `<teiHeader>` in a document that declares what the full
+/// file did.
+fn wrap(header: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE TEI SYSTEM \"tei_all.dtd\">\n<TEI xmlns=\"http://www.tei-c.org/ns/1.0\" xmlns:me=\"http://www.menota.org/ns/1.0\">{}</TEI>",
+        header
+    )
+}
+
+const MS_DESC: [&str; 3] = ["fileDesc", "sourceDesc", "msDesc"];
+
+/// Header being patched, with helpers that keep the surrounding indentation.
+///
+/// libxml only lets a node be modified while no other handle to it is alive,
+/// so nodes are looked up afresh rather than held across changes.
+struct Patch<'a> {
+    doc: &'a Document,
+}
+
+impl Patch<'_> {
+    fn title_stmt(&self, old: &TitleStmt, new: &TitleStmt) -> Result<(), String> {
+        const PATH: &[&str] = &["fileDesc", "titleStmt"];
+
+        if old.title != new.title {
+            let existing = self.find(PATH).as_ref().and_then(main_title);
+            self.put(PATH, "title", existing, non_empty(&new.title))?;
+        }
+        if old.subtitle != new.subtitle {
+            let existing = self.find(PATH).as_ref().and_then(subtitle);
+            if let Some(mut node) = self.put(PATH, "title", existing, non_empty(&new.subtitle))? {
+                set_attr(&mut node, "type", Some("sub"))?;
+            }
+        }
+        self.text(PATH, "author", &old.author, &new.author)?;
+        self.list(
+            PATH,
+            "editor",
+            read_editor,
+            &new.editors,
+            |e| !e.name.is_empty(),
+            Patch::editor,
+        )?;
+        self.list(
+            PATH,
+            "respStmt",
+            read_resp_stmt,
+            &new.resp_stmts,
+            |r| !r.resp.is_empty() && !r.name.is_empty(),
+            Patch::resp_stmt,
+        )
+    }
+
+    fn publication_stmt(&self, old: &PublicationStmt, new: &PublicationStmt) -> Result<(), String> {
+        const PATH: &[&str] = &["fileDesc", "publicationStmt"];
+        self.text(PATH, "publisher", &old.publisher, &new.publisher)?;
+        self.text(PATH, "pubPlace", &old.pub_place, &new.pub_place)?;
+        self.text(PATH, "distributor", &old.distributor, &new.distributor)?;
+
+        if old.date != new.date {
+            // The date lives in @when where there is one; its display text is
+            // only rewritten when it said the same thing
+            let value = non_empty(&new.date);
+            let date = self.child(PATH, "date");
+            let has_when = date
+                .as_ref()
+                .map_or(true, |d| d.get_property("when").is_some());
+            let text_is_date = date
+                .as_ref()
+                .map_or(true, |d| !has_when || Some(get_text_content(d)) == old.date);
+            match (date, value) {
+                (Some(mut node), Some(value)) if !text_is_date => {
+                    set_attr(&mut node, "when", Some(value))?
+                }
+                (date, value) => {
+                    if let Some(mut node) = self.put(PATH, "date", date, value)? {
+                        if has_when {
+                            set_attr(&mut node, "when", value)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        if old.idno != new.idno || old.idno_type != new.idno_type {
+            let existing = self.child(PATH, "idno");
+            if let Some(mut node) = self.put(PATH, "idno", existing, non_empty(&new.idno))? {
+                set_attr(&mut node, "type", non_empty(&new.idno_type))?;
+            }
+        }
+
+        if old.availability != new.availability {
+            let existing = self.child(PATH, "availability");
+            let Some(avail) = &new.availability else {
+                if let Some(node) = existing {
+                    remove(node);
+                }
+                return Ok(());
+            };
+            let mut node = match existing {
+                Some(node) => node,
+                None => self.insert(&mut self.ensure(PATH)?, "availability")?,
+            };
+            set_attr(&mut node, "status", non_empty(&avail.status))?;
+            drop(node);
+
+            let path = &["fileDesc", "publicationStmt", "availability"];
+            let old = old.availability.clone().unwrap_or_default();
+            if old.license != avail.license || old.license_url != avail.license_url {
+                let licence = self.child(path, "licence");
+                if let Some(mut licence) =
+                    self.put(path, "licence", licence, non_empty(&avail.license))?
+                {
+                    set_attr(&mut licence, "target", non_empty(&avail.license_url))?;
+                }
+            }
+            self.text(path, "p", &old.note, &avail.note)?;
+        }
+        Ok(())
+    }
+
+    fn ms_identifier(&self, old: &MsIdentifier, new: &MsIdentifier) -> Result<(), String> {
+        let path = &[&MS_DESC[..], &["msIdentifier"]].concat();
+        self.text(path, "country", &old.country, &new.country)?;
+        self.text(path, "settlement", &old.settlement, &new.settlement)?;
+        self.text(path, "repository", &old.repository, &new.repository)?;
+        self.text(path, "collection", &old.collection, &new.collection)?;
+        self.text(path, "idno", &old.idno, &new.idno)?;
+        self.list(
+            path,
+            "altIdentifier",
+            read_alt_identifier,
+            &new.alt_identifiers,
+            |a| !a.idno.is_empty(),
+            Patch::alt_identifier,
+        )?;
+        self.text(path, "msName", &old.ms_name, &new.ms_name)
+    }
+
+    fn ms_contents(&self, old: &MsContents, new: &MsContents) -> Result<(), String> {
+        let path = &[&MS_DESC[..], &["msContents"]].concat();
+        self.text(path, "summary", &old.summary, &new.summary)?;
+
+        if old.text_lang != new.text_lang || old.text_lang_note != new.text_lang_note {
+            let existing = self.child(path, "textLang");
+            let lang = non_empty(&new.text_lang);
+            let text = non_empty(&new.text_lang_note).or(lang);
+            if let Some(mut node) = self.put(path, "textLang", existing, text)? {
+                set_attr(&mut node, "mainLang", lang)?;
+            }
+        }
+        self.list(
+            path,
+            "msItem",
+            read_ms_item,
+            &new.items,
+            |_| true,
+            Patch::ms_item,
+        )
+    }
+
+    fn phys_desc(&self, old: &PhysDesc, new: &PhysDesc) -> Result<(), String> {
+        let phys = [&MS_DESC[..], &["physDesc"]].concat();
+        let support = &[&phys[..], &["objectDesc", "supportDesc"]].concat();
+        self.text(support, "support", &old.material, &new.material)?;
+        self.text(support, "extent", &old.extent, &new.extent)?;
+        self.text(support, "foliation", &old.foliation, &new.foliation)?;
+        self.text(support, "collation", &old.collation, &new.collation)?;
+        self.text(support, "condition", &old.condition, &new.condition)?;
+
+        if old.form != new.form {
+            let object = &[&phys[..], &["objectDesc"]].concat();
+            match non_empty(&new.form) {
+                Some(form) => set_attr(&mut self.ensure(object)?, "form", Some(form))?,
+                None => {
+                    if let Some(mut node) = self.find(object) {
+                        set_attr(&mut node, "form", None)?;
+                    }
+                }
+            }
+        }
+
+        let layout = &[&phys[..], &["objectDesc", "layoutDesc"]].concat();
+        self.text(layout, "layout", &old.layout, &new.layout)?;
+
+        let hands = &[&phys[..], &["handDesc"]].concat();
+        self.list(
+            hands,
+            "handNote",
+            read_hand_note,
+            &new.hands,
+            |_| true,
+            Patch::hand_note,
+        )?;
+
+        let deco = &[&phys[..], &["decoDesc"]].concat();
+        self.list(
+            deco,
+            "decoNote",
+            read_deco_note,
+            &new.decorations,
+            |_| true,
+            Patch::deco_note,
+        )?;
+        self.text(&phys, "additions", &old.additions, &new.additions)?;
+
+        if old.binding != new.binding {
+            // Only the first binding is modelled
+            let path = &[&phys[..], &["bindingDesc"]].concat();
+            let existing = self.child(path, "binding");
+            match (existing, &new.binding) {
+                (Some(node), None) => remove(node),
+                (Some(mut node), Some(binding)) => {
+                    let mut replacement = self.fragment(&binding.to_xml(column(&node)))?;
+                    node.add_prev_sibling(&mut replacement)
+                        .map_err(|e| format!("Failed to replace <binding>: {}", e))?;
+                    node.unlink();
+                }
+                (None, Some(binding)) => {
+                    let mut parent = self.ensure(path)?;
+                    let mut node = self.fragment(&binding.to_xml(column(&parent) + 2))?;
+                    self.place(&mut parent, "binding", &mut node)?;
+                }
+                (None, None) => {}
+            }
+        }
+
+        self.text(&phys, "accMat", &old.acc_mat, &new.acc_mat)
+    }
+
+    fn history(&self, old: &History, new: &History) -> Result<(), String> {
+        let path = &[&MS_DESC[..], &["history"]].concat();
+        let origin = &[&path[..], &["origin"]].concat();
+
+        if old.orig_date != new.orig_date {
+            let existing = self.child(origin, "origDate");
+            let display = new
+                .orig_date
+                .as_ref()
+                .map(|d| d.display.as_deref().unwrap_or_default());
+            if let Some(mut node) = self.put(origin, "origDate", existing, display)? {
+                let date = new.orig_date.clone().unwrap_or_default();
+                set_attr(&mut node, "when", non_empty(&date.when))?;
+                set_attr(&mut node, "notBefore", non_empty(&date.not_before))?;
+                set_attr(&mut node, "notAfter", non_empty(&date.not_after))?;
+            }
+        }
+        self.text(origin, "origPlace", &old.orig_place, &new.orig_place)?;
+        self.text(path, "provenance", &old.provenance, &new.provenance)?;
+        self.text(path, "acquisition", &old.acquisition, &new.acquisition)
+    }
+
+    // ------------------------------------------------------------------------
+    // List items
+    // ------------------------------------------------------------------------
+
+    fn editor(&self, editor: &Person, _indent: usize) -> Result<Node, String> {
+        let mut node = self.element("editor", Some(&editor.name))?;
+        if let (Some(id), Some(id_type)) = (&editor.identifier, &editor.identifier_type) {
+            set_attr(&mut node, "ref", Some(&format!("{}:{}", id_type, id)))?;
+        }
+        Ok(node)
+    }
+
+    fn resp_stmt(&self, resp: &RespStmt, _indent: usize) -> Result<Node, String> {
+        let mut node = self.element("respStmt", None)?;
+        set_attr(&mut node, "xml:id", resp.id.as_deref())?;
+        node.add_child(&mut self.element("resp", Some(&resp.resp))?)?;
+        node.add_child(&mut self.element("name", Some(&resp.name))?)?;
+        Ok(node)
+    }
+
+    fn alt_identifier(&self, alt: &AltIdentifier, _indent: usize) -> Result<Node, String> {
+        let mut node = self.element("altIdentifier", None)?;
+        set_attr(
+            &mut node,
+            "type",
+            Some(alt.id_type.as_str()).filter(|t| !t.is_empty()),
+        )?;
+        node.add_child(&mut self.element("idno", Some(&alt.idno))?)?;
+        Ok(node)
+    }
+
+    fn hand_note(&self, hand: &HandNote, _indent: usize) -> Result<Node, String> {
+        let mut node = self.element("handNote", hand.description.as_deref())?;
+        set_attr(&mut node, "xml:id", non_empty(&hand.id))?;
+        set_attr(&mut node, "script", non_empty(&hand.script))?;
+        set_attr(&mut node, "scope", non_empty(&hand.scope))?;
+        Ok(node)
+    }
+
+    fn ms_item(&self, item: &MsItem, indent: usize) -> Result<Node, String> {
+        self.fragment(&item.to_xml(indent))
+    }
+
+    fn deco_note(&self, deco: &DecoNote, indent: usize) -> Result<Node, String> {
+        self.fragment(&deco.to_xml(indent))
+    }
+
+    fn ms_part(&self, part: &MsPart, indent: usize) -> Result<Node, String> {
+        self.fragment(&part.to_xml(indent))
+    }
+
+    fn language(&self, language: &Language, _indent: usize) -> Result<Node, String> {
+        let name = language.name.as_deref().unwrap_or(&language.ident);
+        let mut node = self.element("language", Some(name))?;
+        set_attr(&mut node, "ident", Some(&language.ident))?;
+        set_attr(
+            &mut node,
+            "usage",
+            language.usage.map(|u| u.to_string()).as_deref(),
+        )?;
+        Ok(node)
+    }
+
+    fn change(&self, change: &Change, _indent: usize) -> Result<Node, String> {
+        let mut node = self.element("change", Some(&change.summary))?;
+        set_attr(&mut node, "when", change.when.as_deref())?;
+        set_attr(&mut node, "who", change.who.as_deref())?;
+        Ok(node)
+    }
+
+    // ------------------------------------------------------------------------
+    // Tree helpers
+    // ------------------------------------------------------------------------
+
+    /// Update the `name` child under `path` if its value changed.
+    fn text(
+        &self,
+        path: &[&str],
+        name: &str,
+        old: &Option<String>,
+        new: &Option<String>,
+    ) -> Result<(), String> {
+        if old != new {
+            let existing = self.child(path, name);
+            self.put(path, name, existing, non_empty(new))?;
+        }
+        Ok(())
+    }
+
+    /// Set the text of `existing`, creating it as a `name` child of the
+    /// element at `path` if needed, or remove it when `value` is `None`.
+    fn put(
+        &self,
+        path: &[&str],
+        name: &str,
+        existing: Option<Node>,
+        value: Option<&str>,
+    ) -> Result<Option<Node>, String> {
+        let Some(value) = value else {
+            if let Some(node) = existing {
+                remove(node);
+            }
+            return Ok(None);
+        };
+        let mut node = match existing {
+            Some(node) => node,
+            None => self.insert(&mut self.ensure(path)?, name)?,
+        };
+        node.set_content(&escape_text(value))
+            .map_err(|e| format!("Failed to set <{}>: {}", name, e))?;
+        Ok(Some(node))
+    }
+
+    /// Patch a repeated element item by item, leaving unchanged items alone.
+    ///
+    /// Items shared at the start and end are kept, so adding an entry at the
+    /// front (as in `revisionDesc`) does not rewrite the ones after it.
+    fn list<T: PartialEq>(
+        &self,
+        path: &[&str],
+        name: &str,
+        read: fn(&Node) -> Option<T>,
+        edited: &[T],
+        keep: fn(&T) -> bool,
+        build: fn(&Self, &T, usize) -> Result<Node, String>,
+    ) -> Result<(), String> {
+        let existing = self
+            .find(path)
+            .map(|parent| items(&parent, name, read))
+            .unwrap_or_default();
+        let edited: Vec<&T> = edited.iter().filter(|item| keep(item)).collect();
+        if existing.len() == edited.len() && existing.iter().zip(&edited).all(|((_, a), b)| a == *b)
+        {
+            return Ok(());
+        }
+
+        let shared = existing.len().min(edited.len());
+        let prefix = (0..shared)
+            .take_while(|&i| existing[i].1 == *edited[i])
+            .count();
+        let suffix = (0..shared - prefix)
+            .take_while(|&i| existing[existing.len() - 1 - i].1 == *edited[edited.len() - 1 - i])
+            .count();
+
+        // Hold only the nodes either side of the changed run, so that they can
+        // be mutated
+        let mut existing = existing;
+        let mut next = existing
+            .split_off(existing.len() - suffix)
+            .into_iter()
+            .next()
+            .map(|(node, _)| node);
+        let changed = existing.split_off(prefix);
+        let mut last = existing.pop().map(|(node, _)| node);
+        drop(existing);
+
+        let mut existing = changed.into_iter();
+        let mut edited = edited[prefix..edited.len() - suffix].iter().copied();
+        loop {
+            match (existing.next(), edited.next()) {
+                (Some((node, old)), Some(new)) if old == *new => last = Some(node),
+                (Some((mut node, _)), Some(new)) => {
+                    let mut replacement = build(self, new, column(&node))?;
+                    node.add_prev_sibling(&mut replacement)
+                        .map_err(|e| format!("Failed to replace <{}>: {}", name, e))?;
+                    node.unlink();
+                    last = Some(replacement);
+                }
+                (Some((node, _)), None) => remove(node),
+                (None, Some(new)) => match (last.take(), next.as_mut()) {
+                    (Some(mut anchor), _) => {
+                        let mut node = build(self, new, column(&anchor))?;
+                        self.insert_after(&mut anchor, &mut node)?;
+                        last = Some(node);
+                    }
+                    (None, Some(anchor)) => {
+                        let mut node = build(self, new, column(anchor))?;
+                        self.insert_before(anchor, &mut node)?;
+                        last = Some(node);
+                    }
+                    (None, None) => {
+                        let mut parent = self.ensure(path)?;
+                        let mut node = build(self, new, column(&parent) + 2)?;
+                        self.place(&mut parent, name, &mut node)?;
+                        last = Some(node);
+                    }
+                },
+                (None, None) => return Ok(()),
+            }
+        }
+    }
+
+    fn header(&self) -> Result<Node, String> {
+        let root = self.doc.get_root_element().ok_or("No root element found")?;
+        find_element(&root, "teiHeader").ok_or_else(|| "No <teiHeader> element found".to_string())
+    }
+
+    fn find(&self, path: &[&str]) -> Option<Node> {
+        path.iter()
+            .try_fold(self.header().ok()?, |node, name| find_element(&node, name))
+    }
+
+    fn child(&self, path: &[&str], name: &str) -> Option<Node> {
+        self.find(path)
+            .and_then(|parent| find_element(&parent, name))
+    }
+
+    /// Find the element at `path`, creating any missing steps.
+    fn ensure(&self, path: &[&str]) -> Result<Node, String> {
+        let mut node = self.header()?;
+        for name in path {
+            node = match find_element(&node, name) {
+                Some(child) => child,
+                None => self.insert(&mut node, name)?,
+            };
+        }
+        Ok(node)
+    }
+
+    fn element(&self, name: &str, text: Option<&str>) -> Result<Node, String> {
+        let mut node =
+            Node::new(name, None, self.doc).map_err(|_| format!("Failed to create <{}>", name))?;
+        if let Some(text) = text {
+            node.set_content(&escape_text(text))
+                .map_err(|e| format!("Failed to set <{}>: {}", name, e))?;
+        }
+        Ok(node)
+    }
+
+    /// Create an empty `name` element in its TEI position under `parent`.
+    fn insert(&self, parent: &mut Node, name: &str) -> Result<Node, String> {
+        let mut node = self.element(name, None)?;
+        self.place(parent, name, &mut node)?;
+        Ok(node)
+    }
+
+    /// Add `node` to `parent` after the siblings TEI puts before it.
+    fn place(&self, parent: &mut Node, name: &str, node: &mut Node) -> Result<(), String> {
+        let order = child_order(&parent.get_name());
+        let rank = |n: &Node| order.iter().position(|o| *o == n.get_name());
+        let own = order.iter().position(|o| *o == name);
+
+        if let Some(own) = own {
+            let before = element_children(parent)
+                .into_iter()
+                .rev()
+                .find(|c| rank(c).is_some_and(|r| r <= own));
+            if let Some(mut anchor) = before {
+                return self.insert_after(&mut anchor, node);
+            }
+            let after = element_children(parent)
+                .into_iter()
+                .find(|c| rank(c).is_some());
+            if let Some(mut anchor) = after {
+                return self.insert_before(&mut anchor, node);
+            }
+        }
+        match element_children(parent).into_iter().last() {
+            Some(mut anchor) => self.insert_after(&mut anchor, node),
+            None => self.append_first(parent, node),
+        }
+    }
+
+    fn insert_after(&self, anchor: &mut Node, node: &mut Node) -> Result<(), String> {
+        anchor
+            .add_next_sibling(node)
+            .map_err(|e| format!("Failed to insert <{}>: {}", node.get_name(), e))?;
+        if let Some(indent) = indent_before(anchor) {
+            node.add_prev_sibling(&mut self.text_node(&indent)?)
+                .map_err(|e| format!("Failed to indent <{}>: {}", node.get_name(), e))?;
+        }
+        Ok(())
+    }
+
+    fn insert_before(&self, anchor: &mut Node, node: &mut Node) -> Result<(), String> {
+        let indent = indent_before(anchor);
+        anchor
+            .add_prev_sibling(node)
+            .map_err(|e| format!("Failed to insert <{}>: {}", node.get_name(), e))?;
+        if let Some(indent) = indent {
+            anchor
+                .add_prev_sibling(&mut self.text_node(&indent)?)
+                .map_err(|e| format!("Failed to indent <{}>: {}", node.get_name(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Add the first element child of `parent`, indented one step further.
+    fn append_first(&self, parent: &mut Node, node: &mut Node) -> Result<(), String> {
+        let blank = parent.get_child_nodes().iter().all(is_whitespace);
+        let outer = indent_before(parent)
+            .map(|ws| ws[ws.rfind('\n').unwrap_or(0)..].to_string())
+            .filter(|ws| blank && ws.starts_with('\n'));
+
+        match outer {
+            Some(outer) => {
+                for mut child in parent.get_child_nodes() {
+                    child.unlink();
+                }
+                parent.add_child(&mut self.text_node(&format!("{}  ", outer))?)?;
+                parent.add_child(node)?;
+                parent.add_child(&mut self.text_node(&outer)?)?;
+            }
+            None => parent.add_child(node)?,
+        }
+        Ok(())
+    }
+
+    /// Parse generated XML into an element of the header document.
+    fn fragment(&self, xml: &str) -> Result<Node, String> {
+        let source = Parser::default()
+            .parse_string(format!("<fragment>{}</fragment>", xml))
+            .map_err(|e| format!("Failed to parse generated XML: {}", e))?;
+        let mut node = source
+            .get_root_element()
+            .and_then(|root| element_children(&root).into_iter().next())
+            .ok_or("Generated XML is empty")?;
+        node.unlink();
+        self.doc
+            .clone()
+            .import_node(&mut node)
+            .map_err(|_| "Failed to import generated XML".to_string())
+    }
+
+    fn text_node(&self, text: &str) -> Result<Node, String> {
+        Node::new_text(text, self.doc).map_err(|_| "Failed to create text node".to_string())
+    }
+}
+
+/// Where TEI expects each child, for the parents the model writes into.
+fn child_order(parent: &str) -> &'static [&'static str] {
+    match parent {
+        "teiHeader" => &[
+            "fileDesc",
+            "encodingDesc",
+            "profileDesc",
+            "xenoData",
+            "revisionDesc",
+        ],
+        "fileDesc" => &[
+            "titleStmt",
+            "editionStmt",
+            "extent",
+            "publicationStmt",
+            "seriesStmt",
+            "notesStmt",
+            "sourceDesc",
+        ],
+        "titleStmt" => &[
+            "title",
+            "author",
+            "editor",
+            "sponsor",
+            "funder",
+            "principal",
+            "respStmt",
+        ],
+        "publicationStmt" => &[
+            "publisher",
+            "pubPlace",
+            "date",
+            "distributor",
+            "authority",
+            "idno",
+            "availability",
+        ],
+        "availability" => &["licence", "p"],
+        "sourceDesc" => &["msDesc"],
+        "msDesc" => &[
+            "msIdentifier",
+            "head",
+            "msContents",
+            "physDesc",
+            "history",
+            "additional",
+            "msPart",
+        ],
+        "msIdentifier" => &[
+            "placeName",
+            "country",
+            "region",
+            "settlement",
+            "institution",
+            "repository",
+            "collection",
+            "idno",
+            "altIdentifier",
+            "msName",
+        ],
+        "msContents" => &["summary", "textLang", "titlePage", "msItem", "msItemStruct"],
+        "physDesc" => &[
+            "p",
+            "objectDesc",
+            "handDesc",
+            "typeDesc",
+            "scriptDesc",
+            "musicNotation",
+            "decoDesc",
+            "additions",
+            "bindingDesc",
+            "sealDesc",
+            "accMat",
+        ],
+        "objectDesc" => &["supportDesc", "layoutDesc"],
+        "supportDesc" => &["support", "extent", "foliation", "collation", "condition"],
+        "history" => &["summary", "origin", "provenance", "acquisition"],
+        "origin" => &["origPlace", "origDate"],
+        "profileDesc" => &["creation", "langUsage", "textClass", "handNotes"],
+        "encodingDesc" => &[
+            "projectDesc",
+            "samplingDecl",
+            "editorialDecl",
+            "tagsDecl",
+            "refsDecl",
+            "classDecl",
+        ],
+        "editorialDecl" => &[
+            "correction",
+            "normalization",
+            "quotation",
+            "hyphenation",
+            "segmentation",
+            "interpretation",
+        ],
+        _ => &[],
+    }
+}
+
+/// Remove `node` together with the indentation in front of it.
+fn remove(mut node: Node) {
+    if let Some(mut prev) = node.get_prev_sibling() {
+        if is_whitespace(&prev) {
+            prev.unlink();
+        }
+    }
+    node.unlink();
+}
+
+fn set_attr(node: &mut Node, name: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(value) => node.set_attribute(name, value),
+        None => node.remove_attribute(name),
+    }
+    .map_err(|e| format!("Failed to set @{}: {}", name, e))
+}
+
+/// Column `node` is indented to.
+fn column(node: &Node) -> usize {
+    indent_before(node)
+        .map(|ws| ws.len() - ws.rfind('\n').map_or(0, |i| i + 1))
+        .unwrap_or(0)
+}
+
+/// Whitespace text immediately before `node`, if any.
+fn indent_before(node: &Node) -> Option<String> {
+    node.get_prev_sibling()
+        .filter(is_whitespace)
+        .map(|prev| prev.get_content())
+}
+
+fn is_whitespace(node: &Node) -> bool {
+    node.get_type() == Some(NodeType::TextNode) && node.get_content().trim().is_empty()
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
+/// `set_content` reads entity references, so markup characters go in escaped.
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;")
+}
+
+fn find_element(node: &Node, name: &str) -> Option<Node> {
+    let mut child = node.get_first_child();
+    while let Some(c) = child {
+        if c.get_type() == Some(NodeType::ElementNode) && c.get_name() == name {
+            return Some(c);
+        }
+        child = c.get_next_sibling();
+    }
+    None
+}
+
+fn element_children(node: &Node) -> Vec<Node> {
+    let mut children = Vec::new();
+    let mut child = node.get_first_child();
+    while let Some(c) = child {
+        if c.get_type() == Some(NodeType::ElementNode) {
+            children.push(c.clone());
+        }
+        child = c.get_next_sibling();
+    }
+    children
+}
+
+/// Text of a collation formula, with `<hi rend="sup">` quire sizes written as
+/// superscript characters so `1<hi rend="sup">8</hi>` reads as "1⁸", not "18"
+fn collation_text(node: &Node) -> String {
+    fn collect(node: &Node, sup: bool, text: &mut String) {
+        let mut child = node.get_first_child();
+        while let Some(c) = child {
+            match c.get_type() {
+                Some(NodeType::TextNode) if sup => {
+                    text.extend(c.get_content().chars().map(superscript));
+                }
+                Some(NodeType::TextNode) => text.push_str(&c.get_content()),
+                Some(NodeType::ElementNode) => {
+                    let raised = c.get_name() == "hi"
+                        && c.get_property("rend")
+                            .is_some_and(|rend| rend.split_whitespace().any(|r| r == "sup"));
+                    collect(&c, sup || raised, text);
+                }
+                _ => {}
+            }
+            child = c.get_next_sibling();
+        }
+    }
+
+    let mut text = String::new();
+    collect(node, false, &mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn superscript(c: char) -> char {
+    match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '(' => '⁽',
+        ')' => '⁾',
+        _ => c,
+    }
+}
+
+fn get_text_content(node: &Node) -> String {
+    let content = node.get_content();
+    // Normalize whitespace
+    content.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = r#"<teiHeader>
+    <fileDesc>
+      <titleStmt>
+        <title>Njáls saga</title>
+        <editor ref="orcid:0000-0001-2345-6789">Test Editor</editor>
+      </titleStmt>
+      <publicationStmt>
+        <publisher>Test Publisher</publisher>
+        <date when="2024">2024</date>
+      </publicationStmt>
+      <sourceDesc>
+        <msDesc xml:id="AM02-0132">
+          <msIdentifier>
+            <country>Iceland</country>
+            <repository>Stofnun Árna Magnússonar</repository>
+            <idno>AM 132 fol.</idno>
+          </msIdentifier>
+          <msContents>
+            <msItem n="1">
+              <title>Njáls saga</title>
+              <note>Defective at the start</note>
+            </msItem>
+          </msContents>
+          <physDesc>
+            <objectDesc form="codex">
+              <supportDesc material="perg">
+                <support>Parchment</support>
+              </supportDesc>
+            </objectDesc>
+            <decoDesc>
+              <decoNote>Initials in red &amp; green</decoNote>
+            </decoDesc>
+          </physDesc>
+        </msDesc>
+      </sourceDesc>
+    </fileDesc>
+    <!-- catalogue revision history -->
+    <revisionDesc>
+      <change when="2009-02-03">Created</change>
+    </revisionDesc>
+  </teiHeader>"#;
+
+    fn baseline() -> Metadata {
+        let mut metadata = read(HEADER);
+        metadata.original_header = Some(HEADER.to_string());
+        metadata
+    }
+
+    fn read(header: &str) -> Metadata {
+        let doc = Parser::default().parse_string(header).unwrap();
+        read_header(&doc.get_root_element().unwrap())
+    }
+
+    #[test]
+    fn test_unedited_header_is_unchanged() {
+        assert_eq!(patch_header(HEADER, &baseline()).unwrap(), HEADER);
+    }
+
+    #[test]
+    fn test_edit_keeps_unmodelled_content() {
+        let mut metadata = baseline();
+        metadata.ms_identifier.idno = Some("AM 132 fol. & 133".to_string());
+        let patched = patch_header(HEADER, &metadata).unwrap();
+
+        assert!(patched.contains("<idno>AM 132 fol. &amp; 133</idno>"));
+        assert_eq!(
+            patched.replace("AM 132 fol. &amp; 133", "AM 132 fol."),
+            HEADER,
+            "only the edited field should change"
+        );
+    }
+
+    #[test]
+    fn test_new_fields_are_created_in_place() {
+        let mut metadata = baseline();
+        metadata.ms_identifier.settlement = Some("Reykjavík".to_string());
+        metadata.phys_desc.extent = Some("95 leaves".to_string());
+        metadata.history.orig_place = Some("Iceland".to_string());
+        let patched = patch_header(HEADER, &metadata).unwrap();
+
+        assert!(patched.contains(
+            "<country>Iceland</country>\n            <settlement>Reykjavík</settlement>\n            <repository>"
+        ));
+        assert!(patched
+            .contains("<support>Parchment</support>\n                <extent>95 leaves</extent>"));
+        // history goes after physDesc, and the decoration is still there
+        let deco = patched.find("<decoDesc>").unwrap();
+        let history = patched.find("<history>").unwrap();
+        assert!(deco < history);
+        assert!(patched.contains("<origin>\n              <origPlace>Iceland</origPlace>"));
+        assert!(patched.contains("<!-- catalogue revision history -->"));
+
+        let reread = read(&patched);
+        assert_eq!(reread.phys_desc.extent.as_deref(), Some("95 leaves"));
+        assert_eq!(reread.history.orig_place.as_deref(), Some("Iceland"));
+    }
+
+    #[test]
+    fn test_list_items_patched_individually() {
+        let mut metadata = baseline();
+        metadata.title_stmt.editors.push(Person {
+            name: "Second Editor".to_string(),
+            identifier: None,
+            identifier_type: None,
+        });
+        metadata.languages.push(Language {
+            ident: "non".to_string(),
+            usage: None,
+            name: Some("Old Norse".to_string()),
+        });
+        let patched = patch_header(HEADER, &metadata).unwrap();
+
+        assert!(patched.contains(
+            "<editor ref=\"orcid:0000-0001-2345-6789\">Test Editor</editor>\n        <editor>Second Editor</editor>"
+        ));
+        let profile = patched.find("<profileDesc>").unwrap();
+        assert!(patched.find("</fileDesc>").unwrap() < profile);
+        assert!(profile < patched.find("<revisionDesc>").unwrap());
+        assert_eq!(read(&patched).languages.len(), 1);
+
+        metadata.title_stmt.editors.remove(0);
+        let patched = patch_header(HEADER, &metadata).unwrap();
+        assert!(!patched.contains("Test Editor"));
+        assert!(
+            patched.contains("<title>Njáls saga</title>\n        <editor>Second Editor</editor>")
+        );
+    }
+
+    #[test]
+    fn test_cleared_field_is_removed() {
+        let mut metadata = baseline();
+        metadata.publication_stmt.publisher = None;
+        let patched = patch_header(HEADER, &metadata).unwrap();
+        assert!(patched.contains("<publicationStmt>\n        <date when=\"2024\">2024</date>"));
+    }
+
+    #[test]
+    fn test_replace_header_in_document() {
+        let xml = format!("<?xml version=\"1.0\"?>\n<TEI>\n  {}\n  <text>", HEADER);
+        let mut metadata = baseline();
+        assert_eq!(replace_header(&xml, &metadata).unwrap(), xml);
+
+        metadata.title_stmt.title = Some("Brennu-Njáls saga".to_string());
+        let replaced = replace_header(&xml, &metadata).unwrap();
+        assert!(replaced.starts_with("<?xml version=\"1.0\"?>\n<TEI>\n  <teiHeader>"));
+        assert!(replaced.ends_with("</teiHeader>\n  <text>"));
+        assert!(replaced.contains("<title>Brennu-Njáls saga</title>"));
+        // The msItem title is not the title statement
+        assert!(replaced.contains("<title>Njáls saga</title>\n              <note>"));
+    }
+
+    const COMPOSITE: &str = r#"<teiHeader>
+    <fileDesc>
+      <sourceDesc>
+        <msDesc>
+          <msIdentifier>
+            <idno>AM 162 fol.</idno>
+          </msIdentifier>
+          <msContents>
+            <msItem n="1">
+              <locus from="1r" to="24v">1r-24v</locus>
+              <title>Egils saga</title>
+              <incipit>Úlfr hét maðr</incipit>
+              <textLang mainLang="non">Old Norse</textLang>
+              <msItem n="1.1">
+                <locus from="1r">1r</locus>
+                <rubric>Hér hefr upp sǫgu Egils</rubric>
+              </msItem>
+            </msItem>
+          </msContents>
+          <physDesc>
+            <objectDesc form="codex">
+              <supportDesc material="perg">
+                <support>Parchment</support>
+                <collation><formula>1<hi rend="sup">8</hi></formula></collation>
+              </supportDesc>
+            </objectDesc>
+            <decoDesc>
+              <decoNote type="initial"><locus from="1r">1r</locus> Large red initial</decoNote>
+            </decoDesc>
+            <additions><p>Marginal notes in a 17th-century hand</p></additions>
+            <bindingDesc>
+              <binding contemporary="false"><p>Modern calf</p></binding>
+            </bindingDesc>
+            <accMat>A paper slip</accMat>
+          </physDesc>
+          <msPart>
+            <msIdentifier>
+              <idno>Part A</idno>
+            </msIdentifier>
+            <history>
+              <origin><origDate notBefore="1250" notAfter="1300">s. xiii²</origDate></origin>
+            </history>
+          </msPart>
+          <msPart>
+            <msIdentifier>
+              <idno>Part B</idno>
+            </msIdentifier>
+            <physDesc>
+              <objectDesc form="leaf"/>
+            </physDesc>
+          </msPart>
+        </msDesc>
+      </sourceDesc>
+    </fileDesc>
+  </teiHeader>"#;
+
+    #[test]
+    fn test_read_composite_ms_desc() {
+        let metadata = read(COMPOSITE);
+
+        let item = &metadata.ms_contents.items[0];
+        assert_eq!(item.n.as_deref(), Some("1"));
+        assert_eq!(item.locus.as_ref().unwrap().to.as_deref(), Some("24v"));
+        assert_eq!(item.title.as_deref(), Some("Egils saga"));
+        assert_eq!(item.text_lang.as_deref(), Some("non"));
+        assert_eq!(
+            item.items[0].rubric.as_deref(),
+            Some("Hér hefr upp sǫgu Egils")
+        );
+
+        let phys = &metadata.phys_desc;
+        assert_eq!(phys.form.as_deref(), Some("codex"));
+        assert_eq!(phys.collation.as_deref(), Some("1⁸"));
+        assert_eq!(phys.decorations[0].deco_type.as_deref(), Some("initial"));
+        assert_eq!(
+            phys.decorations[0].description.as_deref(),
+            Some("Large red initial")
+        );
+        assert_eq!(
+            phys.additions.as_deref(),
+            Some("Marginal notes in a 17th-century hand")
+        );
+        assert_eq!(phys.binding.as_ref().unwrap().contemporary, Some(false));
+        assert_eq!(phys.acc_mat.as_deref(), Some("A paper slip"));
+
+        assert_eq!(metadata.ms_parts.len(), 2);
+        assert_eq!(metadata.ms_parts[0].idno.as_deref(), Some("Part A"));
+        let date = metadata.ms_parts[0].history.orig_date.as_ref().unwrap();
+        assert_eq!(date.not_before.as_deref(), Some("1250"));
+        assert_eq!(metadata.ms_parts[1].phys_desc.form.as_deref(), Some("leaf"));
+    }
+
+    #[test]
+    fn test_generated_ms_desc_reads_back() {
+        let mut metadata = read(COMPOSITE);
+        metadata.title_stmt.title = Some("Egils saga".to_string());
+
+        let generated = metadata.to_tei_header(false);
+        let header = &generated[header_span(&generated).unwrap()];
+        assert!(header
+            .contains("<msPart>\n            <msIdentifier>\n              <idno>Part A</idno>"));
+        assert_eq!(read(header), metadata);
+    }
+
+    #[test]
+    fn test_patch_single_ms_item() {
+        let mut metadata = read(COMPOSITE);
+        metadata.original_header = Some(COMPOSITE.to_string());
+        metadata.ms_contents.items[0].items[0].rubric = Some("Saga Egils".to_string());
+        metadata.ms_parts[1].history.provenance = Some("Iceland".to_string());
+        let patched = patch_header(COMPOSITE, &metadata).unwrap();
+
+        // The edited item is rewritten at its own indentation; the rest is untouched
+        assert!(patched.contains(
+            "<msItem n=\"1.1\">\n                <locus from=\"1r\">1r</locus>\n                <rubric>Saga Egils</rubric>\n              </msItem>"
+        ));
+        assert!(patched.contains("<formula>1<hi rend=\"sup\">8</hi></formula>"));
+        assert!(
+            patched.contains("<origDate notBefore=\"1250\" notAfter=\"1300\">s. xiii²</origDate>")
+        );
+        assert!(patched.contains("<provenance>Iceland</provenance>"));
+
+        let reread = read(&patched);
+        assert_eq!(reread.ms_contents, metadata.ms_contents);
+        assert_eq!(reread.ms_parts, metadata.ms_parts);
+    }
+
+    #[test]
+    fn test_update_revisions_prepends_change() {
+        let mut metadata = Metadata::new();
+        metadata.revisions = read(HEADER).revisions;
+        crate::metadata::revisions::record_change(
+            &mut metadata,
+            "2024-05-01",
+            Some("Anna"),
+            "2 words changed".to_string(),
+        );
+        let document = format!("<TEI>{}<text/></TEI>", HEADER);
+
+        let updated = update_revisions(&document, &metadata).unwrap();
+        assert!(updated.contains(
+            "<revisionDesc>\n      <change when=\"2024-05-01\" who=\"#resp-anna\">2 words changed</change>\n      <change when=\"2009-02-03\">Created</change>"
+        ));
+        assert!(updated.contains("<respStmt xml:id=\"resp-anna\">"));
+        // The rest of the header is not regenerated from the (empty) metadata
+        assert!(updated.contains("<title>Njáls saga</title>"));
+        assert!(updated.contains("<decoNote>Initials in red &amp; green</decoNote>"));
+    }
+
+    #[test]
+    fn test_add_resp_stmts_to_template_header() {
+        let template = crate::template::TemplateManager::get_built_in("tei-p5")
+            .unwrap()
+            .header;
+        let stmt = RespStmt {
+            resp: "Annotation".to_string(),
+            name: "Anna".to_string(),
+            id: Some("resp-anna".to_string()),
+        };
+
+        let updated = add_resp_stmts(&template, std::slice::from_ref(&stmt)).unwrap();
+        assert!(updated.contains("<respStmt xml:id=\"resp-anna\">"), "Got: {}", updated);
+        // What follows the header is untouched
+        let after_header = template.split("</teiHeader>").last().unwrap();
+        assert!(updated.ends_with(after_header));
+
+        // A header that has the statement already is left alone
+        assert_eq!(add_resp_stmts(&updated, &[stmt]).unwrap(), updated);
+    }
+
+    #[test]
+    fn test_orig_date_without_attributes_is_parsed() {
+        let header = HEADER.replace(
+            "</physDesc>",
+            "</physDesc>\n          <history><origin><origDate>s. xiv med.</origDate></origin></history>",
+        );
+        let mut metadata = read(&header);
+        let date = metadata.history.orig_date.as_ref().unwrap();
+        assert_eq!(date.not_before.as_deref(), Some("1325"));
+        assert_eq!(date.not_after.as_deref(), Some("1374"));
+
+        // The parsed bounds are not an edit, so the header is left as written
+        metadata.original_header = Some(header.clone());
+        assert_eq!(patch_header(&header, &metadata).unwrap(), header);
+    }
+}
@@ -3,7 +3,15 @@
 //! This module provides structured metadata types that map to TEI header elements,
 //! allowing form-based editing instead of raw XML manipulation.
 
+pub mod catalogue;
+pub mod crosswalk;
+pub mod dates;
+pub mod header;
+pub mod revisions;
+pub mod validate;
+
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Complete manuscript metadata covering TEI P5 header elements.
 /// All fields are optional to support incremental editing.
@@ -34,6 +42,10 @@ pub struct Metadata {
     #[serde(default)]
     pub history: History,
 
+    /// Codicological units of a composite manuscript (msDesc/msPart)
+    #[serde(default)]
+    pub ms_parts: Vec<MsPart>,
+
     /// Language profile (profileDesc/langUsage)
     #[serde(default)]
     pub languages: Vec<Language>,
@@ -41,6 +53,15 @@ pub struct Metadata {
     /// Encoding description notes (encodingDesc)
     #[serde(default)]
     pub encoding_notes: Option<String>,
+
+    /// Revision history (revisionDesc), most recent change first
+    #[serde(default)]
+    pub revisions: Vec<Change>,
+
+    /// Source of the imported `<teiHeader>`, kept so that regenerating the
+    /// header patches the edited fields into it instead of starting over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_header: Option<String>,
 }
 
 /// Title statement - who created and is responsible for the digital edition
@@ -88,6 +109,10 @@ pub struct RespStmt {
 
     /// Who did it
     pub name: String,
+
+    /// `xml:id` so that `@resp` attributes in the text can point here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
 }
 
 /// Publication information for the digital edition
@@ -183,6 +208,63 @@ pub struct MsContents {
 
     /// Additional language notes
     pub text_lang_note: Option<String>,
+
+    /// Works contained in the manuscript, in order (msItem)
+    #[serde(default)]
+    pub items: Vec<MsItem>,
+}
+
+/// A work or section of the manuscript
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MsItem {
+    /// Item number (@n)
+    pub n: Option<String>,
+
+    /// Leaves the item occupies
+    pub locus: Option<Locus>,
+
+    /// Author of the work
+    pub author: Option<String>,
+
+    /// Title of the work
+    pub title: Option<String>,
+
+    /// Rubric as written in the manuscript
+    pub rubric: Option<String>,
+
+    /// Opening words
+    pub incipit: Option<String>,
+
+    /// Closing words
+    pub explicit: Option<String>,
+
+    /// Scribal colophon
+    pub colophon: Option<String>,
+
+    /// Language of the item (ISO 639 code)
+    pub text_lang: Option<String>,
+
+    /// Further notes on the item
+    pub note: Option<String>,
+
+    /// Sub-items, e.g. the individual sagas of a compilation
+    #[serde(default)]
+    pub items: Vec<MsItem>,
+}
+
+/// A range of leaves, e.g. 1r-24v
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Locus {
+    /// First leaf
+    pub from: Option<String>,
+
+    /// Last leaf
+    pub to: Option<String>,
+
+    /// Displayed text (e.g., "1r-24v")
+    pub text: Option<String>,
 }
 
 /// Physical description of the manuscript
@@ -207,6 +289,50 @@ pub struct PhysDesc {
 
     /// General condition
     pub condition: Option<String>,
+
+    /// Form of the object (codex, leaf, roll, ...)
+    pub form: Option<String>,
+
+    /// Collation formula
+    pub collation: Option<String>,
+
+    /// Decoration (decoDesc)
+    #[serde(default)]
+    pub decorations: Vec<DecoNote>,
+
+    /// Later additions such as marginalia and glosses
+    pub additions: Option<String>,
+
+    /// Binding (bindingDesc)
+    pub binding: Option<Binding>,
+
+    /// Accompanying material
+    pub acc_mat: Option<String>,
+}
+
+/// A note on one kind of decoration
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoNote {
+    /// Kind of decoration (e.g., "initial", "miniature", "border")
+    pub deco_type: Option<String>,
+
+    /// Where it occurs
+    pub locus: Option<Locus>,
+
+    /// Description
+    pub description: Option<String>,
+}
+
+/// Binding of the manuscript
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Binding {
+    /// Whether the binding is contemporary with the manuscript
+    pub contemporary: Option<bool>,
+
+    /// Description
+    pub description: Option<String>,
 }
 
 /// Description of a scribal hand
@@ -243,6 +369,27 @@ pub struct History {
     pub acquisition: Option<String>,
 }
 
+/// A codicological unit of a composite manuscript, with its own contents,
+/// physical description and history
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MsPart {
+    /// Identifier of the part (e.g., "Part I")
+    pub idno: Option<String>,
+
+    /// Contents of the part
+    #[serde(default)]
+    pub ms_contents: MsContents,
+
+    /// Physical description of the part
+    #[serde(default)]
+    pub phys_desc: PhysDesc,
+
+    /// History of the part
+    #[serde(default)]
+    pub history: History,
+}
+
 /// A date or date range
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -260,6 +407,20 @@ pub struct DateRange {
     pub when: Option<String>,
 }
 
+/// A change recorded in the revision history
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    /// Date of the change (YYYY-MM-DD)
+    pub when: Option<String>,
+
+    /// Pointer to whoever made the change (e.g., "#resp-anna")
+    pub who: Option<String>,
+
+    /// What was changed
+    pub summary: String,
+}
+
 /// Language declaration
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -290,7 +451,69 @@ impl Metadata {
             && self.languages.is_empty()
     }
 
-    /// Generate TEI XML for the teiHeader based on metadata
+    /// Make sure every annotation author has a `<respStmt>` with an `xml:id`,
+    /// adding an "Annotation" statement for authors without one.
+    ///
+    /// Returns the `xml:id` to point `@resp` at for each author.
+    pub fn add_annotator_resp_stmts(&mut self, authors: &[String]) -> HashMap<String, String> {
+        self.add_resp_stmts(authors, "Annotation")
+    }
+
+    /// Give each named person a `<respStmt>` with an `xml:id`, adding one with
+    /// the given `resp` if they have none.
+    ///
+    /// A statement that already has an id (e.g. from an imported header) keeps
+    /// it. New ids come from [`resp_id`](crate::annotations::resp_id), numbered
+    /// when two names share a slug ("J. Smith" and "J Smith"). Returns the id
+    /// used for each name.
+    pub fn add_resp_stmts(&mut self, names: &[String], resp: &str) -> HashMap<String, String> {
+        let mut ids = HashMap::new();
+        for name in names {
+            let position = self.title_stmt.resp_stmts.iter().position(|r| r.name == *name);
+            let existing_id = position.and_then(|i| self.title_stmt.resp_stmts[i].id.clone());
+            let id = match existing_id {
+                Some(id) => id,
+                None => {
+                    let id = self.unused_resp_id(name);
+                    match position {
+                        Some(i) => self.title_stmt.resp_stmts[i].id = Some(id.clone()),
+                        None => self.title_stmt.resp_stmts.push(RespStmt {
+                            resp: resp.to_string(),
+                            name: name.clone(),
+                            id: Some(id.clone()),
+                        }),
+                    }
+                    id
+                }
+            };
+            ids.insert(name.clone(), id);
+        }
+        ids
+    }
+
+    /// [`resp_id`](crate::annotations::resp_id) of a name, with a number
+    /// appended if another statement already has that id
+    fn unused_resp_id(&self, name: &str) -> String {
+        let base = crate::annotations::resp_id(name);
+        let taken = |id: &str| {
+            self.title_stmt
+                .resp_stmts
+                .iter()
+                .any(|r| r.id.as_deref() == Some(id))
+        };
+        if !taken(&base) {
+            return base;
+        }
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|id| !taken(id))
+            .expect("some numbered id is free")
+    }
+
+    /// Generate TEI XML for the teiHeader based on metadata.
+    ///
+    /// An imported header is patched rather than regenerated, so content the
+    /// metadata does not model is kept.
     pub fn to_tei_header(&self, include_menota_ns: bool) -> String {
         let mut xml = String::new();
 
@@ -307,7 +530,27 @@ impl Metadata {
         }
         xml.push('\n');
 
-        xml.push_str("  <teiHeader>\n");
+        match self
+            .original_header
+            .as_deref()
+            .map(|original| header::patch_header(original, self))
+        {
+            Some(Ok(patched)) => {
+                xml.push_str("  ");
+                xml.push_str(&patched);
+                xml.push('\n');
+            }
+            _ => xml.push_str(&self.generated_header()),
+        }
+        xml.push_str("  <text>\n");
+        xml.push_str("    <body>\n");
+
+        xml
+    }
+
+    /// Build a teiHeader from the modelled fields alone
+    fn generated_header(&self) -> String {
+        let mut xml = String::from("  <teiHeader>\n");
         xml.push_str("    <fileDesc>\n");
 
         // titleStmt
@@ -321,7 +564,17 @@ impl Metadata {
 
         xml.push_str("    </fileDesc>\n");
 
-        // encodingDesc (if notes present)
+        xml.push_str(&self.encoding_desc_to_xml());
+        xml.push_str(&self.profile_desc_to_xml());
+        xml.push_str(&self.revision_desc_to_xml());
+
+        xml.push_str("  </teiHeader>\n");
+        xml
+    }
+
+    /// `<encodingDesc>` with the normalisation notes, or nothing without them
+    pub(crate) fn encoding_desc_to_xml(&self) -> String {
+        let mut xml = String::new();
         if let Some(ref notes) = self.encoding_notes {
             if !notes.is_empty() {
                 xml.push_str("    <encodingDesc>\n");
@@ -333,8 +586,12 @@ impl Metadata {
                 xml.push_str("    </encodingDesc>\n");
             }
         }
+        xml
+    }
 
-        // profileDesc (languages)
+    /// `<profileDesc>` with the languages, or nothing without them
+    pub(crate) fn profile_desc_to_xml(&self) -> String {
+        let mut xml = String::new();
         if !self.languages.is_empty() {
             xml.push_str("    <profileDesc>\n");
             xml.push_str("      <langUsage>\n");
@@ -353,15 +610,28 @@ impl Metadata {
             xml.push_str("      </langUsage>\n");
             xml.push_str("    </profileDesc>\n");
         }
+        xml
+    }
 
-        xml.push_str("  </teiHeader>\n");
-        xml.push_str("  <text>\n");
-        xml.push_str("    <body>\n");
-
+    /// `<revisionDesc>` with the change history, or nothing without one
+    pub(crate) fn revision_desc_to_xml(&self) -> String {
+        let mut xml = String::new();
+        if !self.revisions.is_empty() {
+            xml.push_str("    <revisionDesc>\n");
+            for change in &self.revisions {
+                xml.push_str(&format!(
+                    "      <change{}{}>{}</change>\n",
+                    attr("when", &change.when),
+                    attr("who", &change.who),
+                    escape_xml(&change.summary)
+                ));
+            }
+            xml.push_str("    </revisionDesc>\n");
+        }
         xml
     }
 
-    fn title_stmt_to_xml(&self) -> String {
+    pub(crate) fn title_stmt_to_xml(&self) -> String {
         let mut xml = String::from("      <titleStmt>\n");
 
         // Title (required in TEI, use placeholder if empty)
@@ -411,7 +681,13 @@ impl Metadata {
         // Responsibility statements
         for resp in &self.title_stmt.resp_stmts {
             if !resp.name.is_empty() && !resp.resp.is_empty() {
-                xml.push_str("        <respStmt>\n");
+                match &resp.id {
+                    Some(id) => xml.push_str(&format!(
+                        "        <respStmt xml:id=\"{}\">\n",
+                        escape_xml(id)
+                    )),
+                    None => xml.push_str("        <respStmt>\n"),
+                }
                 xml.push_str(&format!("          <resp>{}</resp>\n", escape_xml(&resp.resp)));
                 xml.push_str(&format!("          <name>{}</name>\n", escape_xml(&resp.name)));
                 xml.push_str("        </respStmt>\n");
@@ -422,7 +698,7 @@ impl Metadata {
         xml
     }
 
-    fn publication_stmt_to_xml(&self) -> String {
+    pub(crate) fn publication_stmt_to_xml(&self) -> String {
         let mut xml = String::from("      <publicationStmt>\n");
         let ps = &self.publication_stmt;
 
@@ -525,18 +801,23 @@ impl Metadata {
         xml
     }
 
-    fn source_desc_to_xml(&self) -> String {
-        let mut xml = String::from("      <sourceDesc>\n");
-
-        // Check if we have manuscript description fields
-        let has_ms_desc = self.ms_identifier.repository.is_some()
+    /// Whether any manuscript description field is set, i.e. whether
+    /// `<sourceDesc>` gets an `<msDesc>` rather than a placeholder paragraph
+    pub(crate) fn has_ms_desc(&self) -> bool {
+        self.ms_identifier.repository.is_some()
             || self.ms_identifier.idno.is_some()
             || self.ms_identifier.settlement.is_some()
             || self.ms_contents.summary.is_some()
+            || !self.ms_contents.items.is_empty()
             || !self.phys_desc.hands.is_empty()
-            || self.history.orig_date.is_some();
+            || self.history.orig_date.is_some()
+            || !self.ms_parts.is_empty()
+    }
+
+    pub(crate) fn source_desc_to_xml(&self) -> String {
+        let mut xml = String::from("      <sourceDesc>\n");
 
-        if has_ms_desc {
+        if self.has_ms_desc() {
             xml.push_str("        <msDesc>\n");
 
             // msIdentifier
@@ -598,173 +879,11 @@ impl Metadata {
             }
             xml.push_str("          </msIdentifier>\n");
 
-            // msContents
-            if self.ms_contents.summary.is_some() || self.ms_contents.text_lang.is_some() {
-                xml.push_str("          <msContents>\n");
-                if let Some(ref summary) = self.ms_contents.summary {
-                    if !summary.is_empty() {
-                        xml.push_str(&format!(
-                            "            <summary>{}</summary>\n",
-                            escape_xml(summary)
-                        ));
-                    }
-                }
-                if let Some(ref lang) = self.ms_contents.text_lang {
-                    if !lang.is_empty() {
-                        let note = self
-                            .ms_contents
-                            .text_lang_note
-                            .as_deref()
-                            .unwrap_or(lang);
-                        xml.push_str(&format!(
-                            "            <textLang mainLang=\"{}\">{}</textLang>\n",
-                            escape_xml(lang),
-                            escape_xml(note)
-                        ));
-                    }
-                }
-                xml.push_str("          </msContents>\n");
-            }
-
-            // physDesc
-            if self.phys_desc.material.is_some()
-                || self.phys_desc.extent.is_some()
-                || !self.phys_desc.hands.is_empty()
-            {
-                xml.push_str("          <physDesc>\n");
-
-                if self.phys_desc.material.is_some() || self.phys_desc.extent.is_some() {
-                    xml.push_str("            <objectDesc>\n");
-                    xml.push_str("              <supportDesc>\n");
-                    if let Some(ref material) = self.phys_desc.material {
-                        if !material.is_empty() {
-                            xml.push_str(&format!(
-                                "                <support>{}</support>\n",
-                                escape_xml(material)
-                            ));
-                        }
-                    }
-                    if let Some(ref extent) = self.phys_desc.extent {
-                        if !extent.is_empty() {
-                            xml.push_str(&format!(
-                                "                <extent>{}</extent>\n",
-                                escape_xml(extent)
-                            ));
-                        }
-                    }
-                    if let Some(ref foliation) = self.phys_desc.foliation {
-                        if !foliation.is_empty() {
-                            xml.push_str(&format!(
-                                "                <foliation>{}</foliation>\n",
-                                escape_xml(foliation)
-                            ));
-                        }
-                    }
-                    if let Some(ref condition) = self.phys_desc.condition {
-                        if !condition.is_empty() {
-                            xml.push_str(&format!(
-                                "                <condition>{}</condition>\n",
-                                escape_xml(condition)
-                            ));
-                        }
-                    }
-                    xml.push_str("              </supportDesc>\n");
-                    if let Some(ref layout) = self.phys_desc.layout {
-                        if !layout.is_empty() {
-                            xml.push_str("              <layoutDesc>\n");
-                            xml.push_str(&format!(
-                                "                <layout>{}</layout>\n",
-                                escape_xml(layout)
-                            ));
-                            xml.push_str("              </layoutDesc>\n");
-                        }
-                    }
-                    xml.push_str("            </objectDesc>\n");
-                }
-
-                if !self.phys_desc.hands.is_empty() {
-                    xml.push_str("            <handDesc>\n");
-                    for hand in &self.phys_desc.hands {
-                        let mut attrs = String::new();
-                        if let Some(ref id) = hand.id {
-                            attrs.push_str(&format!(r#" xml:id="{}""#, escape_xml(id)));
-                        }
-                        if let Some(ref script) = hand.script {
-                            attrs.push_str(&format!(r#" script="{}""#, escape_xml(script)));
-                        }
-                        if let Some(ref scope) = hand.scope {
-                            attrs.push_str(&format!(r#" scope="{}""#, escape_xml(scope)));
-                        }
-                        let desc = hand.description.as_deref().unwrap_or("");
-                        xml.push_str(&format!(
-                            "              <handNote{}>{}</handNote>\n",
-                            attrs,
-                            escape_xml(desc)
-                        ));
-                    }
-                    xml.push_str("            </handDesc>\n");
-                }
-
-                xml.push_str("          </physDesc>\n");
-            }
-
-            // history
-            if self.history.orig_date.is_some()
-                || self.history.orig_place.is_some()
-                || self.history.provenance.is_some()
-            {
-                xml.push_str("          <history>\n");
-
-                if self.history.orig_date.is_some() || self.history.orig_place.is_some() {
-                    xml.push_str("            <origin>\n");
-                    if let Some(ref date) = self.history.orig_date {
-                        let mut attrs = String::new();
-                        if let Some(ref when) = date.when {
-                            attrs.push_str(&format!(r#" when="{}""#, escape_xml(when)));
-                        }
-                        if let Some(ref nb) = date.not_before {
-                            attrs.push_str(&format!(r#" notBefore="{}""#, escape_xml(nb)));
-                        }
-                        if let Some(ref na) = date.not_after {
-                            attrs.push_str(&format!(r#" notAfter="{}""#, escape_xml(na)));
-                        }
-                        let display = date.display.as_deref().unwrap_or("");
-                        xml.push_str(&format!(
-                            "              <origDate{}>{}</origDate>\n",
-                            attrs,
-                            escape_xml(display)
-                        ));
-                    }
-                    if let Some(ref place) = self.history.orig_place {
-                        if !place.is_empty() {
-                            xml.push_str(&format!(
-                                "              <origPlace>{}</origPlace>\n",
-                                escape_xml(place)
-                            ));
-                        }
-                    }
-                    xml.push_str("            </origin>\n");
-                }
-
-                if let Some(ref prov) = self.history.provenance {
-                    if !prov.is_empty() {
-                        xml.push_str(&format!(
-                            "            <provenance>{}</provenance>\n",
-                            escape_xml(prov)
-                        ));
-                    }
-                }
-
-                if let Some(ref acq) = self.history.acquisition {
-                    if !acq.is_empty() {
-                        xml.push_str(&format!(
-                            "            <acquisition>{}</acquisition>\n",
-                            escape_xml(acq)
-                        ));
-                    }
-                }
-
-                xml.push_str("          </history>\n");
+            xml.push_str(&self.ms_contents.to_xml(10));
+            xml.push_str(&self.phys_desc.to_xml(10));
+            xml.push_str(&self.history.to_xml(10));
+            for part in &self.ms_parts {
+                xml.push_str(&part.to_xml(10));
             }
 
             xml.push_str("        </msDesc>\n");
@@ -787,6 +906,311 @@ impl Metadata {
     }
 }
 
+impl MsIdentifier {
+    /// "Reykjavík, Stofnun Árna Magnússonar, AM 132 fol."
+    pub fn shelfmark(&self) -> Option<String> {
+        let parts: Vec<&str> = [
+            &self.settlement,
+            &self.repository,
+            &self.collection,
+            &self.idno,
+        ]
+        .into_iter()
+        .filter_map(|p| p.as_deref().map(str::trim).filter(|p| !p.is_empty()))
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+impl MsContents {
+    /// `<msContents>` indented by `indent` spaces, or nothing if empty
+    fn to_xml(&self, indent: usize) -> String {
+        if self.summary.is_none() && self.text_lang.is_none() && self.items.is_empty() {
+            return String::new();
+        }
+        let pad = " ".repeat(indent);
+        let mut xml = format!("{}<msContents>\n", pad);
+        push_element(&mut xml, indent + 2, "summary", &self.summary);
+        if let Some(ref lang) = self.text_lang {
+            if !lang.is_empty() {
+                let note = self.text_lang_note.as_deref().unwrap_or(lang);
+                xml.push_str(&format!(
+                    "{}  <textLang mainLang=\"{}\">{}</textLang>\n",
+                    pad,
+                    escape_xml(lang),
+                    escape_xml(note)
+                ));
+            }
+        }
+        for item in &self.items {
+            xml.push_str(&item.to_xml(indent + 2));
+        }
+        xml.push_str(&format!("{}</msContents>\n", pad));
+        xml
+    }
+}
+
+impl MsItem {
+    /// `<msItem>` indented by `indent` spaces, with its sub-items
+    fn to_xml(&self, indent: usize) -> String {
+        let pad = " ".repeat(indent);
+        let mut xml = format!("{}<msItem{}>\n", pad, attr("n", &self.n));
+        if let Some(ref locus) = self.locus {
+            xml.push_str(&format!("{}  {}\n", pad, locus.to_xml()));
+        }
+        push_element(&mut xml, indent + 2, "author", &self.author);
+        push_element(&mut xml, indent + 2, "title", &self.title);
+        push_element(&mut xml, indent + 2, "rubric", &self.rubric);
+        push_element(&mut xml, indent + 2, "incipit", &self.incipit);
+        push_element(&mut xml, indent + 2, "explicit", &self.explicit);
+        push_element(&mut xml, indent + 2, "colophon", &self.colophon);
+        if let Some(ref lang) = self.text_lang {
+            if !lang.is_empty() {
+                xml.push_str(&format!(
+                    "{}  <textLang mainLang=\"{}\">{}</textLang>\n",
+                    pad,
+                    escape_xml(lang),
+                    escape_xml(lang)
+                ));
+            }
+        }
+        push_element(&mut xml, indent + 2, "note", &self.note);
+        for item in &self.items {
+            xml.push_str(&item.to_xml(indent + 2));
+        }
+        xml.push_str(&format!("{}</msItem>\n", pad));
+        xml
+    }
+}
+
+impl Locus {
+    /// `<locus>` on a single line; the text defaults to the range itself
+    fn to_xml(&self) -> String {
+        let text = match (&self.text, &self.from, &self.to) {
+            (Some(text), _, _) if !text.is_empty() => text.clone(),
+            (_, Some(from), Some(to)) if from != to => format!("{}-{}", from, to),
+            (_, Some(from), _) => from.clone(),
+            (_, None, Some(to)) => to.clone(),
+            _ => String::new(),
+        };
+        format!(
+            "<locus{}{}>{}</locus>",
+            attr("from", &self.from),
+            attr("to", &self.to),
+            escape_xml(&text)
+        )
+    }
+}
+
+impl PhysDesc {
+    /// `<physDesc>` indented by `indent` spaces, or nothing if empty
+    fn to_xml(&self, indent: usize) -> String {
+        let has_support = self.material.is_some()
+            || self.extent.is_some()
+            || self.foliation.is_some()
+            || self.collation.is_some()
+            || self.condition.is_some();
+        let has_object = has_support || self.layout.is_some() || self.form.is_some();
+        if !has_object
+            && self.hands.is_empty()
+            && self.decorations.is_empty()
+            && self.additions.is_none()
+            && self.binding.is_none()
+            && self.acc_mat.is_none()
+        {
+            return String::new();
+        }
+
+        let pad = " ".repeat(indent);
+        let mut xml = format!("{}<physDesc>\n", pad);
+
+        if has_object {
+            xml.push_str(&format!("{}  <objectDesc{}>\n", pad, attr("form", &self.form)));
+            if has_support {
+                xml.push_str(&format!("{}    <supportDesc>\n", pad));
+                push_element(&mut xml, indent + 6, "support", &self.material);
+                push_element(&mut xml, indent + 6, "extent", &self.extent);
+                push_element(&mut xml, indent + 6, "foliation", &self.foliation);
+                push_element(&mut xml, indent + 6, "collation", &self.collation);
+                push_element(&mut xml, indent + 6, "condition", &self.condition);
+                xml.push_str(&format!("{}    </supportDesc>\n", pad));
+            }
+            if let Some(ref layout) = self.layout {
+                if !layout.is_empty() {
+                    xml.push_str(&format!("{}    <layoutDesc>\n", pad));
+                    xml.push_str(&format!(
+                        "{}      <layout>{}</layout>\n",
+                        pad,
+                        escape_xml(layout)
+                    ));
+                    xml.push_str(&format!("{}    </layoutDesc>\n", pad));
+                }
+            }
+            xml.push_str(&format!("{}  </objectDesc>\n", pad));
+        }
+
+        if !self.hands.is_empty() {
+            xml.push_str(&format!("{}  <handDesc>\n", pad));
+            for hand in &self.hands {
+                let mut attrs = String::new();
+                if let Some(ref id) = hand.id {
+                    attrs.push_str(&format!(r#" xml:id="{}""#, escape_xml(id)));
+                }
+                if let Some(ref script) = hand.script {
+                    attrs.push_str(&format!(r#" script="{}""#, escape_xml(script)));
+                }
+                if let Some(ref scope) = hand.scope {
+                    attrs.push_str(&format!(r#" scope="{}""#, escape_xml(scope)));
+                }
+                let desc = hand.description.as_deref().unwrap_or("");
+                xml.push_str(&format!(
+                    "{}    <handNote{}>{}</handNote>\n",
+                    pad,
+                    attrs,
+                    escape_xml(desc)
+                ));
+            }
+            xml.push_str(&format!("{}  </handDesc>\n", pad));
+        }
+
+        if !self.decorations.is_empty() {
+            xml.push_str(&format!("{}  <decoDesc>\n", pad));
+            for deco in &self.decorations {
+                xml.push_str(&deco.to_xml(indent + 4));
+            }
+            xml.push_str(&format!("{}  </decoDesc>\n", pad));
+        }
+
+        push_element(&mut xml, indent + 2, "additions", &self.additions);
+
+        if let Some(ref binding) = self.binding {
+            xml.push_str(&format!("{}  <bindingDesc>\n", pad));
+            xml.push_str(&binding.to_xml(indent + 4));
+            xml.push_str(&format!("{}  </bindingDesc>\n", pad));
+        }
+
+        push_element(&mut xml, indent + 2, "accMat", &self.acc_mat);
+
+        xml.push_str(&format!("{}</physDesc>\n", pad));
+        xml
+    }
+}
+
+impl DecoNote {
+    /// `<decoNote>` on a single line at `indent` spaces
+    fn to_xml(&self, indent: usize) -> String {
+        let locus = self
+            .locus
+            .as_ref()
+            .map(|l| format!("{} ", l.to_xml()))
+            .unwrap_or_default();
+        let desc = self.description.as_deref().unwrap_or("");
+        format!(
+            "{}<decoNote{}>{}{}</decoNote>\n",
+            " ".repeat(indent),
+            attr("type", &self.deco_type),
+            locus,
+            escape_xml(desc)
+        )
+    }
+}
+
+impl Binding {
+    /// `<binding>` indented by `indent` spaces
+    fn to_xml(&self, indent: usize) -> String {
+        let pad = " ".repeat(indent);
+        let contemporary = self
+            .contemporary
+            .map(|c| format!(r#" contemporary="{}""#, c))
+            .unwrap_or_default();
+        let mut xml = format!("{}<binding{}>\n", pad, contemporary);
+        push_element(&mut xml, indent + 2, "p", &self.description);
+        xml.push_str(&format!("{}</binding>\n", pad));
+        xml
+    }
+}
+
+impl History {
+    /// `<history>` indented by `indent` spaces, or nothing if empty
+    fn to_xml(&self, indent: usize) -> String {
+        if self.orig_date.is_none()
+            && self.orig_place.is_none()
+            && self.provenance.is_none()
+            && self.acquisition.is_none()
+        {
+            return String::new();
+        }
+        let pad = " ".repeat(indent);
+        let mut xml = format!("{}<history>\n", pad);
+
+        if self.orig_date.is_some() || self.orig_place.is_some() {
+            xml.push_str(&format!("{}  <origin>\n", pad));
+            if let Some(ref date) = self.orig_date {
+                let attrs = format!(
+                    "{}{}{}",
+                    attr("when", &date.when),
+                    attr("notBefore", &date.not_before),
+                    attr("notAfter", &date.not_after)
+                );
+                let display = date.display.as_deref().unwrap_or("");
+                xml.push_str(&format!(
+                    "{}    <origDate{}>{}</origDate>\n",
+                    pad,
+                    attrs,
+                    escape_xml(display)
+                ));
+            }
+            push_element(&mut xml, indent + 4, "origPlace", &self.orig_place);
+            xml.push_str(&format!("{}  </origin>\n", pad));
+        }
+
+        push_element(&mut xml, indent + 2, "provenance", &self.provenance);
+        push_element(&mut xml, indent + 2, "acquisition", &self.acquisition);
+
+        xml.push_str(&format!("{}</history>\n", pad));
+        xml
+    }
+}
+
+impl MsPart {
+    /// `<msPart>` indented by `indent` spaces
+    fn to_xml(&self, indent: usize) -> String {
+        let pad = " ".repeat(indent);
+        let mut xml = format!("{}<msPart>\n", pad);
+        if self.idno.as_deref().is_some_and(|i| !i.is_empty()) {
+            xml.push_str(&format!("{}  <msIdentifier>\n", pad));
+            push_element(&mut xml, indent + 4, "idno", &self.idno);
+            xml.push_str(&format!("{}  </msIdentifier>\n", pad));
+        }
+        xml.push_str(&self.ms_contents.to_xml(indent + 2));
+        xml.push_str(&self.phys_desc.to_xml(indent + 2));
+        xml.push_str(&self.history.to_xml(indent + 2));
+        xml.push_str(&format!("{}</msPart>\n", pad));
+        xml
+    }
+}
+
+/// Push `<name>value</name>` on its own line if `value` is non-empty
+fn push_element(xml: &mut String, indent: usize, name: &str, value: &Option<String>) {
+    if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+        xml.push_str(&format!(
+            "{}<{}>{}</{}>\n",
+            " ".repeat(indent),
+            name,
+            escape_xml(value),
+            name
+        ));
+    }
+}
+
+/// ` name="value"`, or nothing if `value` is missing
+fn attr(name: &str, value: &Option<String>) -> String {
+    value
+        .as_ref()
+        .map(|v| format!(r#" {}="{}""#, name, escape_xml(v)))
+        .unwrap_or_default()
+}
+
 /// Escape special XML characters
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
@@ -869,6 +1293,45 @@ mod tests {
         assert!(header.contains(r#"<language ident="lat" usage="5">Latin</language>"#));
     }
 
+    #[test]
+    fn test_annotator_resp_stmts() {
+        let mut meta = Metadata::new();
+        meta.title_stmt.resp_stmts.push(RespStmt {
+            resp: "Transcription".to_string(),
+            name: "Anna".to_string(),
+            id: None,
+        });
+
+        meta.add_annotator_resp_stmts(&["Anna".to_string(), "Bjorn".to_string()]);
+        let stmts = &meta.title_stmt.resp_stmts;
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(stmts[0].id.as_deref(), Some("resp-anna"));
+        assert_eq!(stmts[1].resp, "Annotation");
+
+        let header = meta.to_tei_header(false);
+        assert!(header.contains(r#"<respStmt xml:id="resp-bjorn">"#));
+    }
+
+    #[test]
+    fn test_resp_stmt_ids_keep_existing_and_avoid_collisions() {
+        let mut meta = Metadata::new();
+        meta.title_stmt.resp_stmts.push(RespStmt {
+            resp: "Transcription".to_string(),
+            name: "Anna Jónsdóttir".to_string(),
+            id: Some("AJ".to_string()),
+        });
+
+        let names = ["Anna Jónsdóttir", "J. Smith", "J Smith"].map(String::from);
+        let ids = meta.add_annotator_resp_stmts(&names);
+        assert_eq!(ids["Anna Jónsdóttir"], "AJ");
+        assert_eq!(ids["J. Smith"], "resp-j-smith");
+        assert_eq!(ids["J Smith"], "resp-j-smith-2");
+
+        // Stable when asked again
+        assert_eq!(meta.add_annotator_resp_stmts(&names), ids);
+        assert_eq!(meta.title_stmt.resp_stmts.len(), 3);
+    }
+
     #[test]
     fn test_serialization_roundtrip() {
         let mut meta = Metadata::new();
@@ -0,0 +1,205 @@
+//! Revision history entries recorded when a project is saved.
+//!
+//! Each save can add a [`Change`] to the front of [`Metadata::revisions`]
+//! summarising what happened since the previous save: words changed, pages
+//! touched and annotations added. The previous state comes from the archive
+//! being overwritten.
+
+use std::collections::HashSet;
+
+use super::{Change, Metadata};
+use crate::annotations::AnnotationSet;
+
+/// What changed between two saves of a project
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveStats {
+    pub words_changed: usize,
+    /// Page numbers (from `///` breaks) on which words changed, in order
+    pub pages_touched: Vec<String>,
+    pub annotations_added: usize,
+}
+
+impl SaveStats {
+    /// Compare the DSL source and annotations of two saves.
+    ///
+    /// Words are compared as a sequence: the run between the longest common
+    /// start and end counts as changed, which is exact for a single edit and
+    /// an upper bound for several.
+    pub fn compare(
+        old_source: &str,
+        new_source: &str,
+        old_annotations: Option<&AnnotationSet>,
+        new_annotations: Option<&AnnotationSet>,
+    ) -> Self {
+        let old = words(old_source);
+        let new = words(new_source);
+
+        let shared = old.len().min(new.len());
+        let prefix = (0..shared).take_while(|&i| old[i].0 == new[i].0).count();
+        let suffix = (0..shared - prefix)
+            .take_while(|&i| old[old.len() - 1 - i].0 == new[new.len() - 1 - i].0)
+            .count();
+        let old_changed = &old[prefix..old.len() - suffix];
+        let new_changed = &new[prefix..new.len() - suffix];
+
+        let mut pages_touched: Vec<String> = Vec::new();
+        for (_, page) in new_changed.iter().chain(old_changed) {
+            if let Some(page) = page {
+                if !pages_touched.contains(page) {
+                    pages_touched.push(page.clone());
+                }
+            }
+        }
+
+        let old_ids: HashSet<&str> = old_annotations
+            .map(|set| set.annotations.iter().map(|a| a.id.as_str()).collect())
+            .unwrap_or_default();
+        let annotations_added = new_annotations
+            .map(|set| {
+                set.annotations
+                    .iter()
+                    .filter(|a| !old_ids.contains(a.id.as_str()))
+                    .count()
+            })
+            .unwrap_or(0);
+
+        Self {
+            words_changed: old_changed.len().max(new_changed.len()),
+            pages_touched,
+            annotations_added,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words_changed == 0 && self.annotations_added == 0
+    }
+
+    /// e.g. "12 words changed on pages 3r, 3v; 2 annotations added"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.words_changed > 0 {
+            let mut words = plural(self.words_changed, "word", "words") + " changed";
+            match self.pages_touched.as_slice() {
+                [] => {}
+                [page] => words.push_str(&format!(" on page {}", page)),
+                pages => words.push_str(&format!(" on pages {}", pages.join(", "))),
+            }
+            parts.push(words);
+        }
+        if self.annotations_added > 0 {
+            parts.push(plural(self.annotations_added, "annotation", "annotations") + " added");
+        }
+        parts.join("; ")
+    }
+}
+
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// Words of a DSL source with the page each is on. Line breaks are not words;
+/// other markup is compared as part of the word it is attached to.
+fn words(source: &str) -> Vec<(&str, Option<String>)> {
+    let mut page: Option<String> = None;
+    let mut words = Vec::new();
+    for token in source.split_whitespace() {
+        if let Some(number) = token.strip_prefix("///") {
+            page = Some(number.to_string()).filter(|n| !n.is_empty());
+        } else if !token.starts_with("//") {
+            words.push((token, page.clone()));
+        }
+    }
+    words
+}
+
+/// Add a change to the front of the revision history. `who` is a person's
+/// name, recorded as a pointer to their `<respStmt>` (a new "Revision" one if
+/// they have none).
+pub fn record_change(metadata: &mut Metadata, when: &str, who: Option<&str>, summary: String) {
+    let who = who.map(str::trim).filter(|w| !w.is_empty()).map(|name| {
+        let ids = metadata.add_resp_stmts(&[name.to_string()], "Revision");
+        format!("#{}", ids[name])
+    });
+    metadata.revisions.insert(
+        0,
+        Change {
+            when: Some(when.to_string()),
+            who,
+            summary,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::Annotation;
+    use crate::metadata::RespStmt;
+
+    #[test]
+    fn test_compare_counts_changed_words_and_pages() {
+        let old = "///1r Hér hefr upp //2 sǫgu Egils ///1v ok er þat vel";
+        let new = "///1r Hér hefr upp //2 sǫgu Egils Skalla-Grímssonar ///1v ok var þat vel";
+
+        let stats = SaveStats::compare(old, new, None, None);
+        assert_eq!(stats.words_changed, 3);
+        assert_eq!(stats.pages_touched, vec!["1r", "1v"]);
+        assert_eq!(stats.summary(), "3 words changed on pages 1r, 1v");
+
+        assert!(SaveStats::compare(old, old, None, None).is_empty());
+    }
+
+    #[test]
+    fn test_compare_counts_new_annotations() {
+        let mut old = AnnotationSet::new();
+        old.add(Annotation::lemma(0, "hér".into(), "xAV".into(), None));
+        let mut new = old.clone();
+        new.add(Annotation::lemma(1, "hefja".into(), "xVB".into(), None));
+
+        let stats = SaveStats::compare("a b", "a b", Some(&old), Some(&new));
+        assert_eq!(stats.annotations_added, 1);
+        assert_eq!(stats.summary(), "1 annotation added");
+
+        let stats = SaveStats::compare("a b", "a c", None, Some(&new));
+        assert_eq!(stats.summary(), "1 word changed; 2 annotations added");
+    }
+
+    #[test]
+    fn test_record_change_adds_resp_stmt() {
+        let mut metadata = Metadata::new();
+        record_change(&mut metadata, "2024-05-01", None, "First".to_string());
+        record_change(
+            &mut metadata,
+            "2024-05-02",
+            Some("Anna Jónsdóttir"),
+            "Second".to_string(),
+        );
+
+        assert_eq!(metadata.revisions[0].summary, "Second");
+        assert_eq!(
+            metadata.revisions[0].who.as_deref(),
+            Some("#resp-anna-jónsdóttir")
+        );
+        assert_eq!(metadata.revisions[1].who, None);
+        assert_eq!(metadata.title_stmt.resp_stmts[0].resp, "Revision");
+
+        let header = metadata.to_tei_header(false);
+        assert!(header.contains(
+            "<revisionDesc>\n      <change when=\"2024-05-02\" who=\"#resp-anna-jónsdóttir\">Second</change>\n      <change when=\"2024-05-01\">First</change>\n    </revisionDesc>"
+        ));
+    }
+
+    #[test]
+    fn test_record_change_points_at_existing_resp_stmt() {
+        let mut metadata = Metadata::new();
+        metadata.title_stmt.resp_stmts.push(RespStmt {
+            resp: "Transcription".to_string(),
+            name: "Anna Jónsdóttir".to_string(),
+            id: Some("AJ".to_string()),
+        });
+        record_change(&mut metadata, "2024-05-02", Some("Anna Jónsdóttir"), "Edit".to_string());
+
+        assert_eq!(metadata.revisions[0].who.as_deref(), Some("#AJ"));
+        assert_eq!(metadata.title_stmt.resp_stmts.len(), 1);
+    }
+}
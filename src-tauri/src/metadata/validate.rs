@@ -0,0 +1,623 @@
+//! Semantic validation of manuscript metadata.
+//!
+//! Deserialising checks only the shape of the metadata; this module checks
+//! the values that end up in the TEI header (dates, language tags, identifier
+//! checksums, cross-references) and reports each problem against the field it
+//! belongs to, using the same camelCase paths as the frontend.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{DateRange, History, Metadata, MsContents, MsItem};
+
+/// A problem with a single metadata field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataDiagnostic {
+    /// Path of the field, e.g. `history.origDate.notBefore` or `languages[1].ident`
+    pub field: String,
+    pub message: String,
+    pub is_warning: bool,
+}
+
+impl MetadataDiagnostic {
+    fn error(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+            is_warning: false,
+        }
+    }
+
+    fn warning(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+            is_warning: true,
+        }
+    }
+}
+
+/// Validate metadata, checking the required fields of the given validation
+/// schema (`"tei-p5"`, `"menota"`, `"menota-p5"`) on top of the general rules.
+pub fn validate(metadata: &Metadata, schema_id: Option<&str>) -> Vec<MetadataDiagnostic> {
+    let mut diagnostics = Vec::new();
+
+    check_required(metadata, schema_id, &mut diagnostics);
+    check_editors(metadata, &mut diagnostics);
+    check_languages(metadata, &mut diagnostics);
+
+    check_ms_contents(&metadata.ms_contents, "msContents", &mut diagnostics);
+    check_history(&metadata.history, "history", &mut diagnostics);
+    for (i, part) in metadata.ms_parts.iter().enumerate() {
+        let path = format!("msParts[{}]", i);
+        check_ms_contents(
+            &part.ms_contents,
+            &format!("{}.msContents", path),
+            &mut diagnostics,
+        );
+        check_history(
+            &part.history,
+            &format!("{}.history", path),
+            &mut diagnostics,
+        );
+    }
+    check_hand_ids(metadata, &mut diagnostics);
+
+    diagnostics
+}
+
+/// A required field's path and a check that it is filled in
+type Requirement = (&'static str, fn(&Metadata) -> bool);
+
+/// Fields a schema needs in order to produce a header it accepts
+fn required_fields(schema_id: Option<&str>) -> Vec<Requirement> {
+    match schema_id {
+        Some("menota" | "menota-p5") => vec![
+            ("msIdentifier.settlement", |m| {
+                non_empty(&m.ms_identifier.settlement)
+            }),
+            ("msIdentifier.repository", |m| {
+                non_empty(&m.ms_identifier.repository)
+            }),
+            ("msIdentifier.idno", |m| non_empty(&m.ms_identifier.idno)),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn check_required(
+    metadata: &Metadata,
+    schema_id: Option<&str>,
+    diagnostics: &mut Vec<MetadataDiagnostic>,
+) {
+    if !non_empty(&metadata.title_stmt.title) {
+        diagnostics.push(MetadataDiagnostic::warning(
+            "titleStmt.title",
+            "No title; the header will use \"Untitled\"",
+        ));
+    }
+
+    let schema = schema_id.unwrap_or("tei-p5");
+    for (field, present) in required_fields(schema_id) {
+        if !present(metadata) {
+            diagnostics.push(MetadataDiagnostic::error(
+                field,
+                format!("Required by the {} schema", schema),
+            ));
+        }
+    }
+}
+
+fn check_editors(metadata: &Metadata, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    for (i, editor) in metadata.title_stmt.editors.iter().enumerate() {
+        let Some(identifier) = editor
+            .identifier
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+        else {
+            continue;
+        };
+        let is_orcid = match editor.identifier_type.as_deref() {
+            Some(kind) => kind.eq_ignore_ascii_case("orcid"),
+            None => identifier.contains("orcid.org"),
+        };
+        if is_orcid && !is_valid_orcid(identifier) {
+            diagnostics.push(MetadataDiagnostic::error(
+                format!("titleStmt.editors[{}].identifier", i),
+                format!("\"{}\" is not a valid ORCID iD", identifier),
+            ));
+        }
+    }
+}
+
+fn check_languages(metadata: &Metadata, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    for (i, language) in metadata.languages.iter().enumerate() {
+        check_language_tag(
+            &language.ident,
+            &format!("languages[{}].ident", i),
+            diagnostics,
+        );
+    }
+
+    let total: u32 = metadata
+        .languages
+        .iter()
+        .filter_map(|l| l.usage)
+        .map(u32::from)
+        .sum();
+    if total > 100 {
+        diagnostics.push(MetadataDiagnostic::error(
+            "languages",
+            format!("Language usage adds up to {}%", total),
+        ));
+    }
+}
+
+fn check_ms_contents(contents: &MsContents, path: &str, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    if let Some(lang) = contents.text_lang.as_deref().filter(|s| !s.is_empty()) {
+        check_language_tag(lang, &format!("{}.textLang", path), diagnostics);
+    }
+    check_ms_items(&contents.items, &format!("{}.items", path), diagnostics);
+}
+
+fn check_ms_items(items: &[MsItem], path: &str, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    for (i, item) in items.iter().enumerate() {
+        let item_path = format!("{}[{}]", path, i);
+        if let Some(lang) = item.text_lang.as_deref().filter(|s| !s.is_empty()) {
+            check_language_tag(lang, &format!("{}.textLang", item_path), diagnostics);
+        }
+        check_ms_items(&item.items, &format!("{}.items", item_path), diagnostics);
+    }
+}
+
+fn check_language_tag(tag: &str, field: &str, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    if !is_bcp47(tag) {
+        diagnostics.push(MetadataDiagnostic::error(
+            field,
+            format!("\"{}\" is not a valid BCP 47 language tag", tag),
+        ));
+    }
+}
+
+fn check_history(history: &History, path: &str, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    if let Some(ref date) = history.orig_date {
+        check_date_range(date, &format!("{}.origDate", path), diagnostics);
+    }
+}
+
+fn check_date_range(date: &DateRange, path: &str, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    let mut parse = |name: &str, value: &Option<String>| {
+        let value = value.as_deref().filter(|s| !s.is_empty())?;
+        let parsed = parse_iso_date(value);
+        if parsed.is_none() {
+            diagnostics.push(MetadataDiagnostic::error(
+                format!("{}.{}", path, name),
+                format!(
+                    "\"{}\" is not an ISO date (YYYY, YYYY-MM or YYYY-MM-DD)",
+                    value
+                ),
+            ));
+        }
+        parsed
+    };
+
+    parse("when", &date.when);
+    let not_before = parse("notBefore", &date.not_before);
+    let not_after = parse("notAfter", &date.not_after);
+
+    if let (Some(from), Some(to)) = (not_before, not_after) {
+        if from.earliest() > to.latest() {
+            diagnostics.push(MetadataDiagnostic::error(
+                format!("{}.notAfter", path),
+                "notAfter is earlier than notBefore",
+            ));
+        }
+    }
+}
+
+/// `xml:id`s of hands must be unique across the whole header
+fn check_hand_ids(metadata: &Metadata, diagnostics: &mut Vec<MetadataDiagnostic>) {
+    let mut seen: HashMap<&str, String> = HashMap::new();
+    let descs = std::iter::once((String::from("physDesc"), &metadata.phys_desc)).chain(
+        metadata
+            .ms_parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| (format!("msParts[{}].physDesc", i), &part.phys_desc)),
+    );
+
+    for (path, phys_desc) in descs {
+        for (i, hand) in phys_desc.hands.iter().enumerate() {
+            let Some(id) = hand.id.as_deref().filter(|s| !s.is_empty()) else {
+                continue;
+            };
+            let field = format!("{}.hands[{}].id", path, i);
+            match seen.get(id) {
+                Some(first) => diagnostics.push(MetadataDiagnostic::error(
+                    field,
+                    format!("Hand id \"{}\" is already used by {}", id, first),
+                )),
+                None => {
+                    seen.insert(id, field);
+                }
+            }
+        }
+    }
+}
+
+fn non_empty(value: &Option<String>) -> bool {
+    value.as_deref().is_some_and(|s| !s.trim().is_empty())
+}
+
+/// An ISO 8601 date at year, month or day precision
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct IsoDate {
+    year: i32,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+impl IsoDate {
+    fn earliest(&self) -> (i32, u32, u32) {
+        (self.year, self.month.unwrap_or(1), self.day.unwrap_or(1))
+    }
+
+    fn latest(&self) -> (i32, u32, u32) {
+        let month = self.month.unwrap_or(12);
+        let day = self.day.unwrap_or_else(|| days_in_month(self.year, month));
+        (self.year, month, day)
+    }
+}
+
+/// Parse `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, with an optional leading minus
+/// for years BCE, as accepted by TEI's `@when`, `@notBefore` and `@notAfter`.
+pub(super) fn parse_iso_date(value: &str) -> Option<IsoDate> {
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let mut parts = rest.split('-');
+
+    let year = parts.next()?;
+    if year.len() < 4 || !year.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let year: i32 = year.parse().ok()?;
+    let year = if negative { -year } else { year };
+
+    let two_digits = |s: &str, max: u32| -> Option<u32> {
+        if s.len() != 2 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let n: u32 = s.parse().ok()?;
+        (1..=max).contains(&n).then_some(n)
+    };
+    let month = match parts.next() {
+        Some(s) => Some(two_digits(s, 12)?),
+        None => None,
+    };
+    let day = match (month, parts.next()) {
+        (Some(m), Some(s)) => Some(two_digits(s, days_in_month(year, m))?),
+        (_, None) => None,
+        (None, Some(_)) => return None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(IsoDate { year, month, day })
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Check the syntax of a BCP 47 language tag (RFC 5646 `langtag` or
+/// private-use tag). Subtags are not checked against the registry.
+fn is_bcp47(tag: &str) -> bool {
+    let subtags: Vec<&str> = tag.split('-').collect();
+    if subtags
+        .iter()
+        .any(|s| s.is_empty() || s.len() > 8 || !s.bytes().all(|b| b.is_ascii_alphanumeric()))
+    {
+        return false;
+    }
+
+    let alpha = |s: &str| s.bytes().all(|b| b.is_ascii_alphabetic());
+    let digit = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let is_private = |s: &str| s.eq_ignore_ascii_case("x");
+    let private_use = |rest: &[&str]| !rest.is_empty();
+
+    if is_private(subtags[0]) {
+        return private_use(&subtags[1..]);
+    }
+
+    // language: 2-3 letters with up to three extlangs, or 4-8 letters
+    let language = subtags[0];
+    if !alpha(language) || language.len() < 2 {
+        return false;
+    }
+    let mut i = 1;
+    if language.len() <= 3 {
+        let mut extlangs = 0;
+        while i < subtags.len() && extlangs < 3 && subtags[i].len() == 3 && alpha(subtags[i]) {
+            i += 1;
+            extlangs += 1;
+        }
+    }
+
+    // script
+    if i < subtags.len() && subtags[i].len() == 4 && alpha(subtags[i]) {
+        i += 1;
+    }
+    // region
+    if i < subtags.len()
+        && ((subtags[i].len() == 2 && alpha(subtags[i]))
+            || (subtags[i].len() == 3 && digit(subtags[i])))
+    {
+        i += 1;
+    }
+    // variants
+    while i < subtags.len()
+        && (subtags[i].len() >= 5
+            || (subtags[i].len() == 4 && subtags[i].as_bytes()[0].is_ascii_digit()))
+    {
+        i += 1;
+    }
+    // extensions
+    while i < subtags.len() && subtags[i].len() == 1 && !is_private(subtags[i]) {
+        let start = i + 1;
+        i = start;
+        while i < subtags.len() && subtags[i].len() >= 2 {
+            i += 1;
+        }
+        if i == start {
+            return false;
+        }
+    }
+    // private use
+    if i < subtags.len() && is_private(subtags[i]) {
+        return private_use(&subtags[i + 1..]);
+    }
+
+    i == subtags.len()
+}
+
+/// Check an ORCID iD, bare or as an `orcid.org` URL, including its
+/// ISO 7064 MOD 11-2 check character.
+fn is_valid_orcid(identifier: &str) -> bool {
+    let id = identifier.trim();
+    let id = id
+        .strip_prefix("https://orcid.org/")
+        .or_else(|| id.strip_prefix("http://orcid.org/"))
+        .or_else(|| id.strip_prefix("orcid.org/"))
+        .unwrap_or(id);
+
+    let groups: Vec<&str> = id.split('-').collect();
+    if groups.len() != 4 || groups.iter().any(|g| g.len() != 4) {
+        return false;
+    }
+    let chars: Vec<char> = groups.concat().chars().collect();
+    let (body, check) = chars.split_at(15);
+    if !body.iter().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let total = body
+        .iter()
+        .map(|c| c.to_digit(10).unwrap())
+        .fold(0, |total, d| (total + d) * 2);
+    let expected = match (12 - total % 11) % 11 {
+        10 => 'X',
+        n => char::from_digit(n, 10).unwrap(),
+    };
+    check[0].to_ascii_uppercase() == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{HandNote, Language, MsPart, Person, PhysDesc};
+    use crate::template::TemplateManager;
+
+    fn fields(diagnostics: &[MetadataDiagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.field.as_str()).collect()
+    }
+
+    fn titled() -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.title_stmt.title = Some("Njáls saga".to_string());
+        metadata
+    }
+
+    #[test]
+    fn test_empty_metadata_only_warns_about_title() {
+        let diagnostics = validate(&Metadata::new(), None);
+        assert_eq!(fields(&diagnostics), vec!["titleStmt.title"]);
+        assert!(diagnostics[0].is_warning);
+        assert!(validate(&titled(), Some("tei-p5")).is_empty());
+    }
+
+    #[test]
+    fn test_menota_requires_ms_identifier() {
+        let mut metadata = titled();
+        metadata.ms_identifier.repository = Some("Árni Magnússon Institute".to_string());
+
+        let diagnostics = validate(&metadata, Some("menota"));
+        assert_eq!(
+            fields(&diagnostics),
+            vec!["msIdentifier.settlement", "msIdentifier.idno"]
+        );
+        assert!(diagnostics.iter().all(|d| !d.is_warning));
+    }
+
+    #[test]
+    fn test_menota_template_schema_requires_ms_identifier() {
+        let template = TemplateManager::get_built_in("menota").unwrap();
+        let diagnostics = validate(&titled(), template.validation_schema_id.as_deref());
+        assert_eq!(
+            fields(&diagnostics),
+            vec![
+                "msIdentifier.settlement",
+                "msIdentifier.repository",
+                "msIdentifier.idno"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_date_range_checks() {
+        let mut metadata = titled();
+        metadata.history.orig_date = Some(DateRange {
+            not_before: Some("1280".to_string()),
+            not_after: Some("1270-13".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            fields(&validate(&metadata, None)),
+            vec!["history.origDate.notAfter"]
+        );
+
+        metadata.history.orig_date = Some(DateRange {
+            not_before: Some("1300".to_string()),
+            not_after: Some("1275".to_string()),
+            ..Default::default()
+        });
+        let diagnostics = validate(&metadata, None);
+        assert_eq!(fields(&diagnostics), vec!["history.origDate.notAfter"]);
+        assert!(diagnostics[0].message.contains("earlier"));
+
+        // Same year at different precision is a valid range
+        metadata.history.orig_date = Some(DateRange {
+            not_before: Some("1275".to_string()),
+            not_after: Some("1275-06-30".to_string()),
+            ..Default::default()
+        });
+        assert!(validate(&metadata, None).is_empty());
+    }
+
+    #[test]
+    fn test_parse_iso_date() {
+        assert!(parse_iso_date("1250").is_some());
+        assert!(parse_iso_date("1250-02").is_some());
+        assert!(parse_iso_date("1300-02-29").is_none());
+        assert!(parse_iso_date("1600-02-29").is_some());
+        assert!(parse_iso_date("-0044-03-15").is_some());
+        assert!(parse_iso_date("ca. 1250").is_none());
+        assert!(parse_iso_date("125").is_none());
+        assert!(parse_iso_date("1250-1-1").is_none());
+    }
+
+    #[test]
+    fn test_language_checks() {
+        let mut metadata = titled();
+        metadata.languages = vec![
+            Language {
+                ident: "non".to_string(),
+                usage: Some(80),
+                name: None,
+            },
+            Language {
+                ident: "la_LA".to_string(),
+                usage: Some(30),
+                name: None,
+            },
+        ];
+        metadata.ms_contents.text_lang = Some("non-IS".to_string());
+
+        assert_eq!(
+            fields(&validate(&metadata, None)),
+            vec!["languages[1].ident", "languages"]
+        );
+    }
+
+    #[test]
+    fn test_is_bcp47() {
+        for tag in [
+            "non",
+            "la",
+            "en-GB",
+            "sr-Latn-RS",
+            "zh-yue-HK",
+            "de-CH-1901",
+            "en-a-bbb-x-a-ccc",
+            "x-menota",
+            "non-x-norm",
+        ] {
+            assert!(is_bcp47(tag), "{} should be valid", tag);
+        }
+        for tag in [
+            "",
+            "n",
+            "la_LA",
+            "en--GB",
+            "en-a",
+            "x",
+            "non-x",
+            "toolongtag",
+        ] {
+            assert!(!is_bcp47(tag), "{} should be invalid", tag);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_hand_ids() {
+        let hand = |id: &str| HandNote {
+            id: Some(id.to_string()),
+            ..Default::default()
+        };
+        let mut metadata = titled();
+        metadata.phys_desc.hands = vec![hand("H1"), hand("H2")];
+        metadata.ms_parts.push(MsPart {
+            phys_desc: PhysDesc {
+                hands: vec![hand("H1")],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let diagnostics = validate(&metadata, None);
+        assert_eq!(
+            fields(&diagnostics),
+            vec!["msParts[0].physDesc.hands[0].id"]
+        );
+        assert!(diagnostics[0].message.contains("physDesc.hands[0].id"));
+    }
+
+    #[test]
+    fn test_orcid_checksum() {
+        assert!(is_valid_orcid("0000-0002-1825-0097"));
+        assert!(is_valid_orcid("https://orcid.org/0000-0002-1694-233X"));
+        assert!(!is_valid_orcid("0000-0002-1825-0098"));
+        assert!(!is_valid_orcid("0000-0002-1825"));
+
+        let mut metadata = titled();
+        metadata.title_stmt.editors = vec![
+            Person {
+                name: "Josiah Carberry".to_string(),
+                identifier: Some("0000-0002-1825-0097".to_string()),
+                identifier_type: Some("ORCID".to_string()),
+            },
+            Person {
+                name: "Someone".to_string(),
+                identifier: Some("https://orcid.org/0000-0002-1825-0091".to_string()),
+                identifier_type: None,
+            },
+            Person {
+                name: "Viaf".to_string(),
+                identifier: Some("12345".to_string()),
+                identifier_type: Some("viaf".to_string()),
+            },
+        ];
+        assert_eq!(
+            fields(&validate(&metadata, None)),
+            vec!["titleStmt.editors[1].identifier"]
+        );
+    }
+}
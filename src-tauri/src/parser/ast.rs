@@ -44,6 +44,11 @@ pub enum Node {
     Head(String),
     /// Normalized-only wrapper: .norm{text}
     Norm(String),
+    /// Diplomatic level override for the word it is attached to: word.dipl{text}
+    DiplOverride(String),
+    /// Normalized level override, written after the diplomatic one:
+    /// word.dipl{text}.norm{text}
+    NormOverride(String),
     /// Unclear reading: ?{text}?
     Unclear(String),
     /// Custom entity: :name:
@@ -23,20 +23,34 @@
 //! - Semantic analysis (`@ana`) from annotations
 //! - Character-level tags (`<c type="initial">`) for paleographic markup
 //! - Inline notes from word annotations
+//!
+//! ## Facsimile Links
+//!
+//! With a [`Facsimile`] (page layout from an HTR import), `<pb>` and `<lb>`
+//! get a `@facs` pointing at the surface and line zone with the same `n`.
 
 use super::ast::Node;
 use super::lexer::Lexer;
 use super::wordtokenizer::WordTokenizer;
 use crate::annotations::AnnotationSet;
 use crate::entities::EntityRegistry;
+use crate::importer::htr::Facsimile;
 use crate::normalizer::LevelDictionary;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Elements whose content the compiler renders through
+/// [`Compiler::compile_fragment_from_dsl`] (`.head{}` and `.supplied{}`).
+///
+/// Words inside them are not counted in the word index, so anything that maps
+/// `<w>` elements back to word indices must skip them too.
+pub const UNINDEXED_ELEMENTS: [&str; 2] = ["head", "supplied"];
+
 /// Configuration options for the DSL compiler.
 ///
 /// Controls output format, word wrapping, and multi-level MENOTA support.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct CompilerConfig {
     /// Wrap text content in `<w>` (word) and `<pc>` (punctuation) elements.
     /// Required for multi-level mode and lemmatization support.
@@ -67,6 +81,17 @@ pub struct LemmaMapping {
     pub normalized: Option<String>,
 }
 
+/// Part of a character-level annotation falling in one word (inclusive offsets).
+struct CharPiece {
+    start: u32,
+    end: u32,
+    open: String,
+    /// Opening tag used when the piece resumes after an element boundary,
+    /// without the linking attributes so that `xml:id` stays unique
+    reopen: String,
+    close: &'static str,
+}
+
 /// Compiles DSL input into TEI-XML output.
 ///
 /// The compiler processes an AST (optionally word-tokenized) and generates TEI-XML.
@@ -93,12 +118,21 @@ pub struct Compiler<'a> {
     lemma_mappings: HashMap<u32, LemmaMapping>,
     /// Full annotation set for semantic, paleographic, and note annotations
     annotations: Option<&'a AnnotationSet>,
+    /// Page layout for `@facs` links on page and line breaks
+    facsimile: Option<&'a Facsimile>,
+    /// `<respStmt>` ids of annotation authors, by name
+    resp_ids: HashMap<String, String>,
+    /// Whether annotation confidence is written as `<certainty>` (not every
+    /// schema allows it)
+    certainty: bool,
     /// Compiler configuration options
     config: CompilerConfig,
     /// Running line number counter (for auto_line_numbers)
     line_number: u32,
     /// Running word index counter (reset per compilation)
     word_index: u32,
+    /// `n` of the most recent page break (for facsimile line zones)
+    page_n: Option<String>,
     /// Tracks whether we're inside a page `<p>` wrapper (for wrap_pages mode)
     in_page_paragraph: bool,
 }
@@ -110,9 +144,13 @@ impl<'a> Compiler<'a> {
             dictionary: None,
             lemma_mappings: HashMap::new(),
             annotations: None,
+            facsimile: None,
+            resp_ids: HashMap::new(),
+            certainty: false,
             config: CompilerConfig::default(),
             line_number: 0,
             word_index: 0,
+            page_n: None,
             in_page_paragraph: false,
         }
     }
@@ -139,6 +177,29 @@ impl<'a> Compiler<'a> {
         self
     }
 
+    /// Set the `<respStmt>` ids that annotation `@resp` pointers use, by author
+    /// name (see [`Metadata::add_annotator_resp_stmts`]). Authors not in the map
+    /// fall back to [`resp_id`](crate::annotations::resp_id).
+    ///
+    /// [`Metadata::add_annotator_resp_stmts`]: crate::metadata::Metadata::add_annotator_resp_stmts
+    pub fn with_resp_ids(mut self, resp_ids: HashMap<String, String>) -> Self {
+        self.resp_ids = resp_ids;
+        self
+    }
+
+    /// Write annotation confidence as `<certainty>` inside `<w>`. Off by
+    /// default; MENOTA schemas do not allow the element.
+    pub fn with_certainty(mut self, certainty: bool) -> Self {
+        self.certainty = certainty;
+        self
+    }
+
+    /// Set the page layout that `<pb>` and `<lb>` link to with `@facs`
+    pub fn with_facsimile(mut self, facsimile: &'a Facsimile) -> Self {
+        self.facsimile = Some(facsimile);
+        self
+    }
+
     pub fn with_config(mut self, config: CompilerConfig) -> Self {
         self.config = config;
         self
@@ -168,6 +229,7 @@ impl<'a> Compiler<'a> {
         // Reset counters for each compilation
         self.line_number = 0;
         self.word_index = 0;
+        self.page_n = None;
         self.in_page_paragraph = false;
 
         let mut output = String::new();
@@ -188,15 +250,23 @@ impl<'a> Compiler<'a> {
             Node::Text(text) => self.escape_xml(text),
             Node::LineBreak(n) => {
                 self.line_number += 1;
+                let n = match n {
+                    Some(num) => Some(num.clone()),
+                    None if self.config.auto_line_numbers => Some(self.line_number.to_string()),
+                    None => None,
+                };
                 match n {
-                    Some(num) => format!("<lb n=\"{}\"/>\n", self.escape_xml(num)),
-                    None if self.config.auto_line_numbers => {
-                        format!("<lb n=\"{}\"/>\n", self.line_number)
-                    }
+                    Some(num) => format!(
+                        "<lb n=\"{}\"{}/>\n",
+                        self.escape_xml(&num),
+                        self.line_facs(&num)
+                    ),
                     None => "<lb/>\n".to_string(),
                 }
             }
             Node::PageBreak(n) => {
+                self.page_n = Some(n.clone());
+                let pb = format!("<pb n=\"{}\"{}/>\n", self.escape_xml(n), self.page_facs(n));
                 let mut result = String::new();
                 if self.config.wrap_pages {
                     // Close previous page paragraph if open
@@ -204,12 +274,12 @@ impl<'a> Compiler<'a> {
                         result.push_str("</p>\n");
                     }
                     // Output page break
-                    result.push_str(&format!("<pb n=\"{}\"/>\n", self.escape_xml(n)));
+                    result.push_str(&pb);
                     // Start new page paragraph
                     result.push_str("<p>\n");
                     self.in_page_paragraph = true;
                 } else {
-                    result.push_str(&format!("<pb n=\"{}\"/>\n", self.escape_xml(n)));
+                    result.push_str(&pb);
                 }
                 result
             }
@@ -246,6 +316,8 @@ impl<'a> Compiler<'a> {
                 format!("<head>{}</head>", content)
             }
             Node::Norm(text) => self.compile_normalized_fragment(text),
+            // Level overrides only apply to multi-level word output
+            Node::DiplOverride(_) | Node::NormOverride(_) => String::new(),
             Node::Unclear(text) => format!("<unclear>{}</unclear>", self.escape_xml(text)),
             Node::Entity(name) => self.compile_entity(name),
             Node::WordContinuation => String::new(), // Consumed by word tokenizer
@@ -291,7 +363,9 @@ impl<'a> Compiler<'a> {
             // Lookup by word INDEX (only confirmed instances have mappings)
             let lemma_attrs = self.get_lemma_attributes_by_index(current_index);
             let ann_attrs = self.get_annotation_attributes(current_index);
-            let notes = self.get_note_elements(current_index);
+            let notes = self.get_note_elements(current_index)
+                + &self.get_certainty_elements(current_index);
+            let content = self.inject_character_tags(&content, current_index);
 
             format!("<w{}{}>{}{}</w>\n", lemma_attrs, ann_attrs, content, notes)
         }
@@ -304,20 +378,27 @@ impl<'a> Compiler<'a> {
     /// - **Diplomatic**: Entities resolved, abbreviations expanded, combining marks removed
     /// - **Normalized**: Full character normalization applied
     ///
-    /// Also injects character-level `<c>` tags from paleographic annotations.
+    /// Also injects character-level `<c>`, `<damage>` and `<unclear>` tags from
+    /// paleographic annotations into the facsimile level.
     fn compile_word_multi_level(&mut self, children: &[Node]) -> String {
+        let (dipl_override, norm_override) = level_overrides(children);
         let facs = self.nodes_to_facs(children);
-        let dipl = self.nodes_to_diplomatic(children);
+        let dipl = match dipl_override {
+            Some(text) => self.compile_level_override(text),
+            None => self.nodes_to_diplomatic(children),
+        };
 
         // Get current word index and increment
         let current_index = self.word_index;
         self.word_index += 1;
 
-        // Check if we have a user-provided normalized form (by index)
-        let norm = if let Some(stored_norm) = self.get_stored_normalized_by_index(current_index) {
+        // An explicit .norm{} override wins over a user-provided normalized
+        // form (by index), which wins over auto-generated normalization
+        let norm = if let Some(text) = norm_override {
+            self.compile_level_override(text)
+        } else if let Some(stored_norm) = self.get_stored_normalized_by_index(current_index) {
             self.escape_xml(&stored_norm)
         } else {
-            // Fall back to auto-generated normalization
             self.nodes_to_normalized(children)
         };
 
@@ -327,7 +408,8 @@ impl<'a> Compiler<'a> {
             // Lookup by word INDEX (only confirmed instances have mappings)
             let lemma_attrs = self.get_lemma_attributes_by_index(current_index);
             let ann_attrs = self.get_annotation_attributes(current_index);
-            let notes = self.get_note_elements(current_index);
+            let notes = self.get_note_elements(current_index)
+                + &self.get_certainty_elements(current_index);
 
             // Inject character annotations into facsimile level
             let facs_with_chars = self.inject_character_tags(&facs, current_index);
@@ -339,21 +421,28 @@ impl<'a> Compiler<'a> {
         }
     }
 
-    /// Injects `<c>` tags for character annotations into compiled XML.
+    /// Injects `<c>`, `<damage>` and `<unclear>` tags for character-level
+    /// annotations into compiled XML.
     ///
     /// This algorithm walks the XML string, maintaining a "visual index" that
     /// counts displayable characters while skipping over XML tags. Entity references
     /// like `&eth;` are treated as single characters to align with the frontend's
-    /// visual character indexing.
+    /// visual character indexing. Text inside `<expan>` is not on the page, so it
+    /// is neither counted nor tagged.
     ///
     /// # Algorithm
     ///
-    /// 1. Collect character annotations for the word (type=initial, capital, etc.)
+    /// 1. Collect the pieces of character annotations falling in this word. A
+    ///    range crossing word boundaries contributes one piece per word, running
+    ///    to the end of every word except the last.
     /// 2. Walk the XML string character by character:
-    ///    - Skip `<...>` tag content (don't increment visual index)
+    ///    - Skip `<...>` tag content (don't increment visual index), closing
+    ///      open pieces before the tag and reopening them at the next character
+    ///    - Copy `<expan>` content through unchanged
     ///    - Treat `&...;` entities as single characters
-    ///    - At each visual index, check if any annotation starts/ends
-    ///    - Insert `<c type="...">` and `</c>` tags accordingly
+    ///    - At each visual index, check if any piece starts/ends
+    ///    - Insert opening and closing tags accordingly
+    /// 3. Close pieces still open at the end of the word
     ///
     /// # Example
     ///
@@ -361,47 +450,76 @@ impl<'a> Compiler<'a> {
     /// - Input: `M&eth;r`
     /// - Output: `<c type="initial">M</c>&eth;r`
     fn inject_character_tags(&self, xml: &str, word_index: u32) -> String {
-        use crate::annotations::{AnnotationType, AnnotationValue, MenotaObservationType};
-        
         let Some(ann_set) = self.annotations else {
             return xml.to_string();
         };
 
-        // Collect character annotations for this word
-        let mut char_anns = Vec::new();
-        for ann in ann_set.for_word(word_index) {
-            if let (
-                AnnotationType::Paleographic,
-                crate::annotations::AnnotationTarget::Character { char_start, char_end, .. },
-                AnnotationValue::MenotaPaleographic { observation_type: MenotaObservationType::Character, char_type: Some(ctype), .. }
-            ) = (&ann.annotation_type, &ann.target, &ann.value) {
-                char_anns.push((*char_start, *char_end, ctype));
-            }
-        }
+        // Collect character annotation pieces for this word
+        let mut char_anns: Vec<CharPiece> = ann_set
+            .active_for_word(word_index)
+            .into_iter()
+            .filter_map(|ann| self.character_piece(ann, word_index))
+            .collect();
 
         if char_anns.is_empty() {
             return xml.to_string();
         }
 
-        // Sort by start index
-        char_anns.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        // Sort by start index (longer pieces first so they nest outside)
+        char_anns.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
 
         let mut result = String::with_capacity(xml.len() + char_anns.len() * 30);
         let mut text_idx = 0;
+        let mut expan_depth = 0;
+        let mut suspended = false;
         let mut chars = xml.chars().peekable();
         
         while let Some(c) = chars.next() {
             // Check for tag start
             if c == '<' {
-                result.push(c);
+                let mut tag = String::from(c);
                 // Consume until '>'
                 for tc in chars.by_ref() {
-                    result.push(tc);
+                    tag.push(tc);
                     if tc == '>' { break; }
                 }
+                if tag.ends_with("/>") {
+                    result.push_str(&tag);
+                    continue;
+                }
+
+                // Pieces must not straddle element boundaries: close the open ones
+                // before the tag, to be reopened at the next counted character
+                if !suspended {
+                    for piece in char_anns.iter().rev() {
+                        if piece.start < text_idx && piece.end >= text_idx {
+                            result.push_str(piece.close);
+                        }
+                    }
+                    suspended = true;
+                }
+                if tag.starts_with("<expan") {
+                    expan_depth += 1;
+                } else if tag == "</expan>" {
+                    expan_depth -= 1;
+                }
+                result.push_str(&tag);
                 continue;
             }
 
+            if expan_depth > 0 {
+                result.push(c);
+                continue;
+            }
+            if suspended {
+                for piece in &char_anns {
+                    if piece.start < text_idx && piece.end >= text_idx {
+                        result.push_str(&piece.reopen);
+                    }
+                }
+                suspended = false;
+            }
+
             // Check for entity start
             if c == '&' {
                 // Collect entity
@@ -423,20 +541,88 @@ impl<'a> Compiler<'a> {
             text_idx += 1;
         }
 
-        // Close any tags that go beyond the text length (robustness)
-        // We find annotations that were opened (start < text_idx) but not closed (end >= text_idx)
-        let mut unclosed: Vec<_> = char_anns.iter()
-            .filter(|(start, end, _)| *start < text_idx && *end >= text_idx)
+        // Close pieces that go beyond the text length: ranges continuing into the
+        // next word, or out-of-bounds offsets. Close in reverse order of opening
+        // to keep proper nesting.
+        for piece in char_anns.iter().rev().filter(|_| !suspended) {
+            if piece.start < text_idx && piece.end >= text_idx {
+                result.push_str(piece.close);
+            }
+        }
+
+        result
+    }
+
+    /// Linking attributes for a piece of a range spanning several words.
+    ///
+    /// Each piece gets an `xml:id` derived from the annotation id and the word
+    /// index, and points to its neighbours with `@prev` and `@next`, so the pieces
+    /// can be read as one element.
+    fn piece_links(ann: &crate::annotations::Annotation, word_index: u32) -> String {
+        let crate::annotations::AnnotationTarget::CharacterRange { start_word, end_word, .. } =
+            ann.target
+        else {
+            return String::new();
+        };
+        if start_word == end_word {
+            return String::new();
+        }
+
+        // xml:id values must be XML names
+        let mut base: String = ann
+            .id
+            .chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '.') { c } else { '_' })
             .collect();
-        
-        // Sort to close inner-most first (reverse of start)
-        unclosed.sort_by(|a, b| b.0.cmp(&a.0));
+        if !base.starts_with(|c: char| c.is_alphabetic()) {
+            base.insert_str(0, "ann-");
+        }
 
-        for _ in unclosed {
-            result.push_str("</c>");
+        let mut links = format!(" xml:id=\"{}-{}\"", base, word_index);
+        if word_index > start_word {
+            links.push_str(&format!(" prev=\"#{}-{}\"", base, word_index - 1));
         }
+        if word_index < end_word {
+            links.push_str(&format!(" next=\"#{}-{}\"", base, word_index + 1));
+        }
+        links
+    }
 
-        result
+    /// The tag piece a character-level annotation contributes to `word_index`.
+    fn character_piece(&self, ann: &crate::annotations::Annotation, word_index: u32) -> Option<CharPiece> {
+        use crate::annotations::{AnnotationType, AnnotationValue, MenotaCharType, MenotaObservationType, PaleographicType};
+
+        if ann.annotation_type != AnnotationType::Paleographic {
+            return None;
+        }
+        let (start, end) = ann.target.char_range_in_word(word_index)?;
+
+        let (tag, close) = match &ann.value {
+            AnnotationValue::MenotaPaleographic {
+                observation_type: MenotaObservationType::Character,
+                char_type: Some(ctype),
+                ..
+            } => {
+                let type_str = match ctype {
+                    MenotaCharType::Initial => "initial",
+                    MenotaCharType::Capital => "capital",
+                    MenotaCharType::Rubric => "rubric",
+                    MenotaCharType::Colored => "colored",
+                };
+                (format!("<c type=\"{}\"", type_str), "</c>")
+            }
+            AnnotationValue::Paleographic { observation_type: PaleographicType::Damage, .. } => {
+                ("<damage".to_string(), "</damage>")
+            }
+            AnnotationValue::Paleographic { observation_type: PaleographicType::Unclear, .. } => {
+                ("<unclear".to_string(), "</unclear>")
+            }
+            _ => return None,
+        };
+        let open = format!("{}{}>", tag, Self::piece_links(ann, word_index));
+        let reopen = format!("{}>", tag);
+
+        Some(CharPiece { start, end, open, reopen, close })
     }
 
     fn process_char_injection(
@@ -444,20 +630,12 @@ impl<'a> Compiler<'a> {
         result: &mut String, 
         content: &str, 
         text_idx: u32, 
-        anns: &[(u32, u32, &crate::annotations::MenotaCharType)]
+        anns: &[CharPiece]
     ) {
-        use crate::annotations::MenotaCharType;
-        
         // Check for starts
-        for (start, _end, ctype) in anns {
-            if *start == text_idx {
-                let type_str = match ctype {
-                    MenotaCharType::Initial => "initial",
-                    MenotaCharType::Capital => "capital",
-                    MenotaCharType::Rubric => "rubric",
-                    MenotaCharType::Colored => "colored",
-                };
-                result.push_str(&format!("<c type=\"{}\">", type_str));
+        for piece in anns {
+            if piece.start == text_idx {
+                result.push_str(&piece.open);
             }
         }
 
@@ -466,9 +644,9 @@ impl<'a> Compiler<'a> {
         // Check for ends (inclusive end index)
         // Close in reverse order of opening to ensure proper XML nesting
         // (anns is sorted by start asc, so we iterate rev)
-        for (_start, end, _) in anns.iter().rev() {
-            if *end == text_idx {
-                result.push_str("</c>");
+        for piece in anns.iter().rev() {
+            if piece.end == text_idx {
+                result.push_str(piece.close);
             }
         }
     }
@@ -501,8 +679,17 @@ impl<'a> Compiler<'a> {
 
         let mut attrs = String::new();
         let mut ana_values = Vec::new();
+        let mut resp_values: Vec<String> = Vec::new();
+
+        for ann in ann_set.active_for_word(word_index) {
+            // Annotation authorship points at the author's <respStmt> in the header
+            if let Some(author) = ann.metadata.as_ref().and_then(|m| m.author.as_deref()) {
+                let pointer = self.resp_pointer(author);
+                if !resp_values.contains(&pointer) {
+                    resp_values.push(pointer);
+                }
+            }
 
-        for ann in ann_set.for_word(word_index) {
             match (&ann.annotation_type, &ann.value) {
                 (AnnotationType::Semantic, AnnotationValue::Semantic { category, subcategory, .. }) => {
                     // Add semantic category to @ana attribute
@@ -594,7 +781,9 @@ impl<'a> Compiler<'a> {
                                 attrs.push_str(&format!(" reason=\"{:?}\"", reason).to_lowercase());
                             }
                             if let Some(r) = resp {
-                                attrs.push_str(&format!(" resp=\"{}\"", r));
+                                if !resp_values.contains(r) {
+                                    resp_values.push(r.clone());
+                                }
                             }
                         }
                         MenotaObservationType::Character => {
@@ -610,9 +799,55 @@ impl<'a> Compiler<'a> {
             attrs.push_str(&format!(" ana=\"{}\"", ana_values.join(" ")));
         }
 
+        if !resp_values.is_empty() {
+            attrs.push_str(&format!(" resp=\"{}\"", self.escape_xml(&resp_values.join(" "))));
+        }
+
         attrs
     }
 
+    /// Get `<certainty>` elements preserving numeric annotation confidence.
+    ///
+    /// The annotation's `confidence` takes precedence over a paleographic
+    /// observation's own `certainty`. Each element points at the attribute it
+    /// qualifies via `@match`; notes and other annotations without an attribute
+    /// on `<w>` are skipped. Nothing is written unless enabled with
+    /// [`Compiler::with_certainty`].
+    fn get_certainty_elements(&self, word_index: u32) -> String {
+        use crate::annotations::AnnotationValue;
+
+        let Some(ann_set) = self.annotations.filter(|_| self.certainty) else {
+            return String::new();
+        };
+
+        let mut elements = String::new();
+
+        for ann in ann_set.active_for_word(word_index) {
+            let (match_attr, value_certainty) = match &ann.value {
+                AnnotationValue::Lemma { .. } => ("@lemma", None),
+                AnnotationValue::Semantic { .. } => ("@ana", None),
+                AnnotationValue::Paleographic { certainty, .. }
+                | AnnotationValue::MenotaPaleographic { certainty, .. } => ("@ana", *certainty),
+                _ => continue,
+            };
+            let metadata = ann.metadata.as_ref();
+            let Some(degree) = metadata.and_then(|m| m.confidence).or(value_certainty) else {
+                continue;
+            };
+
+            let resp = metadata
+                .and_then(|m| m.author.as_deref())
+                .map(|author| format!(" resp=\"{}\"", self.escape_xml(&self.resp_pointer(author))))
+                .unwrap_or_default();
+            elements.push_str(&format!(
+                "<certainty locus=\"value\" match=\"{}\" degree=\"{}\"{}/>",
+                match_attr, degree, resp
+            ));
+        }
+
+        elements
+    }
+
     /// Get note annotations as TEI <note> elements
     fn get_note_elements(&self, word_index: u32) -> String {
         use crate::annotations::{AnnotationType, AnnotationValue};
@@ -623,7 +858,7 @@ impl<'a> Compiler<'a> {
 
         let mut notes = String::new();
 
-        for ann in ann_set.for_word(word_index) {
+        for ann in ann_set.active_for_word(word_index) {
             if let (AnnotationType::Note, AnnotationValue::Note { text, category }) =
                 (&ann.annotation_type, &ann.value)
             {
@@ -865,6 +1100,38 @@ impl<'a> Compiler<'a> {
         }
     }
 
+    /// Facsimile, diplomatic and normalized content of a word, honouring any
+    /// `.dipl{}` / `.norm{}` overrides among its children.
+    fn word_levels(&self, children: &[Node]) -> (String, String, String) {
+        let (dipl_override, norm_override) = level_overrides(children);
+        let facs = self.nodes_to_facs(children);
+        let dipl = match dipl_override {
+            Some(text) => self.compile_level_override(text),
+            None => self.nodes_to_diplomatic(children),
+        };
+        let norm = match norm_override {
+            Some(text) => self.compile_level_override(text),
+            None => self.nodes_to_normalized(children),
+        };
+        (facs, dipl, norm)
+    }
+
+    /// Compiles the text of a level override as-is: no normalization is
+    /// applied, only `:name:` entity references are resolved.
+    fn compile_level_override(&self, text: &str) -> String {
+        let Ok(doc) = Lexer::new(text).parse() else {
+            return self.escape_xml(text);
+        };
+        doc.nodes
+            .iter()
+            .map(|node| match node {
+                Node::Entity(name) => self.compile_entity(name),
+                Node::Text(text) => self.escape_xml(text),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
     fn normalize_text(&self, text: &str) -> String {
         if let Some(dict) = self.dictionary {
             dict.normalize_text(text)
@@ -873,6 +1140,34 @@ impl<'a> Compiler<'a> {
         }
     }
 
+    /// `#id` of an annotation author's `<respStmt>`
+    fn resp_pointer(&self, author: &str) -> String {
+        match self.resp_ids.get(author) {
+            Some(id) => format!("#{}", id),
+            None => format!("#{}", crate::annotations::resp_id(author)),
+        }
+    }
+
+    /// ` facs="#..."` for the surface of page `n`, if there is one
+    fn page_facs(&self, n: &str) -> String {
+        match self.facsimile.and_then(|f| f.surface(n)) {
+            Some(surface) => format!(" facs=\"#{}\"", self.escape_xml(&surface.id)),
+            None => String::new(),
+        }
+    }
+
+    /// ` facs="#..."` for the zone of line `n` on the current page, if there is one
+    fn line_facs(&self, n: &str) -> String {
+        let zone = self
+            .facsimile
+            .zip(self.page_n.as_deref())
+            .and_then(|(f, page_n)| f.line_zone(page_n, n));
+        match zone {
+            Some(zone) => format!(" facs=\"#{}\"", self.escape_xml(&zone.id)),
+            None => String::new(),
+        }
+    }
+
     fn escape_xml(&self, s: &str) -> String {
         s.replace('&', "&amp;")
             .replace('<', "&lt;")
@@ -904,9 +1199,7 @@ impl<'a> Compiler<'a> {
         for node in &nodes {
             if let Node::Word(children) = node {
                 // Generate the three levels
-                let facs = self.nodes_to_facs(children);
-                let dipl = self.nodes_to_diplomatic(children);
-                let norm = self.nodes_to_normalized(children);
+                let (facs, dipl, norm) = self.word_levels(children);
 
                 // Format attributes from provided map
                 let mut attr_str = String::new();
@@ -1046,9 +1339,7 @@ impl<'a> Compiler<'a> {
         for node in &nodes {
             match node {
                 Node::Word(children) => {
-                    let facs = self.nodes_to_facs(children);
-                    let dipl = self.nodes_to_diplomatic(children);
-                    let norm = self.nodes_to_normalized(children);
+                    let (facs, dipl, norm) = self.word_levels(children);
 
                     if !facs.is_empty() || !dipl.is_empty() || !norm.is_empty() {
                         output.push_str(&format!(
@@ -1087,6 +1378,20 @@ impl<'a> Compiler<'a> {
     }
 }
 
+/// The `.dipl{}` and `.norm{}` override texts of a word, if present
+fn level_overrides(children: &[Node]) -> (Option<&str>, Option<&str>) {
+    let mut dipl = None;
+    let mut norm = None;
+    for child in children {
+        match child {
+            Node::DiplOverride(text) => dipl = Some(text.as_str()),
+            Node::NormOverride(text) => norm = Some(text.as_str()),
+            _ => {}
+        }
+    }
+    (dipl, norm)
+}
+
 impl Default for Compiler<'_> {
     fn default() -> Self {
         Self::new()
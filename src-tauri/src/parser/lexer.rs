@@ -102,12 +102,28 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            // Normalized-only wrapper: .norm{text}
+            // Normalized-only wrapper: .norm{text}, or the normalized level
+            // override when it directly follows a .dipl{text} override
             if remaining.starts_with(".norm{") {
+                let is_override =
+                    text_buf.is_empty() && matches!(doc.nodes.last(), Some(Node::DiplOverride(_)));
                 self.flush_text(&mut doc, &mut text_buf);
                 self.pos += 6;
                 let text = self.consume_braced_block()?;
-                doc.push(Node::Norm(text));
+                doc.push(if is_override {
+                    Node::NormOverride(text)
+                } else {
+                    Node::Norm(text)
+                });
+                continue;
+            }
+
+            // Diplomatic level override: word.dipl{text}
+            if remaining.starts_with(".dipl{") {
+                self.flush_text(&mut doc, &mut text_buf);
+                self.pos += 6;
+                let text = self.consume_braced_block()?;
+                doc.push(Node::DiplOverride(text));
                 continue;
             }
 
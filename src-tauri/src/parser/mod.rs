@@ -40,6 +40,7 @@
 //! | `?{txt}?` | `<unclear>txt</unclear>` | Unclear reading |
 //! | `^{txt}` | `<note>txt</note>` | Editorial note |
 //! | `:name:` | `&name;` | Entity reference |
+//! | `w.dipl{d}.norm{n}` | `<me:dipl>d</me:dipl><me:norm>n</me:norm>` | Level overrides for a word |
 //! | `~//` | Word continuation across line break | |
 //! | `upp~haf` | Compound word join | |
 //! | `\|` | Explicit word boundary | |
@@ -80,7 +81,7 @@ mod wordtokenizer;
 #[cfg(test)]
 mod tests;
 
-pub use compiler::{Compiler, CompilerConfig, LemmaMapping};
+pub use compiler::{Compiler, CompilerConfig, LemmaMapping, UNINDEXED_ELEMENTS};
 
 // Re-export for use by importer/patching
 pub use ast::Node;
@@ -91,6 +91,17 @@ fn test_lexer_norm_wrapper() {
     assert!(matches!(&doc.nodes[0], Node::Norm(t) if t == ","));
 }
 
+#[test]
+fn test_lexer_level_overrides() {
+    let mut lexer = Lexer::new("kononge.dipl{konongi}.norm{konungi}.norm{,}");
+    let doc = lexer.parse().unwrap();
+    assert_eq!(doc.nodes.len(), 4);
+    assert!(matches!(&doc.nodes[1], Node::DiplOverride(t) if t == "konongi"));
+    assert!(matches!(&doc.nodes[2], Node::NormOverride(t) if t == "konungi"));
+    // A .norm{} not following a .dipl{} override stays normalized-only content
+    assert!(matches!(&doc.nodes[3], Node::Norm(t) if t == ","));
+}
+
 #[test]
 fn test_lexer_deletion() {
     let mut lexer = Lexer::new("-{removed}-");
@@ -343,6 +354,24 @@ fn test_compiler_norm_wrapper() {
     assert!(result.contains("<me:norm>,</me:norm>"));
 }
 
+#[test]
+fn test_compiler_level_overrides() {
+    let config = CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    };
+    let mut compiler = Compiler::new().with_config(config);
+    let result = compiler
+        .compile("kononge.dipl{konongi}.norm{konungi} ok")
+        .unwrap();
+    assert!(result.contains("<me:facs>kononge</me:facs>"));
+    assert!(result.contains("<me:dipl>konongi</me:dipl>"));
+    assert!(result.contains("<me:norm>konungi</me:norm>"));
+    assert!(result.contains("<me:dipl>ok</me:dipl>"));
+}
+
 #[test]
 fn test_compiler_deletion() {
     let mut compiler = Compiler::new();
@@ -949,6 +978,115 @@ fn test_compiler_annotation_multiple_on_word() {
     assert!(result.contains("<note>A note</note>"));
 }
 
+#[test]
+fn test_compiler_annotation_resp_and_certainty() {
+    use crate::annotations::{
+        Annotation, AnnotationMetadata, AnnotationSet, AnnotationTarget, ReviewStatus,
+    };
+
+    let config = CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: false,
+        wrap_pages: false,
+    };
+
+    let mut semantic = Annotation::semantic(
+        AnnotationTarget::Word { word_index: 0 },
+        "place".to_string(),
+        None,
+        None,
+    );
+    semantic.metadata = Some(AnnotationMetadata {
+        author: Some("Anna Jónsdóttir".to_string()),
+        confidence: Some(0.85),
+        ..Default::default()
+    });
+    let mut rejected = Annotation::semantic(
+        AnnotationTarget::Word { word_index: 0 },
+        "person".to_string(),
+        None,
+        None,
+    );
+    rejected.metadata = Some(AnnotationMetadata {
+        author: Some("Bjorn".to_string()),
+        status: Some(ReviewStatus::Rejected),
+        ..Default::default()
+    });
+
+    let ann_set = AnnotationSet {
+        version: "1.0".to_string(),
+        annotations: vec![semantic, rejected],
+    };
+
+    let mut compiler = Compiler::new()
+        .with_config(config.clone())
+        .with_annotations(&ann_set)
+        .with_certainty(true);
+    let result = compiler.compile("Nidaros").unwrap();
+
+    assert!(result.contains("ana=\"#place\""), "Got: {}", result);
+    assert!(result.contains("resp=\"#resp-anna-jónsdóttir\""), "Got: {}", result);
+    assert!(
+        result.contains(
+            "<certainty locus=\"value\" match=\"@ana\" degree=\"0.85\" resp=\"#resp-anna-jónsdóttir\"/>"
+        ),
+        "Got: {}",
+        result
+    );
+    // Rejected annotations are not compiled
+    assert!(!result.contains("#person"));
+    assert!(!result.contains("resp-bjorn"));
+
+    // Only written where the schema allows it
+    let mut compiler = Compiler::new().with_config(config).with_annotations(&ann_set);
+    let result = compiler.compile("Nidaros").unwrap();
+    assert!(!result.contains("<certainty"), "Got: {}", result);
+}
+
+#[test]
+fn test_compiler_resp_points_at_header_ids() {
+    use crate::annotations::{Annotation, AnnotationMetadata, AnnotationSet, AnnotationTarget};
+    use crate::metadata::{Metadata, RespStmt};
+
+    let config = CompilerConfig {
+        word_wrap: true,
+        ..Default::default()
+    };
+    let mut semantic = Annotation::semantic(
+        AnnotationTarget::Word { word_index: 0 },
+        "place".to_string(),
+        None,
+        None,
+    );
+    semantic.metadata = Some(AnnotationMetadata {
+        author: Some("Anna Jónsdóttir".to_string()),
+        ..Default::default()
+    });
+    let ann_set = AnnotationSet {
+        version: "1.0".to_string(),
+        annotations: vec![semantic],
+    };
+
+    // An imported header already has a statement for the author
+    let mut metadata = Metadata::new();
+    metadata.title_stmt.resp_stmts.push(RespStmt {
+        resp: "Transcription".to_string(),
+        name: "Anna Jónsdóttir".to_string(),
+        id: Some("AJ".to_string()),
+    });
+    let resp_ids = metadata.add_annotator_resp_stmts(&ann_set.authors());
+
+    let mut compiler = Compiler::new()
+        .with_config(config)
+        .with_annotations(&ann_set)
+        .with_resp_ids(resp_ids);
+    let result = compiler.compile("Nidaros").unwrap();
+
+    assert!(result.contains("resp=\"#AJ\""), "Got: {}", result);
+    assert!(metadata.to_tei_header(false).contains(r#"<respStmt xml:id="AJ">"#));
+}
+
 #[test]
 fn test_compiler_menota_character_annotation() {
     use crate::annotations::{
@@ -1150,3 +1288,118 @@ fn test_compiler_menota_character_annotation_out_of_bounds() {
     // Should contain <c type="initial">H</c> - tag must be closed!
     assert!(result.contains(r#"<c type="initial">H</c>"#));
 }
+
+#[test]
+fn test_compiler_character_range_across_words() {
+    use crate::annotations::{
+        Annotation, AnnotationSet, AnnotationTarget, AnnotationType, AnnotationValue,
+        PaleographicType,
+    };
+
+    let config = CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: true,
+        wrap_pages: false,
+    };
+
+    // Damage from "r" in "konungr" through "ok" up to "s" in "saga"
+    let ann_set = AnnotationSet {
+        version: "1.0".to_string(),
+        annotations: vec![Annotation {
+            id: "damage-1".to_string(),
+            annotation_type: AnnotationType::Paleographic,
+            target: AnnotationTarget::CharacterRange {
+                start_word: 0,
+                start_char: 6,
+                end_word: 2,
+                end_char: 0,
+            },
+            value: AnnotationValue::Paleographic {
+                observation_type: PaleographicType::Damage,
+                description: None,
+                certainty: None,
+            },
+            metadata: None,
+        }],
+    };
+
+    let mut compiler = Compiler::new()
+        .with_config(config)
+        .with_annotations(&ann_set);
+    let result = compiler.compile("konungr ok saga").unwrap();
+
+    // One <damage> per word, linked into a chain
+    assert!(
+        result.contains(
+            "<me:facs>konung<damage xml:id=\"damage-1-0\" next=\"#damage-1-1\">r</damage></me:facs>"
+        ),
+        "Got: {}",
+        result
+    );
+    assert!(
+        result.contains(
+            "<me:facs><damage xml:id=\"damage-1-1\" prev=\"#damage-1-0\" next=\"#damage-1-2\">ok</damage></me:facs>"
+        ),
+        "Got: {}",
+        result
+    );
+    assert!(
+        result.contains(
+            "<me:facs><damage xml:id=\"damage-1-2\" prev=\"#damage-1-1\">s</damage>aga</me:facs>"
+        ),
+        "Got: {}",
+        result
+    );
+    // The words keep their @ana as well
+    assert_eq!(result.matches("ana=\"#paleo:damage\"").count(), 3, "Got: {}", result);
+}
+
+#[test]
+fn test_compiler_character_damage_single_level() {
+    use crate::annotations::{
+        Annotation, AnnotationSet, AnnotationTarget, AnnotationType, AnnotationValue,
+        PaleographicType,
+    };
+
+    let config = CompilerConfig {
+        word_wrap: true,
+        auto_line_numbers: false,
+        multi_level: false,
+        wrap_pages: false,
+    };
+
+    // Damage on "ungr" in "k(onung)r": the expansion is not counted
+    let ann_set = AnnotationSet {
+        version: "1.0".to_string(),
+        annotations: vec![Annotation {
+            id: "damage-1".to_string(),
+            annotation_type: AnnotationType::Paleographic,
+            target: AnnotationTarget::Character {
+                word_index: 0,
+                char_start: 1,
+                char_end: 2,
+            },
+            value: AnnotationValue::Paleographic {
+                observation_type: PaleographicType::Damage,
+                description: None,
+                certainty: None,
+            },
+            metadata: None,
+        }],
+    };
+
+    let mut compiler = Compiler::new()
+        .with_config(config)
+        .with_annotations(&ann_set);
+    let result = compiler.compile(".abbr[kr]{konungr}s").unwrap();
+
+    assert!(
+        result.contains(
+            "<w ana=\"#paleo:damage\"><choice><abbr>k<damage>r</damage></abbr>\
+             <expan>konungr</expan></choice><damage>s</damage></w>"
+        ),
+        "Got: {}",
+        result
+    );
+}
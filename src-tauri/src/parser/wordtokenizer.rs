@@ -163,7 +163,9 @@ impl WordTokenizer {
                 | Node::Addition(_)
                 | Node::Note(_)
                 | Node::Unclear(_)
-                | Node::Entity(_) => {
+                | Node::Entity(_)
+                | Node::DiplOverride(_)
+                | Node::NormOverride(_) => {
                     if state == State::BetweenWords {
                         state = State::InWord;
                     }
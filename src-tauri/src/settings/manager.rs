@@ -13,6 +13,7 @@
 //!
 //! All settings have sensible defaults to ensure the app works out of the box.
 
+use crate::importer::plaintext::PlainTextProfile;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -42,6 +43,24 @@ pub struct Settings {
     /// Currently selected XSLT stylesheet ID (default: "default")
     #[serde(default = "default_active_stylesheet_id")]
     pub active_stylesheet_id: String,
+    /// Keep MENOTA dipl/norm readings as `.dipl{}.norm{}` on TEI import (default: false)
+    #[serde(default)]
+    pub import_level_overrides: bool,
+    /// Save inflections of lemmatised words to the dictionary on TEI import (default: false)
+    #[serde(default)]
+    pub import_harvest_inflections: bool,
+    /// Rules for converting typed plain-text transcriptions on import
+    #[serde(default)]
+    pub plain_text_profile: PlainTextProfile,
+    /// Add a `<change>` to the revision history on each project save (default: false)
+    #[serde(default)]
+    pub record_revisions: bool,
+    /// Name recorded as `@who` of those changes (default: None)
+    #[serde(default)]
+    pub revision_author: Option<String>,
+    /// Embed the compiler configuration and resources in saved projects (default: false)
+    #[serde(default)]
+    pub embed_project_resources: bool,
 }
 
 fn default_font_size() -> u32 {
@@ -73,6 +92,12 @@ impl Default for Settings {
             preview_delay: default_preview_delay(),
             active_template_id: None,
             active_stylesheet_id: default_active_stylesheet_id(),
+            import_level_overrides: false,
+            import_harvest_inflections: false,
+            plain_text_profile: PlainTextProfile::default(),
+            record_revisions: false,
+            revision_author: None,
+            embed_project_resources: false,
         }
     }
 }
@@ -76,7 +76,11 @@ impl StylesheetManager {
     /// Creates the stylesheets directory if it doesn't exist.
     pub fn new(app: &AppHandle) -> Result<Self, String> {
         let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
-        let stylesheets_dir = app_data.join("stylesheets");
+        Self::with_dir(app_data.join("stylesheets"))
+    }
+
+    /// Creates a stylesheet manager for stylesheets stored in `stylesheets_dir`.
+    pub fn with_dir(stylesheets_dir: PathBuf) -> Result<Self, String> {
         fs::create_dir_all(&stylesheets_dir).map_err(|e| e.to_string())?;
         let manifest_path = stylesheets_dir.join(MANIFEST_FILE);
 
@@ -141,14 +145,28 @@ impl StylesheetManager {
             .extension()
             .and_then(|ext| ext.to_str())
             .unwrap_or("xsl");
+        let content = fs::read(&source).map_err(|e| e.to_string())?;
 
+        self.add_stylesheet(display_name, extension, &content)
+    }
+
+    /// Stores a stylesheet under a unique ID derived from its display name.
+    ///
+    /// Used for imports from files and from template packages. Returns the new
+    /// stylesheet's info.
+    pub fn add_stylesheet(
+        &self,
+        display_name: &str,
+        extension: &str,
+        content: &[u8],
+    ) -> Result<StylesheetInfo, String> {
         let mut manifest = self.load_manifest();
         let base_id = sanitize_id(display_name);
         let id = unique_id(&base_id, &manifest);
         let file_name = format!("{}.{}", id, extension);
         let target_path = self.stylesheets_dir.join(&file_name);
 
-        fs::copy(&source, &target_path).map_err(|e| e.to_string())?;
+        fs::write(&target_path, content).map_err(|e| e.to_string())?;
 
         let metadata = StylesheetMetadata {
             id: id.clone(),
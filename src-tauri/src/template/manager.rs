@@ -1,4 +1,7 @@
+use crate::importer::tei::helpers::escape_xml_attr;
+use crate::metadata::Metadata;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
@@ -25,6 +28,10 @@ pub struct Template {
     /// Which annotation schema to use ("tei-p5" or "menota")
     #[serde(default)]
     pub annotation_schema_id: Option<String>,
+    /// Dotted version number ("1.2.0"), compared when a package would replace
+    /// an installed template
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 pub struct TemplateManager {
@@ -35,18 +42,26 @@ impl TemplateManager {
     pub fn new(app: &AppHandle) -> Result<Self, String> {
         let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
-        let templates_dir = app_data.join("templates");
-        fs::create_dir_all(&templates_dir).map_err(|e| e.to_string())?;
+        Self::with_dir(app_data.join("templates"))
+    }
 
+    /// A manager for templates stored in `templates_dir`
+    pub fn with_dir(templates_dir: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&templates_dir).map_err(|e| e.to_string())?;
         Ok(Self { templates_dir })
     }
 
+    /// Whether `id` is one of the templates that ship with the app
+    pub fn is_built_in(id: &str) -> bool {
+        Self::get_built_in(id).is_some()
+    }
+
     pub fn list_templates(&self) -> Result<Vec<Template>, String> {
         let mut templates = Vec::new();
 
         // Include built-in templates
-        templates.push(self.tei_p5_template());
-        templates.push(self.menota_template());
+        templates.push(Self::tei_p5_template());
+        templates.push(Self::menota_template());
 
         // Load user templates
         if let Ok(entries) = fs::read_dir(&self.templates_dir) {
@@ -64,11 +79,19 @@ impl TemplateManager {
         Ok(templates)
     }
 
-    pub fn get_template(&self, id: &str) -> Result<Template, String> {
+    /// A template that ships with the app
+    pub fn get_built_in(id: &str) -> Option<Template> {
         match id {
-            "tei-p5" => Ok(self.tei_p5_template()),
-            "menota" => Ok(self.menota_template()),
-            _ => {
+            "tei-p5" => Some(Self::tei_p5_template()),
+            "menota" => Some(Self::menota_template()),
+            _ => None,
+        }
+    }
+
+    pub fn get_template(&self, id: &str) -> Result<Template, String> {
+        match Self::get_built_in(id) {
+            Some(template) => Ok(template),
+            None => {
                 let path = self.templates_dir.join(format!("{}.json", id));
                 let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
                 serde_json::from_str(&content).map_err(|e| e.to_string())
@@ -91,7 +114,7 @@ impl TemplateManager {
         }
     }
 
-    fn tei_p5_template(&self) -> Template {
+    fn tei_p5_template() -> Template {
         Template {
             id: "tei-p5".to_string(),
             name: "TEI P5".to_string(),
@@ -124,25 +147,26 @@ impl TemplateManager {
             wrap_pages: false,
             validation_schema_id: Some("tei-p5".to_string()),
             annotation_schema_id: Some("tei-p5".to_string()),
+            version: None,
         }
     }
 
-    fn menota_template(&self) -> Template {
+    fn menota_template() -> Template {
         Template {
             id: "menota".to_string(),
             name: "Menota".to_string(),
             description: "Menota handbook compatible structure for medieval Nordic texts"
                 .to_string(),
+            // Header sections come from the project metadata
             header: r#"<?xml version="1.0" encoding="UTF-8"?>
 <TEI xmlns="http://www.tei-c.org/ns/1.0" xmlns:me="http://www.menota.org/ns/1.0">
   <teiHeader>
     <fileDesc>
-      <titleStmt>
-        <title><!-- Title --></title>
-      </titleStmt>
-      <publicationStmt>
-        <p><!-- Publication info --></p>
-      </publicationStmt>
+      {{{xml.titleStmt}}}
+      {{{xml.publicationStmt}}}
+{{#if hasMsDesc}}
+      {{{xml.sourceDesc}}}
+{{else}}
       <sourceDesc>
         <msDesc>
           <msIdentifier>
@@ -152,7 +176,11 @@ impl TemplateManager {
           </msIdentifier>
         </msDesc>
       </sourceDesc>
+{{/if}}
     </fileDesc>
+{{#if encodingNotes}}
+    {{{xml.encodingDesc}}}
+{{else}}
     <encodingDesc>
       <editorialDecl>
         <normalization>
@@ -160,6 +188,13 @@ impl TemplateManager {
         </normalization>
       </editorialDecl>
     </encodingDesc>
+{{/if}}
+{{#if xml.profileDesc}}
+    {{{xml.profileDesc}}}
+{{/if}}
+{{#if xml.revisionDesc}}
+    {{{xml.revisionDesc}}}
+{{/if}}
   </teiHeader>
   <text>
     <body>"#
@@ -174,6 +209,460 @@ impl TemplateManager {
             wrap_pages: true,
             validation_schema_id: Some("menota-p5".to_string()),
             annotation_schema_id: Some("menota".to_string()),
+            version: None,
+        }
+    }
+}
+
+// ============================================================================
+// Placeholders
+// ============================================================================
+//
+// Headers and footers may contain placeholders filled in at compile time:
+//
+// - `{{path}}` inserts a value, XML-escaped; `{{{path}}}` inserts it as is
+// - `{{#if path}}...{{else}}...{{/if}}` keeps one branch depending on whether
+//   the value is present and non-empty
+// - `{{#each path}}...{{/each}}` repeats its body for every item of a list,
+//   with the item's fields in scope and the item itself as `{{.}}`
+//
+// Paths are dotted camelCase names into the context built by
+// [`template_context`], e.g. `msIdentifier.idno` or `languages.0.ident`.
+// Missing values are empty. A line holding only a block tag is dropped
+// entirely, so blocks can sit on lines of their own without leaving blanks.
+
+enum Node<'a> {
+    Text(&'a str),
+    Value {
+        path: &'a str,
+        raw: bool,
+    },
+    If {
+        path: &'a str,
+        then: Vec<Node<'a>>,
+        otherwise: Vec<Node<'a>>,
+    },
+    Each {
+        path: &'a str,
+        body: Vec<Node<'a>>,
+    },
+}
+
+enum Block<'a> {
+    Root,
+    If(&'a str),
+    Each(&'a str),
+}
+
+/// An open block while parsing, with the line it started on for errors
+struct Frame<'a> {
+    block: Block<'a>,
+    line: usize,
+    nodes: Vec<Node<'a>>,
+    otherwise: Option<Vec<Node<'a>>>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(block: Block<'a>, line: usize) -> Self {
+        Self {
+            block,
+            line,
+            nodes: Vec::new(),
+            otherwise: None,
+        }
+    }
+
+    fn push(&mut self, node: Node<'a>) {
+        self.otherwise
+            .as_mut()
+            .unwrap_or(&mut self.nodes)
+            .push(node);
+    }
+}
+
+fn line_of(text: &str, pos: usize) -> usize {
+    text[..pos].matches('\n').count() + 1
+}
+
+fn is_path(path: &str) -> bool {
+    path == "."
+        || (!path.is_empty()
+            && path.split('.').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            }))
+}
+
+fn parse(text: &str) -> Result<Vec<Node<'_>>, String> {
+    let mut stack = vec![Frame::new(Block::Root, 1)];
+    let mut text_start = 0;
+
+    while let Some(offset) = text[text_start..].find("{{") {
+        let open = text_start + offset;
+        let line = line_of(text, open);
+        let raw = text[open..].starts_with("{{{");
+        let (inner_start, close_tag) = if raw {
+            (open + 3, "}}}")
+        } else {
+            (open + 2, "}}")
+        };
+        let close = text[inner_start..]
+            .find(close_tag)
+            .map(|i| inner_start + i)
+            .ok_or_else(|| format!("Unterminated placeholder on line {}", line))?;
+        let tag = text[inner_start..close].trim();
+        let mut text_end = open;
+        let mut next = close + close_tag.len();
+
+        let is_block = !raw && (tag.starts_with('#') || tag.starts_with('/') || tag == "else");
+        if is_block {
+            let line_start = text[..open].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = text[next..].find('\n').map_or(text.len(), |i| next + i + 1);
+            if line_start >= text_start
+                && text[line_start..open].trim().is_empty()
+                && text[next..line_end].trim().is_empty()
+            {
+                text_end = line_start;
+                next = line_end;
+            }
+        }
+
+        let frame = stack.last_mut().expect("root frame");
+        if text_end > text_start {
+            frame.push(Node::Text(&text[text_start..text_end]));
+        }
+        text_start = next;
+
+        let (keyword, path) = match tag.split_once(char::is_whitespace) {
+            Some((keyword, path)) => (keyword, path.trim()),
+            None => (tag, ""),
+        };
+        match keyword {
+            "#if" | "#each" => {
+                if !is_path(path) {
+                    return Err(format!("Invalid path in {{{{{}}}}} on line {}", tag, line));
+                }
+                let block = if keyword == "#if" {
+                    Block::If(path)
+                } else {
+                    Block::Each(path)
+                };
+                stack.push(Frame::new(block, line));
+            }
+            "else" if path.is_empty() => match frame.block {
+                Block::If(_) if frame.otherwise.is_none() => frame.otherwise = Some(Vec::new()),
+                _ => return Err(format!("Unexpected {{{{else}}}} on line {}", line)),
+            },
+            "/if" | "/each" if path.is_empty() => {
+                let closed = stack.pop().expect("root frame");
+                let node = match (closed.block, keyword) {
+                    (Block::If(path), "/if") => Node::If {
+                        path,
+                        then: closed.nodes,
+                        otherwise: closed.otherwise.unwrap_or_default(),
+                    },
+                    (Block::Each(path), "/each") => Node::Each {
+                        path,
+                        body: closed.nodes,
+                    },
+                    _ => return Err(format!("Unexpected {{{{{}}}}} on line {}", tag, line)),
+                };
+                stack.last_mut().expect("root frame").push(node);
+            }
+            _ if is_path(tag) => frame.push(Node::Value { path: tag, raw }),
+            _ => {
+                return Err(format!(
+                    "Invalid placeholder {{{{{}}}}} on line {}",
+                    tag, line
+                ))
+            }
         }
     }
+
+    let mut root = stack.remove(0);
+    if let Some(open) = stack.last() {
+        let (keyword, path) = match open.block {
+            Block::If(path) => ("if", path),
+            Block::Each(path) => ("each", path),
+            Block::Root => unreachable!(),
+        };
+        return Err(format!(
+            "{{{{#{} {}}}}} on line {} is not closed",
+            keyword, path, open.line
+        ));
+    }
+    if text_start < text.len() {
+        root.push(Node::Text(&text[text_start..]));
+    }
+    Ok(root.nodes)
+}
+
+/// Resolve a dotted path, innermost scope first
+fn lookup<'v>(scopes: &[&'v Value], path: &str) -> Option<&'v Value> {
+    if path == "." {
+        return scopes.last().copied();
+    }
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let mut value = scopes.iter().rev().find_map(|scope| scope.get(first))?;
+    for part in parts {
+        value = match value {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => value.get(part)?,
+        };
+    }
+    Some(value)
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64() != Some(0.0),
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Object(fields)) => !fields.is_empty(),
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Array(items) => items
+            .iter()
+            .map(to_text)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Null | Value::Object(_) => String::new(),
+    }
+}
+
+fn render_nodes(nodes: &[Node], scopes: &mut Vec<&Value>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value { path, raw } => {
+                let text = lookup(scopes, path).map(to_text).unwrap_or_default();
+                if *raw {
+                    out.push_str(&text);
+                } else {
+                    out.push_str(&escape_xml_attr(&text));
+                }
+            }
+            Node::If {
+                path,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(lookup(scopes, path)) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, scopes, out);
+            }
+            Node::Each { path, body } => {
+                let items: Vec<&Value> = match lookup(scopes, path) {
+                    Some(Value::Array(items)) => items.iter().collect(),
+                    Some(value) if is_truthy(Some(value)) => vec![value],
+                    _ => Vec::new(),
+                };
+                for item in items {
+                    scopes.push(item);
+                    render_nodes(body, scopes, out);
+                    scopes.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Fill in the placeholders of a template header or footer.
+///
+/// Text without placeholders comes back unchanged. Malformed placeholders and
+/// unclosed blocks are errors naming the line they are on.
+pub fn render_template(text: &str, context: &Value) -> Result<String, String> {
+    let nodes = parse(text)?;
+    let mut out = String::with_capacity(text.len());
+    render_nodes(&nodes, &mut vec![context], &mut out);
+    Ok(out)
+}
+
+/// The values placeholders can refer to.
+///
+/// Every metadata field is available under its camelCase path, alongside:
+/// `title` (or "Untitled"), `shelfmark`, `hands`, `hasMsDesc` (whether
+/// `xml.sourceDesc` describes the manuscript rather than holding a placeholder),
+/// `fileName`, `compileDate` (YYYY-MM-DD), and `xml.titleStmt`, `xml.publicationStmt`, `xml.sourceDesc`,
+/// `xml.encodingDesc`, `xml.profileDesc` and `xml.revisionDesc`, the header
+/// sections as generated from the metadata, for use with `{{{...}}}`.
+pub fn template_context(metadata: &Metadata, file_name: Option<&str>, compile_date: &str) -> Value {
+    let mut context = match serde_json::to_value(metadata) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    context.remove("originalHeader");
+
+    let title = metadata
+        .title_stmt
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or("Untitled");
+    context.insert("title".into(), json!(title));
+    context.insert(
+        "shelfmark".into(),
+        json!(metadata.ms_identifier.shelfmark()),
+    );
+    context.insert("hands".into(), json!(metadata.phys_desc.hands));
+    context.insert("hasMsDesc".into(), json!(metadata.has_ms_desc()));
+    context.insert("fileName".into(), json!(file_name));
+    context.insert("compileDate".into(), json!(compile_date));
+
+    // Sections are indented for their usual place; the template supplies the
+    // indentation of their first line
+    let section = |xml: String| json!(xml.trim());
+    context.insert(
+        "xml".into(),
+        json!({
+            "titleStmt": section(metadata.title_stmt_to_xml()),
+            "publicationStmt": section(metadata.publication_stmt_to_xml()),
+            "sourceDesc": section(metadata.source_desc_to_xml()),
+            "encodingDesc": section(metadata.encoding_desc_to_xml()),
+            "profileDesc": section(metadata.profile_desc_to_xml()),
+            "revisionDesc": section(metadata.revision_desc_to_xml()),
+        }),
+    );
+
+    Value::Object(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::HandNote;
+
+    fn sample() -> Metadata {
+        let mut metadata = Metadata::new();
+        metadata.title_stmt.title = Some("Egils saga".to_string());
+        metadata.ms_identifier.settlement = Some("Reykjavík".to_string());
+        metadata.ms_identifier.repository = Some("Stofnun Árna Magnússonar".to_string());
+        metadata.ms_identifier.idno = Some("AM 132 fol.".to_string());
+        metadata.phys_desc.hands = vec![
+            HandNote {
+                id: Some("h1".to_string()),
+                script: Some("gothic".to_string()),
+                ..Default::default()
+            },
+            HandNote {
+                id: Some("h2".to_string()),
+                description: Some("Later <additions>".to_string()),
+                ..Default::default()
+            },
+        ];
+        metadata
+    }
+
+    fn render(text: &str, metadata: &Metadata) -> Result<String, String> {
+        render_template(
+            text,
+            &template_context(metadata, Some("egils.teis"), "2024-05-01"),
+        )
+    }
+
+    #[test]
+    fn test_values_are_escaped_unless_raw() {
+        let mut metadata = sample();
+        metadata.title_stmt.title = Some("Egils saga & <Njála>".to_string());
+        assert_eq!(
+            render("<title>{{title}}</title>", &metadata).unwrap(),
+            "<title>Egils saga &amp; &lt;Njála&gt;</title>"
+        );
+        assert_eq!(
+            render("{{{titleStmt.title}}}", &metadata).unwrap(),
+            "Egils saga & <Njála>"
+        );
+        assert_eq!(
+            render(
+                "{{shelfmark}} | {{ fileName }} | {{compileDate}} | {{missing.field}}",
+                &metadata
+            )
+            .unwrap(),
+            "Reykjavík, Stofnun Árna Magnússonar, AM 132 fol. | egils.teis | 2024-05-01 | "
+        );
+        assert_eq!(render("{{title}}", &Metadata::new()).unwrap(), "Untitled");
+    }
+
+    #[test]
+    fn test_conditionals_and_loops() {
+        let template = "<handDesc>\n{{#each hands}}\n  <handNote xml:id=\"{{id}}\"{{#if script}} script=\"{{script}}\"{{/if}}>{{#if description}}{{description}}{{else}}{{title}}{{/if}}</handNote>\n{{/each}}\n</handDesc>";
+        assert_eq!(
+            render(template, &sample()).unwrap(),
+            "<handDesc>\n  <handNote xml:id=\"h1\" script=\"gothic\">Egils saga</handNote>\n  <handNote xml:id=\"h2\">Later &lt;additions&gt;</handNote>\n</handDesc>"
+        );
+
+        let mut metadata = sample();
+        metadata.phys_desc.hands.clear();
+        assert_eq!(
+            render(
+                "a\n{{#if hands}}\n  hands\n{{else}}\n  none\n{{/if}}\nb",
+                &metadata
+            )
+            .unwrap(),
+            "a\n  none\nb"
+        );
+    }
+
+    #[test]
+    fn test_malformed_placeholders_are_errors() {
+        let metadata = sample();
+        assert_eq!(
+            render("a\n{{#if title}}\nb", &metadata).unwrap_err(),
+            "{{#if title}} on line 2 is not closed"
+        );
+        assert_eq!(
+            render("{{#each hands}}{{/if}}", &metadata).unwrap_err(),
+            "Unexpected {{/if}} on line 1"
+        );
+        assert!(render("{{title", &metadata).is_err());
+        assert!(render("{{title more}}", &metadata).is_err());
+        assert!(render("{{else}}", &metadata).is_err());
+
+        // Text without placeholders is left alone
+        let plain = "<TEI>\n  <text>{ not a placeholder }</text>\n";
+        assert_eq!(render(plain, &metadata).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_menota_header_uses_metadata() {
+        let template = TemplateManager::menota_template();
+        assert!(template.header.contains("{{"));
+
+        let header = render(&template.header, &sample()).unwrap();
+        assert!(header.contains("      <titleStmt>\n        <title>Egils saga</title>"));
+        assert!(header.contains("            <idno>AM 132 fol.</idno>"));
+        assert!(header.contains(r#"<handNote xml:id="h1" script="gothic""#));
+        assert!(header.contains("Transcription follows Menota handbook v3.0"));
+        assert!(!header.contains("{{"));
+        assert!(!header.contains("<revisionDesc>"));
+        assert!(!header.contains("\n\n"));
+
+        // Empty metadata keeps the commented skeleton
+        let header = render(&template.header, &Metadata::new()).unwrap();
+        assert!(header.contains("<idno><!-- Shelfmark --></idno>"));
+        assert!(header.contains("<title>Untitled</title>"));
+
+        // A description without a shelfmark is still used
+        let mut metadata = Metadata::new();
+        metadata.phys_desc.hands = sample().phys_desc.hands;
+        let header = render(&template.header, &metadata).unwrap();
+        assert!(header.contains(r#"<handNote xml:id="h1" script="gothic""#));
+        assert!(!header.contains("<!-- Shelfmark -->"));
+    }
 }
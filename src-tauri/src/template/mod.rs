@@ -1,3 +1,4 @@
 mod manager;
+pub mod package;
 
-pub use manager::{Template, TemplateManager};
+pub use manager::{render_template, template_context, Template, TemplateManager};
@@ -0,0 +1,808 @@
+//! # Template Packages
+//!
+//! A package bundles a template with the files it depends on, so that a house
+//! style can be shared between machines:
+//!
+//! ```text
+//! house-style.sspkg (ZIP)
+//! ├── manifest.json          # Format version, template id/version, contents
+//! ├── template.json          # The template
+//! ├── schema/<file>          # Custom validation schema (.rng or .xsd)
+//! ├── stylesheet/<file>      # XSLT stylesheet for the preview
+//! ├── entities.json          # Custom entity definitions
+//! └── entity-mappings.json   # Entity base letters for the normalizer
+//! ```
+//!
+//! Everything but the manifest and template is optional. Built-in schemas are
+//! referred to by id rather than packaged.
+//!
+//! ## Versions
+//!
+//! `formatVersion` is the layout above; packages from a newer major version
+//! are refused. `version` is the template's own version, shown when a package
+//! would replace an installed template with the same id. Such a clash is
+//! reported back without installing anything until the caller chooses to
+//! replace the installed template or keep both.
+
+use super::manager::{Template, TemplateManager};
+use crate::commands::validate::built_in_schema_file;
+use crate::entities::{CustomEntitiesManager, CustomMappingsManager, Entity};
+use crate::stylesheet::{StylesheetInfo, StylesheetManager};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Package layout version written by this build
+pub const PACKAGE_FORMAT_VERSION: &str = "1.0";
+
+/// Template version recorded for templates that have none
+const DEFAULT_TEMPLATE_VERSION: &str = "1.0.0";
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageManifest {
+    pub format_version: String,
+    pub template_id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Archive path of the schema, e.g. "schema/house.rng"
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// Archive path of the stylesheet, e.g. "stylesheet/house.xsl"
+    #[serde(default)]
+    pub stylesheet: Option<String>,
+    /// Display name of the stylesheet
+    #[serde(default)]
+    pub stylesheet_name: Option<String>,
+    #[serde(default)]
+    pub entities: bool,
+    #[serde(default)]
+    pub entity_mappings: bool,
+    /// Version of Saga-Scribe that wrote the package
+    #[serde(default)]
+    pub app_version: String,
+}
+
+/// A file carried in a package
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageFile {
+    /// Display name (stylesheets) or id (schemas)
+    pub name: String,
+    pub file_name: String,
+    pub content: Vec<u8>,
+}
+
+/// A template and the files it depends on
+#[derive(Debug, Clone)]
+pub struct TemplatePackage {
+    pub template: Template,
+    pub schema: Option<PackageFile>,
+    pub stylesheet: Option<PackageFile>,
+    pub entities: HashMap<String, Entity>,
+    pub entity_mappings: HashMap<String, String>,
+}
+
+/// Where packages are installed
+pub struct PackageStore {
+    pub templates: TemplateManager,
+    pub stylesheets: StylesheetManager,
+    pub entities: CustomEntitiesManager,
+    pub entity_mappings: CustomMappingsManager,
+    /// Directory for schemas that come with packages
+    pub schemas_dir: PathBuf,
+}
+
+/// What to do when a package's template id is already installed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictAction {
+    /// Overwrite the installed template
+    Replace,
+    /// Install the package's template under a new id
+    KeepBoth,
+}
+
+/// An installed template with the same id as the package's
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateConflict {
+    pub template_id: String,
+    pub installed_name: String,
+    pub installed_version: Option<String>,
+    pub package_version: String,
+    /// Whether the package holds a newer version than the installed one
+    pub package_is_newer: bool,
+    /// Built-in templates can only be kept alongside, not replaced
+    pub built_in: bool,
+}
+
+/// Outcome of importing a package
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageImport {
+    /// False when a conflict needs a decision and nothing was installed
+    pub installed: bool,
+    /// The template as installed (or as it would be)
+    pub template: Template,
+    pub conflict: Option<TemplateConflict>,
+    pub stylesheet: Option<StylesheetInfo>,
+    pub entities_added: usize,
+    pub entity_mappings_added: usize,
+    /// Entities and mappings the package defines differently from existing
+    /// ones, which were left as they are
+    pub kept_existing: Vec<String>,
+}
+
+impl TemplatePackage {
+    /// Write the package as a ZIP archive, returning its manifest.
+    pub fn write(&self, path: &Path) -> Result<PackageManifest, String> {
+        let mut template = self.template.clone();
+        let version = template
+            .version
+            .get_or_insert_with(|| DEFAULT_TEMPLATE_VERSION.to_string())
+            .clone();
+
+        let manifest = PackageManifest {
+            format_version: PACKAGE_FORMAT_VERSION.to_string(),
+            template_id: template.id.clone(),
+            name: template.name.clone(),
+            version,
+            description: template.description.clone(),
+            schema: self
+                .schema
+                .as_ref()
+                .map(|file| format!("schema/{}", file.file_name)),
+            stylesheet: self
+                .stylesheet
+                .as_ref()
+                .map(|file| format!("stylesheet/{}", file.file_name)),
+            stylesheet_name: self.stylesheet.as_ref().map(|file| file.name.clone()),
+            entities: !self.entities.is_empty(),
+            entity_mappings: !self.entity_mappings.is_empty(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+
+        let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
+        let mut zip = ZipWriter::new(file);
+
+        add_file(&mut zip, "manifest.json", to_json(&manifest)?.as_bytes())?;
+        add_file(&mut zip, "template.json", to_json(&template)?.as_bytes())?;
+        if let (Some(name), Some(file)) = (&manifest.schema, &self.schema) {
+            add_file(&mut zip, name, &file.content)?;
+        }
+        if let (Some(name), Some(file)) = (&manifest.stylesheet, &self.stylesheet) {
+            add_file(&mut zip, name, &file.content)?;
+        }
+        if manifest.entities {
+            let file = serde_json::json!({ "version": "1.0", "entities": self.entities });
+            add_file(&mut zip, "entities.json", to_json(&file)?.as_bytes())?;
+        }
+        if manifest.entity_mappings {
+            let file = serde_json::json!({ "version": "1.0", "mappings": self.entity_mappings });
+            add_file(&mut zip, "entity-mappings.json", to_json(&file)?.as_bytes())?;
+        }
+
+        zip.finish()
+            .map_err(|e| format!("Failed to finalize package: {}", e))?;
+        Ok(manifest)
+    }
+
+    /// Read a package, checking that this build understands its format.
+    pub fn read(path: &Path) -> Result<(PackageManifest, Self), String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open package: {}", e))?;
+        let mut archive =
+            ZipArchive::new(file).map_err(|e| format!("Failed to read package: {}", e))?;
+
+        let manifest: PackageManifest = read_json(&mut archive, "manifest.json")?;
+        let supported = major_version(PACKAGE_FORMAT_VERSION);
+        match major_version(&manifest.format_version) {
+            major if major == supported => {}
+            major if major > supported => {
+                return Err(format!(
+                    "Package format {} is newer than this version of Saga-Scribe supports ({}); please update",
+                    manifest.format_version, PACKAGE_FORMAT_VERSION
+                ))
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported package format {}",
+                    manifest.format_version
+                ))
+            }
+        }
+
+        let mut template: Template = read_json(&mut archive, "template.json")?;
+        if template.id != manifest.template_id {
+            return Err(format!(
+                "Package manifest names template '{}' but template.json holds '{}'",
+                manifest.template_id, template.id
+            ));
+        }
+        if !is_valid_template_id(&template.id) {
+            return Err(format!("Invalid template id '{}'", template.id));
+        }
+        template.version = Some(manifest.version.clone());
+
+        let schema = match manifest.schema {
+            Some(ref entry) => Some(read_package_file(
+                &mut archive,
+                entry,
+                None,
+                &["rng", "xsd"],
+            )?),
+            None => None,
+        };
+        let stylesheet = match manifest.stylesheet {
+            Some(ref entry) => Some(read_package_file(
+                &mut archive,
+                entry,
+                manifest.stylesheet_name.as_deref(),
+                &["xsl", "xslt"],
+            )?),
+            None => None,
+        };
+
+        #[derive(Deserialize)]
+        struct EntitiesFile {
+            entities: HashMap<String, Entity>,
+        }
+        #[derive(Deserialize)]
+        struct MappingsFile {
+            mappings: HashMap<String, String>,
+        }
+        let entities = if manifest.entities {
+            read_json::<_, EntitiesFile>(&mut archive, "entities.json")?.entities
+        } else {
+            HashMap::new()
+        };
+        let entity_mappings = if manifest.entity_mappings {
+            read_json::<_, MappingsFile>(&mut archive, "entity-mappings.json")?.mappings
+        } else {
+            HashMap::new()
+        };
+
+        Ok((
+            manifest,
+            Self {
+                template,
+                schema,
+                stylesheet,
+                entities,
+                entity_mappings,
+            },
+        ))
+    }
+
+    /// Install the package.
+    ///
+    /// If a template with the same id exists and `on_conflict` is `None`,
+    /// nothing is installed and the conflict is returned for the caller to
+    /// decide on.
+    pub fn install(
+        self,
+        store: &PackageStore,
+        on_conflict: Option<ConflictAction>,
+    ) -> Result<PackageImport, String> {
+        let mut template = self.template;
+        let package_version = template
+            .version
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TEMPLATE_VERSION.to_string());
+
+        let conflict = store
+            .templates
+            .get_template(&template.id)
+            .ok()
+            .map(|installed| TemplateConflict {
+                template_id: installed.id.clone(),
+                installed_name: installed.name,
+                package_is_newer: compare_versions(
+                    &package_version,
+                    installed.version.as_deref().unwrap_or("0"),
+                ) == Ordering::Greater,
+                installed_version: installed.version,
+                package_version: package_version.clone(),
+                built_in: TemplateManager::is_built_in(&installed.id),
+            });
+
+        let mut result = PackageImport {
+            installed: false,
+            template: template.clone(),
+            conflict: conflict.clone(),
+            stylesheet: None,
+            entities_added: 0,
+            entity_mappings_added: 0,
+            kept_existing: Vec::new(),
+        };
+
+        if let Some(ref conflict) = conflict {
+            match on_conflict {
+                None => return Ok(result),
+                Some(ConflictAction::Replace) if conflict.built_in => {
+                    return Err(format!(
+                        "The built-in template '{}' cannot be replaced",
+                        conflict.template_id
+                    ));
+                }
+                Some(ConflictAction::Replace) => {}
+                Some(ConflictAction::KeepBoth) => {
+                    let taken: Vec<String> = store
+                        .templates
+                        .list_templates()?
+                        .into_iter()
+                        .map(|t| t.id)
+                        .collect();
+                    let n = (2..)
+                        .find(|n| !taken.contains(&format!("{}-{}", template.id, n)))
+                        .unwrap_or(2);
+                    template.id = format!("{}-{}", template.id, n);
+                    template.name = format!("{} ({})", template.name, n);
+                }
+            }
+        }
+
+        if let Some(ref schema) = self.schema {
+            template.validation_schema_id = Some(install_schema(&store.schemas_dir, schema)?);
+        }
+
+        if let Some(ref file) = self.stylesheet {
+            result.stylesheet = Some(install_stylesheet(&store.stylesheets, file)?);
+        }
+
+        let existing = store.entities.load();
+        let mut entities: Vec<_> = self.entities.into_iter().collect();
+        entities.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, entity) in entities {
+            match existing.get(&name) {
+                Some(current) if *current == entity => {}
+                Some(_) => result.kept_existing.push(format!("entity '{}'", name)),
+                None => {
+                    store.entities.save(&name, entity)?;
+                    result.entities_added += 1;
+                }
+            }
+        }
+
+        let existing = store.entity_mappings.load();
+        let mut mappings: Vec<_> = self.entity_mappings.into_iter().collect();
+        mappings.sort();
+        for (entity, base) in mappings {
+            match existing.get(&entity) {
+                Some(current) if *current == base => {}
+                Some(_) => result
+                    .kept_existing
+                    .push(format!("mapping for '{}'", entity)),
+                None => {
+                    store.entity_mappings.save(&entity, &base)?;
+                    result.entity_mappings_added += 1;
+                }
+            }
+        }
+
+        store.templates.save_template(&template)?;
+        result.installed = true;
+        result.template = template;
+        Ok(result)
+    }
+}
+
+/// Compare dotted version numbers numerically ("1.10" > "1.9").
+/// Missing parts count as zero, so "1.2" equals "1.2.0".
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            a.get(i)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn major_version(version: &str) -> u64 {
+    version
+        .split('.')
+        .next()
+        .and_then(|major| major.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Template ids become file names, so keep them to a safe alphabet
+fn is_valid_template_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Copy a schema into `dir`, reusing an identical file and otherwise picking a
+/// free name. Returns the schema id (the file stem).
+///
+/// Built-in schema ids resolve to the bundled schema before any installed
+/// file, so a stem such as `menota` is numbered (`menota-2`) like a clash.
+fn install_schema(dir: &Path, file: &PackageFile) -> Result<String, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = Path::new(&file.file_name);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "schema".to_string());
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    for n in 1.. {
+        let id = if n == 1 {
+            stem.clone()
+        } else {
+            format!("{}-{}", stem, n)
+        };
+        if built_in_schema_file(&id).is_some() {
+            continue;
+        }
+        let target = dir.join(format!("{}.{}", id, extension));
+        match fs::read(&target) {
+            Ok(existing) if existing == file.content => return Ok(id),
+            Ok(_) => continue,
+            Err(_) => {
+                fs::write(&target, &file.content).map_err(|e| e.to_string())?;
+                return Ok(id);
+            }
+        }
+    }
+    unreachable!("unbounded range")
+}
+
+/// Add a stylesheet unless an identical one with the same name is installed
+fn install_stylesheet(
+    stylesheets: &StylesheetManager,
+    file: &PackageFile,
+) -> Result<StylesheetInfo, String> {
+    for installed in stylesheets.list_stylesheets()? {
+        if !installed.built_in
+            && installed.name == file.name
+            && fs::read(&installed.path).is_ok_and(|content| content == file.content)
+        {
+            return Ok(installed);
+        }
+    }
+    let extension = Path::new(&file.file_name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "xsl".to_string());
+    stylesheets.add_stylesheet(&file.name, &extension, &file.content)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize: {}", e))
+}
+
+fn add_file<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    content: &[u8],
+) -> Result<(), String> {
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    zip.start_file(name, options)
+        .map_err(|e| format!("Failed to start {}: {}", name, e))?;
+    zip.write_all(content)
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+fn read_bytes<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>, String> {
+    let mut file = archive
+        .by_name(name)
+        .map_err(|e| format!("Failed to find {} in package: {}", name, e))?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(content)
+}
+
+fn read_json<R: Read + Seek, T: serde::de::DeserializeOwned>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<T, String> {
+    let content = read_bytes(archive, name)?;
+    serde_json::from_slice(&content).map_err(|e| format!("Failed to parse {}: {}", name, e))
+}
+
+/// Read a file named in the manifest. Only its base name is kept, and its
+/// extension must be one of `extensions`.
+fn read_package_file<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    entry: &str,
+    name: Option<&str>,
+    extensions: &[&str],
+) -> Result<PackageFile, String> {
+    let path = Path::new(entry);
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid file name '{}' in package", entry))?;
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !extensions.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unexpected file type '{}' in package (expected {})",
+            file_name,
+            extensions.join(", ")
+        ));
+    }
+    let content = read_bytes(archive, entry)?;
+    let name = name.map(str::to_string).unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    Ok(PackageFile {
+        name,
+        file_name,
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "saga-scribe-package-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn store(dir: &Path) -> PackageStore {
+        PackageStore {
+            templates: TemplateManager::with_dir(dir.join("templates")).unwrap(),
+            stylesheets: StylesheetManager::with_dir(dir.join("stylesheets")).unwrap(),
+            entities: CustomEntitiesManager::with_path(dir.join("custom-entities.json")),
+            entity_mappings: CustomMappingsManager::with_path(
+                dir.join("custom-entity-mappings.json"),
+            ),
+            schemas_dir: dir.join("schemas"),
+        }
+    }
+
+    fn house_style(version: &str) -> TemplatePackage {
+        let mut template = TemplateManager::get_built_in("menota").unwrap();
+        template.id = "house".to_string();
+        template.name = "House style".to_string();
+        template.version = Some(version.to_string());
+        template.validation_schema_id = Some("house".to_string());
+
+        let entity = Entity {
+            unicode: "U+A75D".to_string(),
+            char: "ꝝ".to_string(),
+            description: "r rotunda with abbreviation stroke".to_string(),
+            category: "abbreviation".to_string(),
+        };
+        TemplatePackage {
+            template,
+            schema: Some(PackageFile {
+                name: "house".to_string(),
+                file_name: "house.rng".to_string(),
+                content: b"<grammar xmlns=\"http://relaxng.org/ns/structure/1.0\"/>".to_vec(),
+            }),
+            stylesheet: Some(PackageFile {
+                name: "House preview".to_string(),
+                file_name: "house.xsl".to_string(),
+                content: b"<xsl:stylesheet/>".to_vec(),
+            }),
+            entities: HashMap::from([("rrotabbr".to_string(), entity)]),
+            entity_mappings: HashMap::from([("rrotabbr".to_string(), "r".to_string())]),
+        }
+    }
+
+    fn write_and_read(dir: &Path, package: &TemplatePackage) -> TemplatePackage {
+        let path = dir.join("house.sspkg");
+        package.write(&path).unwrap();
+        TemplatePackage::read(&path).unwrap().1
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v2.0.1", "2.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_round_trip_and_install() {
+        let dir = scratch("install");
+        let package = write_and_read(&dir, &house_style("1.2.0"));
+        assert_eq!(package.template.version.as_deref(), Some("1.2.0"));
+        assert_eq!(package.stylesheet.as_ref().unwrap().name, "House preview");
+        assert_eq!(package.entity_mappings["rrotabbr"], "r");
+
+        let store = store(&dir);
+        let result = package.install(&store, None).unwrap();
+        assert!(result.installed);
+        assert!(result.conflict.is_none());
+        assert_eq!(result.entities_added, 1);
+        assert_eq!(result.entity_mappings_added, 1);
+        assert_eq!(result.stylesheet.unwrap().name, "House preview");
+        assert!(dir.join("schemas/house.rng").exists());
+        assert_eq!(
+            store
+                .templates
+                .get_template("house")
+                .unwrap()
+                .version
+                .as_deref(),
+            Some("1.2.0")
+        );
+
+        // A second install reuses the identical schema and stylesheet
+        let again = house_style("1.2.0");
+        let result = again
+            .install(&store, Some(ConflictAction::Replace))
+            .unwrap();
+        assert_eq!(
+            result.template.validation_schema_id.as_deref(),
+            Some("house")
+        );
+        assert_eq!(store.stylesheets.list_stylesheets().unwrap().len(), 2);
+        assert_eq!(result.entities_added, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_id_clash_needs_a_decision() {
+        let dir = scratch("clash");
+        let store = store(&dir);
+        house_style("1.0.0").install(&store, None).unwrap();
+
+        let mut newer = house_style("1.1.0");
+        newer.schema.as_mut().unwrap().content = b"<grammar/>".to_vec();
+        newer
+            .entity_mappings
+            .insert("rrotabbr".to_string(), "ꝛ".to_string());
+
+        let pending = newer.clone().install(&store, None).unwrap();
+        assert!(!pending.installed);
+        let conflict = pending.conflict.unwrap();
+        assert!(conflict.package_is_newer);
+        assert_eq!(conflict.installed_version.as_deref(), Some("1.0.0"));
+        assert_eq!(
+            store
+                .templates
+                .get_template("house")
+                .unwrap()
+                .version
+                .as_deref(),
+            Some("1.0.0")
+        );
+
+        let kept = newer
+            .clone()
+            .install(&store, Some(ConflictAction::KeepBoth))
+            .unwrap();
+        assert_eq!(kept.template.id, "house-2");
+        assert_eq!(kept.template.name, "House style (2)");
+        // The changed schema does not overwrite the installed one
+        assert_eq!(
+            kept.template.validation_schema_id.as_deref(),
+            Some("house-2")
+        );
+        assert_eq!(kept.kept_existing, vec!["mapping for 'rrotabbr'"]);
+
+        let replaced = newer
+            .install(&store, Some(ConflictAction::Replace))
+            .unwrap();
+        assert_eq!(replaced.template.id, "house");
+        assert_eq!(
+            store
+                .templates
+                .get_template("house")
+                .unwrap()
+                .version
+                .as_deref(),
+            Some("1.1.0")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_built_in_templates_cannot_be_replaced() {
+        let dir = scratch("built-in");
+        let store = store(&dir);
+        let mut package = house_style("9.0.0");
+        package.template.id = "menota".to_string();
+
+        let pending = package.clone().install(&store, None).unwrap();
+        assert!(pending.conflict.unwrap().built_in);
+        assert!(package
+            .clone()
+            .install(&store, Some(ConflictAction::Replace))
+            .is_err());
+        let kept = package
+            .install(&store, Some(ConflictAction::KeepBoth))
+            .unwrap();
+        assert_eq!(kept.template.id, "menota-2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_schema_named_like_a_built_in_is_renamed() {
+        let dir = scratch("builtin-schema");
+        let store = store(&dir);
+        let mut package = house_style("1.0.0");
+        package.schema.as_mut().unwrap().file_name = "menota-p5.rng".to_string();
+
+        let result = package.install(&store, None).unwrap();
+        assert_eq!(
+            result.template.validation_schema_id.as_deref(),
+            Some("menota-p5-2")
+        );
+        assert!(dir.join("schemas/menota-p5-2.rng").exists());
+        assert!(!dir.join("schemas/menota-p5.rng").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_newer_format_is_refused() {
+        let dir = scratch("format");
+        let path = dir.join("future.sspkg");
+        let mut package = house_style("1.0.0");
+        package.schema = None;
+        package.write(&path).unwrap();
+
+        // Rewrite the manifest as a future format
+        let (mut manifest, _) = TemplatePackage::read(&path).unwrap();
+        manifest.format_version = "2.0".to_string();
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        add_file(
+            &mut zip,
+            "manifest.json",
+            to_json(&manifest).unwrap().as_bytes(),
+        )
+        .unwrap();
+        add_file(
+            &mut zip,
+            "template.json",
+            to_json(&package.template).unwrap().as_bytes(),
+        )
+        .unwrap();
+        zip.finish().unwrap();
+
+        let error = TemplatePackage::read(&path).unwrap_err();
+        assert!(error.contains("newer"), "{}", error);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -10,6 +10,7 @@ import type {
     AnnotationTarget,
     AnnotationValue,
     AnnotationMetadata,
+    ReviewStatus,
 } from "$lib/types/annotations";
 import {
     createEmptyAnnotationSet,
@@ -19,7 +20,9 @@ import {
     getLemmaMap,
     targetIncludesWord,
     getPrimaryWordIndex,
+    isRejected,
 } from "$lib/types/annotations";
+import { setAnnotationStatus } from "$lib/tauri";
 
 // ============================================================================
 // Annotation History (for undo/redo)
@@ -214,6 +217,21 @@ function createAnnotationStore() {
         });
     }
 
+    /**
+     * Set the review status of an annotation (recorded as an update)
+     */
+    async function setStatus(id: string, status: ReviewStatus): Promise<void> {
+        const updated = await setAnnotationStatus(
+            JSON.stringify(getSet()),
+            id,
+            status,
+        );
+        const annotation = updated.annotations.find((a) => a.id === id);
+        if (annotation) {
+            addAnnotation(annotation);
+        }
+    }
+
     /**
      * Get an annotation by ID
      */
@@ -414,6 +432,7 @@ function createAnnotationStore() {
         // General annotation methods
         add: addAnnotation,
         remove: removeAnnotation,
+        setStatus,
         get: getAnnotation,
         getForWord,
         getByType,
@@ -512,7 +531,8 @@ export const lemmaMappings = derived(annotationStore, ($store) => {
         if (
             ann.type === "lemma" &&
             ann.target.type === "word" &&
-            ann.value.kind === "lemma"
+            ann.value.kind === "lemma" &&
+            !isRejected(ann)
         ) {
             mappings[ann.target.wordIndex] = {
                 lemma: ann.value.lemma,
@@ -20,6 +20,7 @@ function createEditorStore() {
         setFile: (filePath: string | null, content: string) =>
             set({ content, filePath, isDirty: false }),
         markClean: () => update((state) => ({ ...state, isDirty: false })),
+        markDirty: () => update((state) => ({ ...state, isDirty: true })),
         reset: () => set({ content: '', filePath: null, isDirty: false }),
     };
 }
@@ -1,4 +1,11 @@
-import { compileImported, type Segment } from "$lib/tauri";
+import {
+  compileImported,
+  type CompileImportedOptions,
+  type ImportedDocument,
+  type Segment,
+  type StaleAttributes,
+  type TextUnit,
+} from "$lib/tauri";
 
 export interface ImportedState {
   isImportedMode: boolean;
@@ -9,6 +16,8 @@ export interface ImportedState {
   frontMatter: string;
   backMatter: string;
   isMenota: boolean;
+  label: string;
+  units: TextUnit[];
 }
 
 class ImportedStore {
@@ -21,6 +30,16 @@ class ImportedStore {
   frontMatter = $state("");
   backMatter = $state("");
   isMenota = $state(false);
+  /** Label of the main text unit */
+  label = $state("");
+  /** Front/back matter and further texts, edited one at a time */
+  units = $state<TextUnit[]>([]);
+  /** Unit shown in the editor: -1 for the main body, else an index into units */
+  activeUnit = $state(-1);
+  /** DSL of the main body while another unit is in the editor */
+  mainDsl = $state("");
+  /** Words whose lemma/analysis went stale in the last compile */
+  staleAttributes = $state<StaleAttributes[]>([]);
 
   reset() {
     this.isImportedMode = false;
@@ -32,6 +51,11 @@ class ImportedStore {
     this.frontMatter = "";
     this.backMatter = "";
     this.isMenota = false;
+    this.label = "";
+    this.units = [];
+    this.activeUnit = -1;
+    this.mainDsl = "";
+    this.staleAttributes = [];
   }
 
   load(data: Partial<ImportedState>) {
@@ -43,39 +67,73 @@ class ImportedStore {
     if (data.frontMatter) this.frontMatter = data.frontMatter;
     if (data.backMatter) this.backMatter = data.backMatter;
     if (data.isMenota !== undefined) this.isMenota = data.isMenota;
-    this.segmentsJson = JSON.stringify({
+    this.label = data.label ?? "";
+    this.units = data.units ?? [];
+    this.activeUnit = -1;
+    this.mainDsl = "";
+    this.segmentsJson = null;
+  }
+
+  /**
+   * Put another text unit in the editor. Stores the editor content in the
+   * unit it belongs to and returns the DSL of the unit switched to.
+   */
+  switchUnit(index: number, editorDsl: string): string {
+    this.storeActive(editorDsl);
+    this.activeUnit = index;
+    return index < 0 ? this.mainDsl : this.units[index].dsl;
+  }
+
+  /** DSL of the main body, whichever unit the editor shows */
+  mainSource(editorDsl: string): string {
+    return this.activeUnit < 0 ? editorDsl : this.mainDsl;
+  }
+
+  /** Full manifest with every unit's current DSL, for saving */
+  manifest(editorDsl: string): ImportedDocument {
+    this.storeActive(editorDsl);
+    return {
       segments: this.segments,
       is_menota: this.isMenota,
-    });
+      label: this.label,
+      units: this.units,
+    };
+  }
+
+  private storeActive(editorDsl: string) {
+    if (this.activeUnit < 0) {
+      this.mainDsl = editorDsl;
+    } else {
+      this.units[this.activeUnit].dsl = editorDsl;
+    }
+  }
+
+  /** Manifest JSON; the segments are serialized once, the unit DSL each time */
+  private manifestJson(): string {
+    if (this.segmentsJson === null) {
+      this.segmentsJson = JSON.stringify(this.segments);
+    }
+    return `{"segments":${this.segmentsJson},"is_menota":${this.isMenota},"label":${JSON.stringify(this.label)},"units":${JSON.stringify(this.units)}}`;
   }
 
   async compile(
     editedDsl: string,
-    options?: {
-      entitiesJson?: string;
-      normalizerJson?: string;
-      entityMappingsJson?: string;
-      customMappings?: Record<string, string>;
-    },
+    options?: CompileImportedOptions,
   ): Promise<string> {
     if (!this.isImportedMode) {
       throw new Error("Not in imported mode");
     }
 
-    const manifestJson =
-      this.segmentsJson ??
-      JSON.stringify({
-        segments: this.segments,
-        is_menota: this.isMenota,
-      });
-
-    return compileImported(
-      editedDsl,
-      manifestJson,
+    this.storeActive(editedDsl);
+    const result = await compileImported(
+      this.mainDsl,
+      this.manifestJson(),
       this.originalPreamble,
       this.originalPostamble,
       options,
     );
+    this.staleAttributes = result.staleAttributes;
+    return result.xml;
   }
 }
 
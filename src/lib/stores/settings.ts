@@ -4,6 +4,7 @@ import {
   saveSettings as saveSettingsToBackend,
   getSystemTheme,
   setWindowTheme,
+  type PlainTextProfile,
   type Settings as BackendSettings,
 } from "../tauri";
 
@@ -14,8 +15,24 @@ export interface Settings {
   previewDelay: number;
   activeTemplateId: string | null;
   activeStylesheetId: string;
+  importLevelOverrides: boolean;
+  importHarvestInflections: boolean;
+  plainTextProfile: PlainTextProfile;
+  recordRevisions: boolean;
+  revisionAuthor: string | null;
+  embedProjectResources: boolean;
 }
 
+export const DEFAULT_PLAIN_TEXT_PROFILE: PlainTextProfile = {
+  name: "Default",
+  rules: [
+    { open: "||", close: "", construct: "pageBreak" },
+    { open: "|", close: "", construct: "lineBreak" },
+    { open: "(", close: ")", construct: "expansion" },
+    { open: "[", close: "]", construct: "supplied" },
+  ],
+};
+
 const defaultSettings: Settings = {
   fontSize: 14,
   theme: "system",
@@ -23,6 +40,12 @@ const defaultSettings: Settings = {
   previewDelay: 300,
   activeTemplateId: null,
   activeStylesheetId: "default",
+  importLevelOverrides: false,
+  importHarvestInflections: false,
+  plainTextProfile: DEFAULT_PLAIN_TEXT_PROFILE,
+  recordRevisions: false,
+  revisionAuthor: null,
+  embedProjectResources: false,
 };
 
 let saveTimeout: ReturnType<typeof setTimeout> | null = null;
@@ -117,6 +140,19 @@ function createSettingsStore() {
           activeTemplateId: loaded.activeTemplateId,
           activeStylesheetId:
             loaded.activeStylesheetId ?? defaultSettings.activeStylesheetId,
+          importLevelOverrides:
+            loaded.importLevelOverrides ?? defaultSettings.importLevelOverrides,
+          importHarvestInflections:
+            loaded.importHarvestInflections ??
+            defaultSettings.importHarvestInflections,
+          plainTextProfile:
+            loaded.plainTextProfile ?? defaultSettings.plainTextProfile,
+          recordRevisions:
+            loaded.recordRevisions ?? defaultSettings.recordRevisions,
+          revisionAuthor: loaded.revisionAuthor ?? defaultSettings.revisionAuthor,
+          embedProjectResources:
+            loaded.embedProjectResources ??
+            defaultSettings.embedProjectResources,
         };
         set(settings);
         await applyTheme(settings.theme);
@@ -24,6 +24,8 @@ export interface Template {
    * Defaults to "menota" for multiLevel templates, "tei-p5" otherwise.
    */
   annotationSchemaId?: AnnotationSchemaId;
+  /** Dotted version number ("1.2.0"), compared when importing packages */
+  version?: string;
 }
 
 function createTemplateStore() {
@@ -9,6 +9,12 @@ export interface Settings {
   previewDelay: number;
   activeTemplateId: string | null;
   activeStylesheetId: string;
+  importLevelOverrides: boolean;
+  importHarvestInflections?: boolean;
+  plainTextProfile?: PlainTextProfile;
+  recordRevisions?: boolean;
+  revisionAuthor?: string | null;
+  embedProjectResources?: boolean;
 }
 
 export async function loadSettings(): Promise<Settings> {
@@ -68,6 +74,11 @@ export async function exportTei(
   return invoke("export_tei", { path, teiContent });
 }
 
+/** Export DSL as Menotec plain text; resolves to warnings for lossy constructs */
+export async function exportMenotec(path: string, dsl: string): Promise<string[]> {
+  return invoke("export_menotec", { path, dsl });
+}
+
 export async function exportHtml(
   path: string,
   htmlContent: string,
@@ -124,6 +135,21 @@ export type Segment =
 export interface ImportedDocument {
   segments: Segment[];
   is_menota: boolean;
+  /** Display label of the main text unit */
+  label?: string;
+  /** Front/back matter and further texts, in document order */
+  units?: TextUnit[];
+}
+
+/** An editable text unit besides the main body (front, back, grouped text) */
+export interface TextUnit {
+  label: string;
+  /** Element name: front, body or back */
+  kind: string;
+  original_xml: string;
+  segments: Segment[];
+  /** Current DSL of the unit */
+  dsl: string;
 }
 
 export interface ImportResult {
@@ -141,14 +167,188 @@ export interface ImportResult {
   originalPostamble?: string;
   /** Indicates imported mode for round-trip fidelity */
   isImportedMode?: boolean;
+  /** Annotations harvested from <w> attributes (lemma, me:msa, @ana) */
+  annotations?: import("$lib/types/annotations").AnnotationSet;
+  /** Inflections harvested from lemmatised words */
+  inflections?: HarvestedInflection[];
+  /** Page layout (images, regions, line coordinates) from HTR imports */
+  facsimile?: Facsimile;
+  /**
+   * Unsupported constructs and non-editable attributes (TEI imports), or
+   * formatting with no DSL equivalent (DOCX imports)
+   */
+  report?: ImportReport;
+}
+
+/** What an import kept without making it editable */
+export interface ImportReport {
+  /** Number of elements by qualified name */
+  elementCounts: Record<string, number>;
+  /** Elements the DSL does not model */
+  constructs: UnsupportedConstruct[];
+  /** Attributes kept from the original that the DSL cannot edit */
+  attributes: PreservedAttribute[];
+  /** Word-processor formatting with no DSL equivalent (DOCX imports) */
+  formatting?: UnmappedFormatting[];
+}
+
+/** readOnly: markup kept as structure; flattened: markup inside a word shown as plain text */
+export type ConstructHandling = "readOnly" | "flattened";
+
+export interface UnsupportedConstruct {
+  element: string;
+  handling: ConstructHandling;
+  /** Source line number (1-based) */
+  line: number;
+  /** Start of the element's text content */
+  text: string;
+}
+
+export interface PreservedAttribute {
+  element: string;
+  attribute: string;
+  /** Source line numbers (1-based) of each occurrence */
+  lines: number[];
+}
+
+/** Formatting found in a DOCX import that has no DSL equivalent */
+export interface UnmappedFormatting {
+  /** 1-based paragraph number */
+  paragraph: number;
+  /** What was found, e.g. "bold", "footnote", "style Heading1" */
+  formatting: string;
+  /** Text the formatting applied to */
+  text: string;
+}
+
+/** Page layout of a PAGE XML / ALTO import */
+export interface Facsimile {
+  surfaces: FacsimileSurface[];
+}
+
+/** A page image; `n` matches the `///n` break in the DSL */
+export interface FacsimileSurface {
+  id: string;
+  n: string;
+  image?: string;
+  width?: number;
+  height?: number;
+  zones: FacsimileZone[];
+}
+
+/** A region or line; line zones' `n` matches the `//n` break in the DSL */
+export interface FacsimileZone {
+  id: string;
+  kind: "region" | "line";
+  n?: string;
+  /** Polygon as "x,y x,y ..." */
+  points?: string;
+  /** Bounding box [ulx, uly, lrx, lry] */
+  bounds?: [number, number, number, number];
+}
+
+export interface HarvestedInflection {
+  /** Lookup key (diplomatic form, falling back to facsimile) */
+  wordform: string;
+  inflection: InflectedForm;
 }
 
 /**
  * Import a file and convert it to DSL format, also extracting metadata.
  * The backend runs this on a separate async task to avoid blocking the UI.
+ * With `harvestInflections`, lemmatised words are also merged into the
+ * persistent inflection store. With `levelOverrides`, MENOTA words keep their
+ * diplomatic and normalized text as `.dipl{}.norm{}` overrides.
  */
-export async function importFile(path: string): Promise<ImportResult> {
-  return invoke("import_file", { path });
+export async function importFile(
+  path: string,
+  harvestInflections?: boolean,
+  levelOverrides?: boolean,
+  plainTextProfile?: PlainTextProfile,
+): Promise<ImportResult> {
+  return invoke("import_file", {
+    path,
+    harvestInflections,
+    levelOverrides,
+    plainTextProfile,
+  });
+}
+
+export type PlainTextConstruct =
+  | "lineBreak"
+  | "pageBreak"
+  | "expansion"
+  | "supplied"
+  | "gap"
+  | "unclear"
+  | "deletion"
+  | "addition"
+  | "note"
+  | "text";
+
+/** Maps a typing convention (marker or delimiters) to a DSL construct */
+export interface PlainTextRule {
+  /** Break marker, or opening delimiter of a span */
+  open: string;
+  /** Closing delimiter of a span; empty for breaks */
+  close: string;
+  construct: PlainTextConstruct;
+}
+
+export interface PlainTextProfile {
+  name: string;
+  rules: PlainTextRule[];
+}
+
+export interface PlainTextConversion {
+  line: number;
+  rule: string;
+  original: string;
+  dsl: string;
+}
+
+export interface PlainTextPreview {
+  dsl: string;
+  /** Number of conversions per rule */
+  counts: Record<string, number>;
+  conversions: PlainTextConversion[];
+  warnings: { line: number; message: string }[];
+}
+
+/**
+ * Convert a plain-text file with a profile without importing it, listing
+ * every conversion the profile would make.
+ */
+export async function previewPlainText(
+  path: string,
+  profile: PlainTextProfile,
+): Promise<PlainTextPreview> {
+  return invoke("preview_plain_text", { path, profile });
+}
+
+/** One difference between an original file and its unedited round trip */
+export interface FidelityDifference {
+  /** Element path with positions, e.g. /TEI[1]/text[1]/body[1]/p[2]/w[5] */
+  path: string;
+  originalLine: number;
+  roundTripLine: number;
+  kind: string;
+  original: string;
+  roundTrip: string;
+}
+
+export interface FidelityReport {
+  byteIdentical: boolean;
+  canonicalIdentical: boolean;
+  differences: FidelityDifference[];
+}
+
+/**
+ * Import a TEI file and export it again without edits, comparing the result
+ * with the original in canonical XML.
+ */
+export async function checkRoundTrip(path: string): Promise<FidelityReport> {
+  return invoke("check_round_trip", { path });
 }
 
 export async function listTemplates(): Promise<Template[]> {
@@ -167,6 +367,71 @@ export async function deleteTemplate(id: string): Promise<void> {
   return invoke("delete_template", { id });
 }
 
+export interface PackageManifest {
+  formatVersion: string;
+  templateId: string;
+  name: string;
+  version: string;
+  description: string;
+  schema?: string | null;
+  stylesheet?: string | null;
+  stylesheetName?: string | null;
+  entities: boolean;
+  entityMappings: boolean;
+  appVersion: string;
+}
+
+/** How to resolve an id clash when importing a template package */
+export type ConflictAction = "replace" | "keepBoth";
+
+export interface TemplateConflict {
+  templateId: string;
+  installedName: string;
+  installedVersion: string | null;
+  packageVersion: string;
+  packageIsNewer: boolean;
+  builtIn: boolean;
+}
+
+export interface PackageImport {
+  installed: boolean;
+  template: Template;
+  conflict: TemplateConflict | null;
+  stylesheet: StylesheetEntry | null;
+  entitiesAdded: number;
+  entityMappingsAdded: number;
+  /** Entities and mappings that were kept because they differ locally */
+  keptExisting: string[];
+}
+
+export async function exportTemplatePackage(
+  path: string,
+  templateId: string,
+  stylesheetId: string | null,
+  includeEntities: boolean,
+): Promise<PackageManifest> {
+  return invoke("export_template_package", {
+    path,
+    templateId,
+    stylesheetId,
+    includeEntities,
+  });
+}
+
+/**
+ * Import a template package. Without `onConflict`, an id clash installs
+ * nothing and the result describes the conflict.
+ */
+export async function importTemplatePackage(
+  path: string,
+  onConflict?: ConflictAction,
+): Promise<PackageImport> {
+  return invoke("import_template_package", {
+    path,
+    onConflict: onConflict ?? null,
+  });
+}
+
 export interface CompileOptions {
   wordWrap?: boolean;
   autoLineNumbers?: boolean;
@@ -178,6 +443,14 @@ export interface CompileOptions {
   customMappings?: Record<string, string>;
   lemmaMappingsJson?: string;
   annotationsJson?: string;
+  /** Metadata for template placeholders such as {{title}} */
+  metadataJson?: string;
+  /** Project file name, available to templates as {{fileName}} */
+  fileName?: string;
+  /** Page layout to link page/line breaks to and emit as <facsimile> */
+  facsimileJson?: string;
+  /** Validation schema of the template; decides whether <certainty> is written */
+  schemaId?: string;
 }
 
 export async function compileDsl(
@@ -200,6 +473,10 @@ export async function compileDsl(
     customMappings: options?.customMappings ?? null,
     lemmaMappingsJson: options?.lemmaMappingsJson ?? null,
     annotationsJson: options?.annotationsJson ?? null,
+    metadataJson: options?.metadataJson ?? null,
+    fileName: options?.fileName ?? null,
+    facsimileJson: options?.facsimileJson ?? null,
+    schemaId: options?.schemaId ?? null,
   });
 }
 
@@ -208,6 +485,32 @@ export interface CompileImportedOptions {
   normalizerJson?: string;
   entityMappingsJson?: string;
   customMappings?: Record<string, string>;
+  attributePolicy?: AttributePolicy;
+  /** Edited metadata to patch into the original teiHeader */
+  metadataJson?: string;
+}
+
+/** Which original attributes of an edited word carry over */
+export interface AttributePolicy {
+  /** Attributes that only hold for the original form (lemma, me:msa, ...) */
+  linguistic: string[];
+  /** Largest share of changed characters that still counts as a small edit */
+  maxChangeRatio: number;
+  /** Leave stale attributes off the word instead of keeping them */
+  dropStale: boolean;
+}
+
+/** Linguistic attributes of a word whose form changed substantially */
+export interface StaleAttributes {
+  segmentId: number;
+  originalForm: string;
+  newForm: string;
+  attributes: Record<string, string>;
+}
+
+export interface Reconstruction {
+  xml: string;
+  staleAttributes: StaleAttributes[];
 }
 
 export async function compileImported(
@@ -216,7 +519,7 @@ export async function compileImported(
   preamble: string,
   postamble: string,
   options?: CompileImportedOptions,
-): Promise<string> {
+): Promise<Reconstruction> {
   return invoke("compile_imported", {
     editedDsl,
     segmentsJson,
@@ -226,6 +529,8 @@ export async function compileImported(
     normalizerJson: options?.normalizerJson ?? null,
     entityMappingsJson: options?.entityMappingsJson ?? null,
     customMappings: options?.customMappings ?? null,
+    attributePolicy: options?.attributePolicy ?? null,
+    metadataJson: options?.metadataJson ?? null,
   });
 }
 
@@ -472,9 +777,44 @@ export interface ProjectData {
   original_preamble?: string;
   /** Original XML postamble (new in v1.4) */
   original_postamble?: string;
+  /** Embedded compiler configuration and resources (new in v1.7) */
+  resources?: ProjectResources;
+  /** Page layout from an HTR import (new in v1.8) */
+  facsimile?: Facsimile;
+}
+
+/** Compiler flags, as stored in a project */
+export interface CompilerConfig {
+  wordWrap: boolean;
+  autoLineNumbers: boolean;
+  multiLevel: boolean;
+  wrapPages: boolean;
 }
 
-// Save project archive (.teis)
+/**
+ * Compiler configuration and resources embedded in a project, used in place
+ * of the template's flags and the user's own entities and dictionaries.
+ */
+export interface ProjectResources {
+  compilerConfig?: CompilerConfig;
+  /** Custom entity definitions */
+  entities?: EntityMap;
+  /** Custom diplomatic mappings */
+  entityMappings?: Record<string, string>;
+  /** Normalizer level dictionary, as raw JSON */
+  normalizer?: string;
+}
+
+/** A change to record in the revision history when saving (new in v1.6) */
+export interface RevisionRequest {
+  /** Name of the person saving */
+  who?: string;
+  /** Description of the change, put before the generated statistics */
+  summary?: string;
+}
+
+// Save project archive (.teis). With a revision request, returns the metadata
+// with the new revision history entry.
 export async function saveProject(
   path: string,
   source: string,
@@ -487,7 +827,10 @@ export async function saveProject(
   originalBodyXml?: string,
   originalPreamble?: string,
   originalPostamble?: string,
-): Promise<void> {
+  revision?: RevisionRequest,
+  resources?: ProjectResources,
+  facsimile?: Facsimile,
+): Promise<Metadata | null> {
   return invoke("save_project", {
     path,
     source,
@@ -500,6 +843,9 @@ export async function saveProject(
     originalBodyXml,
     originalPreamble,
     originalPostamble,
+    revisionJson: revision ? JSON.stringify(revision) : undefined,
+    resourcesJson: resources ? JSON.stringify(resources) : undefined,
+    facsimileJson: facsimile ? JSON.stringify(facsimile) : undefined,
   });
 }
 
@@ -562,14 +908,33 @@ export async function validateXmlWithSchema(
 // Metadata Commands
 // ============================================================================
 
-import type { Metadata } from "$lib/types/metadata";
+import type {
+  CatalogueImport,
+  Metadata,
+  MetadataDiagnostic,
+  MetadataFormat,
+} from "$lib/types/metadata";
 
-// Generate TEI header from structured metadata
+// Generate TEI header from structured metadata.
+// With annotations, each annotation author gets a <respStmt> for @resp pointers.
 export async function generateTeiHeader(
   metadataJson: string,
   includeMenotaNs: boolean,
+  annotationsJson?: string,
+): Promise<string> {
+  return invoke("generate_tei_header", {
+    metadataJson,
+    includeMenotaNs,
+    annotationsJson,
+  });
+}
+
+// Copy the metadata's revision history into the teiHeader of a template header
+export async function updateHeaderRevisions(
+  xml: string,
+  metadataJson: string,
 ): Promise<string> {
-  return invoke("generate_tei_header", { metadataJson, includeMenotaNs });
+  return invoke("update_header_revisions", { xml, metadataJson });
 }
 
 // Generate TEI footer (closing tags)
@@ -577,9 +942,41 @@ export async function generateTeiFooter(): Promise<string> {
   return invoke("generate_tei_footer");
 }
 
-// Validate metadata JSON structure
-export async function validateMetadata(metadataJson: string): Promise<boolean> {
-  return invoke("validate_metadata", { metadataJson });
+// Validate metadata, returning per-field diagnostics. `schemaId` is the
+// active template's validation schema and decides which fields are required.
+export async function validateMetadata(
+  metadataJson: string,
+  schemaId?: string,
+): Promise<MetadataDiagnostic[]> {
+  return invoke("validate_metadata", { metadataJson, schemaId });
+}
+
+// Merge a manuscript description from a local handrit.is / e-manuscripta
+// catalogue file into the metadata. The msDesc is chosen by `shelfmark`, or by
+// the shelfmark already in the metadata; conflicting fields keep their
+// existing value unless `preferCatalogue` is set.
+export async function importCatalogueRecord(
+  path: string,
+  metadataJson: string,
+  shelfmark?: string,
+  preferCatalogue = false,
+): Promise<CatalogueImport> {
+  return invoke("import_catalogue_record", {
+    path,
+    metadataJson,
+    shelfmark,
+    preferCatalogue,
+  });
+}
+
+// Write metadata as an oai_dc Dublin Core record, schema.org JSON-LD, or the
+// label/summary/metadata/rights properties of a IIIF manifest
+export async function exportMetadata(
+  path: string,
+  metadataJson: string,
+  format: MetadataFormat,
+): Promise<void> {
+  return invoke("export_metadata", { path, metadataJson, format });
 }
 
 // Create empty metadata with default values
@@ -589,3 +986,75 @@ export async function createEmptyMetadata(): Promise<string> {
 
 // Re-export Metadata type for convenience
 export type { Metadata };
+
+// ============================================================================
+// Annotation Review Commands
+// ============================================================================
+
+import type {
+  AnnotationConflict,
+  AnnotationSet,
+  ConflictResolution,
+  MergeResult,
+  ReviewStatus,
+  TableFormat,
+  TableImportResult,
+} from "$lib/types/annotations";
+
+// Merge another annotator's set into ours, reporting lemma/msa conflicts
+export async function mergeAnnotationSets(
+  oursJson: string,
+  theirsJson: string,
+): Promise<MergeResult> {
+  return invoke("merge_annotation_sets", { oursJson, theirsJson });
+}
+
+// Apply conflict decisions to a merged set
+export async function resolveAnnotationConflicts(
+  mergedJson: string,
+  conflicts: AnnotationConflict[],
+  resolutions: ConflictResolution[],
+): Promise<AnnotationSet> {
+  return invoke("resolve_annotation_conflicts", {
+    mergedJson,
+    conflicts,
+    resolutions,
+  });
+}
+
+// Set the review status of one annotation
+export async function setAnnotationStatus(
+  annotationsJson: string,
+  id: string,
+  status: ReviewStatus,
+): Promise<AnnotationSet> {
+  return invoke("set_annotation_status", { annotationsJson, id, status });
+}
+
+// Export every word of the compiled XML with its annotations as CSV or TSV
+export async function exportAnnotationTable(
+  xmlContent: string,
+  annotationsJson: string,
+  format: TableFormat,
+): Promise<string> {
+  return invoke("export_annotation_table", {
+    xmlContent,
+    annotationsJson,
+    format,
+  });
+}
+
+// Validate a CSV or TSV table against the compiled XML and apply its rows
+export async function importAnnotationTable(
+  xmlContent: string,
+  tableContent: string,
+  annotationsJson: string,
+  format: TableFormat,
+): Promise<TableImportResult> {
+  return invoke("import_annotation_table", {
+    xmlContent,
+    tableContent,
+    annotationsJson,
+    format,
+  });
+}
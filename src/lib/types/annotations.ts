@@ -17,7 +17,15 @@ export type AnnotationType =
 export type AnnotationTarget =
     | { type: "word"; wordIndex: number }
     | { type: "char"; wordIndex: number; charStart: number; charEnd: number }
-    | { type: "span"; startWord: number; endWord: number };
+    | { type: "span"; startWord: number; endWord: number }
+    /** Character range crossing word boundaries (inclusive offsets) */
+    | {
+          type: "charRange";
+          startWord: number;
+          startChar: number;
+          endWord: number;
+          endChar: number;
+      };
 
 /** Types of paleographic observations */
 export type PaleographicType =
@@ -105,6 +113,16 @@ export interface AnnotationMetadata {
     confidence?: number;
     source?: string;
     note?: string;
+    /** Review state; absent means accepted */
+    status?: ReviewStatus;
+}
+
+/** Review state of an annotation in a multi-annotator workflow */
+export type ReviewStatus = "proposed" | "accepted" | "rejected";
+
+/** Whether a reviewer rejected the annotation; rejected ones are not compiled */
+export function isRejected(ann: Annotation): boolean {
+    return ann.metadata?.status === "rejected";
 }
 
 /** A single annotation attached to a target in the text */
@@ -122,6 +140,47 @@ export interface AnnotationSet {
     annotations: Annotation[];
 }
 
+/** Two different lemma annotations for the same word */
+export interface AnnotationConflict {
+    wordIndex: number;
+    /** Differing fields ("lemma", "msa") */
+    fields: string[];
+    ours: Annotation;
+    theirs: Annotation;
+}
+
+/** Result of merging two annotation sets */
+export interface MergeResult {
+    /** Combined set, holding our side of every unresolved conflict */
+    merged: AnnotationSet;
+    conflicts: AnnotationConflict[];
+}
+
+/** Delimited text flavour for annotation tables */
+export type TableFormat = "csv" | "tsv";
+
+/** A table row that could not be applied */
+export interface TableMismatch {
+    /** 1-based row number in the table (the header is row 1) */
+    row: number;
+    wordIndex?: number;
+    message: string;
+}
+
+/** Result of applying an annotation table */
+export interface TableImportResult {
+    annotations: AnnotationSet;
+    /** Number of rows that changed the set */
+    applied: number;
+    mismatches: TableMismatch[];
+}
+
+/** A decision for the conflict on one word */
+export interface ConflictResolution {
+    wordIndex: number;
+    choice: "ours" | "theirs";
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -229,6 +288,7 @@ export function targetIncludesWord(
         case "char":
             return target.wordIndex === wordIndex;
         case "span":
+        case "charRange":
             return wordIndex >= target.startWord && wordIndex <= target.endWord;
     }
 }
@@ -240,6 +300,7 @@ export function getPrimaryWordIndex(target: AnnotationTarget): number {
         case "char":
             return target.wordIndex;
         case "span":
+        case "charRange":
             return target.startWord;
     }
 }
@@ -272,7 +333,8 @@ export function getLemmaMap(
         if (
             ann.type === "lemma" &&
             ann.target.type === "word" &&
-            ann.value.kind === "lemma"
+            ann.value.kind === "lemma" &&
+            !isRejected(ann)
         ) {
             map.set(ann.target.wordIndex, {
                 lemma: ann.value.lemma,
@@ -23,11 +23,20 @@ export interface Metadata {
     /** History of the manuscript (msDesc/history) */
     history: History;
 
+    /** Codicological units of a composite manuscript (msDesc/msPart) */
+    msParts: MsPart[];
+
     /** Language profile (profileDesc/langUsage) */
     languages: Language[];
 
     /** Encoding description notes (encodingDesc) */
     encodingNotes?: string;
+
+    /** Revision history (revisionDesc), most recent change first */
+    revisions: Change[];
+
+    /** Source of an imported teiHeader; edits are patched into it on export */
+    originalHeader?: string;
 }
 
 /** Title statement - who created and is responsible for the digital edition */
@@ -67,6 +76,9 @@ export interface RespStmt {
 
     /** Who did it */
     name: string;
+
+    /** xml:id so that @resp attributes in the text can point here */
+    id?: string;
 }
 
 /** Publication information for the digital edition */
@@ -151,6 +163,57 @@ export interface MsContents {
 
     /** Additional language notes */
     textLangNote?: string;
+
+    /** Works contained in the manuscript, in order (msItem) */
+    items: MsItem[];
+}
+
+/** A work or section of the manuscript */
+export interface MsItem {
+    /** Item number (@n) */
+    n?: string;
+
+    /** Leaves the item occupies */
+    locus?: Locus;
+
+    /** Author of the work */
+    author?: string;
+
+    /** Title of the work */
+    title?: string;
+
+    /** Rubric as written in the manuscript */
+    rubric?: string;
+
+    /** Opening words */
+    incipit?: string;
+
+    /** Closing words */
+    explicit?: string;
+
+    /** Scribal colophon */
+    colophon?: string;
+
+    /** Language of the item (ISO 639 code) */
+    textLang?: string;
+
+    /** Further notes on the item */
+    note?: string;
+
+    /** Sub-items, e.g. the individual sagas of a compilation */
+    items: MsItem[];
+}
+
+/** A range of leaves, e.g. 1r-24v */
+export interface Locus {
+    /** First leaf */
+    from?: string;
+
+    /** Last leaf */
+    to?: string;
+
+    /** Displayed text (e.g., "1r-24v") */
+    text?: string;
 }
 
 /** Physical description of the manuscript */
@@ -172,6 +235,45 @@ export interface PhysDesc {
 
     /** General condition */
     condition?: string;
+
+    /** Form of the object (codex, leaf, roll, ...) */
+    form?: string;
+
+    /** Collation formula */
+    collation?: string;
+
+    /** Decoration (decoDesc) */
+    decorations: DecoNote[];
+
+    /** Later additions such as marginalia and glosses */
+    additions?: string;
+
+    /** Binding (bindingDesc) */
+    binding?: Binding;
+
+    /** Accompanying material */
+    accMat?: string;
+}
+
+/** A note on one kind of decoration */
+export interface DecoNote {
+    /** Kind of decoration (e.g., "initial", "miniature", "border") */
+    decoType?: string;
+
+    /** Where it occurs */
+    locus?: Locus;
+
+    /** Description */
+    description?: string;
+}
+
+/** Binding of the manuscript */
+export interface Binding {
+    /** Whether the binding is contemporary with the manuscript */
+    contemporary?: boolean;
+
+    /** Description */
+    description?: string;
 }
 
 /** Description of a scribal hand */
@@ -204,6 +306,22 @@ export interface History {
     acquisition?: string;
 }
 
+/** A codicological unit of a composite manuscript, with its own contents,
+ *  physical description and history */
+export interface MsPart {
+    /** Identifier of the part (e.g., "Part I") */
+    idno?: string;
+
+    /** Contents of the part */
+    msContents: MsContents;
+
+    /** Physical description of the part */
+    physDesc: PhysDesc;
+
+    /** History of the part */
+    history: History;
+}
+
 /** A date or date range */
 export interface DateRange {
     /** Displayed date text (e.g., "ca. 1270-1280") */
@@ -219,6 +337,18 @@ export interface DateRange {
     when?: string;
 }
 
+/** A change recorded in the revision history */
+export interface Change {
+    /** Date of the change (YYYY-MM-DD) */
+    when?: string;
+
+    /** Pointer to whoever made the change (e.g., "#resp-anna") */
+    who?: string;
+
+    /** What was changed */
+    summary: string;
+}
+
 /** Language declaration */
 export interface Language {
     /** ISO 639 language code (e.g., "non" for Old Norse) */
@@ -231,6 +361,36 @@ export interface Language {
     name?: string;
 }
 
+/** A problem with a single metadata field, reported by validateMetadata */
+export interface MetadataDiagnostic {
+    /** Path of the field, e.g. "history.origDate.notBefore" or "languages[1].ident" */
+    field: string;
+    message: string;
+    isWarning: boolean;
+}
+
+/** A field whose catalogue value differs from the existing one */
+export interface FieldConflict {
+    field: string;
+    existing: unknown;
+    catalogue: unknown;
+}
+
+/** Result of merging a catalogue record into existing metadata */
+export interface CatalogueImport {
+    /** The merged metadata */
+    metadata: Metadata;
+    /** Shelfmark of the msDesc that was used */
+    shelfmark?: string;
+    /** Fields filled in from the catalogue, e.g. "physDesc.hands" */
+    taken: string[];
+    /** Fields where the catalogue disagreed with a value already present */
+    conflicts: FieldConflict[];
+}
+
+/** Target of exportMetadata */
+export type MetadataFormat = "dublinCore" | "jsonLd" | "iiif";
+
 /** Create empty metadata with default values */
 export function createEmptyMetadata(): Metadata {
     return {
@@ -263,6 +423,7 @@ export function createEmptyMetadata(): Metadata {
             summary: undefined,
             textLang: undefined,
             textLangNote: undefined,
+            items: [],
         },
         physDesc: {
             material: undefined,
@@ -271,6 +432,7 @@ export function createEmptyMetadata(): Metadata {
             layout: undefined,
             hands: [],
             condition: undefined,
+            decorations: [],
         },
         history: {
             origDate: undefined,
@@ -278,8 +440,10 @@ export function createEmptyMetadata(): Metadata {
             provenance: undefined,
             acquisition: undefined,
         },
+        msParts: [],
         languages: [],
         encodingNotes: undefined,
+        revisions: [],
     };
 }
 
@@ -291,7 +455,8 @@ export function isMetadataEmpty(metadata: Metadata): boolean {
         metadata.titleStmt.editors.length === 0 &&
         !metadata.msIdentifier.repository &&
         !metadata.msIdentifier.idno &&
-        metadata.languages.length === 0
+        metadata.languages.length === 0 &&
+        !metadata.originalHeader
     );
 }
 